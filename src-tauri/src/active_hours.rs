@@ -0,0 +1,126 @@
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use ts_rs::TS;
+
+/// When a pipeline matters, in local time, e.g. weekdays 09:00-18:00
+///
+/// A window whose `end` is before its `start` runs past midnight and belongs
+/// to the day it starts on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(test, derive(TS))]
+pub struct ActiveHours {
+    /// Days the window starts on, e.g. ["Mon", "Tue"]
+    pub days: Vec<Weekday>,
+    /// "HH:MM"
+    pub start: String,
+    /// "HH:MM"
+    pub end: String,
+}
+
+impl ActiveHours {
+    /// Check `start`/`end` parse as "HH:MM"
+    pub fn validate(&self) -> Result<(), String> {
+        parse_time(&self.start)?;
+        parse_time(&self.end)?;
+        Ok(())
+    }
+
+    /// Whether `now` (local time) falls inside the window
+    ///
+    /// Unparseable times count as always active, so a bad config never hides failures.
+    pub fn is_active_at(&self, now: NaiveDateTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return true;
+        };
+        let time = now.time();
+        let today = now.weekday();
+
+        if start <= end {
+            self.days.contains(&today) && time >= start && time < end
+        } else if time >= start {
+            // Evening part of an overnight window
+            self.days.contains(&today)
+        } else if time < end {
+            // Early-morning part, which started the day before
+            self.days.contains(&today.pred())
+        } else {
+            false
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|e| format!("Invalid active hours time '{}': {}", value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        // 2024-01-01 is a Monday
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+    }
+
+    fn weekdays(start: &str, end: &str) -> ActiveHours {
+        ActiveHours {
+            days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    #[test]
+    fn daytime_window() {
+        let hours = weekdays("09:00", "18:00");
+
+        assert!(!hours.is_active_at(at(1, "08:59")));
+        assert!(hours.is_active_at(at(1, "09:00")));
+        assert!(hours.is_active_at(at(1, "17:59")));
+        assert!(!hours.is_active_at(at(1, "18:00")));
+        assert!(!hours.is_active_at(at(2, "02:00")));
+    }
+
+    #[test]
+    fn weekend_is_inactive() {
+        let hours = weekdays("09:00", "18:00");
+
+        assert!(hours.is_active_at(at(5, "12:00"))); // Friday
+        assert!(!hours.is_active_at(at(6, "12:00"))); // Saturday
+        assert!(!hours.is_active_at(at(7, "12:00"))); // Sunday
+        assert!(hours.is_active_at(at(8, "12:00"))); // Monday
+    }
+
+    #[test]
+    fn overnight_window_crosses_midnight() {
+        let hours = weekdays("22:00", "06:00");
+
+        assert!(hours.is_active_at(at(1, "23:30"))); // Monday night
+        assert!(hours.is_active_at(at(2, "05:59"))); // Tuesday morning, from Monday
+        assert!(!hours.is_active_at(at(2, "06:00")));
+        assert!(!hours.is_active_at(at(2, "12:00")));
+    }
+
+    #[test]
+    fn overnight_window_follows_the_starting_day_over_the_weekend() {
+        let hours = weekdays("22:00", "06:00");
+
+        assert!(hours.is_active_at(at(6, "03:00"))); // Saturday morning, from Friday
+        assert!(!hours.is_active_at(at(6, "23:00"))); // Saturday night
+        assert!(!hours.is_active_at(at(8, "03:00"))); // Monday morning, from Sunday
+        assert!(hours.is_active_at(at(8, "22:00")));
+    }
+
+    #[test]
+    fn invalid_times_are_always_active() {
+        let hours = weekdays("9am", "18:00");
+
+        assert!(hours.validate().is_err());
+        assert!(hours.is_active_at(at(6, "12:00")));
+    }
+}
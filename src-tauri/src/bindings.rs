@@ -0,0 +1,64 @@
+//! TypeScript bindings for the IPC payloads, checked into `src/bindings`
+//!
+//! `cargo test` fails when the checked-in files no longer match the Rust
+//! types. Regenerate them with `UPDATE_BINDINGS=1 cargo test bindings`.
+
+use crate::bitbucket::{Project, Repository, Workspace, WorkspaceMember};
+use crate::config::{MonitoredPipeline, StatusSnapshot};
+use crate::digest::{DigestSettings, WeeklyDigest};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Frontend directory the bindings are written to
+fn bindings_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/bindings")
+}
+
+/// Export every payload type, and the types they refer to, into `dir`
+fn export_all(dir: &Path) -> Result<(), ts_rs::ExportError> {
+    StatusSnapshot::export_all_to(dir)?;
+    MonitoredPipeline::export_all_to(dir)?;
+    Workspace::export_all_to(dir)?;
+    Project::export_all_to(dir)?;
+    Repository::export_all_to(dir)?;
+    WorkspaceMember::export_all_to(dir)?;
+    WeeklyDigest::export_all_to(dir)?;
+    DigestSettings::export_all_to(dir)?;
+    Ok(())
+}
+
+/// File name to contents for every binding in `dir`
+fn read_bindings(dir: &Path) -> BTreeMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return BTreeMap::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let contents = std::fs::read_to_string(entry.path()).unwrap_or_default();
+            (entry.file_name().to_string_lossy().into_owned(), contents)
+        })
+        .collect()
+}
+
+#[test]
+fn bindings_are_up_to_date() {
+    if std::env::var_os("UPDATE_BINDINGS").is_some() {
+        let _ = std::fs::remove_dir_all(bindings_dir());
+        export_all(&bindings_dir()).expect("bindings should export");
+        return;
+    }
+
+    let fresh = std::env::temp_dir().join(format!("cdmenu-bindings-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&fresh);
+    export_all(&fresh).expect("bindings should export");
+    let expected = read_bindings(&fresh);
+    let _ = std::fs::remove_dir_all(&fresh);
+
+    assert_eq!(
+        read_bindings(&bindings_dir()),
+        expected,
+        "src/bindings is out of date; run `UPDATE_BINDINGS=1 cargo test bindings`"
+    );
+}
@@ -0,0 +1,285 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Usage is measured over this rolling window
+pub const BUDGET_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Bitbucket allows 1,000 repository API requests an hour; leave room for other tools
+pub const DEFAULT_REQUESTS_PER_HOUR: u32 = 800;
+
+/// Polling slows down once Bitbucket reports fewer requests than this left
+pub const LOW_RATE_LIMIT_REMAINING: u32 = 10;
+
+// Requests made by every client in this process
+static API_BUDGET: Mutex<Budget> = Mutex::new(Budget::new(DEFAULT_REQUESTS_PER_HOUR));
+
+// Quota reported by the most recent response that carried rate limit headers
+static LATEST_RATE_LIMIT: Mutex<Option<RateLimitInfo>> = Mutex::new(None);
+
+/// Quota left, from Bitbucket's `X-RateLimit-*` response headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RateLimitInfo {
+    pub limit: u32,
+    pub remaining: u32,
+    /// Unix time (seconds) the quota resets at
+    pub reset_at: u64,
+}
+
+impl RateLimitInfo {
+    /// Few enough requests left that polling should slow down
+    pub fn is_low(&self) -> bool {
+        self.remaining < LOW_RATE_LIMIT_REMAINING
+    }
+
+    /// Seconds until the quota resets, when it is used up and hasn't reset yet
+    pub fn exhausted_for(&self, now_unix: u64) -> Option<u64> {
+        if self.remaining > 0 {
+            return None;
+        }
+        self.reset_at.checked_sub(now_unix).filter(|seconds| *seconds > 0)
+    }
+}
+
+/// API requests per workspace over the last hour, and how far polling must slow to stay in budget
+#[derive(Debug)]
+pub struct Budget {
+    limit_per_hour: u32,
+    requests: BTreeMap<String, VecDeque<Instant>>,
+    /// Last time Bitbucket reported a workspace close to its rate limit
+    near_limit: BTreeMap<String, Instant>,
+    /// Polling checks, to work out what one check costs
+    checks: VecDeque<Instant>,
+}
+
+/// Budget usage of the busiest workspace, as shown in settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BudgetState {
+    pub limit_per_hour: u32,
+    pub busiest_workspace: Option<String>,
+    pub used_last_hour: u32,
+    /// Requests an hour the busiest workspace would use at the configured interval
+    pub projected_per_hour: u32,
+    /// Bitbucket reported the workspace near its rate limit within the last hour
+    pub near_limit: bool,
+    /// Factor the polling interval is stretched by (1 when within budget)
+    pub stretch: u64,
+}
+
+impl Budget {
+    pub const fn new(limit_per_hour: u32) -> Self {
+        Self {
+            limit_per_hour,
+            requests: BTreeMap::new(),
+            near_limit: BTreeMap::new(),
+            checks: VecDeque::new(),
+        }
+    }
+
+    pub fn set_limit(&mut self, limit_per_hour: u32) {
+        self.limit_per_hour = limit_per_hour.max(1);
+    }
+
+    pub fn record_request(&mut self, workspace: &str, now: Instant) {
+        self.requests.entry(workspace.to_string()).or_default().push_back(now);
+    }
+
+    pub fn record_near_limit(&mut self, workspace: &str, now: Instant) {
+        self.near_limit.insert(workspace.to_string(), now);
+    }
+
+    pub fn record_check(&mut self, now: Instant) {
+        self.checks.push_back(now);
+    }
+
+    // Helper: Drop everything older than the window
+    fn prune(&mut self, now: Instant) {
+        let expired = |t: &Instant| now.saturating_duration_since(*t) >= BUDGET_WINDOW;
+        for times in self.requests.values_mut() {
+            while times.front().is_some_and(expired) {
+                times.pop_front();
+            }
+        }
+        self.requests.retain(|_, times| !times.is_empty());
+        self.near_limit.retain(|_, t| !expired(t));
+        while self.checks.front().is_some_and(expired) {
+            self.checks.pop_front();
+        }
+    }
+
+    /// Usage of the busiest workspace and the interval stretch that keeps it within budget
+    ///
+    /// Each workspace's cost per check is projected to `base_interval_seconds`, so the
+    /// stretch is only lifted once checks get cheaper or the near-limit report expires,
+    /// not as soon as the slower polling has lowered the hourly count.
+    pub fn state(&mut self, base_interval_seconds: u64, now: Instant) -> BudgetState {
+        self.prune(now);
+        let checks = self.checks.len().max(1) as u64;
+        let checks_per_hour = BUDGET_WINDOW.as_secs() / base_interval_seconds.max(1);
+
+        let mut state = BudgetState {
+            limit_per_hour: self.limit_per_hour,
+            busiest_workspace: None,
+            used_last_hour: 0,
+            projected_per_hour: 0,
+            near_limit: false,
+            stretch: 1,
+        };
+        for (workspace, times) in &self.requests {
+            let used = times.len() as u64;
+            let projected = (used.div_ceil(checks) * checks_per_hour).min(u32::MAX as u64);
+            let near_limit = self.near_limit.contains_key(workspace);
+            let mut stretch = projected.div_ceil(self.limit_per_hour as u64).max(1);
+            if near_limit {
+                stretch = stretch.max(2);
+            }
+            let busier = stretch > state.stretch
+                || (stretch == state.stretch && projected > state.projected_per_hour as u64);
+            if state.busiest_workspace.is_none() || busier {
+                state = BudgetState {
+                    busiest_workspace: Some(workspace.clone()),
+                    used_last_hour: used as u32,
+                    projected_per_hour: projected as u32,
+                    near_limit,
+                    stretch,
+                    ..state
+                };
+            }
+        }
+        state
+    }
+}
+
+/// Set the requests per workspace per hour polling should stay within
+pub fn set_api_budget(limit_per_hour: u32) {
+    if let Ok(mut budget) = API_BUDGET.lock() {
+        budget.set_limit(limit_per_hour);
+    }
+}
+
+/// Count a polling check, so requests can be projected per check
+pub fn record_check() {
+    if let Ok(mut budget) = API_BUDGET.lock() {
+        budget.record_check(Instant::now());
+    }
+}
+
+/// Current budget usage for polling at `base_interval_seconds`
+pub fn api_budget_state(base_interval_seconds: u64) -> BudgetState {
+    match API_BUDGET.lock() {
+        Ok(mut budget) => budget.state(base_interval_seconds, Instant::now()),
+        Err(_) => Budget::new(DEFAULT_REQUESTS_PER_HOUR).state(base_interval_seconds, Instant::now()),
+    }
+}
+
+// Helper: Count a request against its workspace; `near_limit` comes from the response headers
+pub(crate) fn record_request(workspace: &str, near_limit: bool) {
+    if let Ok(mut budget) = API_BUDGET.lock() {
+        let now = Instant::now();
+        budget.record_request(workspace, now);
+        if near_limit {
+            budget.record_near_limit(workspace, now);
+        }
+    }
+}
+
+/// Current Unix time in seconds, to compare with `RateLimitInfo::reset_at`
+pub fn now_unix() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Remember the quota reported by a response
+pub(crate) fn record_rate_limit(rate_limit: RateLimitInfo) {
+    if let Ok(mut latest) = LATEST_RATE_LIMIT.lock() {
+        *latest = Some(rate_limit);
+    }
+}
+
+/// Quota reported by the most recent response that carried rate limit headers
+pub fn latest_rate_limit() -> Option<RateLimitInfo> {
+    LATEST_RATE_LIMIT.lock().ok().and_then(|latest| *latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget_after_checks(limit: u32, checks: u32, requests_per_check: u32, now: Instant) -> Budget {
+        let mut budget = Budget::new(limit);
+        for _ in 0..checks {
+            budget.record_check(now);
+            for _ in 0..requests_per_check {
+                budget.record_request("acme", now);
+            }
+        }
+        budget
+    }
+
+    #[test]
+    fn stays_at_the_base_interval_within_budget() {
+        let now = Instant::now();
+        // 10 requests a check, 60 checks an hour
+        let mut budget = budget_after_checks(800, 5, 10, now);
+
+        let state = budget.state(60, now);
+
+        assert_eq!(state.projected_per_hour, 600);
+        assert_eq!(state.used_last_hour, 50);
+        assert_eq!(state.stretch, 1);
+    }
+
+    #[test]
+    fn stretches_the_interval_until_the_projection_fits() {
+        let now = Instant::now();
+        let mut budget = budget_after_checks(800, 5, 30, now);
+
+        let state = budget.state(60, now);
+
+        assert_eq!(state.busiest_workspace.as_deref(), Some("acme"));
+        assert_eq!(state.projected_per_hour, 1800);
+        assert_eq!(state.stretch, 3);
+    }
+
+    #[test]
+    fn near_limit_report_slows_polling_for_an_hour() {
+        let now = Instant::now();
+        let mut budget = budget_after_checks(800, 1, 1, now);
+        budget.record_near_limit("acme", now);
+
+        assert_eq!(budget.state(60, now).stretch, 2);
+        assert!(budget.state(60, now).near_limit);
+
+        let later = now + BUDGET_WINDOW;
+        let state = budget.state(60, later);
+        assert_eq!(state.stretch, 1);
+        assert_eq!(state.busiest_workspace, None);
+    }
+
+    #[test]
+    fn rate_limit_is_exhausted_only_until_it_resets() {
+        let rate_limit = RateLimitInfo {
+            limit: 1000,
+            remaining: 0,
+            reset_at: 1_000,
+        };
+
+        assert!(rate_limit.is_low());
+        assert_eq!(rate_limit.exhausted_for(940), Some(60));
+        assert_eq!(rate_limit.exhausted_for(1_000), None);
+
+        let low = RateLimitInfo {
+            remaining: 9,
+            ..rate_limit
+        };
+        assert!(low.is_low());
+        assert_eq!(low.exhausted_for(940), None);
+        assert!(!RateLimitInfo {
+            remaining: LOW_RATE_LIMIT_REMAINING,
+            ..rate_limit
+        }
+        .is_low());
+    }
+}
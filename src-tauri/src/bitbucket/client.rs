@@ -1,73 +1,327 @@
 use base64::{engine::general_purpose::STANDARD, Engine};
-use reqwest::{header, Client};
+use reqwest::{header, Certificate, Client};
+use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
-use super::types::{PaginatedResponse, Pipeline, PipelineStep, Project, Repository, Workspace};
+use super::budget::{self, RateLimitInfo};
+use super::conditional;
+use super::types::{
+    Account, CommitRef, CredentialCheck, Deployment, Environment, PaginatedResponse, Pipeline,
+    PipelineSchedule, PipelineStep, PipelineVariable, PipelinesConfig, Project, PullRequest,
+    Repository, Workspace, WorkspaceMember, WorkspaceMembership,
+};
 
 const BITBUCKET_API_BASE: &str = "https://api.bitbucket.org/2.0";
 
+/// Username stored for workspace and repository access tokens, which have no username
+pub const ACCESS_TOKEN_USERNAME: &str = "x-token-auth";
+
+/// Upper bound on pages followed for one listing, in case `next` links loop
+const MAX_PAGES: usize = 20;
+
+/// Runs requested when looking for a branch's latest run with a server-side filter
+const BRANCH_PAGE_LEN: u32 = 5;
+
+/// Runs checked client-side when the server-side branch filter finds nothing
+const BRANCH_FALLBACK_PAGE_LEN: u32 = 20;
+
+const BITBUCKET_WEB_BASE: &str = "https://bitbucket.org";
+
+/// Build the canonical web URL for a pipeline run
+fn get_pipeline_url(workspace: &str, repo_slug: &str, build_number: u32) -> String {
+    format!(
+        "{}/{}/{}/pipelines/results/{}",
+        BITBUCKET_WEB_BASE, workspace, repo_slug, build_number
+    )
+}
+
+/// Web URL for a pipeline run, preferring API-provided links
+///
+/// Uses the run's own `links.html`, then the repository's web URL, and only
+/// formats a bitbucket.org URL when neither is available.
+pub fn resolve_pipeline_url(
+    pipeline: &Pipeline,
+    repo_url: Option<&str>,
+    workspace: &str,
+    repo_slug: &str,
+) -> String {
+    if let Some(url) = pipeline.html_url() {
+        return url.to_string();
+    }
+    match repo_url {
+        Some(repo_url) => format!(
+            "{}/pipelines/results/{}",
+            repo_url.trim_end_matches('/'),
+            pipeline.build_number
+        ),
+        None => get_pipeline_url(workspace, repo_slug, pipeline.build_number),
+    }
+}
+
+/// Web URL for a repository's pipelines list
+pub fn get_pipelines_list_url(repo_url: Option<&str>, workspace: &str, repo_slug: &str) -> String {
+    match repo_url {
+        Some(repo_url) => format!("{}/pipelines", repo_url.trim_end_matches('/')),
+        None => format!("{}/{}/{}/pipelines", BITBUCKET_WEB_BASE, workspace, repo_slug),
+    }
+}
+
+/// Web URL for a repository's source view
+pub fn get_repository_url(repo_url: Option<&str>, workspace: &str, repo_slug: &str) -> String {
+    match repo_url {
+        Some(repo_url) => repo_url.trim_end_matches('/').to_string(),
+        None => format!("{}/{}/{}", BITBUCKET_WEB_BASE, workspace, repo_slug),
+    }
+}
+
+/// Web URL for a branch within a repository
+pub fn get_branch_url(repo_url: &str, branch: &str) -> String {
+    format!("{}/branch/{}", repo_url.trim_end_matches('/'), branch)
+}
+
 #[derive(Error, Debug)]
 pub enum BitbucketError {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
     #[error("Authentication failed - check username and app password")]
     AuthenticationFailed,
-    #[error("Rate limited - please wait before retrying")]
-    RateLimited,
+    #[error("Rate limited - please wait before retrying{}", retry_after_hint(.0))]
+    RateLimited(Option<u64>),
+    #[error("Access denied - the app password may be missing a scope: {0}")]
+    Forbidden(String),
     #[error("Resource not found: {0}")]
     NotFound(String),
     #[error("API error: {0}")]
     ApiError(String),
+    #[error("TLS error: {0}")]
+    Tls(String),
+    #[error("Invalid CA certificate: {0}")]
+    InvalidCaCertificate(String),
+}
+
+impl BitbucketError {
+    /// Whether the request never got a response, e.g. the connection failed or timed out
+    pub fn is_network_error(&self) -> bool {
+        matches!(self, BitbucketError::Http(e) if e.is_connect() || e.is_timeout() || e.is_request())
+    }
+}
+
+/// Whether `s` is a well-formed workspace slug: lowercase letters, digits, `_` and `-`
+pub fn is_valid_slug(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(is_slug_byte)
+}
+
+/// Whether `s` is a well-formed repository slug, which may also contain `.`
+pub fn is_valid_repo_slug(s: &str) -> bool {
+    !s.trim_matches('.').is_empty() && s.bytes().all(|b| b == b'.' || is_slug_byte(b))
+}
+
+fn is_slug_byte(b: u8) -> bool {
+    b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-'
+}
+
+// Helper: Fail before sending a request that could only 404
+fn check_slugs(workspace: &str, repo_slug: Option<&str>) -> Result<(), BitbucketError> {
+    if !is_valid_slug(workspace) {
+        return Err(BitbucketError::ApiError(format!(
+            "Invalid workspace slug: {:?}",
+            workspace
+        )));
+    }
+    match repo_slug {
+        Some(slug) if !is_valid_repo_slug(slug) => Err(BitbucketError::ApiError(format!(
+            "Invalid repository slug: {:?}",
+            slug
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Helper: Quote a value for a `q` filter, escaping what would end the string early
+fn quote_query_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn retry_after_hint(retry_after: &Option<u64>) -> String {
+    match retry_after {
+        Some(seconds) => format!(" (retry after {}s)", seconds),
+        None => String::new(),
+    }
+}
+
+/// TLS options applied when building the HTTP client
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    /// PEM bundle of additional trusted root certificates
+    pub custom_ca_path: Option<PathBuf>,
+    /// Skip certificate verification entirely (insecure)
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl TlsConfig {
+    /// Load the custom CA bundle, if configured, validating that it parses as PEM
+    pub fn load_ca_certificates(&self) -> Result<Vec<Certificate>, BitbucketError> {
+        let Some(path) = &self.custom_ca_path else {
+            return Ok(Vec::new());
+        };
+
+        let pem = std::fs::read(path).map_err(|e| {
+            BitbucketError::InvalidCaCertificate(format!("{}: {}", path.display(), e))
+        })?;
+        let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+            BitbucketError::InvalidCaCertificate(format!(
+                "{} is not a valid PEM bundle: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if certs.is_empty() {
+            return Err(BitbucketError::InvalidCaCertificate(format!(
+                "{} contains no PEM certificates",
+                path.display()
+            )));
+        }
+        Ok(certs)
+    }
+
+    /// HTTP client trusting the custom CA bundle and honouring the verification setting
+    pub fn build_client(&self, timeout: Duration) -> Result<Client, BitbucketError> {
+        let mut builder = Client::builder().timeout(timeout);
+        for cert in self.load_ca_certificates()? {
+            builder = builder.add_root_certificate(cert);
+        }
+        if self.danger_accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        Ok(builder.build()?)
+    }
 }
 
 /// Client for interacting with the Bitbucket Cloud REST API
 pub struct BitbucketClient {
     client: Client,
     auth_header: String,
+    /// Keys this client's entries in the conditional request cache
+    credentials: u64,
+    api_base: String,
 }
 
 impl BitbucketClient {
-    /// Create a new Bitbucket client with basic auth credentials
-    pub fn new(username: &str, app_password: &str) -> Self {
-        let credentials = format!("{}:{}", username, app_password);
-        let auth_header = format!("Basic {}", STANDARD.encode(credentials));
+    /// Create a new Bitbucket client with credentials and TLS options
+    ///
+    /// An empty username or `ACCESS_TOKEN_USERNAME` means `app_password` is an access
+    /// token, sent as a Bearer token; anything else uses basic auth.
+    pub fn new(
+        username: &str,
+        app_password: &str,
+        tls: &TlsConfig,
+    ) -> Result<Self, BitbucketError> {
+        let auth_header = if is_access_token_username(username) {
+            format!("Bearer {}", app_password)
+        } else {
+            let credentials = format!("{}:{}", username, app_password);
+            format!("Basic {}", STANDARD.encode(credentials))
+        };
 
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = tls.build_client(Duration::from_secs(30))?;
 
-        Self {
+        Ok(Self {
             client,
+            credentials: conditional::credentials_fingerprint(&auth_header),
             auth_header,
-        }
+            api_base: BITBUCKET_API_BASE.to_string(),
+        })
+    }
+
+    /// Send API requests to another base URL (e.g. a mock server in tests)
+    pub fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
     }
 
     /// Get all workspaces accessible to the authenticated user
     pub async fn get_workspaces(&self) -> Result<Vec<Workspace>, BitbucketError> {
-        let url = format!("{}/workspaces?pagelen=100", BITBUCKET_API_BASE);
-        let response: PaginatedResponse<Workspace> = self.get(&url).await?;
-        Ok(response.values)
+        let url = format!("{}/workspaces?pagelen=100", self.api_base);
+        self.get_all(&url).await
     }
 
     /// Get all projects in a workspace
     pub async fn get_projects(&self, workspace: &str) -> Result<Vec<Project>, BitbucketError> {
+        check_slugs(workspace, None)?;
         let url = format!(
             "{}/workspaces/{}/projects?pagelen=100",
-            BITBUCKET_API_BASE, workspace
+            self.api_base, workspace
         );
-        let response: PaginatedResponse<Project> = self.get(&url).await?;
-        Ok(response.values)
+        self.get_all(&url).await
+    }
+
+    /// Get all members of a workspace (needs the `account` read scope)
+    pub async fn get_workspace_members(
+        &self,
+        workspace: &str,
+    ) -> Result<Vec<WorkspaceMember>, BitbucketError> {
+        check_slugs(workspace, None)?;
+        let url = format!(
+            "{}/workspaces/{}/members?pagelen=100",
+            self.api_base, workspace
+        );
+        let memberships: Vec<WorkspaceMembership> = self.get_all(&url).await?;
+        Ok(memberships.into_iter().map(|m| m.user).collect())
     }
 
     /// Get all repositories in a workspace
     pub async fn get_repositories(&self, workspace: &str) -> Result<Vec<Repository>, BitbucketError> {
+        check_slugs(workspace, None)?;
         let url = format!(
             "{}/repositories/{}?pagelen=100&sort=-updated_on",
-            BITBUCKET_API_BASE, workspace
+            self.api_base, workspace
         );
-        let response: PaginatedResponse<Repository> = self.get(&url).await?;
-        Ok(response.values)
+        self.get_all(&url).await
+    }
+
+    /// Get a single repository
+    pub async fn get_repository(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+    ) -> Result<Repository, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let url = format!(
+            "{}/repositories/{}/{}",
+            self.api_base, workspace, repo_slug
+        );
+        let (repository, _) = self.get(&url).await?;
+        Ok(repository)
+    }
+
+    /// Get a repository's Pipelines settings, e.g. whether Pipelines is enabled
+    pub async fn get_pipelines_config(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+    ) -> Result<PipelinesConfig, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let url = format!(
+            "{}/repositories/{}/{}/pipelines_config",
+            self.api_base, workspace, repo_slug
+        );
+        let (config, _) = self.get(&url).await?;
+        Ok(config)
+    }
+
+    /// Get a repository's scheduled pipeline triggers
+    pub async fn get_pipeline_schedules(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+    ) -> Result<Vec<PipelineSchedule>, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let url = format!(
+            "{}/repositories/{}/{}/pipelines_config/schedules/",
+            self.api_base, workspace, repo_slug
+        );
+        self.get_all(&url).await
     }
 
     /// Get repositories in a workspace filtered by project key
@@ -76,26 +330,59 @@ impl BitbucketClient {
         workspace: &str,
         project_key: &str,
     ) -> Result<Vec<Repository>, BitbucketError> {
+        check_slugs(workspace, None)?;
         let url = format!(
             "{}/repositories/{}?pagelen=100&sort=-updated_on&q=project.key=\"{}\"",
-            BITBUCKET_API_BASE, workspace, project_key
+            self.api_base, workspace, project_key
         );
-        let response: PaginatedResponse<Repository> = self.get(&url).await?;
-        Ok(response.values)
+        self.get_all(&url).await
     }
 
-    /// Get recent pipelines for a repository
+    /// Get recent pipelines for a repository, optionally filtered by branch
     pub async fn get_pipelines(
         &self,
         workspace: &str,
         repo_slug: &str,
+        branch: Option<&str>,
         limit: u32,
     ) -> Result<Vec<Pipeline>, BitbucketError> {
-        let url = format!(
-            "{}/repositories/{}/{}/pipelines/?sort=-created_on&pagelen={}",
-            BITBUCKET_API_BASE, workspace, repo_slug, limit
+        let filter = branch.map(|branch| ("target.branch", branch));
+        self.list_pipelines(workspace, repo_slug, filter, limit).await
+    }
+
+    /// Get recent pipelines matching a `q` query, e.g. `target.ref_name="main"`
+    pub async fn get_pipelines_filtered(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<Pipeline>, BitbucketError> {
+        self.list_pipelines(workspace, repo_slug, Some(("q", query)), limit)
+            .await
+    }
+
+    // Helper: Most recent pipelines first, with an optional query filter
+    async fn list_pipelines(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+        filter: Option<(&str, &str)>,
+        limit: u32,
+    ) -> Result<Vec<Pipeline>, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let base = format!(
+            "{}/repositories/{}/{}/pipelines/",
+            self.api_base, workspace, repo_slug
         );
-        let response: PaginatedResponse<Pipeline> = self.get(&url).await?;
+        let limit = limit.to_string();
+        let mut params = vec![("sort", "-created_on"), ("pagelen", limit.as_str())];
+        if let Some(filter) = filter {
+            params.push(filter);
+        }
+        let url = reqwest::Url::parse_with_params(&base, &params)
+            .map_err(|e| BitbucketError::ApiError(format!("Invalid pipelines URL: {}", e)))?;
+        let (response, _): (PaginatedResponse<Pipeline>, _) = self.get(url.as_str()).await?;
         Ok(response.values)
     }
 
@@ -106,20 +393,75 @@ impl BitbucketClient {
         repo_slug: &str,
         branch: Option<&str>,
     ) -> Result<Option<Pipeline>, BitbucketError> {
-        // Fetch recent pipelines
-        let pipelines = self.get_pipelines(workspace, repo_slug, 20).await?;
-
-        // If branch filter is specified, find the first matching pipeline
         if let Some(branch_name) = branch {
-            Ok(pipelines
-                .into_iter()
-                .find(|p| p.target.ref_name.as_deref() == Some(branch_name)))
+            // Double-check the branch in case the API ignores the filter
+            let on_branch = |pipelines: Vec<Pipeline>| {
+                pipelines
+                    .into_iter()
+                    .find(|p| p.target.ref_name.as_deref() == Some(branch_name))
+            };
+
+            // Only that branch's runs come back, so a short page is enough
+            let query = format!("target.ref_name={}", quote_query_value(branch_name));
+            let pipelines = self
+                .get_pipelines_filtered(workspace, repo_slug, &query, BRANCH_PAGE_LEN)
+                .await?;
+            if let Some(pipeline) = on_branch(pipelines) {
+                return Ok(Some(pipeline));
+            }
+
+            // `q` isn't documented for pipelines, so before concluding the branch has no
+            // runs, look through a longer page filtered by the `target.branch` parameter
+            let pipelines = self
+                .get_pipelines(workspace, repo_slug, Some(branch_name), BRANCH_FALLBACK_PAGE_LEN)
+                .await?;
+            Ok(on_branch(pipelines))
         } else {
             // Return the most recent pipeline
+            let pipelines = self.get_pipelines(workspace, repo_slug, None, 20).await?;
             Ok(pipelines.into_iter().next())
         }
     }
 
+    /// Get the latest run of a specific pipeline definition (e.g. a custom pipeline), on any branch
+    pub async fn get_latest_pipeline_by_selector(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+        selector_uuid: &str,
+    ) -> Result<Option<Pipeline>, BitbucketError> {
+        let filter = Some(("target.selector.pattern", selector_uuid));
+        let pipelines = self.list_pipelines(workspace, repo_slug, filter, 20).await?;
+
+        // Double-check the selector in case the API ignores the filter
+        Ok(pipelines.into_iter().find(|p| {
+            p.target
+                .selector
+                .as_ref()
+                .and_then(|s| s.pattern.as_deref())
+                == Some(selector_uuid)
+        }))
+    }
+
+    /// Get a pipeline by its build number, returning None if it doesn't exist
+    pub async fn get_pipeline_by_build_number(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+        build_number: u32,
+    ) -> Result<Option<Pipeline>, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let url = format!(
+            "{}/repositories/{}/{}/pipelines/{}",
+            self.api_base, workspace, repo_slug, build_number
+        );
+        match self.get(&url).await {
+            Ok((pipeline, _)) => Ok(Some(pipeline)),
+            Err(BitbucketError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Get steps for a specific pipeline
     pub async fn get_pipeline_steps(
         &self,
@@ -127,48 +469,730 @@ impl BitbucketClient {
         repo_slug: &str,
         pipeline_uuid: &str,
     ) -> Result<Vec<PipelineStep>, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
         let url = format!(
             "{}/repositories/{}/{}/pipelines/{}/steps/",
-            BITBUCKET_API_BASE, workspace, repo_slug, pipeline_uuid
+            self.api_base, workspace, repo_slug, pipeline_uuid
         );
-        let response: PaginatedResponse<PipelineStep> = self.get(&url).await?;
+        let (response, _): (PaginatedResponse<PipelineStep>, _) = self.get(&url).await?;
         Ok(response.values)
     }
 
-    /// Validate credentials by attempting to fetch workspaces
-    pub async fn validate_credentials(&self) -> Result<bool, BitbucketError> {
-        match self.get_workspaces().await {
-            Ok(_) => Ok(true),
-            Err(BitbucketError::AuthenticationFailed) => Ok(false),
+    /// Get the first pending step of a run, i.e. the manual step a paused pipeline waits on
+    pub async fn get_pending_step(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+        pipeline_uuid: &str,
+    ) -> Result<Option<PipelineStep>, BitbucketError> {
+        let steps = self
+            .get_pipeline_steps(workspace, repo_slug, pipeline_uuid)
+            .await?;
+        Ok(steps.into_iter().find(|s| s.is_pending()))
+    }
+
+    /// Start a custom pipeline (e.g. `custom: deploy-staging`) on a branch
+    pub async fn run_custom_pipeline(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+        branch: &str,
+        selector: &str,
+        variables: &[PipelineVariable],
+    ) -> Result<Pipeline, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let url = format!(
+            "{}/repositories/{}/{}/pipelines/",
+            self.api_base, workspace, repo_slug
+        );
+        let mut body = serde_json::json!({
+            "target": {
+                "type": "pipeline_ref_target",
+                "ref_type": "branch",
+                "ref_name": branch,
+                "selector": { "type": "custom", "pattern": selector },
+            },
+        });
+        if !variables.is_empty() {
+            body["variables"] = serde_json::json!(variables);
+        }
+        self.post(&url, &body).await
+    }
+
+    /// Get the authenticated user's open pull requests in a repository
+    ///
+    /// Needs an account: workspace and repository access tokens can't read `/user`.
+    pub async fn get_my_open_pull_requests(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+    ) -> Result<Vec<PullRequest>, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let uuid = self
+            .get_current_user()
+            .await?
+            .uuid
+            .ok_or_else(|| BitbucketError::ApiError("Account has no uuid".to_string()))?;
+        let base = format!(
+            "{}/repositories/{}/{}/pullrequests",
+            self.api_base, workspace, repo_slug
+        );
+        let query = format!("state=\"OPEN\" AND author.uuid=\"{}\"", uuid);
+        let url = reqwest::Url::parse_with_params(&base, &[("q", query.as_str()), ("pagelen", "50")])
+            .map_err(|e| BitbucketError::ApiError(format!("Invalid pull requests URL: {}", e)))?;
+        self.get_all(url.as_str()).await
+    }
+
+    /// Get a repository's deployment environments
+    pub async fn get_environments(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+    ) -> Result<Vec<Environment>, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let url = format!(
+            "{}/repositories/{}/{}/environments/?pagelen=100",
+            self.api_base, workspace, repo_slug
+        );
+        self.get_all(&url).await
+    }
+
+    /// Commit of the latest successful deployment to an environment, if it has one
+    pub async fn get_latest_deployed_commit(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+        environment_uuid: &str,
+    ) -> Result<Option<String>, BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let base = format!(
+            "{}/repositories/{}/{}/deployments/",
+            self.api_base, workspace, repo_slug
+        );
+        let query = format!("environment.uuid=\"{}\"", environment_uuid);
+        let url = reqwest::Url::parse_with_params(
+            &base,
+            &[
+                ("q", query.as_str()),
+                ("sort", "-state.completed_on"),
+                ("pagelen", "20"),
+            ],
+        )
+        .map_err(|e| BitbucketError::ApiError(format!("Invalid deployments URL: {}", e)))?;
+        // Failed and running deployments come first after a bad deploy, so skip past them
+        let (response, _): (PaginatedResponse<Deployment>, _) = self.get(url.as_str()).await?;
+        Ok(response
+            .values
+            .iter()
+            .find_map(Deployment::successful_commit)
+            .map(str::to_string))
+    }
+
+    /// Number of commits reachable from `include` but not from `exclude`
+    ///
+    /// Also returns whether counting stopped at `MAX_PAGES`, in which case the
+    /// number is only a lower bound.
+    pub async fn count_commits_ahead(
+        &self,
+        workspace: &str,
+        repo_slug: &str,
+        include: &str,
+        exclude: &str,
+    ) -> Result<(u32, bool), BitbucketError> {
+        check_slugs(workspace, Some(repo_slug))?;
+        let base = format!(
+            "{}/repositories/{}/{}/commits/{}",
+            self.api_base, workspace, repo_slug, include
+        );
+        // Only the hashes are needed to count
+        let url = reqwest::Url::parse_with_params(
+            &base,
+            &[
+                ("exclude", exclude),
+                ("fields", "next,values.hash"),
+                ("pagelen", "100"),
+            ],
+        )
+        .map_err(|e| BitbucketError::ApiError(format!("Invalid commits URL: {}", e)))?;
+        let (commits, truncated): (Vec<CommitRef>, _) = self.get_pages(url.as_str()).await?;
+        Ok((commits.len() as u32, truncated))
+    }
+
+    /// Get the authenticated user's account
+    pub async fn get_current_user(&self) -> Result<Account, BitbucketError> {
+        let url = format!("{}/user", self.api_base);
+        let (account, _) = self.get(&url).await?;
+        Ok(account)
+    }
+
+    /// Check which scopes the credentials have by probing account, repository and
+    /// pipeline endpoints
+    ///
+    /// Pipeline access is probed against the first repository found; with no
+    /// repositories to probe it is assumed readable.
+    pub async fn validate_credentials(&self) -> Result<CredentialCheck, BitbucketError> {
+        let mut check = CredentialCheck::default();
+
+        match self.get_current_user().await {
+            Ok(_) => check.can_read_account = true,
+            Err(BitbucketError::AuthenticationFailed) => return Ok(check),
+            Err(BitbucketError::Forbidden(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        self.probe_workspaces(&mut check).await?;
+        Ok(check)
+    }
+
+    /// Check which scopes an access token has
+    ///
+    /// A repository token can't list workspaces even when it is valid, so with
+    /// `repository` the token is checked against that repository first; a rejection
+    /// there means the token itself is invalid. Without one, the workspace listing
+    /// decides: rejected means invalid, forbidden means the token lacks workspace scope.
+    pub async fn validate_access_token(
+        &self,
+        repository: Option<(&str, &str)>,
+    ) -> Result<CredentialCheck, BitbucketError> {
+        let mut check = CredentialCheck::default();
+
+        let Some((workspace, repo_slug)) = repository else {
+            self.probe_workspaces(&mut check).await?;
+            return Ok(check);
+        };
+
+        self.get_repository(workspace, repo_slug).await?;
+        check.can_read_repos = true;
+        check.can_read_pipelines = self.can_read_pipelines(workspace, repo_slug).await?;
+        check.can_list_workspaces = match self.get_workspaces().await {
+            Ok(_) => true,
+            Err(BitbucketError::AuthenticationFailed | BitbucketError::Forbidden(_)) => false,
+            Err(e) => return Err(e),
+        };
+        Ok(check)
+    }
+
+    // Helper: List workspaces, then probe repository and pipeline access through the first
+    // repository found; with no repositories to probe pipelines are assumed readable
+    async fn probe_workspaces(&self, check: &mut CredentialCheck) -> Result<(), BitbucketError> {
+        let workspaces = self.get_workspaces().await?;
+        check.can_list_workspaces = true;
+        check.can_read_pipelines = match self.find_probe_repo(workspaces, check).await? {
+            Some((workspace, repo_slug)) => self.can_read_pipelines(&workspace, &repo_slug).await?,
+            None => check.can_read_repos,
+        };
+        Ok(())
+    }
+
+    // Helper: First repository of the given workspaces, noting whether any could be listed
+    async fn find_probe_repo(
+        &self,
+        workspaces: Vec<Workspace>,
+        check: &mut CredentialCheck,
+    ) -> Result<Option<(String, String)>, BitbucketError> {
+        for workspace in workspaces {
+            let url = format!(
+                "{}/repositories/{}?pagelen=1&sort=-updated_on",
+                self.api_base, workspace.slug
+            );
+            match self.get::<PaginatedResponse<Repository>>(&url).await {
+                Ok((response, _)) => {
+                    check.can_read_repos = true;
+                    if let Some(repo) = response.values.into_iter().next() {
+                        return Ok(Some((workspace.slug, repo.slug)));
+                    }
+                }
+                Err(BitbucketError::Forbidden(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+
+    // Helper: Whether the pipelines of a repository can be read
+    async fn can_read_pipelines(&self, workspace: &str, repo_slug: &str) -> Result<bool, BitbucketError> {
+        match self.get_pipelines(workspace, repo_slug, None, 1).await {
+            // Pipelines not enabled on the repo still means the scope is there
+            Ok(_) | Err(BitbucketError::NotFound(_)) => Ok(true),
+            Err(BitbucketError::Forbidden(_)) => Ok(false),
             Err(e) => Err(e),
         }
     }
 
-    /// Make a GET request to the Bitbucket API
-    async fn get<T: for<'de> serde::Deserialize<'de>>(
+    /// GET a paginated listing, following `next` links
+    async fn get_all<T: for<'de> serde::Deserialize<'de> + Clone + Send + Sync + 'static>(
+        &self,
+        url: &str,
+    ) -> Result<Vec<T>, BitbucketError> {
+        Ok(self.get_pages(url).await?.0)
+    }
+
+    /// Like `get_all`, also returning whether pages were left unfetched at `MAX_PAGES`
+    async fn get_pages<T: for<'de> serde::Deserialize<'de> + Clone + Send + Sync + 'static>(
+        &self,
+        url: &str,
+    ) -> Result<(Vec<T>, bool), BitbucketError> {
+        let mut values = Vec::new();
+        let mut next = Some(url.to_string());
+        let mut pages = 0;
+        while let Some(url) = next {
+            if pages == MAX_PAGES {
+                tracing::warn!("Stopped after {} pages of {}", MAX_PAGES, url);
+                return Ok((values, true));
+            }
+            let (response, _): (PaginatedResponse<T>, _) = self.get(&url).await?;
+            values.extend(response.values);
+            next = response.next;
+            pages += 1;
+        }
+        Ok((values, false))
+    }
+
+    /// Make a GET request to the Bitbucket API, returning the quota left when reported
+    ///
+    /// A response seen before is revalidated with its `ETag` or `Last-Modified`; when
+    /// Bitbucket answers 304 Not Modified the earlier parsed value is returned.
+    async fn get<T: for<'de> serde::Deserialize<'de> + Clone + Send + Sync + 'static>(
         &self,
         url: &str,
+    ) -> Result<(T, Option<RateLimitInfo>), BitbucketError> {
+        let cached = conditional::lookup::<T>(self.credentials, url);
+        let mut request = self
+            .client
+            .get(url)
+            .header(header::AUTHORIZATION, &self.auth_header)
+            .header(header::ACCEPT, "application/json");
+        if let Some(cached) = &cached {
+            request = cached.validators.apply(request);
+        }
+        let response = request.send().await.map_err(classify_send_error)?;
+
+        let rate_limit = self.track(url, &response);
+        if let (reqwest::StatusCode::NOT_MODIFIED, Some(cached)) = (response.status(), cached) {
+            conditional::record_hit();
+            return Ok(((*cached.value).clone(), rate_limit));
+        }
+        let validators = conditional::Validators::from_headers(response.headers());
+        let value: T = read_response(url, response).await?;
+        conditional::record_miss(self.credentials, url, validators, value.clone());
+        Ok((value, rate_limit))
+    }
+
+    /// Make a POST request with a JSON body to the Bitbucket API
+    async fn post<B: serde::Serialize, T: for<'de> serde::Deserialize<'de>>(
+        &self,
+        url: &str,
+        body: &B,
     ) -> Result<T, BitbucketError> {
         let response = self
             .client
-            .get(url)
+            .post(url)
             .header(header::AUTHORIZATION, &self.auth_header)
             .header(header::ACCEPT, "application/json")
+            .json(body)
             .send()
-            .await?;
+            .await
+            .map_err(classify_send_error)?;
+
+        self.track(url, &response);
+        read_response(url, response).await
+    }
 
-        match response.status().as_u16() {
-            200 => Ok(response.json().await?),
-            401 => Err(BitbucketError::AuthenticationFailed),
-            429 => Err(BitbucketError::RateLimited),
-            404 => Err(BitbucketError::NotFound(url.to_string())),
-            status => {
-                let body = response.text().await.unwrap_or_default();
-                Err(BitbucketError::ApiError(format!(
-                    "Status {}: {}",
-                    status, body
-                )))
+    // Helper: Count a request against its workspace's API budget and note the quota left
+    fn track(&self, url: &str, response: &reqwest::Response) -> Option<RateLimitInfo> {
+        let rate_limit = rate_limit_of(response.headers());
+        if let Some(rate_limit) = rate_limit {
+            budget::record_rate_limit(rate_limit);
+        }
+        let path = url.strip_prefix(self.api_base.as_str()).unwrap_or(url);
+        let Some(workspace) = workspace_of(path) else {
+            return rate_limit;
+        };
+        let near_limit = response.status().as_u16() == 429
+            || response
+                .headers()
+                .get("x-ratelimit-nearlimit")
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+        budget::record_request(workspace, near_limit);
+        rate_limit
+    }
+}
+
+/// Quota from the `X-RateLimit-*` headers, when all three are present
+fn rate_limit_of(headers: &header::HeaderMap) -> Option<RateLimitInfo> {
+    fn number<N: std::str::FromStr>(headers: &header::HeaderMap, name: &str) -> Option<N> {
+        headers.get(name)?.to_str().ok()?.trim().parse().ok()
+    }
+    Some(RateLimitInfo {
+        limit: number(headers, "x-ratelimit-limit")?,
+        remaining: number(headers, "x-ratelimit-remaining")?,
+        reset_at: number(headers, "x-ratelimit-reset")?,
+    })
+}
+
+/// Whether a saved username stands for an access token rather than an account
+pub fn is_access_token_username(username: &str) -> bool {
+    username.is_empty() || username == ACCESS_TOKEN_USERNAME
+}
+
+/// Workspace an API path belongs to, e.g. `acme` for `/repositories/acme/api/pipelines/`
+fn workspace_of(path: &str) -> Option<&str> {
+    let path = path.split('?').next()?;
+    let mut segments = path.trim_start_matches('/').split('/');
+    match segments.next()? {
+        "repositories" | "workspaces" => segments.next().filter(|s| !s.is_empty()),
+        _ => None,
+    }
+}
+
+/// Decode a successful response, or map its status to a `BitbucketError`
+async fn read_response<T: for<'de> serde::Deserialize<'de>>(
+    url: &str,
+    response: reqwest::Response,
+) -> Result<T, BitbucketError> {
+    match response.status().as_u16() {
+        200 | 201 => Ok(response.json().await?),
+        401 => Err(BitbucketError::AuthenticationFailed),
+        403 => Err(BitbucketError::Forbidden(url.to_string())),
+        429 => {
+            let retry_after = response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok());
+            Err(BitbucketError::RateLimited(retry_after))
+        }
+        404 => Err(BitbucketError::NotFound(url.to_string())),
+        status => {
+            let body = response.text().await.unwrap_or_default();
+            Err(BitbucketError::ApiError(format!(
+                "Status {}: {}",
+                status, body
+            )))
+        }
+    }
+}
+
+/// Distinguish certificate failures from other transport errors
+fn classify_send_error(error: reqwest::Error) -> BitbucketError {
+    let mut source: Option<&dyn std::error::Error> = Some(&error);
+    while let Some(e) = source {
+        let message = e.to_string().to_lowercase();
+        if message.contains("certificate") || message.contains("tls") {
+            return BitbucketError::Tls(e.to_string());
+        }
+        source = e.source();
+    }
+    BitbucketError::Http(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIPELINE_WITH_LINKS: &str = r#"{
+        "uuid": "{p1}",
+        "build_number": 142,
+        "state": { "name": "COMPLETED", "result": { "name": "FAILED" } },
+        "target": { "ref_type": "branch", "ref_name": "main" },
+        "created_on": "2024-01-01T00:00:00Z",
+        "completed_on": "2024-01-01T00:05:00Z",
+        "links": {
+            "self": { "href": "https://api.example.com/2.0/repositories/ws/repo/pipelines/{p1}" },
+            "html": { "href": "https://code.example.com/ws/repo/pipelines/results/142" }
+        }
+    }"#;
+
+    const PIPELINE_WITHOUT_LINKS: &str = r#"{
+        "uuid": "{p2}",
+        "build_number": 7,
+        "state": { "name": "IN_PROGRESS" },
+        "target": { "ref_type": "branch", "ref_name": "main" },
+        "created_on": "2024-01-01T00:00:00Z",
+        "completed_on": null
+    }"#;
+
+    const REPOSITORY_WITH_LINKS: &str = r#"{
+        "uuid": "{r1}",
+        "slug": "repo",
+        "name": "Repo",
+        "full_name": "ws/repo",
+        "project": null,
+        "links": { "html": { "href": "https://code.example.com/ws/repo" } }
+    }"#;
+
+    const REPOSITORY_WITHOUT_LINKS: &str = r#"{
+        "uuid": "{r2}",
+        "slug": "repo",
+        "name": "Repo",
+        "full_name": "ws/repo",
+        "project": null
+    }"#;
+
+    fn pipeline(json: &str) -> Pipeline {
+        serde_json::from_str(json).expect("pipeline fixture should parse")
+    }
+
+    fn repository(json: &str) -> Repository {
+        serde_json::from_str(json).expect("repository fixture should parse")
+    }
+
+    #[test]
+    fn prefers_pipeline_html_link() {
+        let repo = repository(REPOSITORY_WITH_LINKS);
+        let url = resolve_pipeline_url(&pipeline(PIPELINE_WITH_LINKS), repo.html_url(), "ws", "repo");
+        assert_eq!(url, "https://code.example.com/ws/repo/pipelines/results/142");
+    }
+
+    #[test]
+    fn falls_back_to_repository_link() {
+        let repo = repository(REPOSITORY_WITH_LINKS);
+        let url = resolve_pipeline_url(&pipeline(PIPELINE_WITHOUT_LINKS), repo.html_url(), "ws", "repo");
+        assert_eq!(url, "https://code.example.com/ws/repo/pipelines/results/7");
+    }
+
+    #[test]
+    fn formats_url_when_links_are_missing() {
+        let repo = repository(REPOSITORY_WITHOUT_LINKS);
+        assert_eq!(repo.html_url(), None);
+        let url = resolve_pipeline_url(&pipeline(PIPELINE_WITHOUT_LINKS), repo.html_url(), "ws", "repo");
+        assert_eq!(url, "https://bitbucket.org/ws/repo/pipelines/results/7");
+    }
+
+    #[test]
+    fn pipelines_list_url_uses_repository_link() {
+        assert_eq!(
+            get_pipelines_list_url(Some("https://code.example.com/ws/repo/"), "ws", "repo"),
+            "https://code.example.com/ws/repo/pipelines"
+        );
+        assert_eq!(
+            get_pipelines_list_url(None, "ws", "repo"),
+            "https://bitbucket.org/ws/repo/pipelines"
+        );
+    }
+
+    #[test]
+    fn slugs_must_be_lowercase_without_spaces() {
+        assert!(is_valid_slug("my-team_2"));
+        assert!(!is_valid_slug("My-Workspace"));
+        assert!(!is_valid_slug("my workspace"));
+        assert!(!is_valid_slug(""));
+        assert!(is_valid_repo_slug("docs.example.com"));
+        assert!(!is_valid_repo_slug("."));
+        assert!(!is_valid_repo_slug("api/v2"));
+    }
+
+    #[test]
+    fn query_values_are_quoted_and_escaped() {
+        assert_eq!(quote_query_value("main"), r#""main""#);
+        assert_eq!(quote_query_value(r#"fix/"quoted""#), r#""fix/\"quoted\"""#);
+        assert_eq!(quote_query_value(r"back\slash"), r#""back\\slash""#);
+    }
+
+    #[test]
+    fn requests_are_budgeted_to_their_workspace() {
+        assert_eq!(workspace_of("/repositories/acme/api/pipelines/?sort=-created_on"), Some("acme"));
+        assert_eq!(workspace_of("/workspaces/acme/projects"), Some("acme"));
+        assert_eq!(workspace_of("/repositories/acme?page=2"), Some("acme"));
+        assert_eq!(workspace_of("/user"), None);
+        assert_eq!(workspace_of("/workspaces?pagelen=100"), None);
+    }
+
+    #[tokio::test]
+    async fn invalid_slugs_fail_without_a_request() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("GET", mockito::Matcher::Any).expect(0).create_async().await;
+        let client = client().with_api_base(&server.url());
+
+        match client.get_repositories("my workspace").await {
+            Err(BitbucketError::ApiError(message)) => {
+                assert_eq!(message, "Invalid workspace slug: \"my workspace\"")
             }
+            other => panic!("expected ApiError, got {:?}", other),
         }
+        let result = client.get_latest_pipeline("acme", "API", None).await;
+        assert!(matches!(result, Err(BitbucketError::ApiError(m)) if m.starts_with("Invalid repository slug")));
+        mock.assert_async().await;
+    }
+
+    fn client() -> BitbucketClient {
+        BitbucketClient::new("user", "secret", &TlsConfig::default())
+            .expect("client should build without TLS overrides")
+    }
+
+    /// Expected Authorization header for `client()` ("user:secret")
+    const AUTH_HEADER: &str = "Basic dXNlcjpzZWNyZXQ=";
+
+    #[tokio::test]
+    async fn get_maps_401_to_authentication_failed() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("GET", "/x").with_status(401).create_async().await;
+
+        let result = client().get::<Workspace>(&format!("{}/x", server.url())).await;
+
+        assert!(matches!(result, Err(BitbucketError::AuthenticationFailed)));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_maps_429_to_rate_limited() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("GET", "/x").with_status(429).create_async().await;
+
+        let result = client().get::<Workspace>(&format!("{}/x", server.url())).await;
+
+        assert!(matches!(result, Err(BitbucketError::RateLimited(None))));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_maps_404_to_not_found_with_url() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("GET", "/x").with_status(404).create_async().await;
+        let url = format!("{}/x", server.url());
+
+        let result = client().get::<Workspace>(&url).await;
+
+        match result {
+            Err(BitbucketError::NotFound(not_found)) => assert_eq!(not_found, url),
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_maps_500_to_api_error_with_status_and_body() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/x")
+            .with_status(500)
+            .with_body("boom")
+            .create_async()
+            .await;
+
+        let result = client().get::<Workspace>(&format!("{}/x", server.url())).await;
+
+        match result {
+            Err(BitbucketError::ApiError(message)) => assert_eq!(message, "Status 500: boom"),
+            other => panic!("expected ApiError, got {:?}", other),
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_parses_valid_json() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"uuid": "{w1}", "slug": "ws", "name": "Workspace"}"#)
+            .create_async()
+            .await;
+
+        let (workspace, rate_limit) = client()
+            .get::<Workspace>(&format!("{}/x", server.url()))
+            .await
+            .expect("valid JSON should parse");
+
+        assert_eq!(workspace.slug, "ws");
+        assert_eq!(rate_limit, None);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_reports_invalid_json_as_decode_error() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/x")
+            .with_status(200)
+            .with_body("not json")
+            .create_async()
+            .await;
+
+        let result = client().get::<Workspace>(&format!("{}/x", server.url())).await;
+
+        match result {
+            Err(BitbucketError::Http(e)) => assert!(e.is_decode()),
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_sends_basic_auth_header_on_every_request() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", mockito::Matcher::Any)
+            .match_header("authorization", AUTH_HEADER)
+            .with_status(200)
+            .with_body(r#"{"uuid": "{w1}", "slug": "ws", "name": "Workspace"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let client = client();
+
+        client.get::<Workspace>(&format!("{}/a", server.url())).await.unwrap();
+        client.get::<Workspace>(&format!("{}/b", server.url())).await.unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_returns_rate_limit_headers() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/x")
+            .with_status(200)
+            .with_header("x-ratelimit-limit", "1000")
+            .with_header("x-ratelimit-remaining", "7")
+            .with_header("x-ratelimit-reset", "1704067200")
+            .with_body(r#"{"uuid": "{w1}", "slug": "ws", "name": "Workspace"}"#)
+            .create_async()
+            .await;
+
+        let (_, rate_limit) = client()
+            .get::<Workspace>(&format!("{}/x", server.url()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            rate_limit,
+            Some(RateLimitInfo {
+                limit: 1000,
+                remaining: 7,
+                reset_at: 1704067200,
+            })
+        );
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn workspace_members_are_unwrapped_from_memberships() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/workspaces/acme/members?pagelen=100")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"values": [{"type": "workspace_membership", "user": {
+                    "account_id": "557058:1", "display_name": "Ada Lovelace", "nickname": "ada"
+                }}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let members = client()
+            .with_api_base(&server.url())
+            .get_workspace_members("acme")
+            .await
+            .expect("members should parse");
+
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].account_id, "557058:1");
+        assert_eq!(members[0].display_name, "Ada Lovelace");
+        assert_eq!(members[0].nickname, "ada");
+        mock.assert_async().await;
     }
 }
@@ -0,0 +1,252 @@
+use reqwest::header::{self, HeaderMap};
+use serde::Serialize;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// Responses kept for conditional requests; the least recently used goes first
+pub const CONDITIONAL_CACHE_CAPACITY: usize = 256;
+
+// Parsed responses of every client in this process, by credentials and URL
+static CONDITIONAL_CACHE: Mutex<ConditionalCache> =
+    Mutex::new(ConditionalCache::new(CONDITIONAL_CACHE_CAPACITY));
+
+/// `ETag` and `Last-Modified` of a response, sent back to ask whether it changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Validators of a response; None when it has neither header
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let validators = Self {
+            etag: value(header::ETAG),
+            last_modified: value(header::LAST_MODIFIED),
+        };
+        (validators != Self::default()).then_some(validators)
+    }
+
+    /// Ask the server to answer 304 Not Modified if the response is unchanged
+    pub fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+/// A cached response, parsed into the type it was requested as
+pub struct Cached<T> {
+    pub validators: Validators,
+    pub value: Arc<T>,
+}
+
+struct Entry {
+    validators: Validators,
+    value: Arc<dyn Any + Send + Sync>,
+    last_used: u64,
+}
+
+/// How often conditional requests saved a download, as shown in settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ConditionalCacheStats {
+    pub entries: usize,
+    /// Requests answered 304 Not Modified and served from the cache
+    pub hits: u64,
+    /// Requests that downloaded and parsed a full response
+    pub misses: u64,
+}
+
+/// Bounded least-recently-used cache of parsed responses with their validators
+///
+/// Entries are keyed by a fingerprint of the credentials as well as the URL, so
+/// responses seen with old credentials are never served with new ones.
+pub struct ConditionalCache {
+    capacity: usize,
+    entries: BTreeMap<(u64, String), Entry>,
+    /// Bumped on every use; the entry with the lowest `last_used` is evicted first
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl ConditionalCache {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The cached response for `url`, if there is one of type `T`
+    pub fn lookup<T: Send + Sync + 'static>(&mut self, credentials: u64, url: &str) -> Option<Cached<T>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(&(credentials, url.to_string()))?;
+        entry.last_used = self.clock;
+        Some(Cached {
+            validators: entry.validators.clone(),
+            value: entry.value.clone().downcast::<T>().ok()?,
+        })
+    }
+
+    pub fn store<T: Send + Sync + 'static>(
+        &mut self,
+        credentials: u64,
+        url: &str,
+        validators: Validators,
+        value: T,
+    ) {
+        self.clock += 1;
+        let key = (credentials, url.to_string());
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(
+            key,
+            Entry {
+                validators,
+                value: Arc::new(value),
+                last_used: self.clock,
+            },
+        );
+    }
+
+    pub fn record_hit(&mut self) {
+        self.hits += 1;
+    }
+
+    pub fn record_miss(&mut self) {
+        self.misses += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn stats(&self) -> ConditionalCacheStats {
+        ConditionalCacheStats {
+            entries: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
+
+/// Fingerprint of an Authorization header, so entries aren't keyed by the secret itself
+pub(crate) fn credentials_fingerprint(auth_header: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    auth_header.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub(crate) fn lookup<T: Send + Sync + 'static>(credentials: u64, url: &str) -> Option<Cached<T>> {
+    CONDITIONAL_CACHE.lock().ok()?.lookup(credentials, url)
+}
+
+pub(crate) fn record_hit() {
+    if let Ok(mut cache) = CONDITIONAL_CACHE.lock() {
+        cache.record_hit();
+    }
+}
+
+// Helper: Count a full download, keeping the response when it can be revalidated
+pub(crate) fn record_miss<T: Send + Sync + 'static>(
+    credentials: u64,
+    url: &str,
+    validators: Option<Validators>,
+    value: T,
+) {
+    if let Ok(mut cache) = CONDITIONAL_CACHE.lock() {
+        cache.record_miss();
+        if let Some(validators) = validators {
+            cache.store(credentials, url, validators, value);
+        }
+    }
+}
+
+/// Drop every cached response, e.g. when the credentials change
+pub fn clear_conditional_cache() {
+    if let Ok(mut cache) = CONDITIONAL_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+/// Hits, misses and size of the cache since launch
+pub fn conditional_cache_stats() -> ConditionalCacheStats {
+    CONDITIONAL_CACHE
+        .lock()
+        .map(|cache| cache.stats())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validators(etag: &str) -> Validators {
+        Validators {
+            etag: Some(etag.to_string()),
+            last_modified: None,
+        }
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let mut cache = ConditionalCache::new(2);
+        cache.store(1, "/a", validators("\"a\""), 1u32);
+        cache.store(1, "/b", validators("\"b\""), 2u32);
+        // Using /a makes /b the oldest
+        assert!(cache.lookup::<u32>(1, "/a").is_some());
+
+        cache.store(1, "/c", validators("\"c\""), 3u32);
+
+        assert!(cache.lookup::<u32>(1, "/b").is_none());
+        assert_eq!(*cache.lookup::<u32>(1, "/a").unwrap().value, 1);
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn entries_are_only_served_for_the_same_credentials_and_type() {
+        let mut cache = ConditionalCache::new(4);
+        cache.store(1, "/a", validators("\"a\""), "cached".to_string());
+
+        assert!(cache.lookup::<String>(2, "/a").is_none());
+        assert!(cache.lookup::<u32>(1, "/a").is_none());
+        let cached = cache.lookup::<String>(1, "/a").unwrap();
+        assert_eq!(cached.validators.etag.as_deref(), Some("\"a\""));
+        assert_eq!(*cached.value, "cached");
+    }
+
+    #[test]
+    fn responses_without_validators_are_not_cacheable() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Validators::from_headers(&headers), None);
+
+        headers.insert(header::LAST_MODIFIED, "Wed, 01 May 2024 09:00:00 GMT".parse().unwrap());
+        let validators = Validators::from_headers(&headers).unwrap();
+        assert_eq!(validators.etag, None);
+        assert_eq!(validators.last_modified.as_deref(), Some("Wed, 01 May 2024 09:00:00 GMT"));
+    }
+}
@@ -1,5 +1,12 @@
+pub mod budget;
 pub mod client;
+pub mod conditional;
 pub mod types;
 
-pub use client::BitbucketClient;
+pub use client::{
+    get_branch_url, get_pipelines_list_url, get_repository_url, is_access_token_username,
+    is_valid_repo_slug, is_valid_slug, resolve_pipeline_url, BitbucketClient, BitbucketError, TlsConfig,
+    ACCESS_TOKEN_USERNAME,
+};
+pub use budget::RateLimitInfo;
 pub use types::*;
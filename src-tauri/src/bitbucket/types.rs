@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use ts_rs::TS;
 
 /// Paginated response wrapper from Bitbucket API
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct PaginatedResponse<T> {
     pub values: Vec<T>,
     pub page: Option<u32>,
@@ -11,14 +13,34 @@ pub struct PaginatedResponse<T> {
 
 /// Bitbucket workspace
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(TS))]
 pub struct Workspace {
     pub uuid: String,
     pub slug: String,
     pub name: String,
 }
 
+/// Member of a workspace, as listed by `/workspaces/{workspace}/members`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(TS))]
+pub struct WorkspaceMember {
+    #[serde(default)]
+    pub account_id: String,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub nickname: String,
+}
+
+/// Workspace membership entry wrapping the member's account
+#[derive(Debug, Deserialize, Clone)]
+pub struct WorkspaceMembership {
+    pub user: WorkspaceMember,
+}
+
 /// Bitbucket project (within a workspace)
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(TS))]
 pub struct Project {
     pub uuid: String,
     pub key: String,
@@ -27,12 +49,52 @@ pub struct Project {
 
 /// Bitbucket repository
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(TS))]
 pub struct Repository {
     pub uuid: String,
     pub slug: String,
     pub name: String,
     pub full_name: String,
     pub project: Option<Project>,
+    #[serde(default)]
+    pub links: Option<Links>,
+    /// Branch the repository builds by default
+    #[serde(default)]
+    pub mainbranch: Option<BranchRef>,
+}
+
+/// Hypermedia links attached to API objects
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[cfg_attr(test, derive(TS))]
+pub struct Links {
+    /// Web page for the object
+    pub html: Option<Link>,
+}
+
+/// A single hypermedia link
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(TS))]
+pub struct Link {
+    pub href: String,
+}
+
+impl Links {
+    /// Web URL, if the API provided one
+    pub fn html_href(&self) -> Option<&str> {
+        self.html.as_ref().map(|l| l.href.as_str())
+    }
+}
+
+impl Repository {
+    /// Web URL of the repository, if the API provided one
+    pub fn html_url(&self) -> Option<&str> {
+        self.links.as_ref()?.html_href()
+    }
+
+    /// Name of the repository's main branch, if it has one
+    pub fn main_branch_name(&self) -> Option<&str> {
+        self.mainbranch.as_ref().map(|b| b.name.as_str())
+    }
 }
 
 /// Bitbucket pipeline
@@ -44,6 +106,17 @@ pub struct Pipeline {
     pub target: PipelineTarget,
     pub created_on: String,
     pub completed_on: Option<String>,
+    #[serde(default)]
+    pub links: Option<Links>,
+    /// How the run was started (Bitbucket Cloud reports this at the top level)
+    #[serde(default)]
+    pub trigger: Option<Trigger>,
+    /// Variables the run was started with; list responses usually leave these out
+    #[serde(default)]
+    pub variables: Option<Vec<PipelineVariable>>,
+    /// Account that started the run; the pusher for push-triggered runs
+    #[serde(default)]
+    pub creator: Option<Account>,
 }
 
 /// Pipeline state containing status and result
@@ -72,6 +145,175 @@ pub struct PipelineStage {
 pub struct PipelineResult {
     /// "SUCCESSFUL", "FAILED", "STOPPED", "EXPIRED", "ERROR"
     pub name: String,
+    /// Who stopped the pipeline (present when STOPPED)
+    pub terminator: Option<Account>,
+}
+
+/// Bitbucket user account (subset of fields)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Account {
+    pub display_name: Option<String>,
+    pub nickname: Option<String>,
+    pub uuid: Option<String>,
+}
+
+impl Account {
+    /// Best available name for display
+    pub fn name(&self) -> Option<&str> {
+        self.display_name.as_deref().or(self.nickname.as_deref())
+    }
+}
+
+/// Which API areas the saved credentials can read
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CredentialCheck {
+    pub can_read_account: bool,
+    pub can_read_repos: bool,
+    pub can_read_pipelines: bool,
+    /// False for repository access tokens, which only see their own repository
+    pub can_list_workspaces: bool,
+}
+
+/// Bitbucket pull request (subset of fields)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PullRequest {
+    pub id: u32,
+    pub title: String,
+    pub source: PullRequestEndpoint,
+    #[serde(default)]
+    pub links: Option<Links>,
+}
+
+/// Branch side of a pull request
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PullRequestEndpoint {
+    pub branch: BranchRef,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(TS))]
+pub struct BranchRef {
+    pub name: String,
+}
+
+impl PullRequest {
+    /// Web URL of the pull request, if the API provided one
+    pub fn html_url(&self) -> Option<&str> {
+        self.links.as_ref()?.html_href()
+    }
+}
+
+/// Repository Pipelines settings (subset of fields)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PipelinesConfig {
+    pub enabled: bool,
+}
+
+/// Scheduled trigger of a repository's pipelines
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(from = "ScheduleResponse")]
+pub struct PipelineSchedule {
+    pub uuid: String,
+    pub enabled: bool,
+    /// Seven-field cron expression, evaluated in UTC
+    pub cron_pattern: String,
+    /// Branch the scheduled runs are for
+    pub branch: Option<String>,
+    /// When the next run is due, if Bitbucket reports it
+    pub next_scheduled: Option<String>,
+}
+
+/// Schedule as the API returns it, with the branch nested in its target
+#[derive(Deserialize)]
+struct ScheduleResponse {
+    uuid: String,
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    cron_pattern: String,
+    #[serde(default)]
+    target: Option<ScheduleTarget>,
+    #[serde(default)]
+    next_scheduled: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ScheduleTarget {
+    ref_name: Option<String>,
+}
+
+impl From<ScheduleResponse> for PipelineSchedule {
+    fn from(response: ScheduleResponse) -> Self {
+        Self {
+            uuid: response.uuid,
+            enabled: response.enabled,
+            cron_pattern: response.cron_pattern,
+            branch: response.target.and_then(|t| t.ref_name),
+            next_scheduled: response.next_scheduled,
+        }
+    }
+}
+
+/// Bitbucket deployment environment, e.g. "Staging"
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Environment {
+    pub uuid: String,
+    pub name: String,
+    #[serde(default)]
+    pub slug: Option<String>,
+}
+
+impl Environment {
+    /// Whether `name` refers to this environment, by name or slug, ignoring case
+    pub fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self.slug.as_deref().is_some_and(|slug| slug.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Bitbucket deployment (subset of fields)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Deployment {
+    pub state: DeploymentState,
+    #[serde(default)]
+    pub release: Option<DeploymentRelease>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeploymentState {
+    /// e.g. "COMPLETED", "IN_PROGRESS"
+    pub name: String,
+    /// Outcome of a completed deployment, e.g. "SUCCESSFUL"
+    #[serde(default)]
+    pub status: Option<DeploymentStatus>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeploymentStatus {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeploymentRelease {
+    #[serde(default)]
+    pub commit: Option<CommitRef>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CommitRef {
+    pub hash: String,
+}
+
+impl Deployment {
+    /// Commit of a deployment that completed successfully
+    pub fn successful_commit(&self) -> Option<&str> {
+        let successful = self.state.name == "COMPLETED"
+            && self.state.status.as_ref().is_some_and(|s| s.name == "SUCCESSFUL");
+        if !successful {
+            return None;
+        }
+        Some(self.release.as_ref()?.commit.as_ref()?.hash.as_str())
+    }
 }
 
 /// Pipeline target (branch/tag info)
@@ -79,6 +321,59 @@ pub struct PipelineResult {
 pub struct PipelineTarget {
     pub ref_type: Option<String>,
     pub ref_name: Option<String>,
+    /// How the run was started, when reported on the target
+    #[serde(default)]
+    pub trigger: Option<Trigger>,
+    /// Which pipeline definition in bitbucket-pipelines.yml ran
+    #[serde(default)]
+    pub selector: Option<PipelineSelector>,
+    /// Variables of the run, when reported on the target
+    #[serde(default)]
+    pub variables: Option<Vec<PipelineVariable>>,
+}
+
+/// Pipeline definition a run was started from
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PipelineSelector {
+    /// e.g. "branches", "tags", "custom" or "default"
+    #[serde(rename = "type")]
+    pub selector_type: Option<String>,
+    /// Branch/tag glob or custom pipeline name
+    pub pattern: Option<String>,
+}
+
+/// A variable passed to a pipeline run started through the API
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct PipelineVariable {
+    pub key: String,
+    /// Secured values come back from the API already masked
+    #[serde(default)]
+    pub value: String,
+    /// Hide the value in Bitbucket's logs and UI
+    #[serde(default)]
+    pub secured: bool,
+}
+
+/// What started a pipeline run
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Trigger {
+    /// e.g. "pipeline_trigger_manual", "pipeline_trigger_push"
+    #[serde(rename = "type")]
+    pub trigger_type: Option<String>,
+    /// e.g. "MANUAL", "PUSH", "PULLREQUEST", "SCHEDULE"
+    pub name: Option<String>,
+}
+
+impl Trigger {
+    /// Short lowercase label, e.g. "schedule"
+    pub fn label(&self) -> Option<String> {
+        if let Some(name) = &self.name {
+            return Some(name.to_lowercase());
+        }
+        self.trigger_type
+            .as_deref()
+            .map(|t| t.trim_start_matches("pipeline_trigger_").to_lowercase())
+    }
 }
 
 /// Pipeline step (individual stage in a pipeline)
@@ -95,6 +390,15 @@ pub struct StepState {
     pub name: Option<String>,
     #[serde(rename = "type")]
     pub state_type: Option<String>,
+    /// Present once the step has completed
+    pub result: Option<StepResult>,
+}
+
+/// Result of a completed pipeline step
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StepResult {
+    /// "SUCCESSFUL", "FAILED", "ERROR", "STOPPED", "NOT_RUN"
+    pub name: String,
 }
 
 impl PipelineStep {
@@ -110,6 +414,14 @@ impl PipelineStep {
         }
         false
     }
+
+    /// Check if this step completed with a failure
+    pub fn is_failed(&self) -> bool {
+        self.state
+            .as_ref()
+            .and_then(|s| s.result.as_ref())
+            .is_some_and(|r| matches!(r.name.as_str(), "FAILED" | "ERROR"))
+    }
 }
 
 impl Pipeline {
@@ -122,6 +434,19 @@ impl Pipeline {
         }
     }
 
+    /// Check if the pipeline was stopped manually
+    pub fn is_stopped(&self) -> bool {
+        self.state
+            .result
+            .as_ref()
+            .is_some_and(|r| r.name == "STOPPED")
+    }
+
+    /// Name of the user who stopped the pipeline, if known
+    pub fn stopped_by(&self) -> Option<&str> {
+        self.state.result.as_ref()?.terminator.as_ref()?.name()
+    }
+
     /// Check if the pipeline completed successfully
     pub fn is_successful(&self) -> bool {
         if let Some(result) = &self.state.result {
@@ -157,6 +482,49 @@ impl Pipeline {
         in_progress && !self.is_paused()
     }
 
+    /// Run time in seconds: until completion, or until now if still running
+    pub fn duration_secs(&self) -> Option<u64> {
+        let created = chrono::DateTime::parse_from_rfc3339(&self.created_on).ok()?;
+        let end = match &self.completed_on {
+            Some(completed) => chrono::DateTime::parse_from_rfc3339(completed).ok()?,
+            None => chrono::Utc::now().fixed_offset(),
+        };
+        u64::try_from((end - created).num_seconds()).ok()
+    }
+
+    /// Web URL of this run, if the API provided one
+    pub fn html_url(&self) -> Option<&str> {
+        self.links.as_ref()?.html_href()
+    }
+
+    /// How the run was started, e.g. "push" or "schedule"
+    pub fn trigger_type(&self) -> Option<String> {
+        self.trigger
+            .as_ref()
+            .or(self.target.trigger.as_ref())
+            .and_then(Trigger::label)
+    }
+
+    /// Whether the run was a custom pipeline or started by hand, the runs that take variables
+    pub fn is_custom_or_manual(&self) -> bool {
+        let custom = self
+            .target
+            .selector
+            .as_ref()
+            .and_then(|s| s.selector_type.as_deref())
+            == Some("custom");
+        custom || self.trigger_type().as_deref() == Some("manual")
+    }
+
+    /// Variables as (key, value) pairs, values exactly as the API returned them
+    pub fn variable_pairs(&self) -> Option<Vec<(String, String)>> {
+        self.variables
+            .as_ref()
+            .or(self.target.variables.as_ref())
+            .filter(|vars| !vars.is_empty())
+            .map(|vars| vars.iter().map(|v| (v.key.clone(), v.value.clone())).collect())
+    }
+
     /// Get the branch name if available
     pub fn branch(&self) -> Option<&str> {
         self.target.ref_name.as_deref()
@@ -1,17 +1,52 @@
-use crate::bitbucket::{BitbucketClient, Pipeline, Project, Repository, Workspace};
-use crate::config::{AppState, Credentials, MonitoredPipeline, OverallStatus, PersistedConfig};
+use crate::activity::{self, ActivityEvent};
+use crate::bitbucket::{budget, conditional};
+use crate::bitbucket::{
+    get_pipelines_list_url, get_repository_url, is_valid_repo_slug, is_valid_slug, BitbucketClient,
+    BitbucketError, CredentialCheck, Pipeline, PipelineSchedule, PipelineVariable, Project,
+    RateLimitInfo, Repository, Workspace, WorkspaceMember,
+};
+use crate::config::{
+    read_config_file, validate_monitored_pipelines, validate_polling_interval, write_config_file,
+    AppState, AttentionAnimation, ConnectionStatus, CredentialKind, Credentials, IconTheme,
+    MonitoredGroup, MonitoredPipeline, NotificationCategory, PersistedConfig, PipelineStatusInfo,
+    RunRecord, RunResult, SessionStats, StatusSnapshot, CONFIG_BACKUP_FILE, CONFIG_FILE,
+};
+use crate::default_branch::{self, ResolvedBranch};
+use crate::digest::{self, DigestSettings, WeeklyDigest};
+use crate::groups;
+use crate::hotkeys::{self, HotkeyError};
+use crate::i18n::{self, t, Locale};
+use crate::incidents::Incident;
+use crate::pipeline_detail::{self, PipelineDetail, PipelineKey};
+use crate::polling::{self, Notifier, SingleRefreshRequest};
+use crate::power::{self, PollMetrics, PollingPolicy, PollingStatus};
+use crate::quick_open::{self, StatusMatch};
+use crate::reliability::{self, ReliabilityReport};
+use crate::storage::{self, StorageStatus};
+use crate::tray;
+use crate::wallboard;
+use crate::webhooks::{self, NotificationRoutes, WebhookEvent, WebhookResponse};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
 use std::sync::Arc;
 use tauri::{command, AppHandle, Emitter, Manager, State};
-use tokio::sync::Mutex;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+/// How long the config writer waits for further changes before writing
+const CONFIG_WRITE_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How long a workspace's member list is reused before it is fetched again
+const MEMBER_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(3600);
 
 /// Get all workspaces accessible to the user
 #[command]
 pub async fn get_workspaces(
+    state: State<'_, Arc<Mutex<AppState>>>,
     username: String,
     app_password: String,
 ) -> Result<Vec<Workspace>, String> {
-    let client = BitbucketClient::new(&username, &app_password);
+    let client = build_client(&state, &username, &app_password).await?;
     client
         .get_workspaces()
         .await
@@ -21,11 +56,12 @@ pub async fn get_workspaces(
 /// Get all projects in a workspace
 #[command]
 pub async fn get_projects(
+    state: State<'_, Arc<Mutex<AppState>>>,
     username: String,
     app_password: String,
     workspace: String,
 ) -> Result<Vec<Project>, String> {
-    let client = BitbucketClient::new(&username, &app_password);
+    let client = build_client(&state, &username, &app_password).await?;
     client
         .get_projects(&workspace)
         .await
@@ -35,80 +71,319 @@ pub async fn get_projects(
 /// Get all repositories in a workspace
 #[command]
 pub async fn get_repositories(
+    state: State<'_, Arc<Mutex<AppState>>>,
     username: String,
     app_password: String,
     workspace: String,
 ) -> Result<Vec<Repository>, String> {
-    let client = BitbucketClient::new(&username, &app_password);
+    let client = build_client(&state, &username, &app_password).await?;
     client
         .get_repositories(&workspace)
         .await
         .map_err(|e| format!("{}", e))
 }
 
+/// Get the members of a workspace, for the team roster in settings
+#[command]
+pub async fn get_workspace_members(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    username: String,
+    app_password: String,
+    workspace: String,
+) -> Result<Vec<WorkspaceMember>, String> {
+    // Keyed by account too, since another account may see different members or none at all
+    let cache_key = (username.clone(), workspace.clone());
+    if let Some((fetched_at, members)) = state.lock().await.member_cache.get(&cache_key) {
+        if fetched_at.elapsed() < MEMBER_CACHE_TTL {
+            return Ok(members.clone());
+        }
+    }
+
+    let client = build_client(&state, &username, &app_password).await?;
+    let members = client
+        .get_workspace_members(&workspace)
+        .await
+        .map_err(|e| match e {
+            BitbucketError::Forbidden(_) => {
+                "Listing workspace members needs the Account: Read scope on your API token".to_string()
+            }
+            e => format!("{}", e),
+        })?;
+    state
+        .lock()
+        .await
+        .member_cache
+        .insert(cache_key, (std::time::Instant::now(), members.clone()));
+    Ok(members)
+}
+
+/// Look up one repository by slug, for showing its details before it is added
+#[command]
+pub async fn get_repository_details(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    username: String,
+    app_password: String,
+    workspace: String,
+    repo_slug: String,
+) -> Result<Repository, String> {
+    let client = build_client(&state, &username, &app_password).await?;
+    client
+        .get_repository(&workspace, &repo_slug)
+        .await
+        .map_err(|e| format!("{}", e))
+}
+
+/// Get a repository's scheduled pipeline triggers
+#[command]
+pub async fn get_pipeline_schedules(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    username: String,
+    app_password: String,
+    workspace: String,
+    repo_slug: String,
+) -> Result<Vec<PipelineSchedule>, String> {
+    let client = build_client(&state, &username, &app_password).await?;
+    client
+        .get_pipeline_schedules(&workspace, &repo_slug)
+        .await
+        .map_err(|e| format!("{}", e))
+}
+
 /// Get repositories filtered by project
 #[command]
 pub async fn get_repositories_by_project(
+    state: State<'_, Arc<Mutex<AppState>>>,
     username: String,
     app_password: String,
     workspace: String,
     project_key: String,
 ) -> Result<Vec<Repository>, String> {
-    let client = BitbucketClient::new(&username, &app_password);
+    let client = build_client(&state, &username, &app_password).await?;
     client
         .get_repositories_by_project(&workspace, &project_key)
         .await
         .map_err(|e| format!("{}", e))
 }
 
-/// Get recent pipelines for a repository
+/// Get recent pipelines for a repository, optionally for a single branch
+///
+/// Custom and manual runs carry their variables, fetched from the run's detail
+/// when the list leaves them out.
 #[command]
 pub async fn get_pipelines(
+    state: State<'_, Arc<Mutex<AppState>>>,
     username: String,
     app_password: String,
     workspace: String,
     repo_slug: String,
+    branch: Option<String>,
 ) -> Result<Vec<Pipeline>, String> {
-    let client = BitbucketClient::new(&username, &app_password);
+    let client = build_client(&state, &username, &app_password).await?;
+    let mut pipelines = client
+        .get_pipelines(&workspace, &repo_slug, branch.as_deref(), 10)
+        .await
+        .map_err(|e| format!("{}", e))?;
+    for pipeline in pipelines
+        .iter_mut()
+        .filter(|p| p.is_custom_or_manual() && p.variables.is_none() && p.target.variables.is_none())
+    {
+        if let Ok(Some(detail)) = client
+            .get_pipeline_by_build_number(&workspace, &repo_slug, pipeline.build_number)
+            .await
+        {
+            pipeline.variables = detail.variables.or(detail.target.variables);
+        }
+    }
+    Ok(pipelines)
+}
+
+/// Get a single pipeline by build number
+#[command]
+pub async fn get_pipeline_by_build_number(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    username: String,
+    app_password: String,
+    workspace: String,
+    repo_slug: String,
+    build_number: u32,
+) -> Result<Option<Pipeline>, String> {
+    let client = build_client(&state, &username, &app_password).await?;
     client
-        .get_pipelines(&workspace, &repo_slug, 10)
+        .get_pipeline_by_build_number(&workspace, &repo_slug, build_number)
         .await
         .map_err(|e| format!("{}", e))
 }
 
+/// Preview the current status of a pipeline before adding it to the monitored list
+#[command]
+pub async fn preview_pipeline(
+    app_handle: AppHandle,
+    workspace: String,
+    repo_slug: String,
+    branch: Option<String>,
+) -> Result<PipelineStatusInfo, String> {
+    polling::preview_pipeline(&app_handle, workspace, repo_slug, branch).await
+}
+
+/// Open a repository's web page, using the API-provided link when known
+#[command]
+pub async fn open_repository(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    workspace: String,
+    repo_slug: String,
+) -> Result<(), String> {
+    let repo_url = {
+        let state_guard = state.lock().await;
+        state_guard
+            .all_monitored_pipelines()
+            .into_iter()
+            .find(|p| p.workspace == workspace && p.repo_slug == repo_slug)
+            .and_then(|p| p.repo_url)
+    };
+    let url = get_repository_url(repo_url.as_deref(), &workspace, &repo_slug);
+    open::that(&url).map_err(|e| format!("Failed to open {}: {}", url, e))
+}
+
+/// Copy the latest run's link of a monitored pipeline, or its pipelines list without one
+#[command]
+pub async fn copy_pipeline_url(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    workspace: String,
+    repo_slug: String,
+    branch: Option<String>,
+) -> Result<(), String> {
+    let (pipeline_url, repo_url) = {
+        let state_guard = state.lock().await;
+        let status = state_guard.last_status.as_ref().and_then(|status| {
+            status.pipeline_statuses().iter().find(|p| {
+                p.workspace == workspace
+                    && p.repo_slug == repo_slug
+                    && (branch.is_none() || p.branch == branch)
+            })
+        });
+        let repo_url = status.and_then(|p| p.repo_url.clone()).or_else(|| {
+            state_guard
+                .all_monitored_pipelines()
+                .into_iter()
+                .find(|p| p.workspace == workspace && p.repo_slug == repo_slug)
+                .and_then(|p| p.repo_url)
+        });
+        (status.and_then(|p| p.pipeline_url.clone()), repo_url)
+    };
+    let url = pipeline_url
+        .unwrap_or_else(|| get_pipelines_list_url(repo_url.as_deref(), &workspace, &repo_slug));
+    copy_link(&app_handle, &url)
+}
+
+/// Put a link on the clipboard and confirm with a brief notification
+pub fn copy_link(app_handle: &AppHandle, url: &str) -> Result<(), String> {
+    app_handle
+        .clipboard()
+        .write_text(url)
+        .map_err(|e| format!("Failed to copy {}: {}", url, e))?;
+    app_handle.notify(t("notify.link_copied"), url);
+    Ok(())
+}
+
 /// Save user credentials (username in state, password obfuscated in config)
+///
+/// With `credential_kind` of `access_token` the username is optional and `app_password`
+/// is a workspace or repository access token. A repository token can't list workspaces,
+/// so pass the `repository` ("workspace/repo") it was created for to validate against.
 #[command]
 pub async fn save_credentials(
     app_handle: AppHandle,
     state: State<'_, Arc<Mutex<AppState>>>,
     username: String,
     app_password: String,
-) -> Result<(), String> {
+    credential_kind: Option<CredentialKind>,
+    repository: Option<String>,
+) -> Result<CredentialCheck, String> {
+    let credentials = match credential_kind.unwrap_or_default() {
+        CredentialKind::AppPassword => Credentials { username },
+        CredentialKind::AccessToken => Credentials::access_token(),
+    };
+
     // Validate credentials first
-    let client = BitbucketClient::new(&username, &app_password);
-    if !client
-        .validate_credentials()
-        .await
-        .map_err(|e| format!("{}", e))?
-    {
-        return Err("Invalid credentials".to_string());
+    let client = build_client(&state, &credentials.username, &app_password).await?;
+    let check = match credentials.kind() {
+        CredentialKind::AppPassword => {
+            let check = client
+                .validate_credentials()
+                .await
+                .map_err(|e| format!("{}", e))?;
+            if !check.can_read_account {
+                return Err("Invalid credentials".to_string());
+            }
+            check
+        }
+        CredentialKind::AccessToken => validate_access_token(&client, repository.as_deref()).await?,
+    };
+    // Missing scopes are reported to the caller rather than blocking the save
+    if !check.can_read_pipelines {
+        tracing::warn!("App password cannot read pipelines (missing pipelines:read scope?)");
     }
 
-    // Store username in state
+    // Update state and persist together; roll back both on failure so state and disk agree.
+    // This writes directly (not via the queue) so a failure can be reported and rolled back.
+    let old_password = retrieve_password(&app_handle).ok().flatten();
     {
         let mut state_guard = state.lock().await;
-        state_guard.credentials = Some(Credentials {
-            username: username.clone(),
-        });
+        state_guard
+            .update_or_rollback(
+                |s| {
+                    s.credentials = Some(credentials.clone());
+                    s.has_completed_setup = true;
+                    s.auth_broken = false;
+                },
+                |s| {
+                    save_password(&app_handle, &app_password)?;
+                    write_config(&app_handle, s.to_persisted())
+                },
+            )
+            .inspect_err(|_| restore_password(&app_handle, old_password.as_deref()))?;
     }
 
-    // Save password to secure config
-    save_password(&app_handle, &app_password)?;
+    // Responses fetched with the old credentials shouldn't be revalidated with the new ones
+    conditional::clear_conditional_cache();
 
-    // Save config to disk
+    // Queue the same snapshot so an older queued write can't overwrite it
     save_config_helper(&app_handle, &state).await?;
+    Ok(check)
+}
 
-    Ok(())
+// Helper: Check an access token, telling an invalid token apart from one that only
+// lacks workspace scope
+async fn validate_access_token(
+    client: &BitbucketClient,
+    repository: Option<&str>,
+) -> Result<CredentialCheck, String> {
+    let repository = match repository.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(repository) => Some(
+            repository
+                .split_once('/')
+                .filter(|(workspace, repo_slug)| is_valid_slug(workspace) && is_valid_repo_slug(repo_slug))
+                .ok_or_else(|| format!("'{}' is not in workspace/repo form", repository))?,
+        ),
+        None => None,
+    };
+
+    match client.validate_access_token(repository).await {
+        Ok(check) => Ok(check),
+        Err(BitbucketError::AuthenticationFailed) => Err(
+            "Token invalid: Bitbucket rejected it. Check it was copied in full and has not expired"
+                .to_string(),
+        ),
+        Err(BitbucketError::Forbidden(_)) if repository.is_none() => Err(
+            "Token lacks workspace scope: enter the repository a repository token was created for".to_string(),
+        ),
+        Err(BitbucketError::Forbidden(_) | BitbucketError::NotFound(_)) => Err(format!(
+            "Token cannot access {}: check the repository name and the token's repository:read scope",
+            repository.map(|(w, r)| format!("{}/{}", w, r)).unwrap_or_default()
+        )),
+        Err(e) => Err(format!("{}", e)),
+    }
 }
 
 /// Get the saved username (if any)
@@ -131,15 +406,35 @@ pub async fn get_app_password(app_handle: AppHandle) -> Result<Option<String>, S
 pub async fn save_monitored_pipelines(
     app_handle: AppHandle,
     state: State<'_, Arc<Mutex<AppState>>>,
-    pipelines: Vec<MonitoredPipeline>,
+    mut pipelines: Vec<MonitoredPipeline>,
 ) -> Result<(), String> {
+    validate_monitored_pipelines(&pipelines)?;
     {
         let mut state_guard = state.lock().await;
+        webhooks::validate_pipeline_routes(&state_guard.notification_routes, &pipelines)?;
+        for hours in pipelines.iter().filter_map(|p| p.active_hours.as_ref()) {
+            hours.validate()?;
+        }
+        stamp_added_at(&state_guard.monitored_pipelines, &mut pipelines);
         state_guard.monitored_pipelines = pipelines;
     }
     save_config_helper(&app_handle, &state).await
 }
 
+// Helper: Keep `added_at` for pipelines already monitored and stamp new ones with now
+fn stamp_added_at(old: &[MonitoredPipeline], pipelines: &mut [MonitoredPipeline]) {
+    let now = chrono::Utc::now().to_rfc3339();
+    for pipeline in pipelines {
+        let existing = old
+            .iter()
+            .find(|p| p.workspace == pipeline.workspace && p.repo_slug == pipeline.repo_slug);
+        pipeline.added_at = match existing {
+            Some(p) => p.added_at.clone(),
+            None => Some(now.clone()),
+        };
+    }
+}
+
 /// Get the list of monitored pipelines
 #[command]
 pub async fn get_monitored_pipelines(
@@ -149,130 +444,1405 @@ pub async fn get_monitored_pipelines(
     Ok(state_guard.monitored_pipelines.clone())
 }
 
-/// Get the current pipeline status
+/// Remove monitored pipelines whose repositories no longer exist
 #[command]
-pub async fn get_pipeline_statuses(
+pub async fn confirm_remove_stale_pipelines(
+    app_handle: AppHandle,
     state: State<'_, Arc<Mutex<AppState>>>,
-) -> Result<Option<OverallStatus>, String> {
-    let state_guard = state.lock().await;
-    Ok(state_guard.last_status.clone())
+) -> Result<usize, String> {
+    let removed = {
+        let mut state_guard = state.lock().await;
+        let before = state_guard.monitored_pipelines.len() + state_guard.group_pipelines.len();
+
+        let stale: Vec<(String, String)> = state_guard
+            .consecutive_not_found
+            .keys()
+            .filter(|(workspace, repo_slug)| state_guard.is_stale(workspace, repo_slug))
+            .cloned()
+            .collect();
+        let is_stale = |p: &MonitoredPipeline| {
+            stale
+                .iter()
+                .any(|(workspace, repo_slug)| &p.workspace == workspace && &p.repo_slug == repo_slug)
+        };
+        state_guard.monitored_pipelines.retain(|p| !is_stale(p));
+        state_guard.group_pipelines.retain(|p| !is_stale(p));
+        for key in &stale {
+            state_guard.consecutive_not_found.remove(key);
+        }
+
+        before - state_guard.monitored_pipelines.len() - state_guard.group_pipelines.len()
+    };
+
+    if removed > 0 {
+        tracing::info!("Removed {} stale pipeline(s)", removed);
+        save_config_helper(&app_handle, &state).await?;
+        let _ = app_handle.emit("trigger-refresh", ());
+    }
+    Ok(removed)
 }
 
-/// Set the polling interval
+/// Stop monitoring a repository on one branch, e.g. from its tray menu
+pub async fn remove_monitored_pipeline(
+    app_handle: &AppHandle,
+    workspace: &str,
+    repo_slug: &str,
+    branch: Option<&str>,
+) -> Result<(), String> {
+    let state: State<'_, Arc<Mutex<AppState>>> = app_handle.state();
+    {
+        let mut state_guard = state.lock().await;
+        let is_match = |p: &MonitoredPipeline| {
+            p.workspace == workspace && p.repo_slug == repo_slug && p.branch.as_deref() == branch
+        };
+        state_guard.monitored_pipelines.retain(|p| !is_match(p));
+        let from_groups: Vec<MonitoredPipeline> =
+            state_guard.group_pipelines.iter().filter(|p| is_match(p)).cloned().collect();
+        for pipeline in &from_groups {
+            state_guard.exclude_from_groups(pipeline);
+        }
+        state_guard.group_pipelines.retain(|p| !is_match(p));
+    }
+    tracing::info!("Stopped monitoring {}/{}", workspace, repo_slug);
+
+    save_config_helper(app_handle, &state).await?;
+    let _ = app_handle.emit("monitored-pipelines-changed", ());
+    let _ = app_handle.emit("trigger-refresh", ());
+    Ok(())
+}
+
+/// Save the list of monitored projects and sync their repositories
 #[command]
-pub async fn set_polling_interval(
+pub async fn save_monitored_groups(
     app_handle: AppHandle,
     state: State<'_, Arc<Mutex<AppState>>>,
-    seconds: u64,
+    groups: Vec<MonitoredGroup>,
 ) -> Result<(), String> {
-    if seconds < 30 {
-        return Err("Polling interval must be at least 30 seconds".to_string());
-    }
     {
         let mut state_guard = state.lock().await;
-        state_guard.polling_interval_seconds = seconds;
+        state_guard.monitored_groups = groups;
     }
-    save_config_helper(&app_handle, &state).await
+    save_config_helper(&app_handle, &state).await?;
+
+    // Pick up membership changes now rather than at the next hourly sync
+    tauri::async_runtime::spawn(async move {
+        groups::sync_monitored_groups(&app_handle).await;
+        let _ = app_handle.emit("trigger-refresh", ());
+    });
+    Ok(())
 }
 
-/// Get the polling interval
+/// Get the list of monitored projects
 #[command]
-pub async fn get_polling_interval(
+pub async fn get_monitored_groups(
     state: State<'_, Arc<Mutex<AppState>>>,
-) -> Result<u64, String> {
+) -> Result<Vec<MonitoredGroup>, String> {
     let state_guard = state.lock().await;
-    Ok(state_guard.polling_interval_seconds)
+    Ok(state_guard.monitored_groups.clone())
 }
 
-/// Trigger an immediate refresh
+/// Get the current pipeline status, and whether a check is in flight
+///
+/// `workspace` and `project_key` limit the status to the matching pipelines.
 #[command]
-pub async fn trigger_refresh(app_handle: AppHandle) -> Result<(), String> {
-    app_handle
-        .emit("trigger-refresh", ())
-        .map_err(|e: tauri::Error| e.to_string())
+pub async fn get_pipeline_statuses(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    workspace: Option<String>,
+    project_key: Option<String>,
+) -> Result<StatusSnapshot, String> {
+    let state_guard = state.lock().await;
+    let status = match (&state_guard.last_status, workspace.is_some() || project_key.is_some()) {
+        (Some(status), true) => Some(status.filtered(workspace.as_deref(), project_key.as_deref())),
+        (status, _) => status.clone(),
+    };
+    Ok(StatusSnapshot {
+        checking: state_guard.is_checking,
+        status,
+    })
 }
 
-// Helper: Save password to secure file (base64 obfuscated for MVP)
-fn save_password(app_handle: &AppHandle, password: &str) -> Result<(), String> {
-    let config_dir = app_handle
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("Failed to get config dir: {}", e))?;
+/// Show or hide the quick-open window
+#[command]
+pub async fn toggle_quick_open(app_handle: AppHandle) -> Result<(), String> {
+    quick_open::toggle_quick_open(&app_handle)
+}
 
-    std::fs::create_dir_all(&config_dir)
-        .map_err(|e| format!("Failed to create config dir: {}", e))?;
+/// Monitored pipelines matching a quick-open query, best first
+#[command]
+pub async fn search_status(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    query: String,
+) -> Result<Vec<StatusMatch>, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard
+        .last_status
+        .as_ref()
+        .map(|status| quick_open::search(status, &query))
+        .unwrap_or_default())
+}
 
-    let creds_path = config_dir.join(".credentials");
-    let encoded = STANDARD.encode(password.as_bytes());
+/// Open a quick-open match: its latest run or repository, or settings without either
+#[command]
+pub async fn open_status_entry(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    key: String,
+) -> Result<(), String> {
+    let url = {
+        let state_guard = state.lock().await;
+        let entry = state_guard
+            .last_status
+            .as_ref()
+            .and_then(|status| quick_open::find_entry(status, &key))
+            .ok_or_else(|| format!("No monitored pipeline {}", key))?;
+        entry.pipeline_url.clone().or_else(|| entry.repo_url.clone())
+    };
+    quick_open::hide_quick_open(&app_handle);
 
-    std::fs::write(&creds_path, encoded)
-        .map_err(|e| format!("Failed to write credentials: {}", e))?;
+    match url {
+        Some(url) => open::that(&url).map_err(|e| format!("Failed to open {}: {}", url, e)),
+        None => {
+            let window = app_handle
+                .get_webview_window("settings")
+                .ok_or("Settings window not found")?;
+            let _ = window.show();
+            let _ = window.set_focus();
+            Ok(())
+        }
+    }
+}
+
+/// Get a pipeline's status, run history, reliability, activity and flags for its detail view
+#[command]
+pub async fn get_pipeline_detail(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    key: PipelineKey,
+) -> Result<PipelineDetail, String> {
+    let state_guard = state.lock().await;
+    pipeline_detail::pipeline_detail(&state_guard, &key, chrono::Utc::now())
+}
+
+/// Events shown by default in the activity log window
+const ACTIVITY_PAGE_SIZE: usize = 200;
+
+/// Open the activity log window
+#[command]
+pub async fn open_activity_log(app_handle: AppHandle) -> Result<(), String> {
+    activity::open_activity_window(&app_handle)
+}
+
+/// Latest activity events, newest first, optionally only those containing `filter`
+#[command]
+pub async fn get_activity_log(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    limit: Option<usize>,
+    filter: Option<String>,
+) -> Result<Vec<ActivityEvent>, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard
+        .activity
+        .recent(limit.unwrap_or(ACTIVITY_PAGE_SIZE), filter.as_deref()))
+}
 
+/// Remove every event from the activity log
+#[command]
+pub async fn clear_activity_log(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<(), String> {
+    let mut state_guard = state.lock().await;
+    state_guard.activity.clear();
+    activity::queue_write(&state_guard);
+    let _ = app_handle.emit("activity-cleared", ());
     Ok(())
 }
 
-// Helper: Retrieve password from secure file
-fn retrieve_password(app_handle: &AppHandle) -> Result<Option<String>, String> {
-    let config_dir = match app_handle.path().app_config_dir() {
-        Ok(dir) => dir,
-        Err(_) => return Ok(None),
-    };
+/// Open the wallboard window
+#[command]
+pub async fn open_wallboard(app_handle: AppHandle) -> Result<(), String> {
+    wallboard::open_wallboard(&app_handle).await
+}
 
-    let creds_path = config_dir.join(".credentials");
+/// Wallboard window options
+#[derive(Debug, Clone, Serialize)]
+pub struct WallboardOptions {
+    pub always_on_top: bool,
+    pub start_on_launch: bool,
+}
 
-    if !creds_path.exists() {
-        return Ok(None);
+/// Set wallboard window options
+#[command]
+pub async fn set_wallboard_options(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    always_on_top: bool,
+    start_on_launch: bool,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.wallboard_always_on_top = always_on_top;
+        state_guard.wallboard_start_on_launch = start_on_launch;
     }
+    wallboard::apply_always_on_top(&app_handle, always_on_top);
+    save_config_helper(&app_handle, &state).await
+}
 
-    let encoded = match std::fs::read_to_string(&creds_path) {
-        Ok(e) => e,
-        Err(_) => return Ok(None),
-    };
-
-    let decoded = STANDARD
-        .decode(encoded.trim())
-        .map_err(|e| format!("Failed to decode credentials: {}", e))?;
+/// Get wallboard window options
+#[command]
+pub async fn get_wallboard_options(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<WallboardOptions, String> {
+    let state_guard = state.lock().await;
+    Ok(WallboardOptions {
+        always_on_top: state_guard.wallboard_always_on_top,
+        start_on_launch: state_guard.wallboard_start_on_launch,
+    })
+}
 
-    String::from_utf8(decoded)
-        .map(Some)
-        .map_err(|e| format!("Invalid credential data: {}", e))
+/// Set the polling interval
+#[command]
+pub async fn set_polling_interval(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    seconds: u64,
+) -> Result<(), String> {
+    apply_polling_interval(&app_handle, &state, seconds).await
 }
 
-// Helper: Save config to disk
-async fn save_config_helper(
+/// Shared by the settings window and the tray submenu
+pub(crate) async fn apply_polling_interval(
     app_handle: &AppHandle,
     state: &State<'_, Arc<Mutex<AppState>>>,
+    seconds: u64,
 ) -> Result<(), String> {
-    let state_guard = state.lock().await;
-    let config = state_guard.to_persisted();
-
-    let config_dir = app_handle
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("Failed to get config dir: {}", e))?;
+    {
+        let mut state_guard = state.lock().await;
+        validate_polling_interval(seconds, state_guard.min_polling_interval_override)?;
+        state_guard.polling_interval_seconds = seconds;
+        // The polling loop reschedules its next check from the new interval
+        if let Some(tx) = &state_guard.polling_interval_tx {
+            tx.send_replace(seconds);
+        }
+    }
+    tray::set_checked_interval(seconds);
+    let _ = app_handle.emit("polling-interval-changed", seconds);
+    save_config_helper(app_handle, state).await
+}
 
-    std::fs::create_dir_all(&config_dir)
-        .map_err(|e| format!("Failed to create config dir: {}", e))?;
+/// Enable or disable notifications for runs awaiting manual approval
+#[command]
+pub async fn set_notify_on_paused(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.notify_on_paused = enabled;
+    }
+    save_config_helper(&app_handle, &state).await
+}
 
-    let config_path = config_dir.join("config.json");
-    let json = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+/// Get whether runs awaiting manual approval trigger a notification
+#[command]
+pub async fn get_notify_on_paused(state: State<'_, Arc<Mutex<AppState>>>) -> Result<bool, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.notify_on_paused)
+}
 
-    std::fs::write(&config_path, json)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+/// Whether desktop notifications of one category are shown
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationCategorySetting {
+    pub category: NotificationCategory,
+    pub enabled: bool,
+}
 
-    Ok(())
+/// Switch a category of desktop notifications on or off
+#[command]
+pub async fn set_notification_category_enabled(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    category: NotificationCategory,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.set_notifications_enabled(category, enabled);
+    }
+    save_config_helper(&app_handle, &state).await
 }
 
-/// Load config from disk
-pub fn load_config(app_handle: &AppHandle) -> Option<PersistedConfig> {
-    let config_dir = app_handle.path().app_config_dir().ok()?;
-    let config_path = config_dir.join("config.json");
+/// Get which categories of desktop notifications are shown
+#[command]
+pub async fn get_notification_categories(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Vec<NotificationCategorySetting>, String> {
+    let state_guard = state.lock().await;
+    Ok(NotificationCategory::ALL
+        .into_iter()
+        .map(|category| NotificationCategorySetting {
+            category,
+            enabled: state_guard.notifications_enabled(category),
+        })
+        .collect())
+}
 
-    if !config_path.exists() {
-        return None;
+/// Make runs awaiting manual approval turn the tray icon red, and re-check so it shows
+#[command]
+pub async fn set_treat_paused_as_unhealthy(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.treat_paused_as_unhealthy = enabled;
     }
+    save_config_helper(&app_handle, &state).await?;
+    let _ = app_handle.emit("trigger-refresh", ());
+    Ok(())
+}
+
+/// Get whether runs awaiting manual approval make the overall status unhealthy
+#[command]
+pub async fn get_treat_paused_as_unhealthy(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<bool, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.treat_paused_as_unhealthy)
+}
+
+/// Make failing pull request pipelines turn the tray icon red, and re-check so it shows
+#[command]
+pub async fn set_include_prs_in_health(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.include_prs_in_health = enabled;
+    }
+    save_config_helper(&app_handle, &state).await?;
+    let _ = app_handle.emit("trigger-refresh", ());
+    Ok(())
+}
+
+/// Get whether failing pull request pipelines make the overall status unhealthy
+#[command]
+pub async fn get_include_prs_in_health(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<bool, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.include_prs_in_health)
+}
+
+/// Set the webhook targets that pipeline failure/recovery events are forwarded to
+#[command]
+pub async fn save_notification_routes(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    routes: NotificationRoutes,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        routes.validate(&state_guard.monitored_pipelines)?;
+        state_guard.notification_routes = routes;
+    }
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get the configured webhook targets
+#[command]
+pub async fn get_notification_routes(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<NotificationRoutes, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.notification_routes.clone())
+}
+
+/// Start a custom pipeline on a branch, e.g. `custom: deploy-staging`
+#[command]
+pub async fn run_custom_pipeline(
+    app_handle: AppHandle,
+    workspace: String,
+    repo_slug: String,
+    branch: String,
+    selector: String,
+    variables: Option<Vec<PipelineVariable>>,
+) -> Result<Pipeline, String> {
+    polling::run_custom_pipeline(
+        &app_handle,
+        &workspace,
+        &repo_slug,
+        &branch,
+        &selector,
+        &variables.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Send a sample failure to a webhook target and return what it answered
+#[command]
+pub async fn test_webhook(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    target_name: String,
+) -> Result<WebhookResponse, String> {
+    let (target, tls) = {
+        let state_guard = state.lock().await;
+        let target = state_guard
+            .notification_routes
+            .targets
+            .iter()
+            .find(|t| t.name == target_name)
+            .cloned()
+            .ok_or_else(|| format!("No webhook target named '{}'", target_name))?;
+        (target, state_guard.tls_config())
+    };
+    webhooks::deliver(&target, &WebhookEvent::sample(), &tls).await
+}
+
+/// Show or hide recent-result glyphs on pipeline menu items
+#[command]
+pub async fn set_show_history_glyphs(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    enabled: bool,
+) -> Result<(), String> {
+    let (last_status, trays) = {
+        let mut state_guard = state.lock().await;
+        state_guard.show_history_glyphs = enabled;
+        (state_guard.last_status.clone(), state_guard.trays.clone())
+    };
+    tray::set_show_history_glyphs(enabled);
+    if last_status.is_some() {
+        tray::update_status_menus(&app_handle, &trays, last_status.as_ref());
+    }
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get whether pipeline menu items show recent-result glyphs
+#[command]
+pub async fn get_show_history_glyphs(state: State<'_, Arc<Mutex<AppState>>>) -> Result<bool, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.show_history_glyphs)
+}
+
+/// Collapse pipelines with no run data into a single menu line
+#[command]
+pub async fn set_hide_unknown_in_menu(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.hide_unknown_in_menu = enabled;
+    }
+    tray::set_hide_unknown_in_menu(enabled);
+    polling::rerender_tray(&app_handle).await;
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get whether pipelines with no run data are collapsed in the menu
+#[command]
+pub async fn get_hide_unknown_in_menu(state: State<'_, Arc<Mutex<AppState>>>) -> Result<bool, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.hide_unknown_in_menu)
+}
+
+/// List running and paused pipelines in their own section at the top of the menu
+#[command]
+pub async fn set_group_running_separately(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.group_running_separately = enabled;
+    }
+    tray::set_group_running_separately(enabled);
+    polling::rerender_tray(&app_handle).await;
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get whether running pipelines get their own menu section
+#[command]
+pub async fn get_group_running_separately(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<bool, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.group_running_separately)
+}
+
+/// Show a tray icon per monitored workspace instead of one for all pipelines
+#[command]
+pub async fn set_multi_tray(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.multi_tray = enabled;
+    }
+    polling::rerender_tray(&app_handle).await;
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get whether each workspace has its own tray icon
+#[command]
+pub async fn get_multi_tray(state: State<'_, Arc<Mutex<AppState>>>) -> Result<bool, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.multi_tray)
+}
+
+/// Set how the tray icon draws attention to a new failure
+#[command]
+pub async fn set_attention_animation(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    animation: AttentionAnimation,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.attention_animation = animation;
+    }
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get how the tray icon draws attention to a new failure
+#[command]
+pub async fn get_attention_animation(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<AttentionAnimation, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.attention_animation)
+}
+
+/// Draw light or dark tray and menu icons, or follow the system appearance
+#[command]
+pub async fn set_icon_theme(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    theme: IconTheme,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.icon_theme = theme;
+    }
+    tray::set_icon_theme(theme);
+    polling::rerender_tray(&app_handle).await;
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get which tray and menu icons are drawn
+#[command]
+pub async fn get_icon_theme(state: State<'_, Arc<Mutex<AppState>>>) -> Result<IconTheme, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.icon_theme)
+}
+
+/// Set the language of the tray, tooltip and notifications; None follows the OS locale
+#[command]
+pub async fn set_locale(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    locale: Option<String>,
+) -> Result<(), String> {
+    if let Some(tag) = &locale {
+        Locale::from_tag(tag).ok_or_else(|| format!("Unsupported language: {}", tag))?;
+    }
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.locale = locale.clone();
+    }
+    i18n::set_locale(i18n::resolve(locale.as_deref()));
+    polling::rerender_tray(&app_handle).await;
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get the language chosen in settings, or None when following the OS locale
+#[command]
+pub async fn get_locale(state: State<'_, Arc<Mutex<AppState>>>) -> Result<Option<String>, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.locale.clone())
+}
+
+/// Recent finished runs for a monitored pipeline
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStats {
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+    /// Oldest first
+    pub history: Vec<RunRecord>,
+    pub passed: usize,
+    pub failed: usize,
+}
+
+impl PipelineStats {
+    /// Stats from the runs remembered for a monitored pipeline
+    pub fn new(state: &AppState, pipeline: MonitoredPipeline) -> Self {
+        let history = state
+            .run_history
+            .get(&format!("{}/{}", pipeline.workspace, pipeline.repo_slug))
+            .cloned()
+            .unwrap_or_default();
+        let failed = history.iter().filter(|r| r.result == RunResult::Failed).count();
+        Self {
+            passed: history.len() - failed,
+            failed,
+            history,
+            workspace: pipeline.workspace,
+            repo_slug: pipeline.repo_slug,
+            repo_name: pipeline.repo_name,
+        }
+    }
+}
+
+/// Get the recent run history of every monitored pipeline
+#[command]
+pub async fn get_pipeline_stats(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Vec<PipelineStats>, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard
+        .all_monitored_pipelines()
+        .into_iter()
+        .map(|p| PipelineStats::new(&state_guard, p))
+        .collect())
+}
+
+/// Get recently resolved outages, most recent first
+#[command]
+pub async fn get_incident_log(state: State<'_, Arc<Mutex<AppState>>>) -> Result<Vec<Incident>, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.incidents.recent())
+}
+
+/// Mean time to recovery, failure frequency and longest outage of each pipeline over
+/// `window_days`; outages still going on count up to now when `include_open` is set
+#[command]
+pub async fn get_reliability_report(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    window_days: u32,
+    include_open: Option<bool>,
+) -> Result<ReliabilityReport, String> {
+    reliability::validate_window(window_days)?;
+    let state_guard = state.lock().await;
+    Ok(reliability::reliability_report(
+        state_guard.incidents.events(),
+        &state_guard.all_monitored_pipelines(),
+        chrono::Utc::now(),
+        window_days,
+        include_open.unwrap_or(false),
+    ))
+}
+
+/// Summarize failures and recoveries over `window_days` (the digest setting if not given)
+#[command]
+pub async fn get_weekly_digest(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    window_days: Option<u32>,
+) -> Result<WeeklyDigest, String> {
+    let state_guard = state.lock().await;
+    let settings = DigestSettings {
+        window_days: window_days.unwrap_or(state_guard.digest_settings.window_days),
+        ..state_guard.digest_settings.clone()
+    };
+    settings.validate()?;
+    Ok(digest::weekly_digest(
+        state_guard.incidents.events(),
+        chrono::Utc::now(),
+        settings.window_days,
+    ))
+}
+
+/// Set when the weekly digest notification is posted
+#[command]
+pub async fn set_digest_settings(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    settings: DigestSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.digest_settings = settings;
+    }
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get when the weekly digest notification is posted
+#[command]
+pub async fn get_digest_settings(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<DigestSettings, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.digest_settings.clone())
+}
+
+/// Set the free-text notes stored at the top of the config file
+#[command]
+pub async fn set_config_notes(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    notes: Option<String>,
+) -> Result<(), String> {
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.config_notes = notes.filter(|n| !n.trim().is_empty());
+    }
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get the free-text notes stored in the config file
+#[command]
+pub async fn get_config_notes(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Option<String>, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.config_notes.clone())
+}
+
+/// Get the polling interval and the countdown to the next check
+#[command]
+pub async fn get_polling_interval(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<PollingStatus, String> {
+    let environment = power::probe_environment().await;
+    let state_guard = state.lock().await;
+    Ok(power::polling_status(&state_guard, environment))
+}
+
+/// Get the effective polling policy (battery/metered adjustments applied)
+#[command]
+pub async fn get_polling_status(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<PollingPolicy, String> {
+    let environment = power::probe_environment().await;
+    let state_guard = state.lock().await;
+    Ok(power::current_policy(&state_guard, environment))
+}
+
+/// Get poll counts and how often conditional requests avoided a download
+#[command]
+pub async fn get_poll_metrics(state: State<'_, Arc<Mutex<AppState>>>) -> Result<PollMetrics, String> {
+    let state_guard = state.lock().await;
+    Ok(power::poll_metrics(&state_guard))
+}
+
+/// API quota Bitbucket last reported, and whether it is low enough to slow polling
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitStatus {
+    #[serde(flatten)]
+    pub quota: RateLimitInfo,
+    pub is_low: bool,
+}
+
+/// Get the API quota Bitbucket last reported, if it has reported one
+#[command]
+pub async fn get_rate_limit_info(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Option<RateLimitStatus>, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.rate_limit.map(|quota| RateLimitStatus {
+        quota,
+        is_low: quota.is_low(),
+    }))
+}
+
+/// Set battery and metered-network polling options
+#[command]
+pub async fn set_power_settings(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    reduce_polling_on_battery: bool,
+    pause_on_metered: bool,
+    battery_interval_multiplier: Option<u64>,
+    api_budget_per_hour: Option<u32>,
+) -> Result<(), String> {
+    if battery_interval_multiplier == Some(0) {
+        return Err("Battery interval multiplier must be at least 1".to_string());
+    }
+    if api_budget_per_hour == Some(0) {
+        return Err("API budget must be at least 1 request per hour".to_string());
+    }
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.reduce_polling_on_battery = reduce_polling_on_battery;
+        state_guard.pause_on_metered = pause_on_metered;
+        if let Some(multiplier) = battery_interval_multiplier {
+            state_guard.battery_interval_multiplier = multiplier;
+        }
+        if let Some(limit) = api_budget_per_hour {
+            state_guard.api_budget_per_hour = limit;
+            budget::set_api_budget(limit);
+        }
+    }
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Forget all settings and credentials, as if cdMenu had just been installed
+///
+/// `confirm` must be true, so a stray call from the frontend can't wipe the config.
+#[command]
+pub async fn reset_to_defaults(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    confirm: bool,
+) -> Result<(), String> {
+    if !confirm {
+        return Err("Reset not confirmed".to_string());
+    }
+
+    let (defaults, deleted) = {
+        let mut state_guard = state.lock().await;
+        let state_guard = &mut *state_guard;
+        // Keep the channels to the background tasks, the result of the storage check and the trays shown
+        *state_guard = AppState {
+            config_write_tx: state_guard.config_write_tx.take(),
+            refresh_tx: state_guard.refresh_tx.take(),
+            activity_write_tx: state_guard.activity_write_tx.take(),
+            polling_interval_tx: state_guard.polling_interval_tx.take(),
+            storage: std::mem::take(&mut state_guard.storage),
+            trays: state_guard.trays.clone(),
+            ..AppState::new()
+        };
+        // Overwrites the file with the emptied log
+        activity::queue_write(state_guard);
+        if let Some(tx) = &state_guard.polling_interval_tx {
+            tx.send_replace(state_guard.polling_interval_seconds);
+        }
+        // Queued while the lock is held, so no snapshot of the old settings can follow it
+        let (done_tx, done_rx) = oneshot::channel();
+        let queued = match &state_guard.config_write_tx {
+            Some(tx) => tx.send(ConfigWrite::Reset(done_tx)).await.is_ok(),
+            None => false,
+        };
+        (state_guard.clone(), queued.then_some(done_rx))
+    };
+
+    match deleted {
+        Some(done_rx) => done_rx
+            .await
+            .map_err(|_| "Config writer has stopped".to_string())??,
+        // No writer running - delete directly
+        None => delete_config_files(&app_handle)?,
+    }
+    tracing::info!("Reset all settings to defaults");
+
+    // Settings mirrored outside AppState
+    if let Err(e) = hotkeys::register_hotkeys(
+        &app_handle,
+        defaults.hotkey_refresh.as_deref(),
+        defaults.hotkey_open_settings.as_deref(),
+        defaults.hotkey_quick_open.as_deref(),
+    ) {
+        tracing::warn!("Failed to register default hotkeys: {}", e.message);
+    }
+    i18n::set_locale(i18n::resolve(defaults.locale.as_deref()));
+    budget::set_api_budget(defaults.api_budget_per_hour);
+    conditional::clear_conditional_cache();
+    tray::set_checked_interval(defaults.polling_interval_seconds);
+    tray::set_show_history_glyphs(defaults.show_history_glyphs);
+    tray::set_hide_unknown_in_menu(defaults.hide_unknown_in_menu);
+    tray::set_group_running_separately(defaults.group_running_separately);
+    tray::set_icon_theme(defaults.icon_theme);
+    tray::set_connection_status(defaults.connection_status);
+    tray::set_session_counts(defaults.session_failure_count, defaults.session_recovery_count);
+    tray::set_mttr(&reliability::reliability_report(
+        defaults.incidents.events(),
+        &[],
+        chrono::Utc::now(),
+        reliability::MTTR_MENU_WINDOW_DAYS,
+        false,
+    ));
+    tray::reset_tray_menu();
+
+    let _ = app_handle.emit("polling-interval-changed", defaults.polling_interval_seconds);
+    let _ = app_handle.emit("config-reset", ());
+    // Without credentials this shows the setup menu and tooltip
+    polling::check_pipelines_once(&app_handle).await;
+    Ok(())
+}
+
+/// Trigger an immediate refresh
+#[command]
+pub async fn trigger_refresh(app_handle: AppHandle) -> Result<(), String> {
+    app_handle
+        .emit("trigger-refresh", ())
+        .map_err(|e: tauri::Error| e.to_string())
+}
+
+/// Configured global hotkeys
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeySettings {
+    pub refresh: Option<String>,
+    pub open_settings: Option<String>,
+    pub quick_open: Option<String>,
+}
+
+/// Trigger an immediate refresh of a single pipeline
+#[command]
+pub async fn trigger_single_refresh(
+    app_handle: AppHandle,
+    workspace: String,
+    repo_slug: String,
+) -> Result<(), String> {
+    app_handle
+        .emit(
+            "trigger-single-refresh",
+            SingleRefreshRequest {
+                workspace,
+                repo_slug,
+            },
+        )
+        .map_err(|e: tauri::Error| e.to_string())
+}
+
+/// Set the global hotkeys (empty string disables a hotkey)
+#[command]
+pub async fn set_hotkeys(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    refresh: Option<String>,
+    open_settings: Option<String>,
+    quick_open: Option<String>,
+) -> Result<(), HotkeyError> {
+    let refresh = refresh.filter(|h| !h.trim().is_empty());
+    let open_settings = open_settings.filter(|h| !h.trim().is_empty());
+    let quick_open = quick_open.filter(|h| !h.trim().is_empty());
+
+    let (old_refresh, old_open_settings, old_quick_open) = {
+        let state_guard = state.lock().await;
+        (
+            state_guard.hotkey_refresh.clone(),
+            state_guard.hotkey_open_settings.clone(),
+            state_guard.hotkey_quick_open.clone(),
+        )
+    };
+
+    if let Err(e) = hotkeys::register_hotkeys(
+        &app_handle,
+        refresh.as_deref(),
+        open_settings.as_deref(),
+        quick_open.as_deref(),
+    ) {
+        // Restore the previous bindings so a failed change doesn't leave none active
+        let _ = hotkeys::register_hotkeys(
+            &app_handle,
+            old_refresh.as_deref(),
+            old_open_settings.as_deref(),
+            old_quick_open.as_deref(),
+        );
+        return Err(e);
+    }
+
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.hotkey_refresh = refresh;
+        state_guard.hotkey_open_settings = open_settings;
+        state_guard.hotkey_quick_open = quick_open;
+    }
+    save_config_helper(&app_handle, &state)
+        .await
+        .map_err(HotkeyError::persist)
+}
+
+/// Get the configured global hotkeys
+#[command]
+pub async fn get_hotkeys(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<HotkeySettings, String> {
+    let state_guard = state.lock().await;
+    Ok(HotkeySettings {
+        refresh: state_guard.hotkey_refresh.clone(),
+        open_settings: state_guard.hotkey_open_settings.clone(),
+        quick_open: state_guard.hotkey_quick_open.clone(),
+    })
+}
+
+/// TLS settings for self-hosted or intercepted connections
+#[derive(Debug, Clone, Serialize)]
+pub struct TlsSettings {
+    pub custom_ca_path: Option<String>,
+    pub danger_accept_invalid_certs: bool,
+}
+
+/// Result of a connection test, with TLS failures reported distinctly
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionTestResult {
+    pub ok: bool,
+    /// "ok", "tls", "invalid_ca", "auth", "network" or "api"
+    pub kind: String,
+    pub message: String,
+}
+
+/// Set TLS options, validating the CA bundle before saving
+#[command]
+pub async fn set_tls_settings(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    custom_ca_path: Option<String>,
+    danger_accept_invalid_certs: bool,
+) -> Result<(), String> {
+    let custom_ca_path = custom_ca_path
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| p.trim().into());
+
+    // Building a client validates that the CA file parses as PEM
+    let tls = crate::bitbucket::TlsConfig {
+        custom_ca_path,
+        danger_accept_invalid_certs,
+    };
+    BitbucketClient::new("", "", &tls).map_err(|e| format!("{}", e))?;
+
+    if danger_accept_invalid_certs {
+        tracing::warn!("TLS certificate verification is DISABLED - connections are not secure");
+    }
+
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.custom_ca_path = tls.custom_ca_path;
+        state_guard.danger_accept_invalid_certs = tls.danger_accept_invalid_certs;
+    }
+    save_config_helper(&app_handle, &state).await
+}
+
+/// Get the current TLS options
+#[command]
+pub async fn get_tls_settings(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<TlsSettings, String> {
+    let state_guard = state.lock().await;
+    Ok(TlsSettings {
+        custom_ca_path: state_guard
+            .custom_ca_path
+            .as_ref()
+            .map(|p| p.display().to_string()),
+        danger_accept_invalid_certs: state_guard.danger_accept_invalid_certs,
+    })
+}
+
+/// Look up the main branch of every pipeline set to follow it, and save what was found
+#[command]
+pub async fn resolve_default_branches(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Vec<ResolvedBranch>, String> {
+    let (credentials, mut pipelines, mut lookups) = {
+        let state_guard = state.lock().await;
+        (
+            state_guard.credentials.clone(),
+            state_guard.all_monitored_pipelines(),
+            state_guard.default_branch_lookups.clone(),
+        )
+    };
+    let credentials = credentials.ok_or("No credentials configured")?;
+    let app_password = retrieve_password(&app_handle)?.ok_or("No app password found")?;
+    let client = build_client(&state, &credentials.username, &app_password).await?;
+
+    let now = std::time::Instant::now();
+    let resolved =
+        default_branch::resolve_default_branches(&client, &mut pipelines, false, &mut lookups, now)
+            .await;
+    let changed = {
+        let mut state_guard = state.lock().await;
+        state_guard.default_branch_lookups = lookups;
+        state_guard.cache_resolved_branches(&resolved)
+    };
+    if changed {
+        save_config_helper(&app_handle, &state).await?;
+        let _ = app_handle.emit("trigger-refresh", ());
+    }
+    Ok(resolved)
+}
+
+/// Follower mode paths, plus whether a feed is currently being followed
+#[derive(Debug, Clone, Serialize)]
+pub struct FollowerSettings {
+    pub follow_status_path: Option<String>,
+    pub publish_status_path: Option<String>,
+    pub following: bool,
+}
 
-    let json = std::fs::read_to_string(&config_path).ok()?;
-    serde_json::from_str(&json).ok()
+/// Set the status file to follow instead of polling, and the file to publish checks to
+#[command]
+pub async fn set_follower_mode(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    follow_status_path: Option<String>,
+    publish_status_path: Option<String>,
+) -> Result<(), String> {
+    let follow_status_path: Option<std::path::PathBuf> = follow_status_path
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| p.trim().into());
+    let publish_status_path: Option<std::path::PathBuf> = publish_status_path
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| p.trim().into());
+    if follow_status_path.is_some() && follow_status_path == publish_status_path {
+        return Err("Cannot follow and publish the same status file".to_string());
+    }
+
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.follow_status_path = follow_status_path;
+        state_guard.publish_status_path = publish_status_path;
+    }
+    save_config_helper(&app_handle, &state).await?;
+
+    let _ = app_handle.emit("trigger-refresh", ());
+    Ok(())
+}
+
+/// Get the follower mode paths
+#[command]
+pub async fn get_follower_mode(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<FollowerSettings, String> {
+    let state_guard = state.lock().await;
+    Ok(FollowerSettings {
+        follow_status_path: state_guard
+            .follow_status_path
+            .as_ref()
+            .map(|p| p.display().to_string()),
+        publish_status_path: state_guard
+            .publish_status_path
+            .as_ref()
+            .map(|p| p.display().to_string()),
+        following: state_guard.following,
+    })
+}
+
+/// Details for troubleshooting, shown in the settings window
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+    pub version: String,
+    pub storage: StorageStatus,
+    pub connection_status: ConnectionStatus,
+    pub session: SessionStats,
+    /// Why the tray fell back to its minimal menu, if it did
+    pub last_menu_build_error: Option<String>,
+}
+
+/// Get app version and storage self-check results
+#[command]
+pub async fn get_diagnostics(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Diagnostics, String> {
+    let state_guard = state.lock().await;
+    Ok(Diagnostics {
+        version: app_handle.package_info().version.to_string(),
+        storage: state_guard.storage.clone(),
+        connection_status: state_guard.connection_status,
+        session: state_guard.session_stats(),
+        last_menu_build_error: state_guard.last_menu_build_error.clone(),
+    })
+}
+
+/// Get the failures, recoveries and checks seen since launch
+#[command]
+pub async fn get_session_stats(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<SessionStats, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.session_stats())
+}
+
+/// Get whether the last checks reached Bitbucket
+#[command]
+pub async fn get_connection_status(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<ConnectionStatus, String> {
+    let state_guard = state.lock().await;
+    Ok(state_guard.connection_status)
+}
+
+/// Test connectivity to Bitbucket with the given credentials and current TLS options
+#[command]
+pub async fn test_connection(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    username: String,
+    app_password: String,
+) -> Result<ConnectionTestResult, String> {
+    let tls = state.lock().await.tls_config();
+    let result = match BitbucketClient::new(&username, &app_password, &tls) {
+        Ok(client) => client.get_workspaces().await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    let (kind, message) = match result {
+        Ok(()) => ("ok", "Connected to Bitbucket".to_string()),
+        Err(e) => {
+            let kind = match e {
+                BitbucketError::Tls(_) => "tls",
+                BitbucketError::InvalidCaCertificate(_) => "invalid_ca",
+                BitbucketError::AuthenticationFailed => "auth",
+                BitbucketError::Forbidden(_) => "forbidden",
+                BitbucketError::Http(_) => "network",
+                _ => "api",
+            };
+            (kind, e.to_string())
+        }
+    };
+
+    Ok(ConnectionTestResult {
+        ok: kind == "ok",
+        kind: kind.to_string(),
+        message,
+    })
+}
+
+// Helper: Build a client using the configured TLS options
+async fn build_client(
+    state: &State<'_, Arc<Mutex<AppState>>>,
+    username: &str,
+    app_password: &str,
+) -> Result<BitbucketClient, String> {
+    let tls = state.lock().await.tls_config();
+    BitbucketClient::new(username, app_password, &tls).map_err(|e| format!("{}", e))
+}
+
+// Helper: Save password to secure file (base64 obfuscated for MVP)
+fn save_password(app_handle: &AppHandle, password: &str) -> Result<(), String> {
+    let config_dir = storage::config_dir(app_handle)?;
+
+    std::fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config dir: {}", e))?;
+
+    let creds_path = config_dir.join(".credentials");
+    let encoded = STANDARD.encode(password.as_bytes());
+
+    std::fs::write(&creds_path, encoded)
+        .map_err(|e| format!("Failed to write credentials to {}: {}", creds_path.display(), e))?;
+
+    Ok(())
+}
+
+// Helper: Retrieve password from secure file
+fn retrieve_password(app_handle: &AppHandle) -> Result<Option<String>, String> {
+    let config_dir = match storage::config_dir(app_handle) {
+        Ok(dir) => dir,
+        Err(_) => return Ok(None),
+    };
+
+    let creds_path = config_dir.join(".credentials");
+
+    if !creds_path.exists() {
+        return Ok(None);
+    }
+
+    let encoded = match std::fs::read_to_string(&creds_path) {
+        Ok(e) => e,
+        Err(_) => return Ok(None),
+    };
+
+    let decoded = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("Failed to decode credentials: {}", e))?;
+
+    String::from_utf8(decoded)
+        .map(Some)
+        .map_err(|e| format!("Invalid credential data: {}", e))
+}
+
+// Helper: Put back the previously saved password (or remove it if there was none)
+fn restore_password(app_handle: &AppHandle, old_password: Option<&str>) {
+    let result = match old_password {
+        Some(password) => save_password(app_handle, password),
+        None => storage::config_dir(app_handle).and_then(|dir| {
+                let creds_path = dir.join(".credentials");
+                if creds_path.exists() {
+                    std::fs::remove_file(creds_path).map_err(|e| e.to_string())
+                } else {
+                    Ok(())
+                }
+            }),
+    };
+    if let Err(e) = result {
+        tracing::error!("Failed to restore previous credentials: {}", e);
+    }
+}
+
+// Helper: Save config to disk
+pub(crate) async fn save_config_helper(
+    app_handle: &AppHandle,
+    state: &State<'_, Arc<Mutex<AppState>>>,
+) -> Result<(), String> {
+    // Queued under the lock so snapshots reach the writer in the order they were taken
+    let state_guard = state.lock().await;
+    let config = state_guard.to_persisted();
+    match &state_guard.config_write_tx {
+        Some(tx) => tx
+            .send(ConfigWrite::Save(config))
+            .await
+            .map_err(|_| "Config writer has stopped".to_string()),
+        // Writer not started yet (early setup) - write directly
+        None => write_config(app_handle, config),
+    }
+}
+
+/// A request queued for the background config writer
+pub enum ConfigWrite {
+    /// Write this snapshot, unless a newer request arrives within `CONFIG_WRITE_DEBOUNCE`
+    Save(PersistedConfig),
+    /// Delete the config files, dropping a snapshot still waiting to be written
+    Reset(oneshot::Sender<Result<(), String>>),
+}
+
+/// Background task that serializes config writes
+///
+/// Snapshots arriving within `CONFIG_WRITE_DEBOUNCE` of each other are
+/// coalesced, so only the latest one is written.
+pub async fn run_config_writer(app_handle: AppHandle, mut rx: mpsc::Receiver<ConfigWrite>) {
+    let mut next = rx.recv().await;
+    while let Some(request) = next.take() {
+        match request {
+            ConfigWrite::Save(config) => {
+                match tokio::time::timeout(CONFIG_WRITE_DEBOUNCE, rx.recv()).await {
+                    Ok(Some(newer)) => next = Some(newer),
+                    // Quiet for a moment, or the queue was closed
+                    _ => {
+                        if let Err(e) = write_config(&app_handle, config) {
+                            tracing::error!("Failed to save config: {}", e);
+                        }
+                        next = rx.recv().await;
+                    }
+                }
+            }
+            ConfigWrite::Reset(done) => {
+                let _ = done.send(delete_config_files(&app_handle));
+                next = rx.recv().await;
+            }
+        }
+    }
+    tracing::info!("Config writer stopped");
+}
+
+// Helper: Delete the config, its backup and the stored credentials
+fn delete_config_files(app_handle: &AppHandle) -> Result<(), String> {
+    let config_dir = storage::config_dir(app_handle)?;
+    for name in [CONFIG_FILE, CONFIG_BACKUP_FILE, ".credentials"] {
+        let path = config_dir.join(name);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+// Helper: Write a config snapshot to disk, tagged with the latest version
+fn write_config(app_handle: &AppHandle, config: PersistedConfig) -> Result<(), String> {
+    write_config_file(&storage::config_dir(app_handle)?, config)
+}
+
+/// Load config from disk, or from the backup if config.json is corrupt
+pub fn load_config(app_handle: &AppHandle) -> Option<PersistedConfig> {
+    read_config_file(&storage::config_dir(app_handle).ok()?)
 }
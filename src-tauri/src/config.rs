@@ -1,34 +1,455 @@
+use crate::active_hours::ActiveHours;
+use crate::activity::{ActivityEvent, ActivityLog};
+use crate::bitbucket::budget::DEFAULT_REQUESTS_PER_HOUR;
+use crate::bitbucket::{
+    is_access_token_username, is_valid_repo_slug, is_valid_slug, BitbucketError, PipelineStep, RateLimitInfo,
+    TlsConfig, WorkspaceMember, ACCESS_TOKEN_USERNAME,
+};
+use crate::commands::ConfigWrite;
+use crate::default_branch::ResolvedBranch;
+use crate::digest::DigestSettings;
+use crate::drift::{DriftCache, DriftEnvironments, EnvironmentDrift};
+use crate::i18n::t;
+use crate::incidents::{IncidentEvent, IncidentTracker};
+use crate::pipelines_disabled::{DisabledCache, PIPELINES_CONFIG_REFRESH};
+use crate::power::PollingMode;
+use crate::polling::RefreshRequest;
+use crate::pull_requests::{PullRequestCache, PullRequestStatus};
+use crate::storage::{StorageStatus, STORAGE_ERROR_TITLE};
+use crate::tray::TrayManager;
+use crate::webhooks::NotificationRoutes;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+#[cfg(test)]
+use ts_rs::TS;
+
+/// Settings file in the config dir
+pub const CONFIG_FILE: &str = "config.json";
+
+/// The settings file as it was before the latest write, read when `CONFIG_FILE` is corrupt
+pub const CONFIG_BACKUP_FILE: &str = "config.json.bak";
+
+/// Shortest polling interval settings accept unless `min_interval_override` is set
+pub const MIN_POLLING_INTERVAL_SECONDS: u64 = 30;
+
+/// Shortest polling interval accepted even with `min_interval_override`
+pub const POLLING_INTERVAL_FLOOR_SECONDS: u64 = 5;
+
+/// Consecutive "not found" checks before a monitored repo is considered stale
+pub const STALE_NOT_FOUND_THRESHOLD: u8 = 3;
+
+/// Default interval multiplier while on battery
+pub const DEFAULT_BATTERY_INTERVAL_MULTIPLIER: u64 = 4;
+
+/// Default global hotkey for refreshing pipeline status
+pub const DEFAULT_HOTKEY_REFRESH: &str = "CmdOrCtrl+Shift+P";
+
+/// Finished runs kept per pipeline for the history glyphs
+pub const RUN_HISTORY_LIMIT: usize = 10;
+
+/// How long a failed repository lookup waits before it is tried again, like the
+/// Pipelines settings lookup
+pub const FAILED_LOOKUP_RETRY: Duration = PIPELINES_CONFIG_REFRESH;
 
 /// Application state shared across the app
 #[derive(Debug, Clone, Default)]
 pub struct AppState {
     pub credentials: Option<Credentials>,
     pub monitored_pipelines: Vec<MonitoredPipeline>,
+    /// Projects whose repositories are monitored automatically
+    pub monitored_groups: Vec<MonitoredGroup>,
+    /// Pipelines synthesized from `monitored_groups`
+    pub group_pipelines: Vec<MonitoredPipeline>,
     pub polling_interval_seconds: u64,
+    /// Lower minimum interval, only ever set by editing config.json
+    pub min_polling_interval_override: Option<u64>,
+    /// Multiply the polling interval while on battery
+    pub reduce_polling_on_battery: bool,
+    /// Stop polling while on a metered connection
+    pub pause_on_metered: bool,
+    pub battery_interval_multiplier: u64,
+    /// API requests per workspace per hour before polling slows down
+    pub api_budget_per_hour: u32,
+    /// Mode the polling loop is currently running in
+    pub polling_mode: PollingMode,
+    /// Whether the last checks reached Bitbucket
+    pub connection_status: ConnectionStatus,
+    /// Pipelines that started failing since launch
+    pub session_failure_count: u64,
+    /// Pipelines that recovered since launch
+    pub session_recovery_count: u64,
+    /// Full checks finished since launch
+    pub polls_completed: u64,
+    /// When this session began, for its uptime
+    pub session_started_at: Instant,
+    /// When the polling loop last started a check; the next is due an interval later
+    pub last_check_at: Option<Instant>,
+    /// How long the last full check took
+    pub last_poll_duration: Option<Duration>,
+    /// Quota Bitbucket reported on the latest response that carried rate limit headers
+    pub rate_limit: Option<RateLimitInfo>,
+    /// Why the status menu last fell back to the minimal menu; None once it builds again
+    pub last_menu_build_error: Option<String>,
+    /// Tray icons shown, one per account
+    pub trays: TrayManager,
     pub last_status: Option<OverallStatus>,
+    /// Details of recent runs, so they aren't refetched every poll
+    pub run_caches: RunCaches,
+    /// Main branch lookups that failed or found no branch
+    pub default_branch_lookups: FailedLookups,
+    /// Workspace members per (username, workspace slug) and when they were fetched
+    pub member_cache: HashMap<(String, String), (Instant, Vec<WorkspaceMember>)>,
+    /// Notify when a run pauses at a manual step (e.g. deployment approval)
+    pub notify_on_paused: bool,
+    /// Failure and recovery notifications switched off in settings (webhooks still fire)
+    pub disabled_notification_categories: Vec<NotificationCategory>,
+    /// Runs awaiting manual approval turn the tray icon red
+    pub treat_paused_as_unhealthy: bool,
+    /// Build number last seen paused per (workspace, repo_slug), so each pause notifies once
+    pub last_paused_build: HashMap<(String, String), u32>,
+    /// Named webhook targets that failure/recovery events are forwarded to
+    pub notification_routes: NotificationRoutes,
+    /// Failure start times and recently resolved outages
+    pub incidents: IncidentTracker,
+    /// When and over how many days the weekly digest is posted
+    pub digest_settings: DigestSettings,
+    /// When the digest notification was last posted
+    pub last_digest_sent: Option<chrono::DateTime<chrono::Utc>>,
+    /// True while a check is in flight
+    pub is_checking: bool,
+    pub wallboard_always_on_top: bool,
+    pub wallboard_start_on_launch: bool,
+    /// Recent finished runs per "workspace/repo_slug", oldest first
+    pub run_history: HashMap<String, Vec<RunRecord>>,
+    /// Append recent results (e.g. " ✓✓✗") to pipeline menu items
+    pub show_history_glyphs: bool,
+    /// Collapse pipelines with no run data into one menu line
+    pub hide_unknown_in_menu: bool,
+    /// List running and paused pipelines in their own menu section, longest running first
+    pub group_running_separately: bool,
+    /// Show a tray icon per workspace instead of one for all pipelines
+    pub multi_tray: bool,
+    /// How the tray icon draws attention to a new failure
+    pub attention_animation: AttentionAnimation,
+    /// Light or dark icons, or following the system appearance
+    pub icon_theme: IconTheme,
+    /// Bitbucket rejected the saved credentials; polling waits until they work again
+    pub auth_broken: bool,
+    /// Render the status another instance publishes to this file instead of polling
+    pub follow_status_path: Option<PathBuf>,
+    /// Write each status here for follower instances
+    pub publish_status_path: Option<PathBuf>,
+    /// Currently rendering a fresh follower feed (false while falling back to polling)
+    pub following: bool,
+    /// Failing pull request pipelines turn the tray icon red
+    pub include_prs_in_health: bool,
+    /// Open pull requests of repositories with `track_my_prs`
+    pub pull_request_cache: PullRequestCache,
+    /// Deployment drift of repositories with `drift_environments`
+    pub drift_cache: DriftCache,
+    /// Pipelines settings of repositories that stay Unknown
+    pub disabled_cache: DisabledCache,
+    /// Last wallboard window size/position
+    pub wallboard_geometry: Option<WindowGeometry>,
+    /// Language tag chosen in settings; None follows the OS locale
+    pub locale: Option<String>,
+    /// Consecutive NotFound errors per (workspace, repo_slug)
+    pub consecutive_not_found: HashMap<(String, String), u8>,
+    /// Global hotkey that refreshes and shows a status summary
+    pub hotkey_refresh: Option<String>,
+    /// Global hotkey that opens the settings window
+    pub hotkey_open_settings: Option<String>,
+    /// Global hotkey that toggles the quick-open window
+    pub hotkey_quick_open: Option<String>,
+    /// Extra CA bundle (PEM) for self-hosted or intercepted TLS
+    pub custom_ca_path: Option<PathBuf>,
+    /// Disable TLS certificate verification (insecure)
+    pub danger_accept_invalid_certs: bool,
+    /// Set once credentials are first saved, so first-run setup doesn't repeat
+    pub has_completed_setup: bool,
+    /// Publishes interval changes to the polling loop (set during setup)
+    pub polling_interval_tx: Option<watch::Sender<u64>>,
+    /// Free-text notes kept at the top of config.json
+    pub config_notes: Option<String>,
+    /// Startup check of the config directory
+    pub storage: StorageStatus,
+    /// Queue drained by the background config writer (set during setup)
+    pub config_write_tx: Option<mpsc::Sender<ConfigWrite>>,
+    /// Manual refresh requests, debounced by the refresh task (set during setup)
+    pub refresh_tx: Option<mpsc::Sender<RefreshRequest>>,
+    /// What cdMenu observed, newest last; kept in its own file rather than the config
+    pub activity: ActivityLog,
+    /// Queue drained by the activity log writer (set during setup)
+    pub activity_write_tx: Option<mpsc::Sender<Vec<ActivityEvent>>>,
 }
 
 /// User credentials (password stored in Stronghold)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Credentials {
+    /// `ACCESS_TOKEN_USERNAME` for access tokens
     pub username: String,
 }
 
+/// What kind of secret the saved password is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialKind {
+    /// App password or API token, used with the account's username
+    #[default]
+    AppPassword,
+    /// Workspace or repository access token, which has no username
+    AccessToken,
+}
+
+impl Credentials {
+    /// Credentials for an access token
+    pub fn access_token() -> Self {
+        Self {
+            username: ACCESS_TOKEN_USERNAME.to_string(),
+        }
+    }
+
+    pub fn kind(&self) -> CredentialKind {
+        if is_access_token_username(&self.username) {
+            CredentialKind::AccessToken
+        } else {
+            CredentialKind::AppPassword
+        }
+    }
+}
+
+/// Details fetched per run uuid, kept only while the run is a pipeline's latest
+#[derive(Debug, Clone, Default)]
+pub struct RunCaches {
+    /// Failed step name per failed run
+    pub failed_steps: HashMap<String, Option<String>>,
+    /// Gate step per finished run, None when the run has no such step
+    pub gate_steps: HashMap<String, Option<PipelineStep>>,
+    /// Variables per custom or manual run, fetched once from the run's detail
+    pub variables: HashMap<String, Option<Vec<(String, String)>>>,
+    /// Repository link lookups that failed; unlike the rest, kept per repository
+    pub repo_url_lookups: FailedLookups,
+}
+
+impl RunCaches {
+    /// Drop entries for runs `keep` rejects
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.failed_steps.retain(|uuid, _| keep(uuid));
+        self.gate_steps.retain(|uuid, _| keep(uuid));
+        self.variables.retain(|uuid, _| keep(uuid));
+    }
+
+    /// Add the entries of another check, e.g. a single-pipeline refresh
+    pub fn extend(&mut self, other: RunCaches) {
+        self.failed_steps.extend(other.failed_steps);
+        self.gate_steps.extend(other.gate_steps);
+        self.variables.extend(other.variables);
+        self.repo_url_lookups.extend(other.repo_url_lookups);
+    }
+}
+
+/// When a lookup per (workspace, repo_slug) last failed, so a broken repository isn't
+/// asked again on every poll
+#[derive(Debug, Clone, Default)]
+pub struct FailedLookups {
+    failed_at: HashMap<(String, String), Instant>,
+}
+
+impl FailedLookups {
+    /// Whether the repository can be looked up, i.e. its last failure is `FAILED_LOOKUP_RETRY` old
+    pub fn can_retry(&self, workspace: &str, repo_slug: &str, now: Instant) -> bool {
+        self.failed_at
+            .get(&(workspace.to_string(), repo_slug.to_string()))
+            .is_none_or(|failed_at| now.duration_since(*failed_at) >= FAILED_LOOKUP_RETRY)
+    }
+
+    pub fn record_failure(&mut self, workspace: &str, repo_slug: &str, now: Instant) {
+        self.failed_at.insert((workspace.to_string(), repo_slug.to_string()), now);
+    }
+
+    pub fn clear(&mut self, workspace: &str, repo_slug: &str) {
+        self.failed_at.remove(&(workspace.to_string(), repo_slug.to_string()));
+    }
+
+    /// Add the failures recorded by another check
+    pub fn extend(&mut self, other: FailedLookups) {
+        self.failed_at.extend(other.failed_at);
+    }
+}
+
 /// A pipeline configuration to monitor
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
 pub struct MonitoredPipeline {
     pub workspace: String,
+    #[serde(alias = "project_key")]
     pub project_key: Option<String>,
+    #[serde(alias = "project_name")]
     pub project_name: Option<String>,
+    #[serde(alias = "repo_slug")]
     pub repo_slug: String,
+    #[serde(alias = "repo_name")]
     pub repo_name: String,
     /// Optional: monitor a specific branch only
     pub branch: Option<String>,
+    /// Repository web URL from the API, used to build pipeline links
+    #[serde(default, alias = "repo_url")]
+    pub repo_url: Option<String>,
+    /// Still shown in the tray, but never notifies
+    #[serde(default)]
+    pub muted: bool,
+    /// When the pipeline was first monitored (RFC 3339, UTC)
+    #[serde(default, alias = "added_at")]
+    pub added_at: Option<String>,
+    /// Webhook target for failure/recovery events; None uses the default target
+    #[serde(default, alias = "notification_route")]
+    pub notification_route: Option<String>,
+    /// Follow one pipeline definition (selector pattern) across all branches instead of `branch`
+    #[serde(default, alias = "pipeline_definition_uuid")]
+    pub pipeline_definition_uuid: Option<String>,
+    /// Outside these hours failures don't count toward overall health or notify
+    #[serde(default, alias = "active_hours")]
+    pub active_hours: Option<ActiveHours>,
+    /// Custom pipelines that can be started from the tray
+    #[serde(default, alias = "quick_actions")]
+    pub quick_actions: Vec<QuickAction>,
+    /// Also list the pipelines of the user's open pull requests in this repository
+    #[serde(default, alias = "track_my_prs")]
+    pub track_my_prs: bool,
+    /// Without a `branch`, follow the repository's main branch instead of any branch
+    #[serde(default, alias = "auto_default_branch")]
+    pub auto_default_branch: bool,
+    /// Main branch looked up for `auto_default_branch`
+    #[serde(default, alias = "resolved_branch")]
+    pub resolved_branch: Option<String>,
+    /// Judge health by this step alone (e.g. "deploy-prod"), ignoring the rest of the run
+    #[serde(default, alias = "gate_step")]
+    pub gate_step: Option<String>,
+    /// Show how many commits one environment's deployment is ahead of another's
+    #[serde(default, alias = "drift_environments")]
+    pub drift_environments: Option<DriftEnvironments>,
+}
+
+/// A custom pipeline run offered in a pipeline's tray submenu, e.g. `custom: deploy-staging`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(test, derive(TS))]
+pub struct QuickAction {
+    pub label: String,
+    /// Custom pipeline name from bitbucket-pipelines.yml
+    pub selector: String,
+    /// Branch to run on; None uses the branch of the latest run
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+/// Shortest polling interval allowed with the given override
+pub fn min_polling_interval(override_seconds: Option<u64>) -> u64 {
+    override_seconds
+        .unwrap_or(MIN_POLLING_INTERVAL_SECONDS)
+        .max(POLLING_INTERVAL_FLOOR_SECONDS)
+}
+
+/// Reject intervals below the minimum, which never goes below `POLLING_INTERVAL_FLOOR_SECONDS`
+pub fn validate_polling_interval(seconds: u64, override_seconds: Option<u64>) -> Result<(), String> {
+    let min = min_polling_interval(override_seconds);
+    if seconds < min {
+        return Err(format!("Polling interval must be at least {} seconds", min));
+    }
+    Ok(())
+}
+
+/// Reject entries that would produce malformed API requests
+pub fn validate_monitored_pipelines(pipelines: &[MonitoredPipeline]) -> Result<(), String> {
+    for (index, pipeline) in pipelines.iter().enumerate() {
+        if pipeline.workspace.trim().is_empty() {
+            return Err(format!("MonitoredPipeline at index {} has empty workspace", index));
+        }
+        if pipeline.repo_slug.trim().is_empty() {
+            return Err(format!("MonitoredPipeline at index {} has empty repo_slug", index));
+        }
+        if !is_valid_slug(&pipeline.workspace) {
+            return Err(format!(
+                "MonitoredPipeline at index {} has invalid workspace slug '{}' (use lowercase letters, digits, '-' and '_')",
+                index, pipeline.workspace
+            ));
+        }
+        if !is_valid_repo_slug(&pipeline.repo_slug) {
+            return Err(format!(
+                "MonitoredPipeline at index {} has invalid repo_slug '{}'",
+                index, pipeline.repo_slug
+            ));
+        }
+        if pipeline.branch.as_deref().is_some_and(|b| b.trim().is_empty()) {
+            return Err(format!(
+                "MonitoredPipeline at index {} has empty branch (use null for any branch)",
+                index
+            ));
+        }
+        for action in &pipeline.quick_actions {
+            if action.label.trim().is_empty() || action.selector.trim().is_empty() {
+                return Err(format!(
+                    "MonitoredPipeline at index {} has a quick action without a label or selector",
+                    index
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl MonitoredPipeline {
+    /// Whether `now` (local time) is outside the pipeline's active hours
+    pub fn is_off_hours(&self, now: chrono::NaiveDateTime) -> bool {
+        self.active_hours
+            .as_ref()
+            .is_some_and(|hours| !hours.is_active_at(now))
+    }
+
+    /// Whether the main branch should be looked up for this pipeline
+    pub fn follows_default_branch(&self) -> bool {
+        self.auto_default_branch && self.branch.is_none()
+    }
+
+    /// Branch the latest run is filtered by, if any
+    pub fn branch_filter(&self) -> Option<&str> {
+        match &self.branch {
+            Some(branch) => Some(branch),
+            None if self.auto_default_branch => self.resolved_branch.as_deref(),
+            None => None,
+        }
+    }
+}
+
+/// A project whose repositories are all monitored, including ones added later
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MonitoredGroup {
+    pub workspace: String,
+    pub project_key: String,
+    /// Optional: monitor a specific branch in every repository
+    pub branch: Option<String>,
+    /// Repository slugs to skip
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Saved window size and position (logical pixels)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WindowGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Name of the monitor the window was on
+    pub monitor: Option<String>,
 }
 
 /// Status of an individual pipeline
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
 pub enum PipelineState {
     Healthy,
     Failed,
@@ -37,8 +458,146 @@ pub enum PipelineState {
     Unknown,
 }
 
+/// Kind of desktop notification, each of which can be switched off in settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    /// A pipeline started failing
+    Failure,
+    /// A failing pipeline passed again
+    Recovery,
+    /// A run is waiting for manual approval
+    Paused,
+}
+
+impl NotificationCategory {
+    pub const ALL: [NotificationCategory; 3] = [Self::Failure, Self::Recovery, Self::Paused];
+}
+
+/// How the tray icon draws attention to a new failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttentionAnimation {
+    /// Turn red without animating
+    #[default]
+    None,
+    /// Blink between red and dimmed red for a few seconds
+    Flash,
+}
+
+/// Which variant of the tray and menu icons to draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IconTheme {
+    /// Follow the system appearance
+    #[default]
+    Auto,
+    /// Icons for a light menu bar and menus
+    Light,
+    /// Icons for a dark menu bar and menus
+    Dark,
+}
+
+/// Whether the last checks could reach Bitbucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(tag = "state", content = "errors", rename_all = "snake_case")]
+pub enum ConnectionStatus {
+    #[default]
+    Connected,
+    /// Some checks hit network errors; the count runs until a check fully succeeds
+    Degraded(u32),
+    /// Every pipeline in the last check hit a network error
+    Disconnected,
+}
+
+impl ConnectionStatus {
+    /// Status after a full check, counting pipelines whose check hit a network error
+    pub fn after_check(self, status: &OverallStatus) -> Self {
+        let total = status.pipeline_statuses.len();
+        let errors = status
+            .pipeline_statuses
+            .iter()
+            .filter(|p| p.network_error)
+            .count();
+        if errors == 0 {
+            ConnectionStatus::Connected
+        } else if errors == total {
+            ConnectionStatus::Disconnected
+        } else {
+            let previous = match self {
+                ConnectionStatus::Degraded(n) => n,
+                _ => 0,
+            };
+            ConnectionStatus::Degraded(previous.saturating_add(errors as u32))
+        }
+    }
+}
+
+/// Activity since launch, for the settings window and the tray tooltip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SessionStats {
+    pub failures: u64,
+    pub recoveries: u64,
+    pub uptime_seconds: u64,
+    pub polls_completed: u64,
+}
+
+/// Why a pipeline's state is Unknown
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+pub enum UnknownReason {
+    /// The repository has never run Pipelines
+    NoRuns,
+    /// The latest run has no step named like the pipeline's `gate_step`
+    GateStepNotFound,
+    ApiError(String),
+    RateLimited,
+    AuthFailed,
+}
+
+impl UnknownReason {
+    pub fn from_error(error: &BitbucketError) -> Self {
+        match error {
+            BitbucketError::AuthenticationFailed => UnknownReason::AuthFailed,
+            BitbucketError::RateLimited(_) => UnknownReason::RateLimited,
+            other => UnknownReason::ApiError(other.to_string()),
+        }
+    }
+}
+
+/// Outcome of a finished run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "snake_case")]
+pub enum RunResult {
+    Passed,
+    Failed,
+}
+
+impl RunResult {
+    /// Single-character marker used in the tray menu
+    pub fn glyph(&self) -> char {
+        match self {
+            RunResult::Passed => '✓',
+            RunResult::Failed => '✗',
+        }
+    }
+}
+
+/// A finished run remembered for the history glyphs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    #[serde(alias = "build_number")]
+    pub build_number: u32,
+    pub result: RunResult,
+}
+
 /// Individual pipeline status info
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
 pub struct PipelineStatusInfo {
     pub workspace: String,
     pub project_key: Option<String>,
@@ -47,24 +606,111 @@ pub struct PipelineStatusInfo {
     pub repo_name: String,
     pub state: PipelineState,
     pub failure_reason: Option<String>,
+    /// Name of the step that failed, for failed pipelines
+    pub failed_step_name: Option<String>,
+    /// Step that alone decides this pipeline's state, when configured
+    #[serde(default)]
+    pub gate_step: Option<String>,
+    /// Debug representation of the `BitbucketError` when the check failed
+    pub error_kind: Option<String>,
+    /// The check couldn't reach Bitbucket, e.g. the connection failed or timed out
+    #[serde(default)]
+    pub network_error: bool,
+    /// Set when the state is Unknown
+    #[serde(default)]
+    pub unknown_reason: Option<UnknownReason>,
     pub pipeline_url: Option<String>,
+    /// Repository web page
+    pub repo_url: Option<String>,
+    /// Branch the latest run was for
+    pub branch: Option<String>,
+    /// Branch view for the latest run's branch
+    pub branch_url: Option<String>,
+    /// Branch the pipeline is monitored on, when one is configured
+    #[serde(default)]
+    pub monitored_branch: Option<String>,
     /// Stage name when pipeline is paused (e.g., deployment environment)
     pub stage_name: Option<String>,
+    /// Pending step a paused pipeline is waiting on
+    pub paused_step_uuid: Option<String>,
+    /// How the latest run was started, e.g. "push" or "schedule"
+    pub trigger_type: Option<String>,
+    /// Who started the latest run, i.e. who pushed the commit for push-triggered runs
+    #[serde(default)]
+    pub commit_author: Option<String>,
+    /// Variables of a custom or manual run, secured values masked as the API returns them
+    #[serde(default)]
+    pub variables: Option<Vec<(String, String)>>,
+    /// Commits deployed to one environment but not yet to the other
+    #[serde(default)]
+    pub environment_drift: Option<EnvironmentDrift>,
+    /// Pipelines is switched off in the repository's settings
+    #[serde(default)]
+    pub pipelines_disabled: bool,
+    /// Notifications are suppressed for this pipeline
+    pub muted: bool,
+    /// Webhook target for failure/recovery events
+    pub notification_route: Option<String>,
+    /// Custom pipelines that can be started from the tray
+    #[serde(default)]
+    pub quick_actions: Vec<QuickAction>,
+    /// Outside the pipeline's active hours: shown, but not counted or notified
+    pub off_hours: bool,
+    /// The current failure has been announced, or passed over while muted
+    #[serde(skip)]
+    pub failure_announced: bool,
+    /// The latest run was stopped by hand; shown as healthy but left out of the run history
+    #[serde(skip)]
+    pub stopped: bool,
+    pub pipeline_uuid: Option<String>,
+    pub build_number: Option<u32>,
+    pub created_on: Option<String>,
+    pub completed_on: Option<String>,
+    /// Run time so far, or total run time once completed
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub duration_secs: Option<u64>,
+    /// Recent finished runs, oldest first
+    #[serde(default)]
+    pub history: Vec<RunRecord>,
+    /// When this entry's data was fetched
+    pub checked_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// Overall status of all monitored pipelines
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
 pub struct OverallStatus {
     pub is_healthy: bool,
     pub failed_pipelines: Vec<FailedPipelineInfo>,
-    pub pipeline_statuses: Vec<PipelineStatusInfo>,
+    /// Private so pipelines can't be added or removed behind `index`'s back
+    pipeline_statuses: Vec<PipelineStatusInfo>,
     pub in_progress_count: usize,
+    /// Pipelines paused waiting for manual approval, not counting off-hours ones
+    pub paused_count: usize,
+    /// Pipelines with no run data (Unknown without an error)
+    #[serde(default)]
+    pub unknown_count: usize,
     pub total_monitored: usize,
     pub last_checked: String,
+    /// Pipelines of the user's open pull requests, listed separately from monitored pipelines
+    #[serde(default)]
+    pub pull_requests: Vec<PullRequestStatus>,
+    /// Runs awaiting approval count against health, so subsets can be summarized the same way
+    #[serde(skip)]
+    pub paused_is_unhealthy: bool,
+    /// Failing pull request pipelines count against health
+    #[serde(skip)]
+    pub prs_are_unhealthy: bool,
+    /// Position in `pipeline_statuses` by (workspace, repo_slug), first entry wins
+    #[serde(skip)]
+    index: HashMap<(String, String), usize>,
 }
 
 /// Information about a failed pipeline
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
 pub struct FailedPipelineInfo {
     pub workspace: String,
     pub repo_slug: String,
@@ -74,56 +720,824 @@ pub struct FailedPipelineInfo {
     pub failure_reason: String,
 }
 
-/// Persisted configuration saved to disk
+/// Last known status plus whether a check is currently running
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(test, derive(TS))]
+pub struct StatusSnapshot {
+    pub checking: bool,
+    pub status: Option<OverallStatus>,
+}
+
+/// Current shape of the `status-updated` event payload
+pub const STATUS_PAYLOAD_VERSION: u32 = 1;
+
+/// Versioned wrapper for the `status-updated` event so shape changes are detectable
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusPayload<'a> {
+    pub version: u32,
+    pub status: &'a OverallStatus,
+}
+
+impl<'a> StatusPayload<'a> {
+    pub fn new(status: &'a OverallStatus) -> Self {
+        Self {
+            version: STATUS_PAYLOAD_VERSION,
+            status,
+        }
+    }
+}
+
+/// Persisted configuration saved to disk, tagged with its format version
+///
+/// When the format changes, add a variant and a `From` migration from the
+/// previous one. Files are always written in the latest version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "version", content = "config")]
+pub enum PersistedConfigV {
+    V1(PersistedConfigV1),
+    V2(PersistedConfigV2),
+}
+
+/// The latest config format
+pub type PersistedConfig = PersistedConfigV2;
+
+impl PersistedConfigV {
+    /// Parse config.json, reading files without a `version` as V1
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid config: {}", e))?;
+        let parsed = if value.get("version").is_some() {
+            serde_json::from_value(value)
+        } else {
+            serde_json::from_value(value).map(PersistedConfigV::V1)
+        };
+        parsed.map_err(|e| format!("Invalid config: {}", e))
+    }
+
+    /// Migrate through each version to the latest format
+    pub fn into_latest(self) -> PersistedConfig {
+        match self {
+            PersistedConfigV::V1(config) => config.into(),
+            PersistedConfigV::V2(config) => config,
+        }
+    }
+}
+
+impl From<PersistedConfig> for PersistedConfigV {
+    fn from(config: PersistedConfig) -> Self {
+        PersistedConfigV::V2(config)
+    }
+}
+
+/// Read the settings in `dir`, falling back to the backup when config.json is corrupt
+///
+/// A config restored from the backup is written back to config.json straight away.
+/// None when neither file exists or neither can be read.
+pub fn read_config_file(dir: &Path) -> Option<PersistedConfig> {
+    let path = dir.join(CONFIG_FILE);
+    let main_exists = match read_config_at(&path) {
+        Some(Ok(config)) => return Some(config),
+        Some(Err(e)) => {
+            tracing::error!("Failed to load {}: {}", path.display(), e);
+            true
+        }
+        None => false,
+    };
+
+    let backup = dir.join(CONFIG_BACKUP_FILE);
+    match read_config_at(&backup)? {
+        Ok(config) => {
+            if main_exists {
+                tracing::warn!("Using backup config \u{2014} main config is corrupt");
+            } else {
+                tracing::warn!("Using backup config \u{2014} main config is missing");
+            }
+            if let Err(e) = std::fs::copy(&backup, &path) {
+                tracing::error!("Failed to restore {} from backup: {}", path.display(), e);
+            }
+            Some(config)
+        }
+        Err(e) => {
+            tracing::error!("Failed to load {}: {}", backup.display(), e);
+            None
+        }
+    }
+}
+
+// Helper: The config at `path`; None when there is no file
+fn read_config_at(path: &Path) -> Option<Result<PersistedConfig, String>> {
+    if !path.exists() {
+        return None;
+    }
+    let parsed = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config: {}", e))
+        .and_then(|json| PersistedConfigV::parse(&json))
+        .map(PersistedConfigV::into_latest);
+    Some(parsed)
+}
+
+/// Write the settings to `dir`, tagged with the latest version
+///
+/// The new file replaces config.json in one step, and the previous one is kept as
+/// the backup `read_config_file` falls back to.
+pub fn write_config_file(dir: &Path, config: PersistedConfig) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+
+    let path = dir.join(CONFIG_FILE);
+    let json = serde_json::to_string_pretty(&PersistedConfigV::from(config))
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)
+        .map_err(|e| format!("Failed to write config to {}: {}", tmp.display(), e))?;
+
+    if path.exists() {
+        let backup = dir.join(CONFIG_BACKUP_FILE);
+        if let Err(e) = std::fs::copy(&path, &backup) {
+            tracing::warn!("Failed to back up config to {}: {}", backup.display(), e);
+        }
+    }
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| format!("Failed to write config to {}: {}", path.display(), e))
+}
+
+/// First config format, with polling settings at the top level
+///
+/// Files written before the format was versioned are read as this version.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct PersistedConfig {
+pub struct PersistedConfigV1 {
+    /// Free-text annotation for people editing the file; ignored by the app.
+    /// Declared first so it serializes at the top of config.json.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
     pub username: Option<String>,
     pub monitored_pipelines: Vec<MonitoredPipeline>,
+    #[serde(default)]
+    pub monitored_groups: Vec<MonitoredGroup>,
+    #[serde(default)]
+    pub group_pipelines: Vec<MonitoredPipeline>,
     pub polling_interval_seconds: u64,
+    #[serde(default)]
+    pub reduce_polling_on_battery: bool,
+    #[serde(default)]
+    pub pause_on_metered: bool,
+    #[serde(default = "default_battery_interval_multiplier")]
+    pub battery_interval_multiplier: u64,
+    #[serde(default = "default_hotkey_refresh")]
+    pub hotkey_refresh: Option<String>,
+    #[serde(default)]
+    pub hotkey_open_settings: Option<String>,
+    #[serde(default)]
+    pub custom_ca_path: Option<PathBuf>,
+    #[serde(default)]
+    pub danger_accept_invalid_certs: bool,
+    #[serde(default)]
+    pub has_completed_setup: bool,
+    #[serde(default)]
+    pub notify_on_paused: bool,
+    #[serde(default)]
+    pub treat_paused_as_unhealthy: bool,
+    #[serde(default)]
+    pub notification_routes: NotificationRoutes,
+    #[serde(default)]
+    pub wallboard_always_on_top: bool,
+    #[serde(default)]
+    pub wallboard_start_on_launch: bool,
+    #[serde(default)]
+    pub run_history: HashMap<String, Vec<RunRecord>>,
+    #[serde(default = "default_show_history_glyphs")]
+    pub show_history_glyphs: bool,
+    #[serde(default)]
+    pub wallboard_geometry: Option<WindowGeometry>,
+}
+
+/// Polling settings, grouped since config version 2
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PollingSettings {
+    pub interval_seconds: u64,
+    #[serde(default)]
+    pub reduce_on_battery: bool,
+    #[serde(default)]
+    pub pause_on_metered: bool,
+    #[serde(default = "default_battery_interval_multiplier")]
+    pub battery_interval_multiplier: u64,
+    #[serde(default = "default_api_budget_per_hour")]
+    pub api_budget_per_hour: u32,
+    /// Replaces `MIN_POLLING_INTERVAL_SECONDS` for power users editing config.json.
+    /// Intervals below 30 seconds can run into Bitbucket's rate limits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_interval_override: Option<u64>,
+}
+
+impl Default for PollingSettings {
+    fn default() -> Self {
+        Self {
+            interval_seconds: 60,
+            reduce_on_battery: false,
+            pause_on_metered: false,
+            battery_interval_multiplier: DEFAULT_BATTERY_INTERVAL_MULTIPLIER,
+            api_budget_per_hour: DEFAULT_REQUESTS_PER_HOUR,
+            min_interval_override: None,
+        }
+    }
+}
+
+/// Current config format
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PersistedConfigV2 {
+    /// Free-text annotation for people editing the file; ignored by the app.
+    /// Declared first so it serializes right after the version in config.json.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    pub username: Option<String>,
+    pub monitored_pipelines: Vec<MonitoredPipeline>,
+    #[serde(default)]
+    pub monitored_groups: Vec<MonitoredGroup>,
+    #[serde(default)]
+    pub group_pipelines: Vec<MonitoredPipeline>,
+    pub polling: PollingSettings,
+    #[serde(default = "default_hotkey_refresh")]
+    pub hotkey_refresh: Option<String>,
+    #[serde(default)]
+    pub hotkey_open_settings: Option<String>,
+    #[serde(default)]
+    pub hotkey_quick_open: Option<String>,
+    #[serde(default)]
+    pub custom_ca_path: Option<PathBuf>,
+    #[serde(default)]
+    pub danger_accept_invalid_certs: bool,
+    #[serde(default)]
+    pub has_completed_setup: bool,
+    #[serde(default)]
+    pub notify_on_paused: bool,
+    #[serde(default)]
+    pub disabled_notification_categories: Vec<NotificationCategory>,
+    #[serde(default)]
+    pub treat_paused_as_unhealthy: bool,
+    #[serde(default)]
+    pub notification_routes: NotificationRoutes,
+    #[serde(default)]
+    pub wallboard_always_on_top: bool,
+    #[serde(default)]
+    pub wallboard_start_on_launch: bool,
+    #[serde(default)]
+    pub run_history: HashMap<String, Vec<RunRecord>>,
+    #[serde(default = "default_show_history_glyphs")]
+    pub show_history_glyphs: bool,
+    #[serde(default)]
+    pub hide_unknown_in_menu: bool,
+    #[serde(default)]
+    pub group_running_separately: bool,
+    #[serde(default)]
+    pub multi_tray: bool,
+    #[serde(default)]
+    pub attention_animation: AttentionAnimation,
+    #[serde(default)]
+    pub icon_theme: IconTheme,
+    #[serde(default)]
+    pub auth_broken: bool,
+    #[serde(default)]
+    pub follow_status_path: Option<PathBuf>,
+    #[serde(default)]
+    pub publish_status_path: Option<PathBuf>,
+    #[serde(default)]
+    pub include_prs_in_health: bool,
+    #[serde(default)]
+    pub wallboard_geometry: Option<WindowGeometry>,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub incident_events: Vec<IncidentEvent>,
+    #[serde(default)]
+    pub digest_settings: DigestSettings,
+    #[serde(default)]
+    pub last_digest_sent: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<PersistedConfigV1> for PersistedConfigV2 {
+    fn from(v1: PersistedConfigV1) -> Self {
+        Self {
+            notes: v1.notes,
+            username: v1.username,
+            monitored_pipelines: v1.monitored_pipelines,
+            monitored_groups: v1.monitored_groups,
+            group_pipelines: v1.group_pipelines,
+            polling: PollingSettings {
+                interval_seconds: v1.polling_interval_seconds,
+                reduce_on_battery: v1.reduce_polling_on_battery,
+                pause_on_metered: v1.pause_on_metered,
+                battery_interval_multiplier: v1.battery_interval_multiplier,
+                api_budget_per_hour: DEFAULT_REQUESTS_PER_HOUR,
+                min_interval_override: None,
+            },
+            hotkey_refresh: v1.hotkey_refresh,
+            hotkey_open_settings: v1.hotkey_open_settings,
+            hotkey_quick_open: None,
+            custom_ca_path: v1.custom_ca_path,
+            danger_accept_invalid_certs: v1.danger_accept_invalid_certs,
+            has_completed_setup: v1.has_completed_setup,
+            notify_on_paused: v1.notify_on_paused,
+            disabled_notification_categories: Vec::new(),
+            treat_paused_as_unhealthy: v1.treat_paused_as_unhealthy,
+            notification_routes: v1.notification_routes,
+            wallboard_always_on_top: v1.wallboard_always_on_top,
+            wallboard_start_on_launch: v1.wallboard_start_on_launch,
+            run_history: v1.run_history,
+            show_history_glyphs: v1.show_history_glyphs,
+            hide_unknown_in_menu: false,
+            group_running_separately: false,
+            multi_tray: false,
+            attention_animation: AttentionAnimation::None,
+            icon_theme: IconTheme::Auto,
+            auth_broken: false,
+            follow_status_path: None,
+            publish_status_path: None,
+            include_prs_in_health: false,
+            wallboard_geometry: v1.wallboard_geometry,
+            locale: None,
+            incident_events: Vec::new(),
+            digest_settings: DigestSettings::default(),
+            last_digest_sent: None,
+        }
+    }
+}
+
+fn default_show_history_glyphs() -> bool {
+    true
+}
+
+fn default_api_budget_per_hour() -> u32 {
+    DEFAULT_REQUESTS_PER_HOUR
+}
+
+fn default_battery_interval_multiplier() -> u64 {
+    DEFAULT_BATTERY_INTERVAL_MULTIPLIER
+}
+
+fn default_hotkey_refresh() -> Option<String> {
+    Some(DEFAULT_HOTKEY_REFRESH.to_string())
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
+            config_notes: None,
             credentials: None,
             monitored_pipelines: Vec::new(),
+            monitored_groups: Vec::new(),
+            group_pipelines: Vec::new(),
             polling_interval_seconds: 60,
+            min_polling_interval_override: None,
+            reduce_polling_on_battery: false,
+            pause_on_metered: false,
+            battery_interval_multiplier: DEFAULT_BATTERY_INTERVAL_MULTIPLIER,
+            api_budget_per_hour: DEFAULT_REQUESTS_PER_HOUR,
+            polling_mode: PollingMode::Normal,
+            connection_status: ConnectionStatus::Connected,
+            session_failure_count: 0,
+            session_recovery_count: 0,
+            polls_completed: 0,
+            session_started_at: Instant::now(),
+            last_check_at: None,
+            last_poll_duration: None,
+            rate_limit: None,
+            last_menu_build_error: None,
+            trays: TrayManager::default(),
             last_status: None,
+            run_caches: RunCaches::default(),
+            default_branch_lookups: FailedLookups::default(),
+            member_cache: HashMap::new(),
+            is_checking: false,
+            config_write_tx: None,
+            refresh_tx: None,
+            activity: ActivityLog::default(),
+            activity_write_tx: None,
+            polling_interval_tx: None,
+            storage: StorageStatus::default(),
+            notify_on_paused: false,
+            disabled_notification_categories: Vec::new(),
+            treat_paused_as_unhealthy: false,
+            notification_routes: NotificationRoutes::default(),
+            last_paused_build: HashMap::new(),
+            incidents: IncidentTracker::default(),
+            digest_settings: DigestSettings::default(),
+            last_digest_sent: None,
+            wallboard_always_on_top: false,
+            wallboard_start_on_launch: false,
+            run_history: HashMap::new(),
+            show_history_glyphs: true,
+            hide_unknown_in_menu: false,
+            group_running_separately: false,
+            multi_tray: false,
+            attention_animation: AttentionAnimation::None,
+            icon_theme: IconTheme::Auto,
+            auth_broken: false,
+            follow_status_path: None,
+            publish_status_path: None,
+            following: false,
+            include_prs_in_health: false,
+            pull_request_cache: PullRequestCache::default(),
+            drift_cache: DriftCache::default(),
+            disabled_cache: DisabledCache::default(),
+            wallboard_geometry: None,
+            locale: None,
+            consecutive_not_found: HashMap::new(),
+            hotkey_refresh: default_hotkey_refresh(),
+            hotkey_open_settings: None,
+            hotkey_quick_open: None,
+            custom_ca_path: None,
+            danger_accept_invalid_certs: false,
+            has_completed_setup: false,
         }
     }
 
     /// Convert to persisted config for saving
     pub fn to_persisted(&self) -> PersistedConfig {
         PersistedConfig {
+            notes: self.config_notes.clone(),
             username: self.credentials.as_ref().map(|c| c.username.clone()),
             monitored_pipelines: self.monitored_pipelines.clone(),
-            polling_interval_seconds: self.polling_interval_seconds,
+            monitored_groups: self.monitored_groups.clone(),
+            group_pipelines: self.group_pipelines.clone(),
+            polling: PollingSettings {
+                interval_seconds: self.polling_interval_seconds,
+                reduce_on_battery: self.reduce_polling_on_battery,
+                pause_on_metered: self.pause_on_metered,
+                battery_interval_multiplier: self.battery_interval_multiplier,
+                api_budget_per_hour: self.api_budget_per_hour,
+                min_interval_override: self.min_polling_interval_override,
+            },
+            hotkey_refresh: self.hotkey_refresh.clone(),
+            hotkey_open_settings: self.hotkey_open_settings.clone(),
+            hotkey_quick_open: self.hotkey_quick_open.clone(),
+            custom_ca_path: self.custom_ca_path.clone(),
+            danger_accept_invalid_certs: self.danger_accept_invalid_certs,
+            has_completed_setup: self.has_completed_setup,
+            notify_on_paused: self.notify_on_paused,
+            disabled_notification_categories: self.disabled_notification_categories.clone(),
+            treat_paused_as_unhealthy: self.treat_paused_as_unhealthy,
+            notification_routes: self.notification_routes.clone(),
+            wallboard_always_on_top: self.wallboard_always_on_top,
+            wallboard_start_on_launch: self.wallboard_start_on_launch,
+            run_history: self.run_history.clone(),
+            show_history_glyphs: self.show_history_glyphs,
+            hide_unknown_in_menu: self.hide_unknown_in_menu,
+            group_running_separately: self.group_running_separately,
+            multi_tray: self.multi_tray,
+            attention_animation: self.attention_animation,
+            icon_theme: self.icon_theme,
+            auth_broken: self.auth_broken,
+            follow_status_path: self.follow_status_path.clone(),
+            publish_status_path: self.publish_status_path.clone(),
+            include_prs_in_health: self.include_prs_in_health,
+            wallboard_geometry: self.wallboard_geometry.clone(),
+            locale: self.locale.clone(),
+            incident_events: self.incidents.events().to_vec(),
+            digest_settings: self.digest_settings.clone(),
+            last_digest_sent: self.last_digest_sent,
         }
     }
 
     /// Load from persisted config
     pub fn from_persisted(config: PersistedConfig) -> Self {
         Self {
+            config_notes: config.notes,
             credentials: config.username.map(|username| Credentials { username }),
             monitored_pipelines: config.monitored_pipelines,
-            polling_interval_seconds: if config.polling_interval_seconds >= 30 {
-                config.polling_interval_seconds
+            monitored_groups: config.monitored_groups,
+            group_pipelines: config.group_pipelines,
+            polling_interval_seconds: if config.polling.interval_seconds
+                >= min_polling_interval(config.polling.min_interval_override)
+            {
+                config.polling.interval_seconds
             } else {
                 60
             },
+            min_polling_interval_override: config.polling.min_interval_override,
+            reduce_polling_on_battery: config.polling.reduce_on_battery,
+            pause_on_metered: config.polling.pause_on_metered,
+            battery_interval_multiplier: config.polling.battery_interval_multiplier.max(1),
+            api_budget_per_hour: config.polling.api_budget_per_hour.max(1),
+            polling_mode: PollingMode::Normal,
+            connection_status: ConnectionStatus::Connected,
+            session_failure_count: 0,
+            session_recovery_count: 0,
+            polls_completed: 0,
+            session_started_at: Instant::now(),
+            last_check_at: None,
+            last_poll_duration: None,
+            rate_limit: None,
+            last_menu_build_error: None,
+            trays: TrayManager::default(),
             last_status: None,
+            run_caches: RunCaches::default(),
+            default_branch_lookups: FailedLookups::default(),
+            member_cache: HashMap::new(),
+            is_checking: false,
+            config_write_tx: None,
+            refresh_tx: None,
+            activity: ActivityLog::default(),
+            activity_write_tx: None,
+            polling_interval_tx: None,
+            storage: StorageStatus::default(),
+            notify_on_paused: config.notify_on_paused,
+            disabled_notification_categories: config.disabled_notification_categories,
+            treat_paused_as_unhealthy: config.treat_paused_as_unhealthy,
+            notification_routes: config.notification_routes,
+            last_paused_build: HashMap::new(),
+            incidents: IncidentTracker::from_events(config.incident_events),
+            digest_settings: config.digest_settings,
+            last_digest_sent: config.last_digest_sent,
+            wallboard_always_on_top: config.wallboard_always_on_top,
+            wallboard_start_on_launch: config.wallboard_start_on_launch,
+            run_history: config.run_history,
+            show_history_glyphs: config.show_history_glyphs,
+            hide_unknown_in_menu: config.hide_unknown_in_menu,
+            group_running_separately: config.group_running_separately,
+            multi_tray: config.multi_tray,
+            attention_animation: config.attention_animation,
+            icon_theme: config.icon_theme,
+            auth_broken: config.auth_broken,
+            follow_status_path: config.follow_status_path,
+            publish_status_path: config.publish_status_path,
+            following: false,
+            include_prs_in_health: config.include_prs_in_health,
+            pull_request_cache: PullRequestCache::default(),
+            drift_cache: DriftCache::default(),
+            disabled_cache: DisabledCache::default(),
+            wallboard_geometry: config.wallboard_geometry,
+            locale: config.locale,
+            consecutive_not_found: HashMap::new(),
+            hotkey_refresh: config.hotkey_refresh,
+            hotkey_open_settings: config.hotkey_open_settings,
+            hotkey_quick_open: config.hotkey_quick_open,
+            custom_ca_path: config.custom_ca_path,
+            danger_accept_invalid_certs: config.danger_accept_invalid_certs,
+            has_completed_setup: config.has_completed_setup,
+        }
+    }
+
+    /// Explicit pipelines plus group-derived ones not already tracked manually
+    pub fn all_monitored_pipelines(&self) -> Vec<MonitoredPipeline> {
+        let mut all = self.monitored_pipelines.clone();
+        for pipeline in &self.group_pipelines {
+            let tracked = all
+                .iter()
+                .any(|p| p.workspace == pipeline.workspace && p.repo_slug == pipeline.repo_slug);
+            if !tracked {
+                all.push(pipeline.clone());
+            }
+        }
+        all
+    }
+
+    /// Exclude a group-derived pipeline's repository from the groups that add it,
+    /// so removing it isn't undone by the next group sync
+    pub fn exclude_from_groups(&mut self, pipeline: &MonitoredPipeline) {
+        let owning = self.monitored_groups.iter_mut().filter(|g| {
+            g.workspace == pipeline.workspace
+                && pipeline.project_key.as_deref() == Some(g.project_key.as_str())
+                && g.branch == pipeline.branch
+        });
+        for group in owning {
+            if !group.exclude.contains(&pipeline.repo_slug) {
+                group.exclude.push(pipeline.repo_slug.clone());
+            }
+        }
+    }
+
+    /// Whether a monitored repo has repeatedly come back as not found
+    pub fn is_stale(&self, workspace: &str, repo_slug: &str) -> bool {
+        self.consecutive_not_found
+            .get(&(workspace.to_string(), repo_slug.to_string()))
+            .is_some_and(|count| *count >= STALE_NOT_FOUND_THRESHOLD)
+    }
+
+    /// Apply `mutate`, then `persist`; if persisting fails, restore the previous state
+    pub fn update_or_rollback<M, P>(&mut self, mutate: M, persist: P) -> Result<(), String>
+    where
+        M: FnOnce(&mut AppState),
+        P: FnOnce(&AppState) -> Result<(), String>,
+    {
+        let previous = self.clone();
+        mutate(self);
+        persist(self).inspect_err(|_| *self = previous)
+    }
+
+    /// Whether desktop notifications of a category are shown
+    ///
+    /// Approval notifications are opt-in through `notify_on_paused`; the others are on
+    /// unless switched off.
+    pub fn notifications_enabled(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::Paused => self.notify_on_paused,
+            _ => !self.disabled_notification_categories.contains(&category),
+        }
+    }
+
+    /// Switch desktop notifications of a category on or off
+    pub fn set_notifications_enabled(&mut self, category: NotificationCategory, enabled: bool) {
+        match category {
+            NotificationCategory::Paused => self.notify_on_paused = enabled,
+            _ => {
+                self.disabled_notification_categories.retain(|c| *c != category);
+                if !enabled {
+                    self.disabled_notification_categories.push(category);
+                }
+            }
+        }
+    }
+
+    /// Cache repository web URLs resolved during a check; returns whether anything changed
+    pub fn cache_repo_urls(&mut self, statuses: &[PipelineStatusInfo]) -> bool {
+        let mut changed = false;
+        let pipelines = self
+            .monitored_pipelines
+            .iter_mut()
+            .chain(self.group_pipelines.iter_mut());
+        for pipeline in pipelines.filter(|p| p.repo_url.is_none()) {
+            let resolved = statuses.iter().find(|s| {
+                s.workspace == pipeline.workspace && s.repo_slug == pipeline.repo_slug
+            });
+            if let Some(url) = resolved.and_then(|s| s.repo_url.clone()) {
+                pipeline.repo_url = Some(url);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Store looked-up main branches on their monitored entries; returns whether anything changed
+    pub fn cache_resolved_branches(&mut self, resolved: &[ResolvedBranch]) -> bool {
+        let mut changed = false;
+        let pipelines = self
+            .monitored_pipelines
+            .iter_mut()
+            .chain(self.group_pipelines.iter_mut());
+        for pipeline in pipelines.filter(|p| p.follows_default_branch()) {
+            let found = resolved
+                .iter()
+                .find(|r| r.workspace == pipeline.workspace && r.repo_slug == pipeline.repo_slug);
+            if let Some(found) = found {
+                if pipeline.resolved_branch.as_deref() != Some(found.branch.as_str()) {
+                    pipeline.resolved_branch = Some(found.branch.clone());
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Remember newly finished runs and attach each pipeline's history to its status
+    ///
+    /// Stopped runs are left out, as is the history of pipelines no longer in `statuses`.
+    /// Returns true when the history changed, so the caller can persist it.
+    pub fn record_run_history(&mut self, statuses: &mut [PipelineStatusInfo]) -> bool {
+        let monitored: HashSet<String> =
+            statuses.iter().map(|s| format!("{}/{}", s.workspace, s.repo_slug)).collect();
+        let before = self.run_history.len();
+        self.run_history.retain(|key, _| monitored.contains(key));
+        let mut changed = self.run_history.len() != before;
+
+        for status in statuses {
+            let key = format!("{}/{}", status.workspace, status.repo_slug);
+            let result = match status.state {
+                PipelineState::Healthy if !status.stopped => Some(RunResult::Passed),
+                PipelineState::Failed => Some(RunResult::Failed),
+                _ => None,
+            };
+            if let (Some(result), Some(build_number), Some(_)) =
+                (result, status.build_number, &status.completed_on)
+            {
+                let history = self.run_history.entry(key.clone()).or_default();
+                if !history.iter().any(|r| r.build_number == build_number) {
+                    history.push(RunRecord { build_number, result });
+                    let excess = history.len().saturating_sub(RUN_HISTORY_LIMIT);
+                    history.drain(..excess);
+                    changed = true;
+                }
+            }
+
+            status.history = self.run_history.get(&key).cloned().unwrap_or_default();
         }
+        changed
+    }
+
+    /// Shortest polling interval settings accept
+    pub fn min_polling_interval(&self) -> u64 {
+        min_polling_interval(self.min_polling_interval_override)
+    }
+
+    /// TLS options for building Bitbucket clients
+    pub fn tls_config(&self) -> TlsConfig {
+        TlsConfig {
+            custom_ca_path: self.custom_ca_path.clone(),
+            danger_accept_invalid_certs: self.danger_accept_invalid_certs,
+        }
+    }
+
+    /// Tooltip header for a status, noting when it comes from another instance
+    pub fn status_title(&self) -> String {
+        if self.following {
+            format!("{} {}", self.tooltip_title(), t("tooltip.following"))
+        } else {
+            self.tooltip_title().to_string()
+        }
+    }
+
+    /// Failures, recoveries and checks since launch
+    pub fn session_stats(&self) -> SessionStats {
+        SessionStats {
+            failures: self.session_failure_count,
+            recoveries: self.session_recovery_count,
+            uptime_seconds: self.session_started_at.elapsed().as_secs(),
+            polls_completed: self.polls_completed,
+        }
+    }
+
+    /// Tooltip header, flagging a storage problem ahead of the polling mode
+    pub fn tooltip_title(&self) -> &'static str {
+        if self.storage.error.is_some() {
+            STORAGE_ERROR_TITLE
+        } else {
+            self.polling_mode.tooltip_title()
+        }
+    }
+}
+
+impl PipelineStatusInfo {
+    /// Status for a monitored pipeline checked at `now`, with no run details filled in
+    pub fn new(
+        pipeline: &MonitoredPipeline,
+        state: PipelineState,
+        now: chrono::DateTime<chrono::Local>,
+    ) -> Self {
+        Self {
+            workspace: pipeline.workspace.clone(),
+            project_key: pipeline.project_key.clone(),
+            project_name: pipeline.project_name.clone(),
+            repo_slug: pipeline.repo_slug.clone(),
+            repo_name: pipeline.repo_name.clone(),
+            state,
+            failure_reason: None,
+            failed_step_name: None,
+            gate_step: pipeline.gate_step.clone(),
+            error_kind: None,
+            network_error: false,
+            unknown_reason: None,
+            pipeline_url: None,
+            repo_url: pipeline.repo_url.clone(),
+            branch: None,
+            branch_url: None,
+            monitored_branch: pipeline.branch.clone(),
+            stage_name: None,
+            paused_step_uuid: None,
+            trigger_type: None,
+            commit_author: None,
+            variables: None,
+            environment_drift: None,
+            pipelines_disabled: false,
+            muted: pipeline.muted,
+            notification_route: pipeline.notification_route.clone(),
+            quick_actions: pipeline.quick_actions.clone(),
+            off_hours: pipeline.is_off_hours(now.naive_local()),
+            failure_announced: false,
+            stopped: false,
+            pipeline_uuid: None,
+            build_number: None,
+            created_on: None,
+            completed_on: None,
+            duration_secs: None,
+            history: Vec::new(),
+            checked_at: now.with_timezone(&chrono::Utc),
+        }
+    }
+
+    /// Unknown because the repository has no runs, rather than because checking it failed
+    pub fn has_no_data(&self) -> bool {
+        matches!(self.state, PipelineState::Unknown) && self.error_kind.is_none()
     }
 }
 
 impl OverallStatus {
+    /// Every pipeline check was rejected for authentication, e.g. after the app password was rotated
+    pub fn all_auth_failed(&self) -> bool {
+        !self.pipeline_statuses.is_empty()
+            && self
+                .pipeline_statuses
+                .iter()
+                .all(|p| p.unknown_reason == Some(UnknownReason::AuthFailed))
+    }
+
+    /// Summarize pipeline statuses; with `paused_is_unhealthy`, runs awaiting approval
+    /// also make the overall status unhealthy
     pub fn new(
         pipeline_statuses: Vec<PipelineStatusInfo>,
         timestamp: String,
+        paused_is_unhealthy: bool,
     ) -> Self {
         let failed_pipelines: Vec<FailedPipelineInfo> = pipeline_statuses
             .iter()
-            .filter(|p| matches!(p.state, PipelineState::Failed))
+            .filter(|p| matches!(p.state, PipelineState::Failed) && !p.off_hours)
             .map(|p| FailedPipelineInfo {
                 workspace: p.workspace.clone(),
                 repo_slug: p.repo_slug.clone(),
@@ -139,17 +1553,701 @@ impl OverallStatus {
             .filter(|p| matches!(p.state, PipelineState::InProgress))
             .count();
 
-        let is_healthy = failed_pipelines.is_empty();
+        let paused_count = pipeline_statuses
+            .iter()
+            .filter(|p| matches!(p.state, PipelineState::Paused) && !p.off_hours)
+            .count();
+
+        let unknown_count = pipeline_statuses.iter().filter(|p| p.has_no_data()).count();
+
+        let is_healthy = failed_pipelines.is_empty() && !(paused_is_unhealthy && paused_count > 0);
         let total_monitored = pipeline_statuses.len();
 
+        let index = index_statuses(&pipeline_statuses);
+
         Self {
             is_healthy,
             failed_pipelines,
             pipeline_statuses,
             in_progress_count,
+            paused_count,
+            unknown_count,
             total_monitored,
             last_checked: timestamp,
+            pull_requests: Vec::new(),
+            paused_is_unhealthy,
+            prs_are_unhealthy: false,
+            index,
         }
     }
+
+    /// Status of a monitored repository, looked up without scanning every pipeline
+    pub fn pipeline(&self, workspace: &str, repo_slug: &str) -> Option<&PipelineStatusInfo> {
+        let i = self.index.get(&(workspace.to_string(), repo_slug.to_string()))?;
+        self.pipeline_statuses.get(*i)
+    }
+
+    /// Every monitored pipeline's status, in check order
+    pub fn pipeline_statuses(&self) -> &[PipelineStatusInfo] {
+        &self.pipeline_statuses
+    }
+
+    /// Statuses to update in place; a slice, so none can be added or removed
+    pub fn pipeline_statuses_mut(&mut self) -> &mut [PipelineStatusInfo] {
+        &mut self.pipeline_statuses
+    }
+
+    /// Take the statuses, e.g. to summarize them again
+    pub fn into_pipeline_statuses(self) -> Vec<PipelineStatusInfo> {
+        self.pipeline_statuses
+    }
+
+    /// Fill in the lookup index, which isn't serialized, after deserializing
+    pub fn reindexed(mut self) -> Self {
+        self.index = index_statuses(&self.pipeline_statuses);
+        self
+    }
+
+    /// Attach pull request pipelines; failing ones only count against health when opted in
+    pub fn with_pull_requests(
+        mut self,
+        pull_requests: Vec<PullRequestStatus>,
+        count_toward_health: bool,
+    ) -> Self {
+        if count_toward_health
+            && pull_requests
+                .iter()
+                .any(|pr| pr.state == PipelineState::Failed)
+        {
+            self.is_healthy = false;
+        }
+        self.pull_requests = pull_requests;
+        self.prs_are_unhealthy = count_toward_health;
+        self
+    }
+
+    /// Failing pull request pipelines, when they count against health
+    pub fn unhealthy_pull_request_count(&self) -> usize {
+        if !self.prs_are_unhealthy {
+            return 0;
+        }
+        self.pull_requests
+            .iter()
+            .filter(|pr| pr.state == PipelineState::Failed)
+            .count()
+    }
+
+    /// Workspaces of the monitored pipelines, in first-seen order
+    pub fn workspaces(&self) -> Vec<String> {
+        let mut workspaces: Vec<String> = Vec::new();
+        for p in &self.pipeline_statuses {
+            if !workspaces.contains(&p.workspace) {
+                workspaces.push(p.workspace.clone());
+            }
+        }
+        workspaces
+    }
+
+    /// The same status limited to one workspace's pipelines and pull requests
+    pub fn for_workspace(&self, workspace: &str) -> OverallStatus {
+        self.filtered(Some(workspace), None)
+    }
+
+    /// The same status limited to pipelines in a workspace and/or project, with their
+    /// repositories' pull requests
+    pub fn filtered(&self, workspace: Option<&str>, project_key: Option<&str>) -> OverallStatus {
+        let pipelines: Vec<PipelineStatusInfo> = self
+            .pipeline_statuses
+            .iter()
+            .filter(|p| workspace.is_none_or(|ws| p.workspace == ws))
+            .filter(|p| project_key.is_none_or(|key| p.project_key.as_deref() == Some(key)))
+            .cloned()
+            .collect();
+        let pull_requests = self
+            .pull_requests
+            .iter()
+            .filter(|pr| {
+                pipelines
+                    .iter()
+                    .any(|p| p.workspace == pr.workspace && p.repo_slug == pr.repo_slug)
+            })
+            .cloned()
+            .collect();
+        OverallStatus::new(pipelines, self.last_checked.clone(), self.paused_is_unhealthy)
+            .with_pull_requests(pull_requests, self.prs_are_unhealthy)
+    }
 }
 
+/// Position of each (workspace, repo_slug) in `statuses`, first entry wins
+fn index_statuses(statuses: &[PipelineStatusInfo]) -> HashMap<(String, String), usize> {
+    let mut index = HashMap::new();
+    for (i, p) in statuses.iter().enumerate() {
+        index
+            .entry((p.workspace.clone(), p.repo_slug.clone()))
+            .or_insert(i);
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn update_or_rollback_keeps_changes_when_persist_succeeds() {
+        let mut state = AppState::new();
+
+        let result = state.update_or_rollback(
+            |s| {
+                s.credentials = Some(Credentials {
+                    username: "new@example.com".to_string(),
+                })
+            },
+            |_| Ok(()),
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(
+            state.credentials.map(|c| c.username).as_deref(),
+            Some("new@example.com")
+        );
+    }
+
+    #[test]
+    fn update_or_rollback_restores_state_when_persist_fails() {
+        let mut state = AppState::new();
+        state.credentials = Some(Credentials {
+            username: "old@example.com".to_string(),
+        });
+
+        let result = state.update_or_rollback(
+            |s| {
+                s.credentials = Some(Credentials {
+                    username: "new@example.com".to_string(),
+                });
+                s.has_completed_setup = true;
+            },
+            |_| Err("Failed to write config: read-only file system".to_string()),
+        );
+
+        assert_eq!(
+            result,
+            Err("Failed to write config: read-only file system".to_string())
+        );
+        assert_eq!(
+            state.credentials.map(|c| c.username).as_deref(),
+            Some("old@example.com")
+        );
+        assert!(!state.has_completed_setup);
+    }
+
+    #[test]
+    fn access_tokens_are_recognised_by_their_placeholder_username() {
+        let account = Credentials {
+            username: "me@example.com".to_string(),
+        };
+        let legacy_empty = Credentials {
+            username: String::new(),
+        };
+
+        assert_eq!(account.kind(), CredentialKind::AppPassword);
+        assert_eq!(Credentials::access_token().kind(), CredentialKind::AccessToken);
+        assert_eq!(legacy_empty.kind(), CredentialKind::AccessToken);
+    }
+
+    fn pipeline(workspace: &str, repo_slug: &str, branch: Option<&str>) -> MonitoredPipeline {
+        MonitoredPipeline {
+            workspace: workspace.to_string(),
+            repo_slug: repo_slug.to_string(),
+            repo_name: repo_slug.to_string(),
+            branch: branch.map(str::to_string),
+            ..MonitoredPipeline::default()
+        }
+    }
+
+    #[test]
+    fn removed_group_pipelines_are_excluded_from_their_group() {
+        let mut state = AppState::new();
+        let group = |project_key: &str| MonitoredGroup {
+            workspace: "ws".to_string(),
+            project_key: project_key.to_string(),
+            branch: None,
+            exclude: Vec::new(),
+        };
+        state.monitored_groups = vec![group("CORE"), group("WEB")];
+        let from_core = MonitoredPipeline {
+            project_key: Some("CORE".to_string()),
+            ..pipeline("ws", "api", None)
+        };
+
+        state.exclude_from_groups(&from_core);
+        state.exclude_from_groups(&from_core);
+
+        assert_eq!(state.monitored_groups[0].exclude, ["api"]);
+        assert!(state.monitored_groups[1].exclude.is_empty());
+    }
+
+    #[test]
+    fn validate_monitored_pipelines_rejects_empty_fields() {
+        let valid = pipeline("ws", "api", None);
+
+        assert_eq!(
+            validate_monitored_pipelines(&[valid.clone(), pipeline("ws", "  ", None)]),
+            Err("MonitoredPipeline at index 1 has empty repo_slug".to_string())
+        );
+        assert_eq!(
+            validate_monitored_pipelines(&[pipeline("", "api", None)]),
+            Err("MonitoredPipeline at index 0 has empty workspace".to_string())
+        );
+        assert!(validate_monitored_pipelines(&[pipeline("My Workspace", "api", None)])
+            .unwrap_err()
+            .contains("invalid workspace slug 'My Workspace'"));
+        assert!(validate_monitored_pipelines(&[pipeline("ws", "API", None)])
+            .unwrap_err()
+            .contains("invalid repo_slug"));
+        assert!(validate_monitored_pipelines(&[pipeline("ws", "api", Some(""))])
+            .unwrap_err()
+            .contains("empty branch"));
+        assert!(validate_monitored_pipelines(&[valid, pipeline("ws", "web", Some("main"))]).is_ok());
+    }
+
+    #[test]
+    fn unversioned_config_is_read_as_v1_and_migrated() {
+        let json = r#"{
+            "username": "me@example.com",
+            "monitored_pipelines": [],
+            "polling_interval_seconds": 120,
+            "reduce_polling_on_battery": true
+        }"#;
+
+        let parsed = PersistedConfigV::parse(json).unwrap();
+        assert!(matches!(parsed, PersistedConfigV::V1(_)));
+
+        let config = parsed.into_latest();
+        assert_eq!(config.username.as_deref(), Some("me@example.com"));
+        assert_eq!(
+            config.polling,
+            PollingSettings {
+                interval_seconds: 120,
+                reduce_on_battery: true,
+                pause_on_metered: false,
+                battery_interval_multiplier: DEFAULT_BATTERY_INTERVAL_MULTIPLIER,
+                api_budget_per_hour: DEFAULT_REQUESTS_PER_HOUR,
+                min_interval_override: None,
+            }
+        );
+    }
+
+    #[test]
+    fn min_interval_override_lowers_the_minimum_down_to_the_floor() {
+        assert!(validate_polling_interval(30, None).is_ok());
+        assert_eq!(
+            validate_polling_interval(10, None),
+            Err("Polling interval must be at least 30 seconds".to_string())
+        );
+        assert!(validate_polling_interval(10, Some(10)).is_ok());
+        assert_eq!(
+            validate_polling_interval(4, Some(1)),
+            Err("Polling interval must be at least 5 seconds".to_string())
+        );
+    }
+
+    #[test]
+    fn min_interval_override_is_read_from_the_file_but_not_written_unset() {
+        let json = r#"{
+            "version": "V2",
+            "config": {
+                "username": null,
+                "monitored_pipelines": [],
+                "polling": { "interval_seconds": 10, "min_interval_override": 10 }
+            }
+        }"#;
+
+        let state = AppState::from_persisted(PersistedConfigV::parse(json).unwrap().into_latest());
+        assert_eq!(state.polling_interval_seconds, 10);
+        assert_eq!(state.min_polling_interval(), 10);
+
+        let saved = serde_json::to_value(PersistedConfigV::from(AppState::new().to_persisted())).unwrap();
+        assert!(saved["config"]["polling"].get("min_interval_override").is_none());
+    }
+
+    #[test]
+    fn config_is_saved_in_the_latest_version() {
+        let mut state = AppState::new();
+        state.polling_interval_seconds = 300;
+
+        let json = serde_json::to_value(PersistedConfigV::from(state.to_persisted())).unwrap();
+        assert_eq!(json["version"], "V2");
+        assert_eq!(json["config"]["polling"]["interval_seconds"], 300);
+
+        let reloaded = PersistedConfigV::parse(&json.to_string()).unwrap();
+        assert!(matches!(reloaded, PersistedConfigV::V2(_)));
+        assert_eq!(reloaded.into_latest().polling.interval_seconds, 300);
+    }
+
+    #[test]
+    fn tagged_v1_config_is_migrated() {
+        let json = r#"{
+            "version": "V1",
+            "config": { "username": null, "monitored_pipelines": [], "polling_interval_seconds": 45 }
+        }"#;
+
+        let config = PersistedConfigV::parse(json).unwrap().into_latest();
+
+        assert_eq!(config.polling.interval_seconds, 45);
+    }
+
+    #[test]
+    fn snake_case_monitored_pipelines_still_load() {
+        let json = r#"{
+            "workspace": "ws",
+            "project_key": null,
+            "project_name": null,
+            "repo_slug": "api",
+            "repo_name": "API",
+            "branch": "main",
+            "track_my_prs": true
+        }"#;
+
+        let loaded: MonitoredPipeline = serde_json::from_str(json).unwrap();
+
+        assert_eq!(loaded.repo_slug, "api");
+        assert!(loaded.track_my_prs);
+        let saved = serde_json::to_value(&loaded).unwrap();
+        assert_eq!(saved["repoSlug"], "api");
+        assert_eq!(serde_json::from_value::<MonitoredPipeline>(saved).unwrap(), loaded);
+    }
+
+    #[test]
+    fn unknown_count_leaves_out_errored_checks() {
+        let no_runs = PipelineStatusInfo::new(&pipeline("ws", "api", None), PipelineState::Unknown, Local::now());
+        let errored = PipelineStatusInfo {
+            error_kind: Some("NotFound(..)".to_string()),
+            ..PipelineStatusInfo::new(&pipeline("ws", "web", None), PipelineState::Unknown, Local::now())
+        };
+        let healthy = PipelineStatusInfo::new(&pipeline("ws", "app", None), PipelineState::Healthy, Local::now());
+
+        let status = OverallStatus::new(vec![no_runs, errored, healthy], "now".to_string(), false);
+
+        assert_eq!(status.unknown_count, 1);
+        assert_eq!(status.total_monitored, 3);
+    }
+
+    #[test]
+    fn failing_pull_requests_only_affect_health_when_opted_in() {
+        let healthy = PipelineStatusInfo::new(&pipeline("ws", "api", None), PipelineState::Healthy, Local::now());
+        let failing_pr = PullRequestStatus {
+            workspace: "ws".to_string(),
+            repo_slug: "api".to_string(),
+            id: 7,
+            title: "Add login".to_string(),
+            branch: "feature/login".to_string(),
+            state: PipelineState::Failed,
+            url: None,
+        };
+        let status = || OverallStatus::new(vec![healthy.clone()], "now".to_string(), false);
+
+        let ignored = status().with_pull_requests(vec![failing_pr.clone()], false);
+        let counted = status().with_pull_requests(vec![failing_pr], true);
+
+        assert!(ignored.is_healthy);
+        assert_eq!(ignored.pull_requests.len(), 1);
+        assert!(!counted.is_healthy);
+    }
+
+    #[test]
+    fn a_new_build_number_makes_statuses_differ() {
+        let failed = PipelineStatusInfo {
+            build_number: Some(41),
+            ..PipelineStatusInfo::new(&pipeline("ws", "api", None), PipelineState::Failed, Local::now())
+        };
+        let failed_again = PipelineStatusInfo {
+            build_number: Some(42),
+            ..failed.clone()
+        };
+        let status = |p: &PipelineStatusInfo| OverallStatus::new(vec![p.clone()], "now".to_string(), false);
+
+        assert_eq!(status(&failed), status(&failed));
+        assert_ne!(status(&failed), status(&failed_again));
+    }
+
+    #[test]
+    fn run_history_leaves_out_stopped_runs_and_removed_pipelines() {
+        let mut state = AppState::new();
+        state.run_history.insert(
+            "ws/removed".to_string(),
+            vec![RunRecord { build_number: 1, result: RunResult::Passed }],
+        );
+        let finished = |slug: &str, build_number, state| PipelineStatusInfo {
+            build_number: Some(build_number),
+            completed_on: Some("2024-05-01T10:00:00Z".to_string()),
+            ..PipelineStatusInfo::new(&pipeline("ws", slug, None), state, Local::now())
+        };
+        let stopped = PipelineStatusInfo { stopped: true, ..finished("web", 4, PipelineState::Healthy) };
+        let no_runs = PipelineStatusInfo::new(&pipeline("ws", "app", None), PipelineState::Unknown, Local::now());
+        let mut statuses = vec![finished("api", 7, PipelineState::Failed), stopped, no_runs];
+
+        assert!(state.record_run_history(&mut statuses));
+
+        assert_eq!(
+            state.run_history.keys().collect::<Vec<_>>(),
+            ["ws/api"],
+            "stopped runs, pipelines without runs and removed pipelines have no history"
+        );
+        assert_eq!(statuses[0].history, [RunRecord { build_number: 7, result: RunResult::Failed }]);
+        assert!(statuses[1].history.is_empty());
+        assert!(!state.record_run_history(&mut statuses));
+    }
+
+    #[test]
+    fn run_history_keeps_the_latest_runs() {
+        let mut state = AppState::new();
+        for build_number in 1..=RUN_HISTORY_LIMIT as u32 + 3 {
+            let mut statuses = vec![PipelineStatusInfo {
+                build_number: Some(build_number),
+                completed_on: Some("2024-05-01T10:00:00Z".to_string()),
+                ..PipelineStatusInfo::new(&pipeline("ws", "api", None), PipelineState::Healthy, Local::now())
+            }];
+            state.record_run_history(&mut statuses);
+        }
+
+        let history = &state.run_history["ws/api"];
+        assert_eq!(history.len(), RUN_HISTORY_LIMIT);
+        assert_eq!(history[0].build_number, 4);
+    }
+
+    #[test]
+    fn off_hours_are_judged_at_the_check_time() {
+        let mut monitored = pipeline("ws", "api", None);
+        monitored.active_hours = Some(ActiveHours {
+            days: vec![chrono::Weekday::Mon],
+            start: "09:00".to_string(),
+            end: "18:00".to_string(),
+        });
+        // 2024-01-01 is a Monday
+        let monday_at = |hour| Local.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap();
+
+        let morning = PipelineStatusInfo::new(&monitored, PipelineState::Failed, monday_at(10));
+        let night = PipelineStatusInfo::new(&monitored, PipelineState::Failed, monday_at(22));
+
+        assert!(!morning.off_hours);
+        assert!(night.off_hours);
+        assert_eq!(night.checked_at, monday_at(22).with_timezone(&chrono::Utc));
+    }
+
+    #[test]
+    fn pipelines_are_looked_up_by_workspace_and_repo() {
+        let main = PipelineStatusInfo::new(&pipeline("ws", "api", Some("main")), PipelineState::Healthy, Local::now());
+        let develop = PipelineStatusInfo::new(&pipeline("ws", "api", Some("develop")), PipelineState::Failed, Local::now());
+        let web = PipelineStatusInfo::new(&pipeline("ws", "web", None), PipelineState::InProgress, Local::now());
+
+        let status = OverallStatus::new(vec![main, develop, web], "now".to_string(), false);
+
+        // A repository monitored on several branches resolves to its first entry
+        assert_eq!(status.pipeline("ws", "api").map(|p| &p.state), Some(&PipelineState::Healthy));
+        assert_eq!(status.pipeline("ws", "web").map(|p| &p.state), Some(&PipelineState::InProgress));
+        assert!(status.pipeline("ws", "missing").is_none());
+    }
+
+    #[test]
+    fn deserialized_statuses_are_found_once_reindexed() {
+        let web = PipelineStatusInfo::new(&pipeline("ws", "web", None), PipelineState::Failed, Local::now());
+        let json = serde_json::to_string(&OverallStatus::new(vec![web], "now".to_string(), false)).unwrap();
+
+        let read: OverallStatus = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(read.reindexed().pipeline("ws", "web").map(|p| &p.state), Some(&PipelineState::Failed));
+    }
+
+    #[test]
+    fn default_branch_is_only_used_without_an_explicit_branch() {
+        let mut auto = pipeline("ws", "api", None);
+        auto.auto_default_branch = true;
+        auto.resolved_branch = Some("master".to_string());
+        let mut explicit = auto.clone();
+        explicit.branch = Some("release".to_string());
+        let mut off = auto.clone();
+        off.auto_default_branch = false;
+
+        assert_eq!(auto.branch_filter(), Some("master"));
+        assert_eq!(explicit.branch_filter(), Some("release"));
+        assert_eq!(off.branch_filter(), None);
+    }
+
+    #[test]
+    fn resolved_branches_are_cached_on_pipelines_that_follow_them() {
+        let mut state = AppState::new();
+        let mut auto = pipeline("ws", "api", None);
+        auto.auto_default_branch = true;
+        state.monitored_pipelines = vec![auto, pipeline("ws", "web", None)];
+        let resolved = |repo_slug: &str| ResolvedBranch {
+            workspace: "ws".to_string(),
+            repo_slug: repo_slug.to_string(),
+            repo_name: repo_slug.to_string(),
+            branch: "main".to_string(),
+        };
+
+        assert!(state.cache_resolved_branches(&[resolved("api"), resolved("web")]));
+        assert!(!state.cache_resolved_branches(&[resolved("api")]));
+        assert_eq!(state.monitored_pipelines[0].resolved_branch.as_deref(), Some("main"));
+        assert_eq!(state.monitored_pipelines[1].resolved_branch, None);
+    }
+
+    #[test]
+    fn failed_lookups_wait_before_they_are_retried() {
+        let now = Instant::now();
+        let mut lookups = FailedLookups::default();
+        assert!(lookups.can_retry("ws", "api", now));
+
+        lookups.record_failure("ws", "api", now);
+
+        assert!(!lookups.can_retry("ws", "api", now + Duration::from_secs(60)));
+        assert!(lookups.can_retry("ws", "api", now + FAILED_LOOKUP_RETRY));
+        assert!(lookups.can_retry("ws", "web", now));
+    }
+
+    #[test]
+    fn notification_categories_can_be_switched_off() {
+        let mut state = AppState::new();
+        assert!(state.notifications_enabled(NotificationCategory::Failure));
+        assert!(!state.notifications_enabled(NotificationCategory::Paused));
+
+        state.set_notifications_enabled(NotificationCategory::Recovery, false);
+        state.set_notifications_enabled(NotificationCategory::Recovery, false);
+        state.set_notifications_enabled(NotificationCategory::Paused, true);
+
+        assert!(state.notifications_enabled(NotificationCategory::Failure));
+        assert!(!state.notifications_enabled(NotificationCategory::Recovery));
+        assert!(state.notify_on_paused);
+        assert_eq!(state.disabled_notification_categories, vec![NotificationCategory::Recovery]);
+    }
+
+    #[test]
+    fn connection_status_counts_network_errors_until_a_clean_check() {
+        let healthy = PipelineStatusInfo::new(&pipeline("ws", "api", None), PipelineState::Healthy, Local::now());
+        let offline = |slug: &str| PipelineStatusInfo {
+            error_kind: Some("Http(reqwest::Error { kind: Request })".to_string()),
+            network_error: true,
+            ..PipelineStatusInfo::new(&pipeline("ws", slug, None), PipelineState::Unknown, Local::now())
+        };
+        let check = |statuses| OverallStatus::new(statuses, "now".to_string(), false);
+
+        let degraded = ConnectionStatus::Connected.after_check(&check(vec![healthy.clone(), offline("web")]));
+        assert_eq!(degraded, ConnectionStatus::Degraded(1));
+        let worse = degraded.after_check(&check(vec![offline("api"), offline("web"), healthy.clone()]));
+        assert_eq!(worse, ConnectionStatus::Degraded(3));
+        assert_eq!(
+            worse.after_check(&check(vec![offline("api"), offline("web")])),
+            ConnectionStatus::Disconnected
+        );
+        assert_eq!(worse.after_check(&check(vec![healthy])), ConnectionStatus::Connected);
+    }
+
+    #[test]
+    fn only_a_check_rejected_everywhere_counts_as_rotated_credentials() {
+        let rejected = |slug: &str| PipelineStatusInfo {
+            unknown_reason: Some(UnknownReason::AuthFailed),
+            ..PipelineStatusInfo::new(&pipeline("ws", slug, None), PipelineState::Unknown, Local::now())
+        };
+        let healthy = PipelineStatusInfo::new(&pipeline("ws", "app", None), PipelineState::Healthy, Local::now());
+        let check = |statuses| OverallStatus::new(statuses, "now".to_string(), false);
+
+        assert!(check(vec![rejected("api"), rejected("web")]).all_auth_failed());
+        assert!(!check(vec![rejected("api"), healthy]).all_auth_failed());
+        assert!(!check(Vec::new()).all_auth_failed());
+    }
+
+    #[test]
+    fn workspace_status_only_counts_its_own_pipelines() {
+        let failed = PipelineStatusInfo::new(&pipeline("acme", "api", None), PipelineState::Failed, Local::now());
+        let paused = PipelineStatusInfo::new(&pipeline("labs", "web", None), PipelineState::Paused, Local::now());
+        let healthy = PipelineStatusInfo::new(&pipeline("labs", "app", None), PipelineState::Healthy, Local::now());
+        let status = OverallStatus::new(vec![failed, paused, healthy], "now".to_string(), true);
+
+        assert_eq!(status.workspaces(), vec!["acme".to_string(), "labs".to_string()]);
+        let acme = status.for_workspace("acme");
+        assert_eq!(acme.total_monitored, 1);
+        assert!(!acme.is_healthy);
+        // Paused runs still count against health, as in the full status
+        let labs = status.for_workspace("labs");
+        assert_eq!(labs.total_monitored, 2);
+        assert_eq!(labs.paused_count, 1);
+        assert!(!labs.is_healthy);
+        assert_eq!(labs.last_checked, "now");
+    }
+
+    #[test]
+    fn status_can_be_filtered_by_project() {
+        let in_project = |slug: &str, key: Option<&str>| {
+            let mut monitored = pipeline("acme", slug, None);
+            monitored.project_key = key.map(str::to_string);
+            PipelineStatusInfo::new(&monitored, PipelineState::Healthy, Local::now())
+        };
+        let statuses = vec![
+            in_project("api", Some("CORE")),
+            in_project("web", Some("WEB")),
+            in_project("app", None),
+        ];
+        let status = OverallStatus::new(statuses, "now".to_string(), false);
+
+        let core = status.filtered(None, Some("CORE"));
+        assert_eq!(core.total_monitored, 1);
+        assert_eq!(core.pipeline_statuses[0].repo_slug, "api");
+        assert_eq!(status.filtered(Some("acme"), None).total_monitored, 3);
+        assert_eq!(status.filtered(Some("labs"), Some("CORE")).total_monitored, 0);
+    }
+
+    fn config_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cdmenu-config-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn config_with_interval(seconds: u64) -> PersistedConfig {
+        let mut state = AppState::new();
+        state.polling_interval_seconds = seconds;
+        state.to_persisted()
+    }
+
+    #[test]
+    fn writing_config_keeps_the_previous_file_as_backup() {
+        let dir = config_dir("backup");
+        write_config_file(&dir, config_with_interval(60)).unwrap();
+        write_config_file(&dir, config_with_interval(120)).unwrap();
+
+        let backup = std::fs::read_to_string(dir.join(CONFIG_BACKUP_FILE)).unwrap();
+        let backup = PersistedConfigV::parse(&backup).unwrap().into_latest();
+        assert_eq!(backup.polling.interval_seconds, 60);
+        assert_eq!(read_config_file(&dir).unwrap().polling.interval_seconds, 120);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn corrupt_config_is_restored_from_backup() {
+        let dir = config_dir("corrupt");
+        write_config_file(&dir, config_with_interval(60)).unwrap();
+        write_config_file(&dir, config_with_interval(120)).unwrap();
+        // A write interrupted part way through
+        std::fs::write(dir.join(CONFIG_FILE), "{\"version\": 2, \"monitored").unwrap();
+
+        let config = read_config_file(&dir).unwrap();
+
+        assert_eq!(config.polling.interval_seconds, 60);
+        let restored = std::fs::read_to_string(dir.join(CONFIG_FILE)).unwrap();
+        assert!(PersistedConfigV::parse(&restored).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn no_config_without_a_readable_file() {
+        let dir = config_dir("unreadable");
+        assert!(read_config_file(&dir).is_none());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(CONFIG_FILE), "not json").unwrap();
+        std::fs::write(dir.join(CONFIG_BACKUP_FILE), "not json either").unwrap();
+        assert!(read_config_file(&dir).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::bitbucket::BitbucketClient;
+use crate::config::{FailedLookups, MonitoredPipeline};
+use serde::Serialize;
+use std::time::Instant;
+
+/// Main branch looked up for a monitored repository
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedBranch {
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+    pub branch: String,
+}
+
+/// Look up the main branch of each pipeline that follows it, filling in `resolved_branch`
+///
+/// With `only_missing`, pipelines that already have a branch resolved are skipped, and so
+/// are repositories in `failures` that aren't due for a retry yet. Lookups that fail, or
+/// repositories without a main branch, are left unresolved and recorded in `failures`.
+pub async fn resolve_default_branches(
+    client: &BitbucketClient,
+    pipelines: &mut [MonitoredPipeline],
+    only_missing: bool,
+    failures: &mut FailedLookups,
+    now: Instant,
+) -> Vec<ResolvedBranch> {
+    let mut resolved = Vec::new();
+    let pending = pipelines
+        .iter_mut()
+        .filter(|p| p.follows_default_branch())
+        .filter(|p| {
+            !only_missing
+                || (p.resolved_branch.is_none() && failures.can_retry(&p.workspace, &p.repo_slug, now))
+        });
+    for pipeline in pending {
+        let repo = match client
+            .get_repository(&pipeline.workspace, &pipeline.repo_slug)
+            .await
+        {
+            Ok(repo) => repo,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to look up main branch of {}/{}: {}",
+                    pipeline.workspace,
+                    pipeline.repo_slug,
+                    e
+                );
+                failures.record_failure(&pipeline.workspace, &pipeline.repo_slug, now);
+                continue;
+            }
+        };
+        let Some(branch) = repo.main_branch_name() else {
+            failures.record_failure(&pipeline.workspace, &pipeline.repo_slug, now);
+            continue;
+        };
+        failures.clear(&pipeline.workspace, &pipeline.repo_slug);
+        pipeline.resolved_branch = Some(branch.to_string());
+        resolved.push(ResolvedBranch {
+            workspace: pipeline.workspace.clone(),
+            repo_slug: pipeline.repo_slug.clone(),
+            repo_name: pipeline.repo_name.clone(),
+            branch: branch.to_string(),
+        });
+    }
+    resolved
+}
@@ -0,0 +1,383 @@
+use crate::commands::save_config_helper;
+use crate::config::AppState;
+use crate::i18n::{t, tf, tp};
+use crate::incidents::{IncidentEvent, IncidentEventKind, INCIDENT_EVENT_RETENTION_DAYS};
+use crate::polling::Notifier;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+#[cfg(test)]
+use ts_rs::TS;
+
+/// How often the scheduler checks whether the digest is due
+const DIGEST_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// How late a missed digest (e.g. while asleep) is still posted
+const DIGEST_GRACE_HOURS: i64 = 12;
+
+/// When the digest notification is posted, in local time, and the days it covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
+pub struct DigestSettings {
+    /// Post the digest as a notification (opt-in)
+    pub enabled: bool,
+    pub weekday: Weekday,
+    /// "HH:MM"
+    pub time: String,
+    pub window_days: u32,
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weekday: Weekday::Mon,
+            time: "09:00".to_string(),
+            window_days: 7,
+        }
+    }
+}
+
+impl DigestSettings {
+    /// Check the time parses and the window fits the kept events
+    pub fn validate(&self) -> Result<(), String> {
+        parse_time(&self.time)?;
+        if self.window_days == 0 || i64::from(self.window_days) > INCIDENT_EVENT_RETENTION_DAYS {
+            return Err(format!(
+                "Digest window must be between 1 and {} days",
+                INCIDENT_EVENT_RETENTION_DAYS
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|e| format!("Invalid digest time '{}': {}", value, e))
+}
+
+/// Failures and outages of one pipeline within a digest window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineDigest {
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+    pub failures: u32,
+    pub recoveries: u32,
+    /// Time spent failing inside the window, including an outage still going on
+    #[cfg_attr(test, ts(type = "number"))]
+    pub red_secs: u64,
+}
+
+/// Summary of failures and recoveries over the last `window_days`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyDigest {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub window_days: u32,
+    pub failures: u32,
+    pub recoveries: u32,
+    /// Mean length of the outages that ended in the window
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub mean_time_to_recovery_secs: Option<u64>,
+    /// Pipelines that failed or were red in the window, worst offender first
+    pub pipelines: Vec<PipelineDigest>,
+    /// One-line summary, as posted in the notification
+    pub headline: String,
+}
+
+/// Aggregate `events` (oldest first) over the `window_days` before `until`
+pub fn weekly_digest(events: &[IncidentEvent], until: DateTime<Utc>, window_days: u32) -> WeeklyDigest {
+    let since = until - Duration::days(window_days.into());
+    let mut pipelines: Vec<PipelineDigest> = Vec::new();
+    let mut outages: HashMap<(&str, &str), &IncidentEvent> = HashMap::new();
+    let mut recovery_secs = Vec::new();
+
+    fn entry<'a>(pipelines: &'a mut Vec<PipelineDigest>, event: &IncidentEvent) -> &'a mut PipelineDigest {
+        let index = match pipelines
+            .iter()
+            .position(|p| p.workspace == event.workspace && p.repo_slug == event.repo_slug)
+        {
+            Some(index) => index,
+            None => {
+                pipelines.push(PipelineDigest {
+                    workspace: event.workspace.clone(),
+                    repo_slug: event.repo_slug.clone(),
+                    repo_name: event.repo_name.clone(),
+                    failures: 0,
+                    recoveries: 0,
+                    red_secs: 0,
+                });
+                pipelines.len() - 1
+            }
+        };
+        &mut pipelines[index]
+    }
+
+    // Seconds of [start, end) that fall inside the window
+    let overlap = |start: DateTime<Utc>, end: DateTime<Utc>| {
+        (end.min(until) - start.max(since)).num_seconds().max(0) as u64
+    };
+
+    for event in events.iter().filter(|e| e.at < until) {
+        let key = (event.workspace.as_str(), event.repo_slug.as_str());
+        let in_window = event.at >= since;
+        match event.kind {
+            IncidentEventKind::Failed => {
+                outages.entry(key).or_insert(event);
+                if in_window {
+                    entry(&mut pipelines, event).failures += 1;
+                }
+            }
+            IncidentEventKind::Recovered => {
+                let red_secs = outages.remove(&key).map_or(0, |failed| overlap(failed.at, event.at));
+                if in_window || red_secs > 0 {
+                    let pipeline = entry(&mut pipelines, event);
+                    pipeline.red_secs += red_secs;
+                    if in_window {
+                        pipeline.recoveries += 1;
+                        recovery_secs.extend(event.duration_secs);
+                    }
+                }
+            }
+        }
+    }
+    // Outages still going on count up to the end of the window
+    for failed in outages.into_values() {
+        let red_secs = overlap(failed.at, until);
+        if red_secs > 0 {
+            entry(&mut pipelines, failed).red_secs += red_secs;
+        }
+    }
+
+    pipelines.sort_by(|a, b| {
+        b.failures
+            .cmp(&a.failures)
+            .then(b.red_secs.cmp(&a.red_secs))
+            .then(a.repo_slug.cmp(&b.repo_slug))
+    });
+
+    let mut digest = WeeklyDigest {
+        since,
+        until,
+        window_days,
+        failures: pipelines.iter().map(|p| p.failures).sum(),
+        recoveries: pipelines.iter().map(|p| p.recoveries).sum(),
+        mean_time_to_recovery_secs: (!recovery_secs.is_empty())
+            .then(|| recovery_secs.iter().sum::<u64>() / recovery_secs.len() as u64),
+        pipelines,
+        headline: String::new(),
+    };
+    digest.headline = digest_headline(&digest);
+    digest
+}
+
+/// e.g. "Last week: 14 failures across 5 pipelines, worst offender: api-service (6)"
+fn digest_headline(digest: &WeeklyDigest) -> String {
+    let window = if digest.window_days == 7 {
+        t("digest.last_week").to_string()
+    } else {
+        tf("digest.last_days", &[("days", &digest.window_days)])
+    };
+    let failing: Vec<&PipelineDigest> = digest.pipelines.iter().filter(|p| p.failures > 0).collect();
+    let Some(worst) = failing.first() else {
+        return tf("digest.headline.clean", &[("window", &window)]);
+    };
+    let name = if worst.repo_name.is_empty() {
+        &worst.repo_slug
+    } else {
+        &worst.repo_name
+    };
+    tf(
+        "digest.headline",
+        &[
+            ("window", &window),
+            ("failures", &tp("digest.failures", digest.failures as usize, &[])),
+            ("pipelines", &tp("digest.pipelines", failing.len(), &[])),
+            ("name", name),
+            ("count", &worst.failures),
+        ],
+    )
+}
+
+/// Whether the digest scheduled by `settings` should be posted at `now` (local time)
+///
+/// A digest missed while the machine slept is still posted within `DIGEST_GRACE_HOURS`.
+pub fn digest_due(
+    settings: &DigestSettings,
+    last_sent: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+) -> bool {
+    if !settings.enabled {
+        return false;
+    }
+    let Ok(time) = parse_time(&settings.time) else {
+        return false;
+    };
+    let days_back =
+        (now.weekday().num_days_from_monday() + 7 - settings.weekday.num_days_from_monday()) % 7;
+    let mut scheduled = (now.date() - Duration::days(days_back.into())).and_time(time);
+    if scheduled > now {
+        scheduled -= Duration::days(7);
+    }
+    now - scheduled < Duration::hours(DIGEST_GRACE_HOURS)
+        && last_sent.is_none_or(|sent| sent < scheduled)
+}
+
+/// Start the background loop that posts the digest notification when it's due
+pub async fn start_digest_scheduler(app_handle: AppHandle) {
+    let mut check_interval =
+        tokio::time::interval(std::time::Duration::from_secs(DIGEST_CHECK_INTERVAL_SECS));
+
+    loop {
+        check_interval.tick().await;
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let digest = {
+            let mut state_guard = state.lock().await;
+            let now = Utc::now();
+            let last_sent = state_guard
+                .last_digest_sent
+                .map(|sent| sent.with_timezone(&Local).naive_local());
+            let local_now = now.with_timezone(&Local).naive_local();
+            if !digest_due(&state_guard.digest_settings, last_sent, local_now) {
+                continue;
+            }
+            state_guard.last_digest_sent = Some(now);
+            weekly_digest(
+                state_guard.incidents.events(),
+                now,
+                state_guard.digest_settings.window_days,
+            )
+        };
+
+        // Notifications can't be clicked on desktop, so the body points to the report instead
+        let body = format!("{}\n{}", digest.headline, t("digest.open_report"));
+        app_handle.notify(t("digest.title"), &body);
+        if let Err(e) = save_config_helper(&app_handle, &state).await {
+            tracing::error!("Failed to save digest time: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    /// 2024-01-01 is a Monday
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap()
+    }
+
+    fn local(day: u32, time: &str) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+    }
+
+    fn event(slug: &str, kind: IncidentEventKind, at: DateTime<Utc>, duration_secs: Option<u64>) -> IncidentEvent {
+        IncidentEvent {
+            workspace: "ws".to_string(),
+            repo_slug: slug.to_string(),
+            repo_name: String::new(),
+            kind,
+            at,
+            duration_secs,
+            url: None,
+        }
+    }
+
+    fn failed(slug: &str, at: DateTime<Utc>) -> IncidentEvent {
+        event(slug, IncidentEventKind::Failed, at, None)
+    }
+
+    fn recovered(slug: &str, at: DateTime<Utc>, since: DateTime<Utc>) -> IncidentEvent {
+        let secs = (at - since).num_seconds() as u64;
+        event(slug, IncidentEventKind::Recovered, at, Some(secs))
+    }
+
+    #[test]
+    fn digest_counts_failures_and_ranks_offenders() {
+        let events = vec![
+            // Before the window: ignored
+            failed("web", at(1, 10)),
+            recovered("web", at(1, 11), at(1, 10)),
+            failed("api", at(9, 10)),
+            failed("api", at(9, 12)),
+            recovered("api", at(9, 14), at(9, 10)),
+            failed("web", at(10, 9)),
+            recovered("web", at(10, 10), at(10, 9)),
+            failed("api", at(11, 8)),
+            recovered("api", at(11, 10), at(11, 8)),
+        ];
+
+        let digest = weekly_digest(&events, at(15, 9), 7);
+
+        assert_eq!(digest.failures, 4);
+        assert_eq!(digest.recoveries, 3);
+        // (4h + 1h + 2h) / 3
+        assert_eq!(digest.mean_time_to_recovery_secs, Some(8400));
+        let slugs: Vec<&str> = digest.pipelines.iter().map(|p| p.repo_slug.as_str()).collect();
+        assert_eq!(slugs, ["api", "web"]);
+        assert_eq!(digest.pipelines[0].failures, 3);
+        assert_eq!(digest.pipelines[0].red_secs, 6 * 3600);
+        assert_eq!(digest.pipelines[1].red_secs, 3600);
+        assert_eq!(
+            digest.headline,
+            "Last week: 4 failures across 2 pipelines, worst offender: api (3)"
+        );
+    }
+
+    #[test]
+    fn red_time_is_clipped_to_the_window() {
+        let events = vec![
+            // Started before the window, fixed inside it
+            failed("api", at(7, 9)),
+            recovered("api", at(8, 12), at(7, 9)),
+            // Still failing at the end
+            failed("web", at(14, 9)),
+        ];
+
+        let digest = weekly_digest(&events, at(15, 9), 7);
+
+        assert_eq!(digest.failures, 1);
+        let red: Vec<(&str, u64)> =
+            digest.pipelines.iter().map(|p| (p.repo_slug.as_str(), p.red_secs)).collect();
+        assert_eq!(red, [("web", 24 * 3600), ("api", 3 * 3600)]);
+    }
+
+    #[test]
+    fn quiet_week_has_a_clean_headline() {
+        let digest = weekly_digest(&[], at(15, 9), 3);
+
+        assert!(digest.pipelines.is_empty());
+        assert_eq!(digest.mean_time_to_recovery_secs, None);
+        assert_eq!(digest.headline, "Last 3 days: no failures");
+    }
+
+    #[test]
+    fn digest_is_due_once_after_its_scheduled_time() {
+        let settings = DigestSettings {
+            enabled: true,
+            ..DigestSettings::default()
+        };
+
+        assert!(!digest_due(&settings, None, local(8, "08:59")));
+        assert!(digest_due(&settings, None, local(8, "09:00")));
+        assert!(!digest_due(&settings, Some(local(8, "09:00")), local(8, "09:01")));
+        // Sent last week, missed this morning while asleep
+        assert!(digest_due(&settings, Some(local(1, "09:00")), local(8, "14:00")));
+        // Too late to still be useful
+        assert!(!digest_due(&settings, Some(local(1, "09:00")), local(9, "09:00")));
+        assert!(!digest_due(&DigestSettings::default(), None, local(8, "09:00")));
+    }
+}
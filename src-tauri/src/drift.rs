@@ -0,0 +1,204 @@
+use crate::bitbucket::{BitbucketClient, BitbucketError};
+use crate::config::{MonitoredPipeline, PipelineStatusInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+#[cfg(test)]
+use ts_rs::TS;
+
+/// How long a repository's deployment drift is reused before it is looked up again
+pub const DRIFT_REFRESH: Duration = Duration::from_secs(900);
+
+/// Two deployment environments to compare, by name or slug, e.g. staging against production
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(test, derive(TS))]
+pub struct DriftEnvironments {
+    pub from: String,
+    pub to: String,
+}
+
+/// Commits deployed to `from` that `to` doesn't have yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+pub struct EnvironmentDrift {
+    pub ahead: u32,
+    /// Counting stopped at the page limit, so `ahead` is a lower bound
+    #[serde(default)]
+    pub truncated: bool,
+    pub from: String,
+    pub to: String,
+}
+
+/// Drift per (workspace, repo_slug), the environments it compares and when it was fetched
+#[derive(Debug, Clone, Default)]
+pub struct DriftCache {
+    entries: HashMap<(String, String), (Instant, DriftEnvironments, Option<EnvironmentDrift>)>,
+}
+
+impl DriftCache {
+    fn is_fresh(
+        &self,
+        key: &(String, String),
+        environments: &DriftEnvironments,
+        now: Instant,
+    ) -> bool {
+        self.entries.get(key).is_some_and(|(fetched_at, compared, _)| {
+            compared == environments && now.duration_since(*fetched_at) < DRIFT_REFRESH
+        })
+    }
+
+    /// Fill in the last known drift of each status's repository
+    pub fn apply(&self, statuses: &mut [PipelineStatusInfo]) {
+        for status in statuses {
+            let key = (status.workspace.clone(), status.repo_slug.clone());
+            status.environment_drift = self.entries.get(&key).and_then(|(_, _, drift)| drift.clone());
+        }
+    }
+}
+
+/// Refresh the drift of repositories with `drift_environments` once it is older than `DRIFT_REFRESH`
+///
+/// A failed lookup also waits for the next refresh, so a broken repository isn't asked again
+/// on every poll.
+pub async fn check_drift(
+    client: &BitbucketClient,
+    monitored: &[MonitoredPipeline],
+    cache: &mut DriftCache,
+    now: Instant,
+) {
+    let mut tracked = HashSet::new();
+
+    for pipeline in monitored {
+        let Some(environments) = &pipeline.drift_environments else {
+            continue;
+        };
+        let key = (pipeline.workspace.clone(), pipeline.repo_slug.clone());
+        // A repository can be monitored on several branches
+        if !tracked.insert(key.clone()) || cache.is_fresh(&key, environments, now) {
+            continue;
+        }
+
+        match fetch_drift(client, &pipeline.workspace, &pipeline.repo_slug, environments).await {
+            Ok(drift) => {
+                cache.entries.insert(key, (now, environments.clone(), drift));
+            }
+            // Keep showing the last drift until the next refresh succeeds
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to check deployment drift for {}/{}: {}",
+                    pipeline.workspace,
+                    pipeline.repo_slug,
+                    e
+                );
+                let previous = cache
+                    .entries
+                    .get(&key)
+                    .filter(|(_, compared, _)| compared == environments)
+                    .and_then(|(_, _, drift)| drift.clone());
+                cache.entries.insert(key, (now, environments.clone(), previous));
+            }
+        }
+    }
+
+    // Forget repositories that stopped comparing environments
+    cache.entries.retain(|key, _| tracked.contains(key));
+}
+
+/// Drift between the environments' latest successful deployments; None until both have one
+async fn fetch_drift(
+    client: &BitbucketClient,
+    workspace: &str,
+    repo_slug: &str,
+    environments: &DriftEnvironments,
+) -> Result<Option<EnvironmentDrift>, BitbucketError> {
+    let all = client.get_environments(workspace, repo_slug).await?;
+    let find = |name: &str| all.iter().find(|env| env.matches(name));
+    let (Some(from), Some(to)) = (find(&environments.from), find(&environments.to)) else {
+        tracing::warn!(
+            "{}/{} has no environment named {} or {}",
+            workspace,
+            repo_slug,
+            environments.from,
+            environments.to
+        );
+        return Ok(None);
+    };
+
+    let from_commit = client.get_latest_deployed_commit(workspace, repo_slug, &from.uuid).await?;
+    let to_commit = client.get_latest_deployed_commit(workspace, repo_slug, &to.uuid).await?;
+    let (Some(from_commit), Some(to_commit)) = (from_commit, to_commit) else {
+        return Ok(None);
+    };
+
+    let (ahead, truncated) = if from_commit == to_commit {
+        (0, false)
+    } else {
+        client
+            .count_commits_ahead(workspace, repo_slug, &from_commit, &to_commit)
+            .await?
+    };
+    Ok(Some(EnvironmentDrift {
+        ahead,
+        truncated,
+        from: environments.from.clone(),
+        to: environments.to.clone(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitbucket::TlsConfig;
+
+    fn staging_vs_prod() -> DriftEnvironments {
+        DriftEnvironments {
+            from: "staging".to_string(),
+            to: "prod".to_string(),
+        }
+    }
+
+    #[test]
+    fn drift_is_refetched_after_the_refresh_interval_or_when_the_environments_change() {
+        let fetched_at = Instant::now();
+        let key = ("ws".to_string(), "api".to_string());
+        let mut cache = DriftCache::default();
+        cache.entries.insert(key.clone(), (fetched_at, staging_vs_prod(), None));
+
+        assert!(cache.is_fresh(&key, &staging_vs_prod(), fetched_at + Duration::from_secs(60)));
+        assert!(!cache.is_fresh(&key, &staging_vs_prod(), fetched_at + DRIFT_REFRESH));
+        let swapped = DriftEnvironments {
+            from: "prod".to_string(),
+            to: "staging".to_string(),
+        };
+        assert!(!cache.is_fresh(&key, &swapped, fetched_at));
+    }
+
+    #[tokio::test]
+    async fn failed_lookups_wait_for_the_next_refresh() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/repositories/ws/api/environments/?pagelen=100")
+            .with_status(500)
+            .expect(1)
+            .create_async()
+            .await;
+        let client = BitbucketClient::new("user", "secret", &TlsConfig::default())
+            .expect("client should build without TLS overrides")
+            .with_api_base(&server.url());
+        let monitored = [MonitoredPipeline {
+            workspace: "ws".to_string(),
+            repo_slug: "api".to_string(),
+            drift_environments: Some(staging_vs_prod()),
+            ..MonitoredPipeline::default()
+        }];
+        let mut cache = DriftCache::default();
+        let now = Instant::now();
+
+        check_drift(&client, &monitored, &mut cache, now).await;
+        check_drift(&client, &monitored, &mut cache, now + Duration::from_secs(60)).await;
+
+        mock.assert_async().await;
+        let key = ("ws".to_string(), "api".to_string());
+        assert!(!cache.is_fresh(&key, &staging_vs_prod(), now + DRIFT_REFRESH));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::config::OverallStatus;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A feed older than this many polling intervals is treated as abandoned
+pub const STALE_INTERVALS: i64 = 3;
+
+/// Status written by a primary instance for followers to render
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedStatus {
+    pub published_at: DateTime<Utc>,
+    /// The primary's polling interval, which staleness is measured against
+    pub interval_seconds: u64,
+    pub status: OverallStatus,
+    /// The status's health settings, which `OverallStatus` doesn't serialize
+    #[serde(default)]
+    pub paused_is_unhealthy: bool,
+    #[serde(default)]
+    pub prs_are_unhealthy: bool,
+}
+
+impl PublishedStatus {
+    /// Whether the primary has missed `STALE_INTERVALS` checks in a row
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        let max_age = self.interval_seconds.max(1) as i64 * STALE_INTERVALS;
+        (now - self.published_at).num_seconds() > max_age
+    }
+}
+
+/// Write the status for followers, replacing the file in one step so they never read half of it
+pub fn publish(path: &Path, status: &PublishedStatus) -> Result<(), String> {
+    let json = serde_json::to_string(status).map_err(|e| format!("{}", e))?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("{}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("{}", e))
+}
+
+/// Read the status a primary instance published
+pub fn read_feed(path: &Path) -> Result<PublishedStatus, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("{}", e))?;
+    let mut feed: PublishedStatus =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid status feed: {}", e))?;
+    feed.status.paused_is_unhealthy = feed.paused_is_unhealthy;
+    feed.status.prs_are_unhealthy = feed.prs_are_unhealthy;
+    feed.status = feed.status.reindexed();
+    Ok(feed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn feed(published_at: DateTime<Utc>, interval_seconds: u64) -> PublishedStatus {
+        PublishedStatus {
+            published_at,
+            interval_seconds,
+            status: OverallStatus::new(Vec::new(), "12:00:00".to_string(), true),
+            paused_is_unhealthy: true,
+            prs_are_unhealthy: false,
+        }
+    }
+
+    #[test]
+    fn feed_goes_stale_after_three_missed_intervals() {
+        let now = Utc::now();
+
+        assert!(!feed(now - Duration::seconds(60), 60).is_stale(now));
+        assert!(!feed(now - Duration::seconds(180), 60).is_stale(now));
+        assert!(feed(now - Duration::seconds(181), 60).is_stale(now));
+        // Measured against the primary's interval, not the follower's
+        assert!(!feed(now - Duration::seconds(600), 300).is_stale(now));
+    }
+
+    #[test]
+    fn published_status_round_trips_through_the_file() {
+        let dir = std::env::temp_dir().join(format!("cdmenu-follower-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("status.json");
+        let published = feed(Utc::now(), 60);
+
+        publish(&path, &published).unwrap();
+        let read = read_feed(&path).unwrap();
+
+        assert_eq!(read.published_at, published.published_at);
+        assert_eq!(read.status.last_checked, "12:00:00");
+        assert!(read.status.paused_is_unhealthy);
+        assert!(!path.with_extension("tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+use crate::bitbucket::BitbucketClient;
+use crate::commands::save_config_helper;
+use crate::config::{AppState, MonitoredPipeline};
+use crate::polling::get_app_password;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::Mutex;
+use tokio::time::{interval, Duration};
+
+/// How often project membership is re-synced
+const GROUP_SYNC_INTERVAL_SECS: u64 = 3600;
+
+/// Start the background loop that keeps group-derived pipelines in sync
+pub async fn start_group_sync(app_handle: AppHandle) {
+    tracing::info!("Starting monitored group sync loop");
+
+    let mut sync_interval = interval(Duration::from_secs(GROUP_SYNC_INTERVAL_SECS));
+
+    loop {
+        // First tick completes immediately, so groups sync at startup
+        sync_interval.tick().await;
+        sync_monitored_groups(&app_handle).await;
+    }
+}
+
+/// List repositories for each monitored group and update the derived pipelines
+pub async fn sync_monitored_groups(app_handle: &AppHandle) {
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+
+    let (credentials, groups, previous, tls) = {
+        let state_guard = state.lock().await;
+        (
+            state_guard.credentials.clone(),
+            state_guard.monitored_groups.clone(),
+            state_guard.group_pipelines.clone(),
+            state_guard.tls_config(),
+        )
+    };
+
+    if groups.is_empty() && previous.is_empty() {
+        return;
+    }
+
+    let Some(credentials) = credentials else {
+        return;
+    };
+    let Some(app_password) = get_app_password(app_handle) else {
+        tracing::warn!("No app password found, skipping group sync");
+        return;
+    };
+
+    let client = match BitbucketClient::new(&credentials.username, &app_password, &tls) {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("Failed to create Bitbucket client for group sync: {}", e);
+            return;
+        }
+    };
+
+    let mut synced: Vec<MonitoredPipeline> = Vec::new();
+
+    for group in &groups {
+        match client
+            .get_repositories_by_project(&group.workspace, &group.project_key)
+            .await
+        {
+            Ok(repos) => {
+                for repo in repos {
+                    if group.exclude.contains(&repo.slug) {
+                        continue;
+                    }
+                    let pipeline = MonitoredPipeline {
+                        repo_url: repo.html_url().map(str::to_string),
+                        workspace: group.workspace.clone(),
+                        project_key: Some(group.project_key.clone()),
+                        project_name: repo.project.map(|p| p.name),
+                        repo_slug: repo.slug,
+                        repo_name: repo.name,
+                        branch: group.branch.clone(),
+                        ..MonitoredPipeline::default()
+                    };
+                    if !synced.contains(&pipeline) {
+                        synced.push(pipeline);
+                    }
+                }
+            }
+            Err(e) => {
+                // Keep the last known membership rather than dropping everything
+                tracing::error!(
+                    "Failed to list repositories for {}/{}: {}",
+                    group.workspace,
+                    group.project_key,
+                    e
+                );
+                let kept = previous.iter().filter(|p| {
+                    p.workspace == group.workspace
+                        && p.project_key.as_deref() == Some(group.project_key.as_str())
+                        && !group.exclude.contains(&p.repo_slug)
+                });
+                synced.extend(kept.cloned());
+            }
+        }
+    }
+
+    let added: Vec<&MonitoredPipeline> = synced
+        .iter()
+        .filter(|p| {
+            !previous
+                .iter()
+                .any(|old| old.workspace == p.workspace && old.repo_slug == p.repo_slug)
+        })
+        .collect();
+    let removed = previous
+        .iter()
+        .filter(|old| {
+            !synced
+                .iter()
+                .any(|p| p.workspace == old.workspace && p.repo_slug == old.repo_slug)
+        })
+        .count();
+
+    if added.is_empty() && removed == 0 && synced == previous {
+        return;
+    }
+
+    tracing::info!(
+        "Group sync: {} added, {} removed, {} total",
+        added.len(),
+        removed,
+        synced.len()
+    );
+
+    if !added.is_empty() {
+        let names: Vec<&str> = added
+            .iter()
+            .map(|p| {
+                if p.repo_name.is_empty() {
+                    p.repo_slug.as_str()
+                } else {
+                    p.repo_name.as_str()
+                }
+            })
+            .collect();
+        let _ = app_handle
+            .notification()
+            .builder()
+            .title("Now Monitoring")
+            .body(format!("{} new: {}", names.len(), names.join(", ")))
+            .show();
+    }
+
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.group_pipelines = synced;
+    }
+    if let Err(e) = save_config_helper(app_handle, &state).await {
+        tracing::error!("Failed to save group pipelines: {}", e);
+    }
+}
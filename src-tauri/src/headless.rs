@@ -0,0 +1,201 @@
+use crate::bitbucket::TlsConfig;
+use crate::config::{validate_monitored_pipelines, MonitoredPipeline, OverallStatus, RunCaches};
+use crate::instance::LaunchArgs;
+use crate::polling;
+
+/// Exit code when every pipeline is healthy
+pub const EXIT_HEALTHY: i32 = 0;
+/// Exit code when any pipeline failed
+pub const EXIT_FAILED: i32 = 1;
+/// Exit code when the environment doesn't describe a check that can run
+pub const EXIT_CONFIG_ERROR: i32 = 2;
+/// Exit code when no pipeline failed but some couldn't be checked, e.g. because the
+/// credentials were rejected, the network was down or a repository doesn't exist
+pub const EXIT_CHECK_ERROR: i32 = 3;
+
+/// Credentials and pipelines read from `CDMENU_*` environment variables
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessConfig {
+    pub username: String,
+    pub app_password: String,
+    pub pipelines: Vec<MonitoredPipeline>,
+}
+
+impl HeadlessConfig {
+    /// Read `CDMENU_USERNAME`, `CDMENU_APP_PASSWORD` and `CDMENU_REPOS` through `var`
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let required = |name: &str| {
+            var(name)
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| format!("{} is not set", name))
+        };
+        Ok(Self {
+            username: required("CDMENU_USERNAME")?,
+            app_password: required("CDMENU_APP_PASSWORD")?,
+            pipelines: parse_repos(&required("CDMENU_REPOS")?)?,
+        })
+    }
+}
+
+/// Parse `CDMENU_REPOS`, e.g. "workspace/repo1:branch,workspace/repo2"
+pub fn parse_repos(value: &str) -> Result<Vec<MonitoredPipeline>, String> {
+    let pipelines = value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (repo, branch) = match entry.split_once(':') {
+                Some((repo, branch)) => (repo, Some(branch.trim()).filter(|b| !b.is_empty())),
+                None => (entry, None),
+            };
+            let (workspace, repo_slug) = repo
+                .split_once('/')
+                .ok_or_else(|| format!("'{}' is not in workspace/repo form", entry))?;
+            Ok(MonitoredPipeline {
+                workspace: workspace.trim().to_string(),
+                repo_slug: repo_slug.trim().to_string(),
+                repo_name: repo_slug.trim().to_string(),
+                branch: branch.map(str::to_string),
+                ..MonitoredPipeline::default()
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if pipelines.is_empty() {
+        return Err("CDMENU_REPOS lists no repositories".to_string());
+    }
+    validate_monitored_pipelines(&pipelines)?;
+    Ok(pipelines)
+}
+
+/// Whether to run a single headless check instead of the tray app
+pub fn requested(launch_args: &LaunchArgs) -> bool {
+    launch_args.check || std::env::var_os("CDMENU_HEADLESS").is_some()
+}
+
+/// Check the pipelines configured in the environment once, print the status as JSON
+/// to stdout and return the process exit code
+///
+/// No window or tray is created, so this works in CI scripts and container health checks.
+pub fn run_check() -> i32 {
+    let config = match HeadlessConfig::from_env(|name| std::env::var(name).ok()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("cdmenu: {}", e);
+            return EXIT_CONFIG_ERROR;
+        }
+    };
+
+    let status = tauri::async_runtime::block_on(polling::check_all_pipelines(
+        &config.username,
+        &config.app_password,
+        &config.pipelines,
+        &TlsConfig::default(),
+        false,
+        &mut RunCaches::default(),
+    ));
+
+    match serde_json::to_string_pretty(&status) {
+        Ok(json) => println!("{}", json),
+        Err(e) => {
+            eprintln!("cdmenu: failed to serialize status: {}", e);
+            return EXIT_CONFIG_ERROR;
+        }
+    }
+    exit_code(&status)
+}
+
+/// Exit code for a checked status
+///
+/// `is_healthy` ignores pipelines that couldn't be checked, so those are looked for
+/// separately rather than reported as healthy.
+pub fn exit_code(status: &OverallStatus) -> i32 {
+    if !status.is_healthy {
+        EXIT_FAILED
+    } else if status
+        .pipeline_statuses()
+        .iter()
+        .any(|p| p.error_kind.is_some())
+    {
+        EXIT_CHECK_ERROR
+    } else {
+        EXIT_HEALTHY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{PipelineState, PipelineStatusInfo};
+    use chrono::Local;
+    use std::collections::HashMap;
+
+    #[test]
+    fn repos_are_parsed_with_optional_branches() {
+        let pipelines = parse_repos("acme/api:main, acme/web ,").unwrap();
+
+        let parsed: Vec<(&str, &str, Option<&str>)> = pipelines
+            .iter()
+            .map(|p| (p.workspace.as_str(), p.repo_slug.as_str(), p.branch.as_deref()))
+            .collect();
+        assert_eq!(parsed, [("acme", "api", Some("main")), ("acme", "web", None)]);
+    }
+
+    #[test]
+    fn malformed_repos_are_rejected() {
+        assert!(parse_repos("api").unwrap_err().contains("workspace/repo"));
+        assert!(parse_repos(" , ").unwrap_err().contains("no repositories"));
+        assert!(parse_repos("acme/").is_err());
+    }
+
+    #[test]
+    fn every_variable_is_required() {
+        let env = HashMap::from([
+            ("CDMENU_USERNAME", "ci-bot"),
+            ("CDMENU_APP_PASSWORD", "secret"),
+            ("CDMENU_REPOS", "acme/api"),
+        ]);
+        let config = HeadlessConfig::from_env(|name| env.get(name).map(|v| v.to_string())).unwrap();
+        assert_eq!(config.username, "ci-bot");
+        assert_eq!(config.pipelines.len(), 1);
+
+        let error = HeadlessConfig::from_env(|name| {
+            (name != "CDMENU_APP_PASSWORD").then(|| env[name].to_string())
+        })
+        .unwrap_err();
+        assert_eq!(error, "CDMENU_APP_PASSWORD is not set");
+    }
+
+    fn checked(states: &[(PipelineState, Option<&str>)]) -> OverallStatus {
+        let pipelines = parse_repos("acme/api").unwrap();
+        let statuses = states
+            .iter()
+            .map(|(state, error)| PipelineStatusInfo {
+                error_kind: error.map(str::to_string),
+                ..PipelineStatusInfo::new(&pipelines[0], state.clone(), Local::now())
+            })
+            .collect();
+        OverallStatus::new(statuses, "2024-01-01 00:00:00".to_string(), false)
+    }
+
+    #[test]
+    fn pipelines_that_could_not_be_checked_are_not_healthy() {
+        assert_eq!(exit_code(&checked(&[(PipelineState::Healthy, None)])), EXIT_HEALTHY);
+        // A repository with no runs yet is Unknown without an error
+        assert_eq!(exit_code(&checked(&[(PipelineState::Unknown, None)])), EXIT_HEALTHY);
+        assert_eq!(
+            exit_code(&checked(&[
+                (PipelineState::Healthy, None),
+                (PipelineState::Unknown, Some("AuthenticationFailed")),
+            ])),
+            EXIT_CHECK_ERROR
+        );
+        assert_eq!(
+            exit_code(&checked(&[
+                (PipelineState::Failed, None),
+                (PipelineState::Unknown, Some("NotFound(..)")),
+            ])),
+            EXIT_FAILED
+        );
+    }
+}
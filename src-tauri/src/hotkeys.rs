@@ -0,0 +1,143 @@
+use crate::polling;
+use crate::quick_open;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Action bound to a global hotkey
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    Refresh,
+    OpenSettings,
+    QuickOpen,
+}
+
+/// Why a hotkey could not be applied
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyErrorKind {
+    /// The shortcut string could not be parsed
+    Invalid,
+    /// The shortcut is already taken (by another app or another action)
+    Conflict,
+    /// The hotkeys were registered but could not be saved
+    Persist,
+}
+
+/// Structured hotkey error returned to the settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyError {
+    pub kind: HotkeyErrorKind,
+    pub action: Option<HotkeyAction>,
+    pub hotkey: Option<String>,
+    pub message: String,
+}
+
+impl HotkeyError {
+    fn new(kind: HotkeyErrorKind, action: HotkeyAction, hotkey: &str, message: String) -> Self {
+        Self {
+            kind,
+            action: Some(action),
+            hotkey: Some(hotkey.to_string()),
+            message,
+        }
+    }
+
+    pub fn persist(message: String) -> Self {
+        Self {
+            kind: HotkeyErrorKind::Persist,
+            action: None,
+            hotkey: None,
+            message,
+        }
+    }
+}
+
+/// Replace all registered global hotkeys with the given bindings
+///
+/// On failure nothing is left registered; callers should restore the previous bindings.
+pub fn register_hotkeys(
+    app_handle: &AppHandle,
+    refresh: Option<&str>,
+    open_settings: Option<&str>,
+    quick_open: Option<&str>,
+) -> Result<(), HotkeyError> {
+    let bindings: Vec<(HotkeyAction, &str, Shortcut)> = [
+        (HotkeyAction::Refresh, refresh),
+        (HotkeyAction::OpenSettings, open_settings),
+        (HotkeyAction::QuickOpen, quick_open),
+    ]
+    .into_iter()
+    .filter_map(|(action, hotkey)| hotkey.map(|h| (action, h)))
+    .map(|(action, hotkey)| {
+        hotkey
+            .parse::<Shortcut>()
+            .map(|shortcut| (action, hotkey, shortcut))
+            .map_err(|e| {
+                HotkeyError::new(HotkeyErrorKind::Invalid, action, hotkey, e.to_string())
+            })
+    })
+    .collect::<Result<_, _>>()?;
+
+    for (i, (action, hotkey, shortcut)) in bindings.iter().enumerate() {
+        if bindings[..i].iter().any(|(_, _, earlier)| earlier == shortcut) {
+            return Err(HotkeyError::new(
+                HotkeyErrorKind::Conflict,
+                *action,
+                hotkey,
+                "Each hotkey must be different".to_string(),
+            ));
+        }
+    }
+
+    let global_shortcut = app_handle.global_shortcut();
+    let _ = global_shortcut.unregister_all();
+
+    for (action, hotkey, shortcut) in bindings {
+        let result = global_shortcut.on_shortcut(shortcut, move |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                handle_hotkey(app, action);
+            }
+        });
+
+        if let Err(e) = result {
+            let _ = global_shortcut.unregister_all();
+            return Err(HotkeyError::new(
+                HotkeyErrorKind::Conflict,
+                action,
+                hotkey,
+                format!("Could not register {}: {}", hotkey, e),
+            ));
+        }
+        tracing::info!("Registered {:?} hotkey: {}", action, hotkey);
+    }
+
+    Ok(())
+}
+
+/// Run the action bound to a pressed hotkey
+fn handle_hotkey(app_handle: &AppHandle, action: HotkeyAction) {
+    match action {
+        HotkeyAction::Refresh => {
+            tracing::info!("Refresh requested from hotkey");
+            let handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                polling::request_refresh(&handle, polling::RefreshRequest { announce: true }).await;
+            });
+        }
+        HotkeyAction::OpenSettings => {
+            tracing::info!("Opening settings window from hotkey");
+            if let Some(window) = app_handle.get_webview_window("settings") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        HotkeyAction::QuickOpen => {
+            tracing::info!("Toggling quick open window from hotkey");
+            if let Err(e) = quick_open::toggle_quick_open(app_handle) {
+                tracing::error!("{}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Languages the tray menu, tooltip and notifications are available in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    De,
+}
+
+// Locale used by `t`, `tf` and `tp` (index into `Locale::ALL`)
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+impl Locale {
+    const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    /// Match a locale tag such as "de", "de-AT" or "de_DE.UTF-8" on its language
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+}
+
+/// Configured locale if it's supported, else the OS locale, else English
+pub fn resolve(configured: Option<&str>) -> Locale {
+    configured
+        .and_then(Locale::from_tag)
+        .or_else(|| os_locale().as_deref().and_then(Locale::from_tag))
+        .unwrap_or(Locale::En)
+}
+
+/// Switch the language of newly rendered strings
+pub fn set_locale(locale: Locale) {
+    let index = Locale::ALL.iter().position(|l| *l == locale).unwrap_or(0);
+    CURRENT.store(index as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> Locale {
+    Locale::ALL[CURRENT.load(Ordering::Relaxed) as usize]
+}
+
+#[cfg(target_os = "macos")]
+fn os_locale() -> Option<String> {
+    // GUI apps don't inherit LANG, so ask the system preferences
+    let output = std::process::Command::new("defaults")
+        .args(["read", "-g", "AppleLocale"])
+        .output()
+        .ok()?;
+    let locale = String::from_utf8(output.stdout).ok()?;
+    Some(locale.trim().to_string()).filter(|l| !l.is_empty())
+}
+
+#[cfg(windows)]
+fn os_locale() -> Option<String> {
+    use windows_sys::Win32::Globalization::GetUserDefaultLocaleName;
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    // Returns the length including the terminating null, or 0 on failure
+    let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+    (len > 1).then(|| String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn os_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Message key, English, German
+///
+/// Placeholders are written `{name}`; plural messages have `.one` and `.other` keys
+/// and receive the count as `{count}`.
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // Tray menu
+    ("menu.loading", "Loading...", "Wird geladen..."),
+    ("menu.refresh", "Refresh Now", "Jetzt aktualisieren"),
+    ("menu.wallboard", "Wallboard...", "Wallboard..."),
+    ("menu.settings", "Settings...", "Einstellungen..."),
+    ("menu.quit", "Quit", "Beenden"),
+    ("menu.polling_interval", "Polling interval", "Abfrageintervall"),
+    ("menu.no_pipelines", "No pipelines configured", "Keine Pipelines konfiguriert"),
+    ("menu.not_configured", "Not configured", "Nicht eingerichtet"),
+    ("menu.set_up_credentials", "Set up credentials...", "Zugangsdaten einrichten..."),
+    ("menu.no_pipelines_selected", "No pipelines selected", "Keine Pipelines ausgewählt"),
+    ("menu.configure_pipelines", "Configure pipelines...", "Pipelines konfigurieren..."),
+    ("menu.open_pipeline", "Open pipeline", "Pipeline öffnen"),
+    ("menu.open_repository", "Open repository", "Repository öffnen"),
+    ("menu.open_branch", "Open branch", "Branch öffnen"),
+    ("menu.copy_link", "Copy link", "Link kopieren"),
+    ("menu.details", "Details in cdMenu...", "Details in cdMenu..."),
+    ("menu.remove_from_monitoring", "Remove from monitoring", "Nicht mehr überwachen"),
+    ("menu.run_action", "Run {label}", "{label} ausführen"),
+    ("menu.no_pipeline_data.one", "{count} with no pipeline data", "{count} ohne Pipeline-Daten"),
+    ("menu.no_pipeline_data.other", "{count} with no pipeline data", "{count} ohne Pipeline-Daten"),
+    ("menu.running_section", "RUNNING", "LAUFEND"),
+    ("menu.elapsed", " ({elapsed})", " ({elapsed})"),
+    ("menu.my_pull_requests", "MY PULL REQUESTS", "MEINE PULL REQUESTS"),
+    ("menu.checked", "Checked {age}", "Geprüft {age}"),
+    ("menu.drift", "{from} +{ahead} vs {to}", "{from} +{ahead} ggü. {to}"),
+    ("menu.drift_at_least", "{from} +{ahead} or more vs {to}", "{from} +{ahead} oder mehr ggü. {to}"),
+    ("menu.mttr", "MTTR: {mttr} over last {days}d", "MTTR: {mttr} in den letzten {days} T."),
+    ("menu.last_checked", "Last checked: {time}", "Zuletzt geprüft: {time}"),
+    // Pipeline status suffixes
+    ("pipeline.failed", " - FAILED", " - FEHLGESCHLAGEN"),
+    ("pipeline.failed_at", " - FAILED ({step})", " - FEHLGESCHLAGEN ({step})"),
+    ("pipeline.running", " - running", " - läuft"),
+    ("pipeline.paused", "paused", "pausiert"),
+    ("pipeline.missing_scope", " - missing pipelines scope", " - Berechtigung „pipelines“ fehlt"),
+    ("pipeline.unreachable", " - unreachable", " - nicht erreichbar"),
+    ("pipeline.disabled", " - pipelines disabled", " - Pipelines deaktiviert"),
+    ("pipeline.gate", " [{step}]", " [{step}]"),
+    ("pipeline.off_hours", " (off-hours)", " (außerhalb der Arbeitszeit)"),
+    // Why a pipeline's state is unknown
+    ("unknown.no_runs", "No pipeline runs found", "Keine Pipeline-Läufe gefunden"),
+    ("unknown.auth_failed", "⚠ Auth error", "⚠ Anmeldefehler"),
+    ("unknown.rate_limited", "⚠ Rate limited", "⚠ Ratenlimit erreicht"),
+    ("digest.title", "Weekly pipeline digest", "Wöchentliche Pipeline-Übersicht"),
+    ("digest.last_week", "Last week", "Letzte Woche"),
+    ("digest.last_days", "Last {days} days", "Letzte {days} Tage"),
+    ("digest.headline", "{window}: {failures} across {pipelines}, worst offender: {name} ({count})", "{window}: {failures} in {pipelines}, häufigster Verursacher: {name} ({count})"),
+    ("digest.headline.clean", "{window}: no failures", "{window}: keine Fehlschläge"),
+    ("digest.failures.one", "{count} failure", "{count} Fehlschlag"),
+    ("digest.failures.other", "{count} failures", "{count} Fehlschläge"),
+    ("digest.pipelines.one", "{count} pipeline", "{count} Pipeline"),
+    ("digest.pipelines.other", "{count} pipelines", "{count} Pipelines"),
+    ("digest.open_report", "Open Settings for the full report", "Den vollständigen Bericht finden Sie in den Einstellungen"),
+    ("unknown.gate_step_not_found", "gate step not found", "Gate-Schritt nicht gefunden"),
+    // Relative ages
+    ("age.just_now", "just now", "gerade eben"),
+    ("age.minutes", "{n}m ago", "vor {n} Min."),
+    ("age.hours", "{n}h ago", "vor {n} Std."),
+    ("age.days", "{n}d ago", "vor {n} T."),
+    // Menu summary header
+    ("summary.all_healthy.one", "✓ {count} pipeline healthy", "✓ {count} Pipeline in Ordnung"),
+    ("summary.all_healthy.other", "✓ All {count} pipelines healthy", "✓ Alle {count} Pipelines in Ordnung"),
+    ("summary.failing.one", "{count} failing", "{count} fehlgeschlagen"),
+    ("summary.failing.other", "{count} failing", "{count} fehlgeschlagen"),
+    ("summary.awaiting_approval.one", "{count} awaiting approval", "{count} wartet auf Freigabe"),
+    ("summary.awaiting_approval.other", "{count} awaiting approval", "{count} warten auf Freigabe"),
+    ("summary.pull_requests_failing.one", "{count} pull request failing", "{count} Pull Request fehlgeschlagen"),
+    ("summary.pull_requests_failing.other", "{count} pull requests failing", "{count} Pull Requests fehlgeschlagen"),
+    ("summary.running.one", "{count} running", "{count} läuft"),
+    ("summary.running.other", "{count} running", "{count} laufen"),
+    // Tooltip
+    ("tooltip.title", "cdMenu", "cdMenu"),
+    ("tooltip.loading", "cdMenu - Loading...", "cdMenu - Wird geladen..."),
+    ("tooltip.not_configured", "cdMenu - Not configured", "cdMenu - Nicht eingerichtet"),
+    ("tooltip.no_pipelines", "cdMenu - No pipelines selected", "cdMenu - Keine Pipelines ausgewählt"),
+    ("tooltip.auth_required", "cdMenu - Auth required", "cdMenu - Anmeldung erforderlich"),
+    ("tooltip.reduced_on_battery", "cdMenu - reduced polling on battery", "cdMenu - seltener abfragen im Akkubetrieb"),
+    ("tooltip.paused_on_metered", "cdMenu - paused on metered network", "cdMenu - pausiert in getakteter Verbindung"),
+    ("tooltip.reduced_for_api_budget", "cdMenu - reduced polling: API budget", "cdMenu - seltener abfragen: API-Budget"),
+    ("tooltip.following", "(following)", "(folgt)"),
+    ("tooltip.unable_to_check", "Unable to check pipelines ({reason})", "Pipelines können nicht geprüft werden ({reason})"),
+    ("tooltip.credentials_rejected", "cdMenu - Credentials rejected, open Settings", "cdMenu - Zugangsdaten abgelehnt, Einstellungen öffnen"),
+    ("tooltip.connection_degraded.one", "⚠ Connection degraded ({count} error)", "⚠ Verbindung gestört ({count} Fehler)"),
+    ("tooltip.connection_degraded.other", "⚠ Connection degraded ({count} errors)", "⚠ Verbindung gestört ({count} Fehler)"),
+    ("tooltip.session", "Session: {failures}, {recoveries}", "Sitzung: {failures}, {recoveries}"),
+    ("tooltip.session_failures.one", "{count} failure", "{count} Fehlschlag"),
+    ("tooltip.session_failures.other", "{count} failures", "{count} Fehlschläge"),
+    ("tooltip.session_recoveries.one", "{count} recovery", "{count} Behebung"),
+    ("tooltip.session_recoveries.other", "{count} recoveries", "{count} Behebungen"),
+    ("tooltip.disconnected", "⚠ Disconnected from Bitbucket", "⚠ Keine Verbindung zu Bitbucket"),
+    ("tooltip.missing_scope", "Missing pipelines scope", "Berechtigung „pipelines“ fehlt"),
+    ("tooltip.unknown_error", "Unknown", "Unbekannt"),
+    ("tooltip.healthy.one", "{count} pipeline healthy", "{count} Pipeline in Ordnung"),
+    ("tooltip.healthy.other", "{count} pipelines healthy", "{count} Pipelines in Ordnung"),
+    ("tooltip.skipped_no_data", " ({count} with no pipeline data)", " ({count} ohne Pipeline-Daten)"),
+    ("tooltip.in_progress.one", "{count} in progress", "{count} läuft"),
+    ("tooltip.in_progress.other", "{count} in progress", "{count} laufen"),
+    ("tooltip.failed.one", "{count} pipeline FAILED", "{count} Pipeline FEHLGESCHLAGEN"),
+    ("tooltip.failed.other", "{count} pipelines FAILED", "{count} Pipelines FEHLGESCHLAGEN"),
+    ("tooltip.more", " +{count} more", " +{count} weitere"),
+    ("tooltip.running", "{name} — running", "{name} — läuft"),
+    ("tooltip.running_triggered", "{name} — running (triggered by: {trigger})", "{name} — läuft (ausgelöst durch: {trigger})"),
+    // Notifications
+    ("notify.failed.title", "Pipeline Failed", "Pipeline fehlgeschlagen"),
+    ("notify.failed.body", "{name} has failed", "{name} ist fehlgeschlagen"),
+    ("notify.fixed.title", "Pipeline Fixed", "Pipeline repariert"),
+    ("notify.fixed.body", "{name} is now healthy", "{name} ist wieder in Ordnung"),
+    ("notify.fixed.after", "{name} is healthy again after {duration}", "{name} ist nach {duration} wieder in Ordnung"),
+    ("notify.awaiting.title", "Deployment awaiting approval", "Deployment wartet auf Freigabe"),
+    ("notify.awaiting.manual_step", "manual step", "manuellem Schritt"),
+    ("notify.awaiting.body", "{name} #{build} is waiting at {step}", "{name} #{build} wartet bei {step}"),
+    ("notify.pipelines_disabled.title", "Pipelines disabled", "Pipelines deaktiviert"),
+    ("notify.pipelines_disabled.body", "Pipelines has been switched off for {name}. Remove it from monitoring from its tray menu.", "Pipelines wurde für {name} ausgeschaltet. Über das Tray-Menü kannst du es aus der Überwachung entfernen."),
+    ("notify.credentials_rejected.title", "Credentials rejected", "Zugangsdaten abgelehnt"),
+    ("notify.credentials_rejected.body", "Bitbucket no longer accepts your app password. Open Settings to enter the new one; checks are paused until then.", "Bitbucket akzeptiert dein App-Passwort nicht mehr. Gib in den Einstellungen das neue ein; bis dahin wird nicht geprüft."),
+    ("notify.started.title", "Pipeline Started", "Pipeline gestartet"),
+    ("notify.started.body", "{repo} #{build} ({selector} on {branch})", "{repo} #{build} ({selector} auf {branch})"),
+    ("notify.not_started.title", "Pipeline Not Started", "Pipeline nicht gestartet"),
+    ("notify.link_copied", "Link copied", "Link kopiert"),
+    ("notify.summary.healthy.one", "{count} pipeline healthy", "{count} Pipeline in Ordnung"),
+    ("notify.summary.healthy.other", "All {count} healthy", "Alle {count} in Ordnung"),
+    ("notify.feed_stale.title", "Status feed stopped", "Status-Feed angehalten"),
+    ("notify.feed_stale.body", "The primary cdMenu hasn't published for a while, so this one is checking Bitbucket directly", "Das primäre cdMenu hat länger nichts veröffentlicht, daher prüft dieses Bitbucket direkt"),
+    ("notify.summary.failed", "{count} failed: {names}", "{count} fehlgeschlagen: {names}"),
+    ("notify.summary.unavailable", "No pipeline status available", "Kein Pipeline-Status verfügbar"),
+];
+
+/// Message in `locale`; unknown keys log a warning and render empty
+fn lookup(locale: Locale, key: &str) -> &'static str {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, en, de)) => match locale {
+            Locale::En => en,
+            Locale::De => de,
+        },
+        None => {
+            tracing::warn!("Missing message '{}'", key);
+            ""
+        }
+    }
+}
+
+// Fill placeholders in one pass, so a value containing `{name}` is never filled in itself
+fn format(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find('}').and_then(|end| {
+            let name = &placeholder[1..end];
+            let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+            Some((end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                text.push_str(&value.to_string());
+                rest = &placeholder[end + 1..];
+            }
+            // Unknown placeholders and stray braces are kept as written
+            None => {
+                text.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+fn plural_key(key: &str, count: usize) -> String {
+    // Both supported languages use the same one/other rule
+    if count == 1 {
+        format!("{}.one", key)
+    } else {
+        format!("{}.other", key)
+    }
+}
+
+/// Message without placeholders in the current locale
+pub fn t(key: &str) -> &'static str {
+    lookup(current(), key)
+}
+
+/// Message with `{name}` placeholders filled in
+pub fn tf(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    format(lookup(current(), key), args)
+}
+
+/// Plural message chosen by `count`, which also fills `{count}`
+pub fn tp(key: &str, count: usize, args: &[(&str, &dyn Display)]) -> String {
+    let mut all: Vec<(&str, &dyn Display)> = vec![("count", &count)];
+    all.extend_from_slice(args);
+    format(lookup(current(), &plural_key(key, count)), &all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn translations_are_complete_and_use_the_same_placeholders() {
+        for (key, en, de) in MESSAGES {
+            assert!(!en.is_empty() && !de.is_empty(), "{} is missing a translation", key);
+            assert_eq!(placeholders(en), placeholders(de), "{} placeholders differ", key);
+        }
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        for (i, (key, _, _)) in MESSAGES.iter().enumerate() {
+            assert!(
+                !MESSAGES[..i].iter().any(|(k, _, _)| k == key),
+                "{} is defined twice",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn plural_messages_pick_one_or_other() {
+        let render = |locale, count: usize| {
+            format(lookup(locale, &plural_key("tooltip.healthy", count)), &[("count", &count)])
+        };
+
+        assert_eq!(render(Locale::En, 1), "1 pipeline healthy");
+        assert_eq!(render(Locale::En, 3), "3 pipelines healthy");
+        assert_eq!(render(Locale::En, 0), "0 pipelines healthy");
+        assert_eq!(render(Locale::De, 1), "1 Pipeline in Ordnung");
+        assert_eq!(render(Locale::De, 2), "2 Pipelines in Ordnung");
+    }
+
+    #[test]
+    fn values_are_not_searched_for_placeholders() {
+        let name = "{branch}";
+        let text = format("{name} on {branch} {missing}", &[("name", &name), ("branch", &"main")]);
+        assert_eq!(text, "{branch} on main {missing}");
+    }
+
+    #[test]
+    fn locale_tags_match_on_language() {
+        assert_eq!(Locale::from_tag("de"), Some(Locale::De));
+        assert_eq!(Locale::from_tag("de-AT"), Some(Locale::De));
+        assert_eq!(Locale::from_tag("de_DE.UTF-8"), Some(Locale::De));
+        assert_eq!(Locale::from_tag("EN_us"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("fr_FR"), None);
+        assert_eq!(resolve(Some("de")), Locale::De);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::config::PipelineStatusInfo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Maximum number of resolved incidents kept in memory
+pub const INCIDENT_LOG_LIMIT: usize = 50;
+
+/// How long failure and recovery events are kept for digests
+pub const INCIDENT_EVENT_RETENTION_DAYS: i64 = 90;
+
+/// A pipeline failing or recovering, persisted for the weekly digest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentEvent {
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+    pub kind: IncidentEventKind,
+    pub at: DateTime<Utc>,
+    /// Length of the outage a recovery ended
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    /// Run that failed or fixed it
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IncidentEventKind {
+    Failed,
+    Recovered,
+}
+
+/// A resolved outage: a pipeline going Failed and later back to Healthy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Incident {
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub duration_secs: u64,
+    /// Run that failed
+    pub failed_url: Option<String>,
+    /// Run that fixed it
+    pub fixed_url: Option<String>,
+}
+
+/// Outage still in progress
+#[derive(Debug, Clone)]
+struct OpenIncident {
+    started_at: DateTime<Utc>,
+    failed_url: Option<String>,
+}
+
+/// Tracks when pipelines started failing and keeps a log of resolved outages
+#[derive(Debug, Clone, Default)]
+pub struct IncidentTracker {
+    open: HashMap<(String, String), OpenIncident>,
+    log: VecDeque<Incident>,
+    /// Every failure and recovery of the last `INCIDENT_EVENT_RETENTION_DAYS`, oldest first
+    events: Vec<IncidentEvent>,
+}
+
+impl IncidentTracker {
+    /// Restore persisted events, reopening outages whose last event is a failure
+    pub fn from_events(events: Vec<IncidentEvent>) -> Self {
+        let mut open = HashMap::new();
+        for event in &events {
+            let key = (event.workspace.clone(), event.repo_slug.clone());
+            match event.kind {
+                IncidentEventKind::Failed => {
+                    open.entry(key).or_insert_with(|| OpenIncident {
+                        started_at: event.at,
+                        failed_url: event.url.clone(),
+                    });
+                }
+                IncidentEventKind::Recovered => {
+                    open.remove(&key);
+                }
+            }
+        }
+        IncidentTracker {
+            open,
+            log: VecDeque::new(),
+            events,
+        }
+    }
+
+    /// Failure and recovery events, oldest first
+    pub fn events(&self) -> &[IncidentEvent] {
+        &self.events
+    }
+
+    /// Record that a pipeline went Failed; an outage already in progress keeps its start time
+    pub fn start(&mut self, pipeline: &PipelineStatusInfo, now: DateTime<Utc>) {
+        self.open
+            .entry((pipeline.workspace.clone(), pipeline.repo_slug.clone()))
+            .or_insert_with(|| OpenIncident {
+                started_at: now,
+                failed_url: pipeline.pipeline_url.clone(),
+            });
+        self.record(pipeline, IncidentEventKind::Failed, now, None);
+    }
+
+    fn record(
+        &mut self,
+        pipeline: &PipelineStatusInfo,
+        kind: IncidentEventKind,
+        at: DateTime<Utc>,
+        duration_secs: Option<u64>,
+    ) {
+        let cutoff = at - chrono::Duration::days(INCIDENT_EVENT_RETENTION_DAYS);
+        self.events.retain(|e| e.at >= cutoff);
+        self.events.push(IncidentEvent {
+            workspace: pipeline.workspace.clone(),
+            repo_slug: pipeline.repo_slug.clone(),
+            repo_name: pipeline.repo_name.clone(),
+            kind,
+            at,
+            duration_secs,
+            url: pipeline.pipeline_url.clone(),
+        });
+    }
+
+    /// Whether an outage of the pipeline is in progress
+    pub fn is_open(&self, workspace: &str, repo_slug: &str) -> bool {
+        self.open.contains_key(&(workspace.to_string(), repo_slug.to_string()))
+    }
+
+    /// Close the pipeline's outage, if one was recorded, and add it to the log
+    pub fn resolve(&mut self, pipeline: &PipelineStatusInfo, now: DateTime<Utc>) -> Option<Incident> {
+        let open = self
+            .open
+            .remove(&(pipeline.workspace.clone(), pipeline.repo_slug.clone()))?;
+
+        let incident = Incident {
+            workspace: pipeline.workspace.clone(),
+            repo_slug: pipeline.repo_slug.clone(),
+            repo_name: pipeline.repo_name.clone(),
+            started_at: open.started_at,
+            ended_at: now,
+            duration_secs: (now - open.started_at).num_seconds().max(0) as u64,
+            failed_url: open.failed_url,
+            fixed_url: pipeline.pipeline_url.clone(),
+        };
+
+        if self.log.len() == INCIDENT_LOG_LIMIT {
+            self.log.pop_front();
+        }
+        self.log.push_back(incident.clone());
+        self.record(pipeline, IncidentEventKind::Recovered, now, Some(incident.duration_secs));
+        Some(incident)
+    }
+
+    /// Resolved incidents, most recent first
+    pub fn recent(&self) -> Vec<Incident> {
+        self.log.iter().rev().cloned().collect()
+    }
+}
+
+/// Format an outage length like "2h 14m", "5m" or "40s"
+pub fn format_duration(secs: u64) -> String {
+    let days = secs / 86_400;
+    let hours = secs % 86_400 / 3_600;
+    let minutes = secs % 3_600 / 60;
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
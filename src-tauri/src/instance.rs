@@ -0,0 +1,77 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Command-line flags understood at launch and from a second instance
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LaunchArgs {
+    /// Don't show any window at startup (for autostart)
+    pub minimized: bool,
+    /// Show the settings window
+    pub settings: bool,
+    /// Check pipelines immediately
+    pub refresh: bool,
+    /// Check the pipelines in `CDMENU_*` environment variables once and exit, without a tray
+    pub check: bool,
+}
+
+impl LaunchArgs {
+    /// Parse flags, ignoring the program path and anything unrecognised
+    pub fn parse<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut parsed = Self::default();
+        for arg in args {
+            match arg.as_ref() {
+                "--minimized" => parsed.minimized = true,
+                "--settings" => parsed.settings = true,
+                "--refresh" => parsed.refresh = true,
+                "--check" => parsed.check = true,
+                _ => {}
+            }
+        }
+        parsed
+    }
+}
+
+/// Handle a second launch forwarded by the single-instance plugin
+pub fn handle_second_instance(app_handle: &AppHandle, args: Vec<String>) {
+    let launch = LaunchArgs::parse(&args);
+    tracing::info!("Second instance launched with {:?}", launch);
+
+    if launch.refresh {
+        let _ = app_handle.emit("trigger-refresh", ());
+    }
+
+    if launch.settings {
+        show_settings(app_handle);
+    } else if !launch.refresh && !launch.minimized && !focus_visible_window(app_handle) {
+        // Plain relaunch: bring something up so the user sees we're running
+        show_settings(app_handle);
+    }
+}
+
+/// Show and focus the settings window
+pub fn show_settings(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("settings") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Focus the first visible app window, returning whether there was one
+fn focus_visible_window(app_handle: &AppHandle) -> bool {
+    let visible = app_handle
+        .webview_windows()
+        .into_values()
+        .find(|w| w.is_visible().unwrap_or(false));
+    match visible {
+        Some(window) => {
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+            true
+        }
+        None => false,
+    }
+}
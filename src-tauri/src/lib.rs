@@ -1,23 +1,73 @@
-mod bitbucket;
+mod active_hours;
+mod activity;
+#[cfg(test)]
+mod bindings;
+pub mod bitbucket;
 mod commands;
 mod config;
+mod default_branch;
+mod digest;
+mod drift;
+mod follower;
+mod groups;
+mod headless;
+mod hotkeys;
+mod i18n;
+mod incidents;
+mod instance;
+mod pipeline_detail;
+mod pipelines_disabled;
 mod polling;
+mod power;
+mod pull_requests;
+mod quick_open;
+mod reliability;
+mod shutdown;
+mod storage;
 mod tray;
+mod wallboard;
+mod webhooks;
 
 use config::AppState;
 use std::sync::Arc;
-use tauri::{Manager, WindowEvent};
+use tauri::{Emitter, Manager, WindowEvent};
 use tokio::sync::Mutex;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let launch_args = instance::LaunchArgs::parse(std::env::args().skip(1));
+    let headless = headless::requested(&launch_args);
+
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        // A headless check keeps stdout for its JSON
+        .with_writer(move || -> Box<dyn std::io::Write> {
+            if headless {
+                Box::new(std::io::stderr())
+            } else {
+                Box::new(std::io::stdout())
+            }
+        })
+        .init();
+
+    if headless {
+        std::process::exit(headless::run_check());
+    }
 
     tauri::Builder::default()
+        // Must be registered first so a second launch exits before doing any work
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            instance::handle_second_instance(app, args);
+        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
-        .setup(|app| {
-            log::info!("Setting up cdMenu...");
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .setup(move |app| {
+            tracing::info!("Setting up cdMenu...");
 
             // Set macOS to accessory mode (no dock icon)
             #[cfg(target_os = "macos")]
@@ -25,35 +75,174 @@ pub fn run() {
                 app.set_activation_policy(tauri::ActivationPolicy::Accessory);
             }
 
+            // Make sure settings can actually be saved before relying on them
+            let storage_status = storage::check_storage(app.handle());
+            if let Some(error) = &storage_status.error {
+                tracing::error!(
+                    "Config directory {:?} is not writable: {}",
+                    storage_status.config_dir,
+                    error
+                );
+            }
+
             // Load persisted config
             let initial_state = if let Some(config) = commands::load_config(app.handle()) {
-                log::info!(
+                tracing::info!(
                     "Loaded config with {} monitored pipelines",
                     config.monitored_pipelines.len()
                 );
                 AppState::from_persisted(config)
             } else {
-                log::info!("No existing config found, using defaults");
+                tracing::info!("No existing config found, using defaults");
                 AppState::new()
             };
 
+            if initial_state.danger_accept_invalid_certs {
+                tracing::warn!("TLS certificate verification is DISABLED - connections are not secure");
+            }
+
+            // Register global hotkeys (a conflict should not block startup)
+            if let Err(e) = hotkeys::register_hotkeys(
+                app.handle(),
+                initial_state.hotkey_refresh.as_deref(),
+                initial_state.hotkey_open_settings.as_deref(),
+                initial_state.hotkey_quick_open.as_deref(),
+            ) {
+                tracing::warn!("Failed to register hotkeys: {}", e.message);
+            }
+
+            // --minimized (autostart) keeps every window hidden at launch
+            let open_wallboard_on_launch =
+                initial_state.wallboard_start_on_launch && !launch_args.minimized;
+            let polling_interval_seconds = initial_state.polling_interval_seconds;
+            let show_history_glyphs = initial_state.show_history_glyphs;
+            let hide_unknown_in_menu = initial_state.hide_unknown_in_menu;
+            let group_running_separately = initial_state.group_running_separately;
+            let icon_theme = initial_state.icon_theme;
+            i18n::set_locale(i18n::resolve(initial_state.locale.as_deref()));
+            bitbucket::budget::set_api_budget(initial_state.api_budget_per_hour);
+
+            // Fresh install: open settings so new users know where to start
+            let is_first_run = initial_state.credentials.is_none()
+                && !initial_state.has_completed_setup
+                && !launch_args.minimized;
+
+            // Quitting stops these tasks and waits for them before exiting
+            let shutdown = shutdown::Shutdown::default();
+
+            // Serialize config writes through a single background task
+            let (config_write_tx, config_write_rx) = tokio::sync::mpsc::channel(32);
+            let mut initial_state = initial_state;
+            initial_state.storage = storage_status.clone();
+            initial_state.config_write_tx = Some(config_write_tx);
+            let app_handle = app.handle().clone();
+            shutdown.track_config_writer(tauri::async_runtime::spawn(async move {
+                commands::run_config_writer(app_handle, config_write_rx).await;
+            }));
+
+            // The activity log lives in its own file, written on a longer debounce
+            if let Some(dir) = &storage_status.config_dir {
+                initial_state.activity = activity::ActivityLog::from_entries(activity::load(dir));
+            }
+            let (activity_write_tx, activity_write_rx) = tokio::sync::mpsc::channel(8);
+            initial_state.activity_write_tx = Some(activity_write_tx);
+            let app_handle = app.handle().clone();
+            shutdown.track_activity_writer(tauri::async_runtime::spawn(async move {
+                activity::run_activity_writer(app_handle, activity_write_rx).await;
+            }));
+
+            // Let the polling loop pick up interval changes while it runs
+            let (interval_tx, interval_rx) =
+                tokio::sync::watch::channel(initial_state.polling_interval_seconds);
+            initial_state.polling_interval_tx = Some(interval_tx);
+
+            // Coalesce bursts of manual refreshes into one check
+            let (refresh_tx, refresh_rx) = tokio::sync::mpsc::channel(8);
+            initial_state.refresh_tx = Some(refresh_tx);
+            initial_state.trays.register(tray::TRAY_ID);
+
             // Initialize shared state
             let app_state = Arc::new(Mutex::new(initial_state));
             app.manage(app_state);
 
             // Build system tray
-            tray::build_tray(app)?;
+            tray::set_checked_interval(polling_interval_seconds);
+            tray::set_show_history_glyphs(show_history_glyphs);
+            tray::set_hide_unknown_in_menu(hide_unknown_in_menu);
+            tray::set_group_running_separately(group_running_separately);
+            tray::set_icon_theme(icon_theme);
+            // The tray has no window of its own, so the hidden settings window reports the appearance
+            if let Some(theme) = app.get_webview_window("settings").and_then(|w| w.theme().ok()) {
+                tray::set_current_theme(theme);
+            }
+            tray::build_tray(app, tray::TRAY_ID)?;
+            if storage_status.error.is_some() {
+                tray::update_tray_tooltip(app.handle(), tray::TRAY_ID, storage::STORAGE_ERROR_TITLE);
+                storage::notify_storage_error(app.handle(), &storage_status);
+            }
 
             // Set up refresh listener
-            polling::setup_refresh_listener(app.handle().clone());
+            shutdown.track(polling::setup_refresh_listener(
+                app.handle().clone(),
+                refresh_rx,
+                shutdown.token.clone(),
+            ));
 
             // Start background polling
             let app_handle = app.handle().clone();
+            let token = shutdown.token.clone();
+            shutdown.track(tauri::async_runtime::spawn(async move {
+                polling::start_polling(app_handle, interval_rx, token).await;
+            }));
+            app.manage(shutdown);
+
+            // launchctl and systemd stop the app with SIGTERM
+            #[cfg(unix)]
+            {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    shutdown::shutdown_on_sigterm(app_handle).await;
+                });
+            }
+
+            if is_first_run {
+                tracing::info!("First run detected, opening settings");
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    // Give the settings window time to load before showing it
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    if let Some(window) = app_handle.get_webview_window("settings") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        let _ = window.emit("first-run", ());
+                    }
+                });
+            } else if launch_args.settings && !launch_args.minimized {
+                instance::show_settings(app.handle());
+            }
+
+            if open_wallboard_on_launch {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = wallboard::open_wallboard(&app_handle).await {
+                        tracing::error!("{}", e);
+                    }
+                });
+            }
+
+            // Keep project-derived pipelines in sync
+            let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                polling::start_polling(app_handle).await;
+                groups::start_group_sync(app_handle).await;
             });
 
-            log::info!("cdMenu setup complete");
+            // Post the weekly digest when it's due
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                digest::start_digest_scheduler(app_handle).await;
+            });
+
+            tracing::info!("cdMenu setup complete");
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -61,23 +250,113 @@ pub fn run() {
             commands::get_projects,
             commands::get_repositories,
             commands::get_repositories_by_project,
+            commands::get_workspace_members,
+            commands::get_repository_details,
+            commands::get_pipeline_schedules,
+            commands::resolve_default_branches,
             commands::get_pipelines,
+            commands::get_pipeline_by_build_number,
+            commands::preview_pipeline,
+            commands::open_repository,
+            commands::copy_pipeline_url,
             commands::save_credentials,
             commands::get_credentials,
             commands::get_app_password,
             commands::save_monitored_pipelines,
             commands::get_monitored_pipelines,
+            commands::confirm_remove_stale_pipelines,
+            commands::save_monitored_groups,
+            commands::get_monitored_groups,
             commands::get_pipeline_statuses,
+            commands::toggle_quick_open,
+            commands::search_status,
+            commands::open_status_entry,
+            commands::open_wallboard,
+            commands::open_activity_log,
+            commands::get_pipeline_detail,
+            commands::get_activity_log,
+            commands::clear_activity_log,
+            commands::set_wallboard_options,
+            commands::get_wallboard_options,
             commands::set_polling_interval,
             commands::get_polling_interval,
+            commands::set_config_notes,
+            commands::get_config_notes,
+            commands::set_notify_on_paused,
+            commands::get_notify_on_paused,
+            commands::set_notification_category_enabled,
+            commands::get_notification_categories,
+            commands::set_treat_paused_as_unhealthy,
+            commands::get_treat_paused_as_unhealthy,
+            commands::set_include_prs_in_health,
+            commands::get_include_prs_in_health,
+            commands::save_notification_routes,
+            commands::get_notification_routes,
+            commands::test_webhook,
+            commands::run_custom_pipeline,
+            commands::get_incident_log,
+            commands::get_reliability_report,
+            commands::get_weekly_digest,
+            commands::set_digest_settings,
+            commands::get_digest_settings,
+            commands::set_show_history_glyphs,
+            commands::get_show_history_glyphs,
+            commands::set_hide_unknown_in_menu,
+            commands::get_hide_unknown_in_menu,
+            commands::set_group_running_separately,
+            commands::get_group_running_separately,
+            commands::set_multi_tray,
+            commands::get_multi_tray,
+            commands::set_attention_animation,
+            commands::set_icon_theme,
+            commands::get_icon_theme,
+            commands::get_attention_animation,
+            commands::set_locale,
+            commands::get_locale,
+            commands::get_pipeline_stats,
+            commands::get_diagnostics,
+            commands::get_connection_status,
+            commands::get_session_stats,
+            commands::get_polling_status,
+            commands::get_poll_metrics,
+            commands::get_rate_limit_info,
+            commands::set_power_settings,
             commands::trigger_refresh,
+            commands::trigger_single_refresh,
+            commands::set_hotkeys,
+            commands::get_hotkeys,
+            commands::set_tls_settings,
+            commands::get_tls_settings,
+            commands::set_follower_mode,
+            commands::get_follower_mode,
+            commands::reset_to_defaults,
+            commands::test_connection,
         ])
         .on_window_event(|window, event| {
-            // Hide settings window on close instead of quitting
+            // Every window reports an appearance change; redraw the tray once
+            if let WindowEvent::ThemeChanged(theme) = event {
+                if window.label() == "settings" {
+                    tray::set_current_theme(*theme);
+                    let app_handle = window.app_handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        polling::rerender_tray(&app_handle).await;
+                    });
+                }
+            }
+            // Hide settings, wallboard, quick-open and activity windows on close instead of quitting
             if let WindowEvent::CloseRequested { api, .. } = event {
                 if window.label() == "settings" {
                     api.prevent_close();
                     let _ = window.hide();
+                } else if window.label() == wallboard::WALLBOARD_LABEL {
+                    api.prevent_close();
+                    wallboard::save_geometry(window);
+                    let _ = window.hide();
+                } else if window.label() == quick_open::QUICK_OPEN_LABEL
+                    || window.label() == activity::ACTIVITY_LABEL
+                {
+                    api.prevent_close();
+                    let _ = window.hide();
                 }
             }
         })
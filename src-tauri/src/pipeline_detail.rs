@@ -0,0 +1,223 @@
+use crate::activity::ActivityEvent;
+use crate::commands::PipelineStats;
+use crate::config::{AppState, MonitoredPipeline, PipelineStatusInfo};
+use crate::instance::show_settings;
+use crate::quick_open::{find_entry, pipeline_key};
+use crate::reliability::{reliability_report, PipelineReliability, MTTR_MENU_WINDOW_DAYS};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+/// Activity log events included in a pipeline's detail view
+pub const DETAIL_ACTIVITY_LIMIT: usize = 20;
+
+/// Identifies a monitored pipeline: a repository, on a branch when monitored on one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineKey {
+    pub workspace: String,
+    pub repo_slug: String,
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+impl PipelineKey {
+    /// The key of the pipeline a status is for
+    pub fn of(status: &PipelineStatusInfo) -> Self {
+        Self {
+            workspace: status.workspace.clone(),
+            repo_slug: status.repo_slug.clone(),
+            branch: status.monitored_branch.clone(),
+        }
+    }
+
+    /// Same form as `quick_open::status_key`, which the activity log records
+    pub fn status_key(&self) -> String {
+        pipeline_key(&self.workspace, &self.repo_slug, self.branch.as_deref())
+    }
+
+    fn matches(&self, pipeline: &MonitoredPipeline) -> bool {
+        pipeline.workspace == self.workspace
+            && pipeline.repo_slug == self.repo_slug
+            && pipeline.branch == self.branch
+    }
+}
+
+/// Payload of the `navigate` event, asking the settings window to show a view
+#[derive(Debug, Clone, Serialize)]
+pub struct NavigateEvent {
+    /// Only "pipeline" so far
+    pub view: &'static str,
+    pub key: PipelineKey,
+}
+
+/// Settings that change how a pipeline is watched, and whether it looks deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineFlags {
+    pub muted: bool,
+    /// Outside the pipeline's active hours, so failures aren't announced
+    pub off_hours: bool,
+    /// Repeatedly not found, so the repository may have been deleted
+    pub stale: bool,
+}
+
+/// Everything the detail view shows for one pipeline
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineDetail {
+    pub key: PipelineKey,
+    /// None until the pipeline has been checked
+    pub status: Option<PipelineStatusInfo>,
+    /// Recent runs and how many passed, as in the settings window's pipeline list
+    pub stats: PipelineStats,
+    /// Over the same window as the MTTR line in the tray menu
+    pub reliability: Option<PipelineReliability>,
+    /// This pipeline's activity log events, newest first
+    pub activity: Vec<ActivityEvent>,
+    pub flags: PipelineFlags,
+}
+
+/// Gather the detail view of a monitored pipeline
+pub fn pipeline_detail(
+    state: &AppState,
+    key: &PipelineKey,
+    now: DateTime<Utc>,
+) -> Result<PipelineDetail, String> {
+    let monitored = state
+        .all_monitored_pipelines()
+        .into_iter()
+        .find(|p| key.matches(p))
+        .ok_or_else(|| format!("No monitored pipeline {}", key.status_key()))?;
+
+    let status_key = key.status_key();
+    let status = state
+        .last_status
+        .as_ref()
+        .and_then(|status| find_entry(status, &status_key))
+        .cloned();
+    let reliability = reliability_report(
+        state.incidents.events(),
+        std::slice::from_ref(&monitored),
+        now,
+        MTTR_MENU_WINDOW_DAYS,
+        true,
+    )
+    .pipelines
+    .into_iter()
+    .find(|r| r.workspace == key.workspace && r.repo_slug == key.repo_slug);
+    let activity = state
+        .activity
+        .entries()
+        .into_iter()
+        .rev()
+        .filter(|e| e.pipeline_key.as_deref() == Some(status_key.as_str()))
+        .take(DETAIL_ACTIVITY_LIMIT)
+        .collect();
+
+    Ok(PipelineDetail {
+        flags: PipelineFlags {
+            muted: monitored.muted,
+            off_hours: status.as_ref().is_some_and(|s| s.off_hours),
+            stale: state.is_stale(&key.workspace, &key.repo_slug),
+        },
+        key: key.clone(),
+        status,
+        stats: PipelineStats::new(state, monitored),
+        reliability,
+        activity,
+    })
+}
+
+/// Show the settings window and ask it to open the pipeline's detail view
+pub fn open_pipeline_detail(app_handle: &AppHandle, key: PipelineKey) {
+    tracing::info!("Opening details for {}", key.status_key());
+    show_settings(app_handle);
+    let event = NavigateEvent {
+        view: "pipeline",
+        key,
+    };
+    if let Err(e) = app_handle.emit_to("settings", "navigate", event) {
+        tracing::warn!("Failed to send navigate event: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::ActivityKind;
+    use crate::config::{OverallStatus, PipelineState, RunRecord, RunResult};
+    use chrono::Local;
+
+    fn monitored(slug: &str, branch: Option<&str>) -> MonitoredPipeline {
+        MonitoredPipeline {
+            workspace: "ws".to_string(),
+            repo_slug: slug.to_string(),
+            repo_name: slug.to_uppercase(),
+            branch: branch.map(str::to_string),
+            ..MonitoredPipeline::default()
+        }
+    }
+
+    fn key(slug: &str, branch: Option<&str>) -> PipelineKey {
+        PipelineKey {
+            workspace: "ws".to_string(),
+            repo_slug: slug.to_string(),
+            branch: branch.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn detail_gathers_status_history_and_activity_for_the_branch() {
+        let mut state = AppState::new();
+        let mut main = monitored("api", Some("main"));
+        main.muted = true;
+        state.monitored_pipelines = vec![main, monitored("api", Some("develop"))];
+        state.last_status = Some(OverallStatus::new(
+            state
+                .monitored_pipelines
+                .iter()
+                .map(|p| PipelineStatusInfo::new(p, PipelineState::Failed, Local::now()))
+                .collect(),
+            "2024-01-01 00:00:00".to_string(),
+            false,
+        ));
+        state.run_history.insert(
+            "ws/api".to_string(),
+            vec![RunRecord {
+                build_number: 7,
+                result: RunResult::Failed,
+            }],
+        );
+        let now = Utc::now();
+        state.activity.push(ActivityEvent::new(now, ActivityKind::Failed, Some("ws/api@develop".to_string()), "develop"));
+        state.activity.push(ActivityEvent::new(now, ActivityKind::Failed, Some("ws/api@main".to_string()), "main"));
+
+        let detail = pipeline_detail(&state, &key("api", Some("main")), now).unwrap();
+
+        assert_eq!(detail.stats.repo_name, "API");
+        assert_eq!(detail.status.unwrap().monitored_branch.as_deref(), Some("main"));
+        assert_eq!(detail.stats.failed, 1);
+        assert_eq!(detail.activity.len(), 1);
+        assert_eq!(detail.activity[0].detail, "main");
+        assert!(detail.flags.muted);
+        assert!(!detail.flags.stale);
+    }
+
+    #[test]
+    fn unmonitored_pipelines_have_no_detail() {
+        let mut state = AppState::new();
+        state.monitored_pipelines = vec![monitored("api", None)];
+
+        let error = pipeline_detail(&state, &key("api", Some("main")), Utc::now()).unwrap_err();
+
+        assert_eq!(error, "No monitored pipeline ws/api@main");
+        assert!(pipeline_detail(&state, &key("api", None), Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn key_uses_the_status_key_form() {
+        let status = PipelineStatusInfo::new(&monitored("api", Some("main")), PipelineState::Healthy, Local::now());
+        assert_eq!(PipelineKey::of(&status).status_key(), crate::quick_open::status_key(&status));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::bitbucket::BitbucketClient;
+use crate::config::{PipelineState, PipelineStatusInfo, UnknownReason};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Checks in a row a repository has to stay Unknown before its Pipelines settings are looked up
+pub const UNKNOWN_CHECKS_BEFORE_LOOKUP: u8 = 2;
+
+/// How long a repository's Pipelines settings are reused before they are looked up again
+pub const PIPELINES_CONFIG_REFRESH: Duration = Duration::from_secs(3600);
+
+/// What is known about repositories that might have Pipelines switched off
+#[derive(Debug, Clone, Default)]
+pub struct DisabledCache {
+    /// Checks in a row each (workspace, repo_slug) came back Unknown
+    unknown_streaks: HashMap<(String, String), u8>,
+    /// Whether Pipelines is enabled, and when that was looked up; None when the lookup failed
+    lookups: HashMap<(String, String), (Instant, Option<bool>)>,
+    /// Repositories already announced as disabled
+    announced: HashSet<(String, String)>,
+}
+
+impl DisabledCache {
+    fn needs_lookup(&self, key: &(String, String), now: Instant) -> bool {
+        let persistent = self
+            .unknown_streaks
+            .get(key)
+            .is_some_and(|streak| *streak >= UNKNOWN_CHECKS_BEFORE_LOOKUP);
+        let fresh = self.lookups.get(key).is_some_and(|(looked_up_at, _)| {
+            now.duration_since(*looked_up_at) < PIPELINES_CONFIG_REFRESH
+        });
+        persistent && !fresh
+    }
+
+    fn is_disabled(&self, key: &(String, String)) -> bool {
+        self.lookups.get(key).is_some_and(|(_, enabled)| *enabled == Some(false))
+    }
+
+    /// Flag the statuses of repositories with Pipelines switched off
+    pub fn apply(&self, statuses: &mut [PipelineStatusInfo]) {
+        for status in statuses {
+            let key = (status.workspace.clone(), status.repo_slug.clone());
+            status.pipelines_disabled = might_be_disabled(status) && self.is_disabled(&key);
+        }
+    }
+
+    /// Disabled repositories not announced yet; each is returned once until Pipelines is back
+    pub fn take_unannounced<'a>(
+        &mut self,
+        statuses: &'a [PipelineStatusInfo],
+    ) -> Vec<&'a PipelineStatusInfo> {
+        statuses
+            .iter()
+            .filter(|status| status.pipelines_disabled)
+            .filter(|status| {
+                self.announced
+                    .insert((status.workspace.clone(), status.repo_slug.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Unknown in a way a repository with Pipelines switched off would be
+fn might_be_disabled(status: &PipelineStatusInfo) -> bool {
+    status.state == PipelineState::Unknown
+        && matches!(
+            status.unknown_reason,
+            Some(UnknownReason::NoRuns | UnknownReason::ApiError(_))
+        )
+}
+
+/// Look up the Pipelines settings of repositories that stay Unknown
+///
+/// Each repository is looked up at most once per `PIPELINES_CONFIG_REFRESH`, whether or
+/// not the lookup succeeds.
+pub async fn check_disabled(
+    client: &BitbucketClient,
+    statuses: &[PipelineStatusInfo],
+    cache: &mut DisabledCache,
+    now: Instant,
+) {
+    let mut unknown = HashSet::new();
+
+    for status in statuses.iter().filter(|status| might_be_disabled(status)) {
+        let key = (status.workspace.clone(), status.repo_slug.clone());
+        // A repository can be monitored on several branches
+        if !unknown.insert(key.clone()) {
+            continue;
+        }
+        let streak = cache.unknown_streaks.entry(key.clone()).or_insert(0);
+        *streak = streak.saturating_add(1);
+        if !cache.needs_lookup(&key, now) {
+            continue;
+        }
+
+        match client.get_pipelines_config(&status.workspace, &status.repo_slug).await {
+            Ok(config) => {
+                if !config.enabled {
+                    tracing::warn!(
+                        "Pipelines is disabled on {}/{}",
+                        status.workspace,
+                        status.repo_slug
+                    );
+                }
+                cache.lookups.insert(key, (now, Some(config.enabled)));
+            }
+            // Not tried again until the refresh is due; an earlier answer still stands
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to check Pipelines settings of {}/{}: {}",
+                    status.workspace,
+                    status.repo_slug,
+                    e
+                );
+                let previous = cache.lookups.get(&key).and_then(|(_, enabled)| *enabled);
+                cache.lookups.insert(key, (now, previous));
+            }
+        }
+    }
+
+    // Repositories that check fine again start over
+    cache.unknown_streaks.retain(|key, _| unknown.contains(key));
+    cache.lookups.retain(|key, _| unknown.contains(key));
+    cache.announced.retain(|key| unknown.contains(key));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> (String, String) {
+        ("ws".to_string(), "api".to_string())
+    }
+
+    #[test]
+    fn settings_are_looked_up_once_unknown_persists_and_then_cached() {
+        let now = Instant::now();
+        let mut cache = DisabledCache::default();
+
+        cache.unknown_streaks.insert(key(), 1);
+        assert!(!cache.needs_lookup(&key(), now));
+
+        cache.unknown_streaks.insert(key(), UNKNOWN_CHECKS_BEFORE_LOOKUP);
+        assert!(cache.needs_lookup(&key(), now));
+
+        cache.lookups.insert(key(), (now, Some(false)));
+        assert!(!cache.needs_lookup(&key(), now + Duration::from_secs(60)));
+        assert!(cache.needs_lookup(&key(), now + PIPELINES_CONFIG_REFRESH));
+    }
+
+    #[test]
+    fn failed_lookups_are_cached_too() {
+        let now = Instant::now();
+        let mut cache = DisabledCache::default();
+        cache.unknown_streaks.insert(key(), UNKNOWN_CHECKS_BEFORE_LOOKUP);
+
+        cache.lookups.insert(key(), (now, None));
+
+        assert!(!cache.needs_lookup(&key(), now + Duration::from_secs(60)));
+        assert!(cache.needs_lookup(&key(), now + PIPELINES_CONFIG_REFRESH));
+        assert!(!cache.is_disabled(&key()));
+    }
+}
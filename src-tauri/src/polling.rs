@@ -1,65 +1,188 @@
-use crate::bitbucket::BitbucketClient;
-use crate::config::{AppState, MonitoredPipeline, OverallStatus, PipelineState, PipelineStatusInfo};
-use crate::tray::{update_tray_icon, update_tray_menu, update_tray_tooltip, TrayStatus};
+use crate::activity::{self, ActivityEvent, ActivityKind};
+use crate::bitbucket::budget;
+use crate::bitbucket::{
+    get_branch_url, get_pipelines_list_url, resolve_pipeline_url, BitbucketClient,
+    BitbucketError, Pipeline, PipelineStep, PipelineVariable, TlsConfig,
+};
+use crate::commands::save_config_helper;
+use crate::config::{
+    AppState, AttentionAnimation, CredentialKind, Credentials, MonitoredPipeline,
+    NotificationCategory, OverallStatus, PipelineState, PipelineStatusInfo, RunCaches,
+    StatusPayload, UnknownReason, STALE_NOT_FOUND_THRESHOLD,
+};
+use crate::default_branch::resolve_default_branches;
+use crate::drift::check_drift;
+use crate::pipelines_disabled::check_disabled;
+use crate::follower::{self, PublishedStatus};
+use crate::i18n::{t, tf, tp};
+use crate::incidents::{format_duration, IncidentTracker};
+use crate::power::{self, PollingMode, PollingPolicy};
+use crate::pull_requests::check_pull_requests;
+use crate::quick_open::{find_entry, status_key};
+use crate::reliability::{reliability_report, MTTR_MENU_WINDOW_DAYS};
+use crate::storage;
+use crate::webhooks::{self, NotificationRoutes, Transition, WebhookEvent};
+use crate::tray::{
+    flash_tray_icon, hidden_unknown_count, last_menu_build_error, reset_tray_menu, set_connection_status,
+    set_mttr, set_session_counts, sync_workspace_trays, update_status_menus, update_tray_icon,
+    update_tray_menu, update_tray_tooltip, MenuContext, TrayManager, TrayStatus, TRAY_ID,
+};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Listener, Manager};
 use tauri_plugin_notification::NotificationExt;
 use tokio::sync::Mutex;
-use tokio::time::{interval, Duration};
+use tokio::sync::{mpsc, watch};
+use tokio::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
 /// Start the background polling loop
-pub async fn start_polling(app_handle: AppHandle) {
-    log::info!("Starting background polling loop");
+///
+/// `interval_rx` carries the base polling interval; a change reschedules the next check
+/// relative to the previous one rather than restarting the wait. Cancelling `shutdown`
+/// stops the loop, abandoning a check in flight.
+pub async fn start_polling(
+    app_handle: AppHandle,
+    interval_rx: watch::Receiver<u64>,
+    shutdown: CancellationToken,
+) {
+    tokio::select! {
+        _ = poll_loop(&app_handle, interval_rx) => {}
+        _ = shutdown.cancelled() => tracing::info!("Polling loop stopped"),
+    }
+}
+
+async fn poll_loop(app_handle: &AppHandle, mut interval_rx: watch::Receiver<u64>) {
+    tracing::info!("Starting background polling loop");
 
     // Initial delay to let the app initialize
     tokio::time::sleep(Duration::from_secs(2)).await;
 
-    // Do an initial check immediately
-    check_pipelines_once(&app_handle).await;
+    // Do an initial check immediately (unless paused)
+    let mut policy = update_polling_policy(app_handle).await;
+    if policy.mode == PollingMode::PausedOnMetered {
+        update_tray_tooltip(app_handle, TRAY_ID, policy.mode.tooltip_title());
+    } else {
+        check_pipelines_once(app_handle).await;
+    }
 
-    // Then poll at regular intervals
-    let mut check_interval = interval(Duration::from_secs(10));
+    let mut interval_seconds = *interval_rx.borrow_and_update();
+    let mut last_check = Instant::now();
+    record_check_started(app_handle, last_check).await;
 
+    // Then poll at the effective interval, re-evaluated every cycle
     loop {
-        check_interval.tick().await;
+        let next_check = last_check + Duration::from_secs(policy.effective_interval_seconds);
 
-        // Get current polling interval from state
-        let interval_secs = {
-            let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
-            let state_guard = state.lock().await;
-            state_guard.polling_interval_seconds
-        };
+        tokio::select! {
+            _ = tokio::time::sleep_until(next_check) => {}
+            Ok(()) = interval_rx.changed() => {
+                let seconds = *interval_rx.borrow_and_update();
+                if seconds != interval_seconds {
+                    // Keep the time already waited; an overdue check runs straight away
+                    interval_seconds = seconds;
+                    policy = update_polling_policy(app_handle).await;
+                    tracing::info!(
+                        "Polling rescheduled: every {}s",
+                        policy.effective_interval_seconds
+                    );
+                }
+                continue;
+            }
+        }
+
+        last_check = Instant::now();
+        record_check_started(app_handle, last_check).await;
+        policy = update_polling_policy(app_handle).await;
+        if policy.mode == PollingMode::PausedOnMetered {
+            update_tray_tooltip(app_handle, TRAY_ID, policy.mode.tooltip_title());
+            continue;
+        }
+
+        check_pipelines_once(app_handle).await;
+    }
+}
 
-        // Adjust interval if needed
-        check_interval = interval(Duration::from_secs(interval_secs));
+/// Remember when the loop's current cycle started, for the next-check countdown
+async fn record_check_started(app_handle: &AppHandle, at: Instant) {
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+    state.lock().await.last_check_at = Some(at.into_std());
+}
 
-        check_pipelines_once(&app_handle).await;
+/// Recompute the polling policy from settings and power/network conditions
+async fn update_polling_policy(app_handle: &AppHandle) -> PollingPolicy {
+    let environment = power::probe_environment().await;
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+    let mut state_guard = state.lock().await;
+    let policy = power::current_policy(&state_guard, environment);
+    if state_guard.polling_mode != policy.mode {
+        tracing::info!("Polling mode changed to {:?}", policy.mode);
+        state_guard.polling_mode = policy.mode;
+        let _ = app_handle.emit("polling-mode-changed", &policy);
     }
+    policy
 }
 
+// Held for the whole of a check, so the polling loop, manual refreshes and single-pipeline
+// refreshes take turns instead of overwriting each other's status
+static CHECK_IN_FLIGHT: Mutex<()> = Mutex::const_new(());
+
 /// Perform a single check of all monitored pipelines
-async fn check_pipelines_once(app_handle: &AppHandle) {
+#[tracing::instrument(name = "poll", skip_all, fields(pipelines = tracing::field::Empty))]
+pub async fn check_pipelines_once(app_handle: &AppHandle) {
+    let _in_flight = CHECK_IN_FLIGHT.lock().await;
+    if follow_status(app_handle).await {
+        return;
+    }
+    let started = Instant::now();
     let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
 
     // Get current configuration
-    let (credentials, monitored) = {
-        let state_guard = state.lock().await;
+    let (
+        credentials,
+        monitored,
+        tls,
+        paused_is_unhealthy,
+        prs_are_unhealthy,
+        mut run_caches,
+        mut pr_cache,
+        mut drift_cache,
+        mut disabled_cache,
+    ) = {
+        let mut state_guard = state.lock().await;
+
+        let monitored = state_guard.all_monitored_pipelines();
+        tracing::Span::current().record("pipelines", monitored.len());
 
         // Skip if no credentials or no pipelines
-        if state_guard.credentials.is_none() || state_guard.monitored_pipelines.is_empty() {
-            update_tray_icon(app_handle, TrayStatus::Gray);
+        if state_guard.credentials.is_none() || monitored.is_empty() {
+            // Drop any lingering status so the next real check rebuilds the menu
+            state_guard.last_status = None;
+            sync_workspace_trays(app_handle, &state_guard.trays, &[]);
+            update_tray_icon(app_handle, TRAY_ID, TrayStatus::Gray);
             if state_guard.credentials.is_none() {
-                update_tray_tooltip(app_handle, "cdMenu - Not configured");
+                update_tray_tooltip(app_handle, TRAY_ID, t("tooltip.not_configured"));
+                update_tray_menu(app_handle, TRAY_ID, MenuContext::NoCredentials);
             } else {
-                update_tray_tooltip(app_handle, "cdMenu - No pipelines selected");
+                update_tray_tooltip(app_handle, TRAY_ID, t("tooltip.no_pipelines"));
+                update_tray_menu(app_handle, TRAY_ID, MenuContext::NoPipelines);
             }
             return;
         }
 
         (
             state_guard.credentials.clone().unwrap(),
-            state_guard.monitored_pipelines.clone(),
+            monitored,
+            state_guard.tls_config(),
+            state_guard.treat_paused_as_unhealthy,
+            state_guard.include_prs_in_health,
+            std::mem::take(&mut state_guard.run_caches),
+            std::mem::take(&mut state_guard.pull_request_cache),
+            std::mem::take(&mut state_guard.drift_cache),
+            std::mem::take(&mut state_guard.disabled_cache),
         )
     };
 
@@ -67,122 +190,484 @@ async fn check_pipelines_once(app_handle: &AppHandle) {
     let app_password = match get_app_password(app_handle) {
         Some(pw) => pw,
         None => {
-            log::warn!("No app password found");
-            update_tray_icon(app_handle, TrayStatus::Gray);
-            update_tray_tooltip(app_handle, "cdMenu - Auth required");
+            tracing::warn!("No app password found");
+            sync_workspace_trays(app_handle, &state.lock().await.trays, &[]);
+            update_tray_icon(app_handle, TRAY_ID, TrayStatus::Gray);
+            update_tray_tooltip(app_handle, TRAY_ID, t("tooltip.auth_required"));
+            update_tray_menu(app_handle, TRAY_ID, MenuContext::NoCredentials);
             return;
         }
     };
 
-    // Check all pipelines
-    log::info!("Checking {} pipelines...", monitored.len());
-    let status = check_all_pipelines(&credentials.username, &app_password, &monitored).await;
-
-    // Update tray based on status
-    if status.is_healthy {
-        update_tray_icon(app_handle, TrayStatus::Green);
-
-        let mut tooltip = format!(
-            "cdMenu\n{} pipeline(s) healthy",
-            status.total_monitored
+    // With the quota used up every request would fail, so wait for it to reset
+    let rate_limit = state.lock().await.rate_limit;
+    if let Some(seconds) = rate_limit.and_then(|r| r.exhausted_for(budget::now_unix())) {
+        tracing::warn!(
+            "Bitbucket rate limit exhausted, skipping check until it resets in {}s",
+            seconds
         );
-        if status.in_progress_count > 0 {
-            tooltip.push_str(&format!("\n{} in progress", status.in_progress_count));
+        record_poll_error(
+            app_handle,
+            &format!("check skipped: rate limit used up, resets in {}s", seconds),
+        )
+        .await;
+        return;
+    }
+
+    // After the password was rejected, only try one cheap request per cycle until it works again
+    let auth_broken = state.lock().await.auth_broken;
+    if auth_broken {
+        if !credentials_accepted(&credentials, &app_password, &tls, &monitored).await {
+            show_credentials_rejected(app_handle, &state.lock().await.trays);
+            return;
         }
-        tooltip.push_str(&format!("\nLast checked: {}", status.last_checked));
+        tracing::info!("Saved credentials are accepted again, resuming polling");
+        set_auth_broken(app_handle, false).await;
+    }
 
-        update_tray_tooltip(app_handle, &tooltip);
-    } else {
-        update_tray_icon(app_handle, TrayStatus::Red);
+    // Check all pipelines
+    tracing::info!("Checking {} pipelines...", monitored.len());
+    set_checking(app_handle, true).await;
+    budget::record_check();
+    let mut monitored = monitored;
+    if monitored.iter().any(|p| p.follows_default_branch() && p.resolved_branch.is_none()) {
+        if let Ok(client) = BitbucketClient::new(&credentials.username, &app_password, &tls) {
+            let mut lookups = std::mem::take(&mut state.lock().await.default_branch_lookups);
+            let now = std::time::Instant::now();
+            let resolved =
+                resolve_default_branches(&client, &mut monitored, true, &mut lookups, now).await;
+            let changed = {
+                let mut state_guard = state.lock().await;
+                state_guard.default_branch_lookups = lookups;
+                state_guard.cache_resolved_branches(&resolved)
+            };
+            if changed {
+                if let Err(e) = save_config_helper(app_handle, &state).await {
+                    tracing::error!("Failed to save default branches: {}", e);
+                }
+            }
+        }
+    }
+    let status = check_all_pipelines(
+        &credentials.username,
+        &app_password,
+        &monitored,
+        &tls,
+        paused_is_unhealthy,
+        &mut run_caches,
+    )
+    .await;
 
-        let failed_names: Vec<String> = status
-            .failed_pipelines
+    // Keep only entries for runs that are still the latest
+    run_caches.retain(|uuid| {
+        status
+            .pipeline_statuses()
             .iter()
-            .take(3) // Limit to 3 for tooltip
-            .map(|p| format!("{}/{}", p.workspace, p.repo_slug))
-            .collect();
+            .any(|p| p.pipeline_uuid.as_deref() == Some(uuid))
+    });
+    let mut status = status;
+    let pull_requests = match BitbucketClient::new(&credentials.username, &app_password, &tls) {
+        Ok(client) => {
+            let now = std::time::Instant::now();
+            check_disabled(&client, status.pipeline_statuses(), &mut disabled_cache, now).await;
+            check_drift(&client, &monitored, &mut drift_cache, now).await;
+            // Access tokens have no user to own pull requests
+            if credentials.kind() == CredentialKind::AccessToken {
+                Vec::new()
+            } else {
+                check_pull_requests(&client, &monitored, &mut pr_cache, now).await
+            }
+        }
+        Err(_) => Vec::new(),
+    };
+    drift_cache.apply(status.pipeline_statuses_mut());
+    disabled_cache.apply(status.pipeline_statuses_mut());
+    for pipeline in disabled_cache.take_unannounced(status.pipeline_statuses()) {
+        if !pipeline.muted {
+            let name = if pipeline.repo_name.is_empty() {
+                &pipeline.repo_slug
+            } else {
+                &pipeline.repo_name
+            };
+            app_handle.notify(
+                t("notify.pipelines_disabled.title"),
+                &tf("notify.pipelines_disabled.body", &[("name", name)]),
+            );
+        }
+    }
+    let status = status.with_pull_requests(pull_requests, prs_are_unhealthy);
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.run_caches = run_caches;
+        state_guard.pull_request_cache = pr_cache;
+        state_guard.drift_cache = drift_cache;
+        state_guard.disabled_cache = disabled_cache;
+        let connection = state_guard.connection_status.after_check(&status);
+        if connection != state_guard.connection_status {
+            tracing::info!("Connection status: {:?}", connection);
+        }
+        state_guard.connection_status = connection;
+        set_connection_status(connection);
+        state_guard.polls_completed += 1;
+        state_guard.last_poll_duration = Some(started.elapsed());
+        state_guard.rate_limit = budget::latest_rate_limit();
+        if let Some(rate_limit) = state_guard.rate_limit.filter(|r| r.is_low()) {
+            tracing::warn!(
+                "Only {} of {} Bitbucket API requests left, slowing down polling",
+                rate_limit.remaining,
+                rate_limit.limit
+            );
+        }
+    }
 
-        let mut tooltip = format!(
-            "cdMenu\n{} pipeline(s) FAILED",
-            status.failed_pipelines.len()
+    if status.all_auth_failed() {
+        tracing::warn!("Bitbucket rejected the saved credentials, pausing polling");
+        record_poll_error(app_handle, "poll error: credentials rejected, polling paused").await;
+        set_auth_broken(app_handle, true).await;
+        app_handle.notify(
+            t("notify.credentials_rejected.title"),
+            t("notify.credentials_rejected.body"),
         );
-        tooltip.push_str(&format!("\n{}", failed_names.join(", ")));
-        if status.failed_pipelines.len() > 3 {
-            tooltip.push_str(&format!(" +{} more", status.failed_pipelines.len() - 3));
+        show_credentials_rejected(app_handle, &state.lock().await.trays);
+        set_checking(app_handle, false).await;
+        return;
+    }
+
+    let repo_urls_changed = {
+        let mut state_guard = state.lock().await;
+        state_guard.cache_repo_urls(status.pipeline_statuses())
+    };
+    if repo_urls_changed {
+        if let Err(e) = save_config_helper(app_handle, &state).await {
+            tracing::error!("Failed to save repository URLs: {}", e);
         }
-        tooltip.push_str(&format!("\nLast checked: {}", status.last_checked));
+    }
+
+    mark_pipeline_stale(app_handle, status.pipeline_statuses()).await;
+    apply_status(app_handle, status).await;
+    set_checking(app_handle, false).await;
+}
 
-        update_tray_tooltip(app_handle, &tooltip);
+/// Add an activity log entry about a whole check
+async fn record_poll_error(app_handle: &AppHandle, detail: &str) {
+    let event = ActivityEvent::new(Utc::now(), ActivityKind::PollError, None, detail);
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+    activity::record(app_handle, &mut *state.lock().await, vec![event]);
+}
+
+/// Whether Bitbucket accepts the credentials, tried with a single cheap request
+///
+/// Access tokens can't read `/user`, so they are tried against the first monitored
+/// repository's pipelines instead.
+async fn credentials_accepted(
+    credentials: &Credentials,
+    app_password: &str,
+    tls: &TlsConfig,
+    monitored: &[MonitoredPipeline],
+) -> bool {
+    let Ok(client) = BitbucketClient::new(&credentials.username, app_password, tls) else {
+        return true;
+    };
+    match (credentials.kind(), monitored.first()) {
+        (CredentialKind::AccessToken, Some(pipeline)) => !is_rejection(
+            &client
+                .get_pipelines(&pipeline.workspace, &pipeline.repo_slug, None, 1)
+                .await,
+        ),
+        _ => !is_rejection(&client.get_current_user().await),
     }
+}
 
-    // Check for status changes and send notifications
+/// Whether a probe's error means the credentials were rejected
+///
+/// `/user` answers 403 to app passwords without the Account scope, and network or
+/// server errors say nothing about the credentials, so only a 401 counts.
+fn is_rejection<T>(result: &Result<T, BitbucketError>) -> bool {
+    matches!(result, Err(BitbucketError::AuthenticationFailed))
+}
+
+/// Point the tray at settings while the saved credentials are rejected
+fn show_credentials_rejected(app_handle: &AppHandle, trays: &TrayManager) {
+    sync_workspace_trays(app_handle, trays, &[]);
+    update_tray_icon(app_handle, TRAY_ID, TrayStatus::Gray);
+    update_tray_tooltip(app_handle, TRAY_ID, t("tooltip.credentials_rejected"));
+    update_tray_menu(app_handle, TRAY_ID, MenuContext::NoCredentials);
+}
+
+/// Set or clear the rejected-credentials flag, dropping the last status while it's set
+async fn set_auth_broken(app_handle: &AppHandle, broken: bool) {
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
     {
-        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let mut state_guard = state.lock().await;
+        state_guard.auth_broken = broken;
+        if broken {
+            state_guard.last_status = None;
+        }
+    }
+    if let Err(e) = save_config_helper(app_handle, &state).await {
+        tracing::error!("Failed to save credentials state: {}", e);
+    }
+}
+
+/// Record whether a check is in flight (reported by `get_pipeline_statuses`)
+async fn set_checking(app_handle: &AppHandle, checking: bool) {
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+    state.lock().await.is_checking = checking;
+}
+
+/// Re-check one monitored pipeline and merge the result into the last known status
+pub async fn check_single_pipeline(app_handle: &AppHandle, workspace: &str, repo_slug: &str) {
+    let in_flight = CHECK_IN_FLIGHT.lock().await;
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+
+    let (
+        credentials,
+        monitored,
+        tls,
+        paused_is_unhealthy,
+        prs_are_unhealthy,
+        last_status,
+        mut run_caches,
+        auth_broken,
+    ) = {
         let state_guard = state.lock().await;
-        if let Some(old_status) = &state_guard.last_status {
-            // Check each pipeline for status changes
-            for new_pipeline in &status.pipeline_statuses {
-                // Find matching old pipeline
-                let old_pipeline = old_status.pipeline_statuses.iter().find(|p| {
-                    p.workspace == new_pipeline.workspace && p.repo_slug == new_pipeline.repo_slug
-                });
+        let monitored: Vec<MonitoredPipeline> = state_guard
+            .all_monitored_pipelines()
+            .into_iter()
+            .filter(|p| p.workspace == workspace && p.repo_slug == repo_slug)
+            .collect();
+        (
+            state_guard.credentials.clone(),
+            monitored,
+            state_guard.tls_config(),
+            state_guard.treat_paused_as_unhealthy,
+            state_guard.include_prs_in_health,
+            state_guard.last_status.clone(),
+            state_guard.run_caches.clone(),
+            state_guard.auth_broken,
+        )
+    };
+
+    // Without a previous full check there is nothing to merge into, and while the
+    // credentials are rejected the full check only probes whether they work again
+    let (Some(credentials), Some(last_status), false) = (credentials, last_status, auth_broken) else {
+        drop(in_flight);
+        check_pipelines_once(app_handle).await;
+        return;
+    };
+    if monitored.is_empty() {
+        tracing::warn!("{}/{} is not monitored", workspace, repo_slug);
+        return;
+    }
+    let Some(app_password) = get_app_password(app_handle) else {
+        tracing::warn!("No app password found");
+        return;
+    };
 
-                if let Some(old) = old_pipeline {
-                    let was_failed = matches!(old.state, PipelineState::Failed);
-                    let is_failed = matches!(new_pipeline.state, PipelineState::Failed);
+    tracing::info!("Checking pipeline {}/{}...", workspace, repo_slug);
+    set_checking(app_handle, true).await;
+    let mut single = check_all_pipelines(
+        &credentials.username,
+        &app_password,
+        &monitored,
+        &tls,
+        paused_is_unhealthy,
+        &mut run_caches,
+    )
+    .await;
+    {
+        let mut state_guard = state.lock().await;
+        state_guard.run_caches.extend(run_caches);
+        // Drift and disabled Pipelines keep their last value until the next full check
+        state_guard.drift_cache.apply(single.pipeline_statuses_mut());
+        state_guard.disabled_cache.apply(single.pipeline_statuses_mut());
+    }
+    mark_pipeline_stale(app_handle, single.pipeline_statuses()).await;
 
-                    let name = if new_pipeline.repo_name.is_empty() {
-                        &new_pipeline.repo_slug
-                    } else {
-                        &new_pipeline.repo_name
-                    };
+    let last_checked = single.last_checked.clone();
+    let pull_requests = last_status.pull_requests.clone();
+    let mut pipeline_statuses = last_status.into_pipeline_statuses();
+    for updated in single.into_pipeline_statuses() {
+        match pipeline_statuses
+            .iter_mut()
+            .find(|p| p.workspace == updated.workspace && p.repo_slug == updated.repo_slug)
+        {
+            Some(existing) => *existing = updated,
+            None => pipeline_statuses.push(updated),
+        }
+    }
 
-                    // Notify on new failure
-                    if !was_failed && is_failed {
-                        let body = if let Some(url) = &new_pipeline.pipeline_url {
-                            format!("{} has failed\n{}", name, url)
-                        } else {
-                            format!("{} has failed", name)
-                        };
-                        let _ = app_handle
-                            .notification()
-                            .builder()
-                            .title("Pipeline Failed")
-                            .body(&body)
-                            .show();
-                    }
+    // Pull requests keep their last status until the next full check
+    let status = OverallStatus::new(pipeline_statuses, last_checked, paused_is_unhealthy)
+        .with_pull_requests(pull_requests, prs_are_unhealthy);
+    apply_status(app_handle, status).await;
+    set_checking(app_handle, false).await;
+}
 
-                    // Notify when fixed
-                    if was_failed && !is_failed && matches!(new_pipeline.state, PipelineState::Healthy) {
-                        let body = if let Some(url) = &new_pipeline.pipeline_url {
-                            format!("{} is now healthy\n{}", name, url)
-                        } else {
-                            format!("{} is now healthy", name)
-                        };
-                        let _ = app_handle
-                            .notification()
-                            .builder()
-                            .title("Pipeline Fixed")
-                            .body(&body)
-                            .show();
-                    }
-                }
-            }
+/// Payload of the `pipeline-stale` event
+#[derive(Debug, Clone, Serialize)]
+pub struct StalePipeline {
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+}
+
+/// Count consecutive NotFound results and flag repos that appear to have been deleted
+async fn mark_pipeline_stale(app_handle: &AppHandle, statuses: &[PipelineStatusInfo]) {
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+    let mut state_guard = state.lock().await;
+
+    for status in statuses {
+        let key = (status.workspace.clone(), status.repo_slug.clone());
+        let not_found = status
+            .error_kind
+            .as_deref()
+            .is_some_and(|kind| kind.starts_with("NotFound"));
+
+        if !not_found {
+            state_guard.consecutive_not_found.remove(&key);
+            continue;
+        }
+
+        let count = state_guard.consecutive_not_found.entry(key).or_insert(0);
+        *count = count.saturating_add(1);
+
+        // Emit once, when the threshold is first reached
+        if *count == STALE_NOT_FOUND_THRESHOLD {
+            tracing::warn!(
+                "{}/{} not found {} times in a row, marking stale",
+                status.workspace,
+                status.repo_slug,
+                STALE_NOT_FOUND_THRESHOLD
+            );
+            let _ = app_handle.emit(
+                "pipeline-stale",
+                StalePipeline {
+                    workspace: status.workspace.clone(),
+                    repo_slug: status.repo_slug.clone(),
+                    repo_name: status.repo_name.clone(),
+                },
+            );
         }
     }
+}
 
-    // Check if status changed before updating menu
-    let status_changed = {
+/// Update tray, notifications and state from a fresh status, then notify the frontend
+async fn apply_status(app_handle: &AppHandle, mut status: OverallStatus) {
+    let (title, trays, multi_tray, attention_animation) = {
         let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
         let state_guard = state.lock().await;
-        match &state_guard.last_status {
-            Some(old) => old.is_healthy != status.is_healthy
-                || old.pipeline_statuses.len() != status.pipeline_statuses.len()
-                || old.pipeline_statuses.iter().zip(status.pipeline_statuses.iter())
-                    .any(|(a, b)| std::mem::discriminant(&a.state) != std::mem::discriminant(&b.state)),
-            None => true,
+        (
+            state_guard.status_title(),
+            state_guard.trays.clone(),
+            state_guard.multi_tray,
+            state_guard.attention_animation,
+        )
+    };
+
+    // Remember newly finished runs for the history glyphs
+    let history_changed = {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let mut state_guard = state.lock().await;
+        state_guard.record_run_history(status.pipeline_statuses_mut())
+    };
+    if history_changed {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        if let Err(e) = save_config_helper(app_handle, &state).await {
+            tracing::error!("Failed to save run history: {}", e);
         }
+    }
+
+    // Check for status changes and send notifications
+    let state_changes;
+    let transitions = {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let mut state_guard = state.lock().await;
+        let state_guard = &mut *state_guard;
+        let notifier = RoutedNotifier {
+            app_handle,
+            routes: &state_guard.notification_routes,
+            disabled: &state_guard.disabled_notification_categories,
+            tls: state_guard.tls_config(),
+        };
+        // Compared against the outages open before this check resolves any of them
+        let changes = activity::status_changes(
+            state_guard.last_status.as_ref(),
+            &status,
+            &state_guard.incidents,
+            Utc::now(),
+        );
+        let transitions = notify_transitions(
+            &notifier,
+            state_guard.last_status.as_ref(),
+            &mut status,
+            &mut state_guard.incidents,
+            Utc::now(),
+        );
+        state_changes = pipeline_state_changes(state_guard.last_status.as_ref(), &status);
+        activity::record(app_handle, state_guard, changes);
+        // Counted before the tray is redrawn so the tooltip's session line is current
+        state_guard.session_failure_count += transitions.failures;
+        state_guard.session_recovery_count += transitions.recoveries;
+        set_session_counts(state_guard.session_failure_count, state_guard.session_recovery_count);
+        set_mttr(&reliability_report(
+            state_guard.incidents.events(),
+            &state_guard.all_monitored_pipelines(),
+            Utc::now(),
+            MTTR_MENU_WINDOW_DAYS,
+            false,
+        ));
+        transitions
     };
+    // Failures and recoveries feed the weekly digest, so they outlive a restart
+    if transitions.incidents_changed {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        if let Err(e) = save_config_helper(app_handle, &state).await {
+            tracing::error!("Failed to save incident events: {}", e);
+        }
+    }
+
+    // Update tray based on status
+    render_tray_status(app_handle, &title, &status, &trays, multi_tray);
+    if attention_animation == AttentionAnimation::Flash {
+        flash_failed_trays(app_handle, &status, &trays, &transitions.announced_workspaces);
+    }
+
+    // Notify once per build when a run reaches a manual approval gate
+    {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let mut state_guard = state.lock().await;
+        // The first check after launch only records what's already paused
+        let should_notify = state_guard.notifications_enabled(NotificationCategory::Paused)
+            && state_guard.last_status.is_some();
+
+        for pipeline in status.pipeline_statuses() {
+            if !matches!(pipeline.state, PipelineState::Paused) {
+                continue;
+            }
+            let Some(build_number) = pipeline.build_number else {
+                continue;
+            };
+            let key = (pipeline.workspace.clone(), pipeline.repo_slug.clone());
+            let newly_paused = state_guard.last_paused_build.insert(key, build_number)
+                != Some(build_number);
+
+            if newly_paused && should_notify && !pipeline.muted && !pipeline.off_hours {
+                let name = if pipeline.repo_name.is_empty() {
+                    &pipeline.repo_slug
+                } else {
+                    &pipeline.repo_name
+                };
+                let step = pipeline.stage_name.as_deref().unwrap_or(t("notify.awaiting.manual_step"));
+                let summary = tf(
+                    "notify.awaiting.body",
+                    &[("name", name), ("build", &build_number), ("step", &step)],
+                );
+                let body = body_with_link(summary, pipeline.pipeline_url.as_deref());
+                app_handle.notify(t("notify.awaiting.title"), &body);
+            }
+        }
+    }
 
     // Store status in state
     {
@@ -191,158 +676,1532 @@ async fn check_pipelines_once(app_handle: &AppHandle) {
         state_guard.last_status = Some(status.clone());
     }
 
-    // Only update tray menu if status changed (avoids menu closing)
-    if status_changed {
-        update_tray_menu(app_handle, Some(&status));
+    // Edits the shown menu in place unless pipelines were added, removed or reordered
+    update_status_menus(app_handle, &trays, Some(&status));
+    {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        state.lock().await.last_menu_build_error = last_menu_build_error();
     }
 
+    publish_status(app_handle, &status).await;
+
     // Emit event to frontend
-    let _ = app_handle.emit("status-updated", &status);
+    let _ = app_handle.emit("status-updated", StatusPayload::new(&status));
+    for change in &state_changes {
+        let _ = app_handle.emit("pipeline-state-changed", change);
+    }
 }
 
-/// Check all monitored pipelines and return aggregated status
-async fn check_all_pipelines(
-    username: &str,
-    app_password: &str,
-    monitored: &[MonitoredPipeline],
-) -> OverallStatus {
-    let client = BitbucketClient::new(username, app_password);
-    let mut pipeline_statuses = Vec::new();
+/// Payload of the `pipeline-state-changed` event, sent for each pipeline whose state
+/// changed so listeners don't have to diff the whole `status-updated` payload
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStateChangedEvent {
+    pub pipeline: PipelineStatusInfo,
+    pub previous_state: PipelineState,
+}
 
-    for pipeline_config in monitored {
-        match client
-            .get_latest_pipeline(
-                &pipeline_config.workspace,
-                &pipeline_config.repo_slug,
-                pipeline_config.branch.as_deref(),
-            )
-            .await
-        {
-            Ok(Some(pipeline)) => {
-                let (state, failure_reason, stage_name) = if pipeline.is_failed() {
-                    (
-                        PipelineState::Failed,
-                        pipeline.state.result.as_ref().map(|r| r.name.clone()),
-                        None,
-                    )
-                } else if pipeline.is_paused() {
-                    // Pipeline is waiting for manual trigger/approval
-                    // Fetch steps to get the name of the pending step
-                    let pending_step_name = match client
-                        .get_pipeline_steps(
-                            &pipeline_config.workspace,
-                            &pipeline_config.repo_slug,
-                            &pipeline.uuid,
-                        )
-                        .await
-                    {
-                        Ok(steps) => {
-                            // Find the first pending step
-                            steps
-                                .iter()
-                                .find(|s| s.is_pending())
-                                .and_then(|s| s.name.clone())
-                                .unwrap_or_else(|| "paused".to_string())
-                        }
-                        Err(_) => "paused".to_string(),
-                    };
-                    (PipelineState::Paused, None, Some(pending_step_name))
-                } else if pipeline.is_in_progress() {
-                    (PipelineState::InProgress, None, None)
-                } else {
-                    (PipelineState::Healthy, None, None)
-                };
+/// Pipelines whose state differs from the previous check; none on the first check
+fn pipeline_state_changes(
+    previous: Option<&OverallStatus>,
+    current: &OverallStatus,
+) -> Vec<PipelineStateChangedEvent> {
+    let Some(previous) = previous else {
+        return Vec::new();
+    };
+    current
+        .pipeline_statuses()
+        .iter()
+        .filter_map(|pipeline| {
+            let before = find_entry(previous, &status_key(pipeline))?;
+            (before.state != pipeline.state).then(|| PipelineStateChangedEvent {
+                pipeline: pipeline.clone(),
+                previous_state: before.state.clone(),
+            })
+        })
+        .collect()
+}
 
-                let pipeline_url = Some(format!(
-                    "https://bitbucket.org/{}/{}/pipelines/results/{}",
-                    pipeline_config.workspace,
-                    pipeline_config.repo_slug,
-                    pipeline.build_number
-                ));
+/// Render the status a primary instance publishes instead of polling
+///
+/// Returns false when follower mode is off or the feed is missing or stale, in which
+/// case this instance checks Bitbucket itself until the feed is fresh again.
+async fn follow_status(app_handle: &AppHandle) -> bool {
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+    let (path, was_following) = {
+        let mut state_guard = state.lock().await;
+        let was_following = state_guard.following;
+        if state_guard.follow_status_path.is_none() {
+            state_guard.following = false;
+        }
+        (state_guard.follow_status_path.clone(), was_following)
+    };
+    let Some(path) = path else {
+        return false;
+    };
 
-                pipeline_statuses.push(PipelineStatusInfo {
-                    workspace: pipeline_config.workspace.clone(),
-                    project_key: pipeline_config.project_key.clone(),
-                    project_name: pipeline_config.project_name.clone(),
-                    repo_slug: pipeline_config.repo_slug.clone(),
-                    repo_name: pipeline_config.repo_name.clone(),
-                    state,
-                    failure_reason,
-                    pipeline_url,
-                    stage_name,
-                });
-            }
-            Ok(None) => {
-                // No pipelines found for this repo - treat as unknown
-                log::debug!(
-                    "No pipelines found for {}/{}",
-                    pipeline_config.workspace,
-                    pipeline_config.repo_slug
-                );
-                pipeline_statuses.push(PipelineStatusInfo {
-                    workspace: pipeline_config.workspace.clone(),
-                    project_key: pipeline_config.project_key.clone(),
-                    project_name: pipeline_config.project_name.clone(),
-                    repo_slug: pipeline_config.repo_slug.clone(),
-                    repo_name: pipeline_config.repo_name.clone(),
-                    state: PipelineState::Unknown,
-                    failure_reason: None,
-                    pipeline_url: Some(format!(
-                        "https://bitbucket.org/{}/{}/pipelines",
-                        pipeline_config.workspace,
-                        pipeline_config.repo_slug
-                    )),
-                    stage_name: None,
-                });
-            }
-            Err(e) => {
-                log::error!(
-                    "Failed to check pipeline {}/{}: {}",
-                    pipeline_config.workspace,
-                    pipeline_config.repo_slug,
-                    e
-                );
-                pipeline_statuses.push(PipelineStatusInfo {
-                    workspace: pipeline_config.workspace.clone(),
-                    project_key: pipeline_config.project_key.clone(),
-                    project_name: pipeline_config.project_name.clone(),
-                    repo_slug: pipeline_config.repo_slug.clone(),
-                    repo_name: pipeline_config.repo_name.clone(),
-                    state: PipelineState::Unknown,
-                    failure_reason: Some(format!("Error: {}", e)),
-                    pipeline_url: None,
-                    stage_name: None,
-                });
+    let feed = match follower::read_feed(&path) {
+        Ok(feed) if !feed.is_stale(Utc::now()) => Some(feed),
+        Ok(feed) => {
+            tracing::warn!("Status feed is stale (published {})", feed.published_at);
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read status feed {}: {}", path.display(), e);
+            None
+        }
+    };
+    state.lock().await.following = feed.is_some();
+
+    match feed {
+        Some(feed) => {
+            apply_status(app_handle, feed.status).await;
+            true
+        }
+        None => {
+            if was_following {
+                app_handle.notify(t("notify.feed_stale.title"), t("notify.feed_stale.body"));
             }
+            false
         }
     }
+}
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
-    OverallStatus::new(pipeline_statuses, timestamp)
+/// Share a status with follower instances when publishing is configured
+async fn publish_status(app_handle: &AppHandle, status: &OverallStatus) {
+    let (path, interval_seconds, following) = {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let state_guard = state.lock().await;
+        (
+            state_guard.publish_status_path.clone(),
+            state_guard.polling_interval_seconds,
+            state_guard.following,
+        )
+    };
+    // Only statuses checked here are published, so two instances can't feed each other
+    let Some(path) = path.filter(|_| !following) else {
+        return;
+    };
+    let feed = PublishedStatus {
+        published_at: Utc::now(),
+        interval_seconds,
+        status: status.clone(),
+        paused_is_unhealthy: status.paused_is_unhealthy,
+        prs_are_unhealthy: status.prs_are_unhealthy,
+    };
+    if let Err(e) = follower::publish(&path, &feed) {
+        tracing::warn!("Failed to publish status to {}: {}", path.display(), e);
+    }
 }
 
-/// Get the app password from config file
-fn get_app_password(app_handle: &AppHandle) -> Option<String> {
-    let config_dir = app_handle.path().app_config_dir().ok()?;
-    let creds_path = config_dir.join(".credentials");
+/// Set the tray icon and tooltip for a status; with `multi_tray`, each workspace's tray
+/// shows only that workspace's pipelines
+fn render_tray_status(
+    app_handle: &AppHandle,
+    title: &str,
+    status: &OverallStatus,
+    trays: &TrayManager,
+    multi_tray: bool,
+) {
+    let workspaces = if multi_tray { status.workspaces() } else { Vec::new() };
+    sync_workspace_trays(app_handle, trays, &workspaces);
 
-    if !creds_path.exists() {
-        return None;
+    update_tray_icon(app_handle, TRAY_ID, tray_status(status));
+    update_tray_tooltip(app_handle, TRAY_ID, &status_tooltip(title, status));
+    for (workspace, id) in trays.workspace_trays() {
+        let scoped = status.for_workspace(&workspace);
+        update_tray_icon(app_handle, &id, tray_status(&scoped));
+        let title = format!("{} · {}", title, workspace);
+        update_tray_tooltip(app_handle, &id, &status_tooltip(&title, &scoped));
     }
+}
 
-    let encoded = std::fs::read_to_string(&creds_path).ok()?;
-    let decoded = STANDARD.decode(encoded.trim()).ok()?;
-    String::from_utf8(decoded).ok()
+/// Flash the tray that shows each announced failure: the main tray, or with
+/// per-workspace trays, those of the failing workspaces
+fn flash_failed_trays(
+    app_handle: &AppHandle,
+    status: &OverallStatus,
+    trays: &TrayManager,
+    announced_workspaces: &BTreeSet<String>,
+) {
+    if announced_workspaces.is_empty() {
+        return;
+    }
+    let workspace_trays = trays.workspace_trays();
+    if workspace_trays.is_empty() {
+        if tray_status(status) == TrayStatus::Red {
+            flash_tray_icon(app_handle, TRAY_ID);
+        }
+        return;
+    }
+    for (workspace, id) in workspace_trays {
+        if announced_workspaces.contains(&workspace)
+            && tray_status(&status.for_workspace(&workspace)) == TrayStatus::Red
+        {
+            flash_tray_icon(app_handle, &id);
+        }
+    }
 }
 
-/// Listen for manual refresh triggers
-pub fn setup_refresh_listener(app_handle: AppHandle) {
-    let handle = app_handle.clone();
-    app_handle.listen("trigger-refresh", move |_| {
-        let handle = handle.clone();
-        tauri::async_runtime::spawn(async move {
-            log::info!("Manual refresh triggered");
-            check_pipelines_once(&handle).await;
-        });
-    });
+/// Icon color for a status
+fn tray_status(status: &OverallStatus) -> TrayStatus {
+    if all_errored(status) {
+        TrayStatus::Gray
+    } else if status.is_healthy {
+        TrayStatus::Green
+    } else {
+        TrayStatus::Red
+    }
+}
+
+/// Every check errored - surface the error kind instead of reporting healthy
+fn all_errored(status: &OverallStatus) -> bool {
+    !status.pipeline_statuses().is_empty()
+        && status
+            .pipeline_statuses()
+            .iter()
+            .all(|p| matches!(p.state, PipelineState::Unknown) && p.error_kind.is_some())
+}
+
+/// Tooltip describing a status under the polling mode's title
+fn status_tooltip(title: &str, status: &OverallStatus) -> String {
+    let last_checked = format!(
+        "\n{}",
+        tf("menu.last_checked", &[("time", &status.last_checked)])
+    );
+
+    if all_errored(status) {
+        let first = &status.pipeline_statuses()[0];
+        let error_kind = match (&first.unknown_reason, first.error_kind.as_deref()) {
+            (Some(UnknownReason::AuthFailed), _) => t("unknown.auth_failed"),
+            (Some(UnknownReason::RateLimited), _) => t("unknown.rate_limited"),
+            (_, Some(kind)) if kind.starts_with("Forbidden") => t("tooltip.missing_scope"),
+            (_, Some(kind)) => kind.split('(').next().unwrap_or(kind),
+            (_, None) => t("tooltip.unknown_error"),
+        };
+        return format!(
+            "{}\n{}{}",
+            title,
+            tf("tooltip.unable_to_check", &[("reason", &error_kind)]),
+            last_checked
+        );
+    }
+
+    let mut tooltip = title.to_string();
+    if status.is_healthy {
+        let hidden = hidden_unknown_count(status);
+        tooltip.push_str(&format!(
+            "\n{}",
+            tp("tooltip.healthy", status.total_monitored - hidden, &[])
+        ));
+        if hidden > 0 {
+            tooltip.push_str(&tf("tooltip.skipped_no_data", &[("count", &hidden)]));
+        }
+        if status.in_progress_count > 0 {
+            tooltip.push_str(&format!(
+                "\n{}",
+                tp("tooltip.in_progress", status.in_progress_count, &[])
+            ));
+        }
+    } else {
+        let failed_names: Vec<String> = status
+            .failed_pipelines
+            .iter()
+            .take(3) // Limit to 3 for tooltip
+            .map(|p| format!("{}/{}", p.workspace, p.repo_slug))
+            .collect();
+
+        if !status.failed_pipelines.is_empty() {
+            tooltip.push_str(&format!(
+                "\n{}\n{}",
+                tp("tooltip.failed", status.failed_pipelines.len(), &[]),
+                failed_names.join(", ")
+            ));
+            if status.failed_pipelines.len() > 3 {
+                tooltip.push_str(&tf(
+                    "tooltip.more",
+                    &[("count", &(status.failed_pipelines.len() - 3))],
+                ));
+            }
+        }
+        let failing_prs = status.unhealthy_pull_request_count();
+        if failing_prs > 0 {
+            tooltip.push_str(&format!(
+                "\n{}",
+                tp("summary.pull_requests_failing", failing_prs, &[])
+            ));
+        }
+        // Only unhealthy on their own when paused runs are treated as unhealthy
+        if status.paused_count > 0 {
+            tooltip.push_str(&format!(
+                "\n{}",
+                tp("summary.awaiting_approval", status.paused_count, &[])
+            ));
+        }
+    }
+    tooltip.push_str(&running_tooltip_lines(status));
+    tooltip.push_str(&last_checked);
+    tooltip
+}
+
+/// Redraw the tray from the last status, e.g. after the language changes
+pub async fn rerender_tray(app_handle: &AppHandle) {
+    let (title, last_status, trays, multi_tray) = {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let state_guard = state.lock().await;
+        (
+            state_guard.status_title(),
+            state_guard.last_status.clone(),
+            state_guard.trays.clone(),
+            state_guard.multi_tray,
+        )
+    };
+
+    reset_tray_menu();
+    match last_status {
+        Some(status) => {
+            render_tray_status(app_handle, &title, &status, &trays, multi_tray);
+            update_status_menus(app_handle, &trays, Some(&status));
+        }
+        // Setup menus and the initial tooltip are redrawn by a check
+        None => check_pipelines_once(app_handle).await,
+    }
+}
+
+/// Notification text with the page to open on its own line
+///
+/// The notification plugin only offers action buttons and click events on mobile, so on
+/// desktop the link has to be part of the body.
+fn body_with_link(summary: String, url: Option<&str>) -> String {
+    match url {
+        Some(url) => format!("{}\n{}", summary, url),
+        None => summary,
+    }
+}
+
+/// Destination for desktop notifications (replaced in tests)
+pub trait Notifier {
+    fn notify(&self, title: &str, body: &str);
+
+    /// Notify about a specific pipeline's failure or recovery
+    fn notify_pipeline(
+        &self,
+        _pipeline: &PipelineStatusInfo,
+        _transition: Transition,
+        title: &str,
+        body: &str,
+    ) {
+        self.notify(title, body);
+    }
+}
+
+impl Notifier for AppHandle {
+    fn notify(&self, title: &str, body: &str) {
+        let _ = self.notification().builder().title(title).body(body).show();
+    }
+}
+
+/// Desktop notifications that also forward pipeline events to the pipeline's webhook route
+struct RoutedNotifier<'a> {
+    app_handle: &'a AppHandle,
+    routes: &'a NotificationRoutes,
+    /// Categories shown only through webhooks, not on the desktop
+    disabled: &'a [NotificationCategory],
+    /// Webhooks are posted with the same TLS options as API requests
+    tls: TlsConfig,
+}
+
+impl Notifier for RoutedNotifier<'_> {
+    fn notify(&self, title: &str, body: &str) {
+        self.app_handle.notify(title, body);
+    }
+
+    fn notify_pipeline(
+        &self,
+        pipeline: &PipelineStatusInfo,
+        transition: Transition,
+        title: &str,
+        body: &str,
+    ) {
+        let category = match transition {
+            Transition::Failed => NotificationCategory::Failure,
+            Transition::Fixed => NotificationCategory::Recovery,
+        };
+        if !self.disabled.contains(&category) {
+            self.notify(title, body);
+        }
+
+        let Some(target) = self.routes.target_for(pipeline.notification_route.as_deref()) else {
+            return;
+        };
+        let target = target.clone();
+        let event = WebhookEvent::new(pipeline, transition, title, body);
+        let tls = self.tls.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = webhooks::send(&target, &event, &tls).await {
+                tracing::warn!("Failed to send to webhook '{}': {}", target.name, e);
+            }
+        });
+    }
+}
+
+/// Failures and recoveries found by `notify_transitions`
+#[derive(Debug, Default, PartialEq)]
+struct Transitions {
+    /// Pipelines that went from not failed to failed
+    failures: u64,
+    /// Pipelines that went from failed to healthy
+    recoveries: u64,
+    /// A failure or recovery was recorded in the incident log
+    incidents_changed: bool,
+    /// Workspaces with a failure announced, i.e. not muted or off-hours
+    announced_workspaces: BTreeSet<String>,
+}
+
+/// Send "Pipeline Failed" / "Pipeline Fixed" notifications for state changes since the last check
+///
+/// Also records outages in `incidents` so the recovery notification can say how long it lasted.
+fn notify_transitions(
+    notifier: &impl Notifier,
+    old_status: Option<&OverallStatus>,
+    status: &mut OverallStatus,
+    incidents: &mut IncidentTracker,
+    now: DateTime<Utc>,
+) -> Transitions {
+    // Nothing to compare against on the first check; failures already there count as announced
+    let Some(old_status) = old_status else {
+        for pipeline in status.pipeline_statuses_mut() {
+            pipeline.failure_announced =
+                matches!(pipeline.state, PipelineState::Failed) && !pipeline.off_hours;
+        }
+        return Transitions::default();
+    };
+    let mut transitions = Transitions::default();
+
+    for new_pipeline in status.pipeline_statuses_mut() {
+        // Find matching old pipeline
+        let Some(old) = old_status.pipeline(&new_pipeline.workspace, &new_pipeline.repo_slug) else {
+            continue;
+        };
+
+        // A run fixing a failure shows as running or paused first, so a pipeline is still
+        // failing until it is healthy again, whatever the last check showed
+        let was_failed = matches!(old.state, PipelineState::Failed)
+            || incidents.is_open(&new_pipeline.workspace, &new_pipeline.repo_slug);
+        // A failure seen only off-hours was never announced, so it notifies once active hours begin
+        let was_announced = was_failed && old.failure_announced;
+        let is_failed = matches!(new_pipeline.state, PipelineState::Failed);
+        // A fresh build failing again counts as a new failure
+        let new_run = old.build_number != new_pipeline.build_number;
+
+        let name = if new_pipeline.repo_name.is_empty() {
+            &new_pipeline.repo_slug
+        } else {
+            &new_pipeline.repo_name
+        };
+
+        if is_failed && !was_failed {
+            transitions.failures += 1;
+        }
+
+        // Notify on new failure
+        if is_failed && (!was_announced || new_run) {
+            incidents.start(new_pipeline, now);
+            transitions.incidents_changed = true;
+
+            if !new_pipeline.off_hours {
+                new_pipeline.failure_announced = true;
+                if !new_pipeline.muted {
+                    transitions.announced_workspaces.insert(new_pipeline.workspace.clone());
+                    let summary = tf("notify.failed.body", &[("name", name)]);
+                    let body = body_with_link(summary, new_pipeline.pipeline_url.as_deref());
+                    notifier.notify_pipeline(new_pipeline, Transition::Failed, t("notify.failed.title"), &body);
+                }
+            }
+        } else if is_failed {
+            new_pipeline.failure_announced = true;
+        } else if !matches!(new_pipeline.state, PipelineState::Healthy) {
+            // Carried through the fixing run, so its recovery is announced too
+            new_pipeline.failure_announced = was_announced;
+        }
+
+        // Notify when fixed
+        if was_failed && matches!(new_pipeline.state, PipelineState::Healthy) {
+            transitions.recoveries += 1;
+            let summary = match incidents.resolve(new_pipeline, now) {
+                Some(incident) => {
+                    transitions.incidents_changed = true;
+                    tf(
+                        "notify.fixed.after",
+                        &[("name", name), ("duration", &format_duration(incident.duration_secs))],
+                    )
+                }
+                None => tf("notify.fixed.body", &[("name", name)]),
+            };
+
+            if was_announced && !new_pipeline.muted && !new_pipeline.off_hours {
+                let body = body_with_link(summary, new_pipeline.pipeline_url.as_deref());
+                notifier.notify_pipeline(new_pipeline, Transition::Fixed, t("notify.fixed.title"), &body);
+            }
+        }
+    }
+    transitions
+}
+
+/// Tooltip lines for running pipelines, with what triggered them
+fn running_tooltip_lines(status: &OverallStatus) -> String {
+    status
+        .pipeline_statuses()
+        .iter()
+        .filter(|p| matches!(p.state, PipelineState::InProgress))
+        .take(3) // Limit to 3 for tooltip
+        .map(|p| {
+            let name = if p.repo_name.is_empty() {
+                &p.repo_slug
+            } else {
+                &p.repo_name
+            };
+            let line = match &p.trigger_type {
+                Some(trigger) => {
+                    tf("tooltip.running_triggered", &[("name", name), ("trigger", trigger)])
+                }
+                None => tf("tooltip.running", &[("name", name)]),
+            };
+            format!("\n{}", line)
+        })
+        .collect()
+}
+
+/// Show a transient notification summarizing the last known status
+pub async fn notify_health_summary(app_handle: &AppHandle) {
+    let body = {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let state_guard = state.lock().await;
+        match &state_guard.last_status {
+            Some(status) if status.is_healthy => {
+                tp("notify.summary.healthy", status.total_monitored, &[])
+            }
+            Some(status) => {
+                let names: Vec<&str> = status
+                    .failed_pipelines
+                    .iter()
+                    .map(|p| {
+                        if p.repo_name.is_empty() {
+                            p.repo_slug.as_str()
+                        } else {
+                            p.repo_name.as_str()
+                        }
+                    })
+                    .collect();
+                if names.is_empty() {
+                    tp("summary.awaiting_approval", status.paused_count, &[])
+                } else {
+                    tf(
+                        "notify.summary.failed",
+                        &[("count", &names.len()), ("names", &names.join(", "))],
+                    )
+                }
+            }
+            None => t("notify.summary.unavailable").to_string(),
+        }
+    };
+
+    let _ = app_handle
+        .notification()
+        .builder()
+        .title("cdMenu")
+        .body(&body)
+        .show();
+}
+
+/// Check all monitored pipelines and return aggregated status
+pub(crate) async fn check_all_pipelines(
+    username: &str,
+    app_password: &str,
+    monitored: &[MonitoredPipeline],
+    tls: &TlsConfig,
+    paused_is_unhealthy: bool,
+    run_caches: &mut RunCaches,
+) -> OverallStatus {
+    let now = chrono::Local::now();
+    let timestamp = now.format("%H:%M:%S").to_string();
+    let mut pipeline_statuses = Vec::new();
+
+    let client = match BitbucketClient::new(username, app_password, tls) {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("Failed to create Bitbucket client: {}", e);
+            for pipeline_config in monitored {
+                pipeline_statuses.push(PipelineStatusInfo {
+                    failure_reason: Some(format!("Error: {}", e)),
+                    error_kind: Some(format!("{:?}", e)),
+                    network_error: e.is_network_error(),
+                    unknown_reason: Some(UnknownReason::from_error(&e)),
+                    ..PipelineStatusInfo::new(pipeline_config, PipelineState::Unknown, now)
+                });
+            }
+            return OverallStatus::new(pipeline_statuses, timestamp, paused_is_unhealthy);
+        }
+    };
+
+    for pipeline_config in monitored {
+        pipeline_statuses.push(check_pipeline(&client, pipeline_config, run_caches).await);
+    }
+
+    OverallStatus::new(pipeline_statuses, timestamp, paused_is_unhealthy)
+}
+
+/// Check a pipeline that isn't monitored yet, without touching state or the tray
+pub async fn preview_pipeline(
+    app_handle: &AppHandle,
+    workspace: String,
+    repo_slug: String,
+    branch: Option<String>,
+) -> Result<PipelineStatusInfo, String> {
+    let (credentials, tls) = {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let state_guard = state.lock().await;
+        (state_guard.credentials.clone(), state_guard.tls_config())
+    };
+    let credentials = credentials.ok_or("No credentials saved")?;
+    let app_password = get_app_password(app_handle).ok_or("No app password saved")?;
+    let client = BitbucketClient::new(&credentials.username, &app_password, &tls)
+        .map_err(|e| format!("{}", e))?;
+
+    let pipeline = MonitoredPipeline {
+        workspace,
+        repo_name: repo_slug.clone(),
+        repo_slug,
+        branch,
+        ..MonitoredPipeline::default()
+    };
+    // Throwaway cache so previews don't leak into the monitored step cache
+    Ok(check_pipeline(&client, &pipeline, &mut RunCaches::default()).await)
+}
+
+/// Start a custom pipeline, announce it, and refresh the repository's status
+pub async fn run_custom_pipeline(
+    app_handle: &AppHandle,
+    workspace: &str,
+    repo_slug: &str,
+    branch: &str,
+    selector: &str,
+    variables: &[PipelineVariable],
+) -> Result<Pipeline, String> {
+    let (credentials, tls, repo_url) = {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let state_guard = state.lock().await;
+        // Group pipelines cache their link too
+        let repo_url = state_guard
+            .all_monitored_pipelines()
+            .into_iter()
+            .find(|p| p.workspace == workspace && p.repo_slug == repo_slug)
+            .and_then(|p| p.repo_url);
+        (state_guard.credentials.clone(), state_guard.tls_config(), repo_url)
+    };
+    let credentials = credentials.ok_or("No credentials saved")?;
+    let app_password = get_app_password(app_handle).ok_or("No app password saved")?;
+    let client = BitbucketClient::new(&credentials.username, &app_password, &tls)
+        .map_err(|e| format!("{}", e))?;
+
+    tracing::info!(
+        "Running custom pipeline '{}' on {}/{} ({})",
+        selector,
+        workspace,
+        repo_slug,
+        branch
+    );
+    let pipeline = client
+        .run_custom_pipeline(workspace, repo_slug, branch, selector, variables)
+        .await
+        .map_err(|e| format!("{}", e))?;
+
+    let url = resolve_pipeline_url(&pipeline, repo_url.as_deref(), workspace, repo_slug);
+    let summary = tf(
+        "notify.started.body",
+        &[
+            ("repo", &repo_slug),
+            ("build", &pipeline.build_number),
+            ("selector", &selector),
+            ("branch", &branch),
+        ],
+    );
+    app_handle.notify(t("notify.started.title"), &body_with_link(summary, Some(&url)));
+    check_single_pipeline(app_handle, workspace, repo_slug).await;
+
+    Ok(pipeline)
+}
+
+/// Check a single monitored pipeline's latest run
+#[tracing::instrument(
+    name = "check_pipeline",
+    level = "debug",
+    skip_all,
+    fields(workspace = %pipeline_config.workspace, repo = %pipeline_config.repo_slug)
+)]
+async fn check_pipeline(
+    client: &BitbucketClient,
+    pipeline_config: &MonitoredPipeline,
+    run_caches: &mut RunCaches,
+) -> PipelineStatusInfo {
+    let mut status = check_latest_run(client, pipeline_config, run_caches).await;
+
+    // A 404 from the pipelines endpoint only means the repository is gone (and counts
+    // toward stale detection) if the repository itself can't be found either
+    if status.error_kind.as_deref().is_some_and(|kind| kind.starts_with("NotFound")) {
+        if let Ok(repo) = client
+            .get_repository(&pipeline_config.workspace, &pipeline_config.repo_slug)
+            .await
+        {
+            let e = BitbucketError::ApiError(
+                "Pipelines are not enabled for this repository".to_string(),
+            );
+            status.failure_reason = Some(format!("Error: {}", e));
+            status.error_kind = Some(format!("{:?}", e));
+            status.unknown_reason = Some(UnknownReason::from_error(&e));
+            status.repo_url = repo.html_url().map(str::to_string);
+        }
+    }
+
+    // Look up the repository link once; the caller caches it in config. A failed lookup
+    // waits for FAILED_LOOKUP_RETRY before it is tried again.
+    let now = std::time::Instant::now();
+    let (workspace, repo_slug) = (&pipeline_config.workspace, &pipeline_config.repo_slug);
+    if status.repo_url.is_none()
+        && status.error_kind.is_none()
+        && run_caches.repo_url_lookups.can_retry(workspace, repo_slug, now)
+    {
+        status.repo_url = client
+            .get_repository(workspace, repo_slug)
+            .await
+            .ok()
+            .and_then(|repo| repo.html_url().map(str::to_string));
+        if status.repo_url.is_none() {
+            run_caches.repo_url_lookups.record_failure(workspace, repo_slug, now);
+        }
+    }
+    if let (Some(repo_url), Some(branch)) = (&status.repo_url, &status.branch) {
+        status.branch_url = Some(get_branch_url(repo_url, branch));
+    }
+    status
+}
+
+/// Status of the latest run for a monitored pipeline
+async fn check_latest_run(
+    client: &BitbucketClient,
+    pipeline_config: &MonitoredPipeline,
+    run_caches: &mut RunCaches,
+) -> PipelineStatusInfo {
+    let latest = match &pipeline_config.pipeline_definition_uuid {
+        Some(selector) => {
+            client
+                .get_latest_pipeline_by_selector(
+                    &pipeline_config.workspace,
+                    &pipeline_config.repo_slug,
+                    selector,
+                )
+                .await
+        }
+        None => {
+            client
+                .get_latest_pipeline(
+                    &pipeline_config.workspace,
+                    &pipeline_config.repo_slug,
+                    pipeline_config.branch_filter(),
+                )
+                .await
+        }
+    };
+    let now = chrono::Local::now();
+
+    match latest {
+        Ok(Some(pipeline)) => {
+            let mut failed_step_name = None;
+            let mut paused_step_uuid = None;
+            let mut unknown_reason = None;
+            let (state, failure_reason, stage_name) = if let Some(gate) = &pipeline_config.gate_step {
+                let (state, reason, unknown) =
+                    check_gate_step(client, pipeline_config, &pipeline, gate, &mut run_caches.gate_steps).await;
+                unknown_reason = unknown;
+                let stage_name = (state == PipelineState::Paused).then(|| gate.clone());
+                (state, reason, stage_name)
+            } else if pipeline.is_failed() {
+                // Steps of a finished run never change, so fetch them once per run
+                failed_step_name = match run_caches.failed_steps.get(&pipeline.uuid) {
+                    Some(cached) => cached.clone(),
+                    None => match client
+                        .get_pipeline_steps(
+                            &pipeline_config.workspace,
+                            &pipeline_config.repo_slug,
+                            &pipeline.uuid,
+                        )
+                        .await
+                    {
+                        Ok(steps) => {
+                            let name =
+                                steps.into_iter().find(|s| s.is_failed()).and_then(|s| s.name);
+                            run_caches.failed_steps.insert(pipeline.uuid.clone(), name.clone());
+                            name
+                        }
+                        // Not cached, so the next poll tries again
+                        Err(e) => {
+                            tracing::warn!("Failed to fetch steps of {}: {}", pipeline.uuid, e);
+                            None
+                        }
+                    },
+                };
+                let reason = match &failed_step_name {
+                    Some(step) => format!("step '{}' failed", step),
+                    None => pipeline
+                        .state
+                        .result
+                        .as_ref()
+                        .map(|r| r.name.clone())
+                        .unwrap_or_else(|| "FAILED".to_string()),
+                };
+                (PipelineState::Failed, Some(reason), None)
+            } else if pipeline.is_stopped() {
+                let reason = match pipeline.stopped_by() {
+                    Some(user) => format!("stopped by {}", user),
+                    None => "stopped".to_string(),
+                };
+                (PipelineState::Healthy, Some(reason), None)
+            } else if pipeline.is_paused() {
+                // Pipeline is waiting for manual trigger/approval
+                // Fetch steps to find the pending step
+                let pending_step = client
+                    .get_pending_step(
+                        &pipeline_config.workspace,
+                        &pipeline_config.repo_slug,
+                        &pipeline.uuid,
+                    )
+                    .await
+                    .ok()
+                    .flatten();
+                paused_step_uuid = pending_step.as_ref().map(|s| s.uuid.clone());
+
+                // Step names can be empty; the stage name on the run is the next best label
+                let stage_name = pending_step
+                    .and_then(|s| s.name)
+                    .filter(|name| !name.is_empty())
+                    .or_else(|| {
+                        pipeline
+                            .state
+                            .stage
+                            .as_ref()
+                            .and_then(|stage| stage.name.clone())
+                            .filter(|name| !name.is_empty())
+                    })
+                    .unwrap_or_else(|| "paused".to_string());
+                (PipelineState::Paused, None, Some(stage_name))
+            } else if pipeline.is_in_progress() {
+                (PipelineState::InProgress, None, None)
+            } else {
+                (PipelineState::Healthy, None, None)
+            };
+
+            // Variables never change once a run has started, so fetch the detail once per run
+            let variables = if !pipeline.is_custom_or_manual() {
+                None
+            } else if let Some(vars) = pipeline.variable_pairs() {
+                Some(vars)
+            } else if let Some(cached) = run_caches.variables.get(&pipeline.uuid) {
+                cached.clone()
+            } else {
+                match client
+                    .get_pipeline_by_build_number(
+                        &pipeline_config.workspace,
+                        &pipeline_config.repo_slug,
+                        pipeline.build_number,
+                    )
+                    .await
+                {
+                    Ok(detail) => {
+                        let vars = detail.and_then(|detail| detail.variable_pairs());
+                        run_caches.variables.insert(pipeline.uuid.clone(), vars.clone());
+                        vars
+                    }
+                    // Not cached, so the next poll tries again
+                    Err(e) => {
+                        tracing::warn!("Failed to fetch variables of {}: {}", pipeline.uuid, e);
+                        None
+                    }
+                }
+            };
+
+            let pipeline_url = Some(resolve_pipeline_url(
+                &pipeline,
+                pipeline_config.repo_url.as_deref(),
+                &pipeline_config.workspace,
+                &pipeline_config.repo_slug,
+            ));
+
+            PipelineStatusInfo {
+                failure_reason,
+                failed_step_name,
+                unknown_reason,
+                pipeline_url,
+                branch: pipeline.branch().map(str::to_string),
+                stage_name,
+                paused_step_uuid,
+                trigger_type: pipeline.trigger_type(),
+                commit_author: pipeline
+                    .creator
+                    .as_ref()
+                    .and_then(|creator| creator.name())
+                    .map(str::to_string),
+                variables,
+                // A gated pipeline's history follows the gate step, not the run
+                stopped: pipeline_config.gate_step.is_none() && pipeline.is_stopped(),
+                pipeline_uuid: Some(pipeline.uuid.clone()),
+                build_number: Some(pipeline.build_number),
+                duration_secs: pipeline.duration_secs(),
+                created_on: Some(pipeline.created_on),
+                completed_on: pipeline.completed_on,
+                ..PipelineStatusInfo::new(pipeline_config, state, now)
+            }
+        }
+        Ok(None) => {
+            // No pipelines found for this repo - treat as unknown
+            tracing::debug!(
+                "No pipelines found for {}/{}",
+                pipeline_config.workspace,
+                pipeline_config.repo_slug
+            );
+            PipelineStatusInfo {
+                pipeline_url: Some(get_pipelines_list_url(
+                    pipeline_config.repo_url.as_deref(),
+                    &pipeline_config.workspace,
+                    &pipeline_config.repo_slug,
+                )),
+                unknown_reason: Some(UnknownReason::NoRuns),
+                ..PipelineStatusInfo::new(pipeline_config, PipelineState::Unknown, now)
+            }
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to check pipeline {}/{}: {}",
+                pipeline_config.workspace,
+                pipeline_config.repo_slug,
+                e
+            );
+            PipelineStatusInfo {
+                failure_reason: Some(format!("Error: {}", e)),
+                error_kind: Some(format!("{:?}", e)),
+                network_error: e.is_network_error(),
+                unknown_reason: Some(UnknownReason::from_error(&e)),
+                ..PipelineStatusInfo::new(pipeline_config, PipelineState::Unknown, now)
+            }
+        }
+    }
+}
+
+/// State of a gated pipeline from its gate step alone, ignoring the rest of the run
+async fn check_gate_step(
+    client: &BitbucketClient,
+    pipeline_config: &MonitoredPipeline,
+    pipeline: &Pipeline,
+    gate: &str,
+    gate_cache: &mut HashMap<String, Option<PipelineStep>>,
+) -> (PipelineState, Option<String>, Option<UnknownReason>) {
+    let step = match gate_cache.get(&pipeline.uuid) {
+        Some(cached) => cached.clone(),
+        None => {
+            let steps = match client
+                .get_pipeline_steps(
+                    &pipeline_config.workspace,
+                    &pipeline_config.repo_slug,
+                    &pipeline.uuid,
+                )
+                .await
+            {
+                Ok(steps) => steps,
+                Err(e) => {
+                    return (
+                        PipelineState::Unknown,
+                        Some(format!("Error: {}", e)),
+                        Some(UnknownReason::from_error(&e)),
+                    )
+                }
+            };
+            let step = steps.into_iter().find(|s| s.name.as_deref() == Some(gate));
+            // Steps of a finished run never change, so fetch them once per run
+            if pipeline.completed_on.is_some() {
+                gate_cache.insert(pipeline.uuid.clone(), step.clone());
+            }
+            step
+        }
+    };
+
+    match step {
+        Some(step) => {
+            let (state, reason) = gate_state(&step, pipeline.is_paused());
+            (state, reason, None)
+        }
+        None => (
+            PipelineState::Unknown,
+            Some("gate step not found".to_string()),
+            Some(UnknownReason::GateStepNotFound),
+        ),
+    }
+}
+
+/// Map a gate step's state and result onto the pipeline's state
+fn gate_state(step: &PipelineStep, run_paused: bool) -> (PipelineState, Option<String>) {
+    let name = step.name.as_deref().unwrap_or_default();
+    if step.is_failed() {
+        return (PipelineState::Failed, Some(format!("step '{}' failed", name)));
+    }
+    if step.is_pending() {
+        // A pending step in a paused run is the manual step it waits on
+        let state = if run_paused { PipelineState::Paused } else { PipelineState::InProgress };
+        return (state, None);
+    }
+    let state = step.state.as_ref();
+    match state.and_then(|s| s.result.as_ref()).map(|r| r.name.as_str()) {
+        Some("SUCCESSFUL") => (PipelineState::Healthy, None),
+        // Stopped or skipped, e.g. after an earlier step failed: the run never got
+        // through its gate, so it isn't healthy
+        Some(result) => (
+            PipelineState::Failed,
+            Some(format!("step '{}' {}", name, result.to_lowercase().replace('_', " "))),
+        ),
+        None => (PipelineState::InProgress, None),
+    }
+}
+
+/// Get the app password from config file
+pub fn get_app_password(app_handle: &AppHandle) -> Option<String> {
+    let config_dir = storage::config_dir(app_handle).ok()?;
+    let creds_path = config_dir.join(".credentials");
+
+    if !creds_path.exists() {
+        return None;
+    }
+
+    let encoded = std::fs::read_to_string(&creds_path).ok()?;
+    let decoded = STANDARD.decode(encoded.trim()).ok()?;
+    String::from_utf8(decoded).ok()
+}
+
+/// Quiet period after the last manual refresh request before checking
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// A manual refresh; requests in one burst share a check
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshRequest {
+    /// Show a health summary notification after the check, as the refresh hotkey does
+    pub announce: bool,
+}
+
+/// Queue a manual refresh for the refresh task, which debounces bursts of them
+pub async fn request_refresh(app_handle: &AppHandle, request: RefreshRequest) {
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+    let refresh_tx = state.lock().await.refresh_tx.clone();
+    // A full queue already holds a pending refresh
+    if let Some(tx) = refresh_tx {
+        let _ = tx.try_send(request);
+    }
+}
+
+/// Wait for a refresh request, then until none arrives for `quiet`
+///
+/// Returns the burst's requests merged into one, or None once every sender is gone.
+async fn next_refresh(
+    rx: &mut mpsc::Receiver<RefreshRequest>,
+    quiet: Duration,
+) -> Option<RefreshRequest> {
+    let mut request = rx.recv().await?;
+    while let Ok(Some(next)) = tokio::time::timeout(quiet, rx.recv()).await {
+        request.announce |= next.announce;
+    }
+    Some(request)
+}
+
+/// Payload of the `trigger-single-refresh` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SingleRefreshRequest {
+    pub workspace: String,
+    pub repo_slug: String,
+}
+
+/// Listen for manual refresh triggers, checking once per burst of them
+///
+/// Returns the task running the checks, which stops when `shutdown` is cancelled.
+pub fn setup_refresh_listener(
+    app_handle: AppHandle,
+    mut refresh_rx: mpsc::Receiver<RefreshRequest>,
+    shutdown: CancellationToken,
+) -> tauri::async_runtime::JoinHandle<()> {
+    let handle = app_handle.clone();
+    let refresh_task = tauri::async_runtime::spawn(async move {
+        let refreshes = async {
+            while let Some(request) = next_refresh(&mut refresh_rx, REFRESH_DEBOUNCE).await {
+                tracing::info!("Manual refresh triggered");
+                check_pipelines_once(&handle).await;
+                if request.announce {
+                    notify_health_summary(&handle).await;
+                }
+            }
+        };
+        tokio::select! {
+            _ = refreshes => {}
+            _ = shutdown.cancelled() => tracing::info!("Refresh task stopped"),
+        }
+    });
+
+    let handle = app_handle.clone();
+    app_handle.listen("trigger-refresh", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            request_refresh(&handle, RefreshRequest::default()).await;
+        });
+    });
+
+    let handle = app_handle.clone();
+    app_handle.listen("trigger-single-refresh", move |event| {
+        let request: SingleRefreshRequest = match serde_json::from_str(event.payload()) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::warn!("Invalid single refresh payload: {}", e);
+                return;
+            }
+        };
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tracing::info!(
+                "Single refresh triggered for {}/{}",
+                request.workspace,
+                request.repo_slug
+            );
+            check_single_pipeline(&handle, &request.workspace, &request.repo_slug).await;
+        });
+    });
+
+    refresh_task
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MonitoredPipeline;
+    use chrono::Local;
+    use std::cell::RefCell;
+
+    /// Records notifications instead of showing them
+    #[derive(Default)]
+    struct MockNotifier {
+        sent: RefCell<Vec<(String, String)>>,
+    }
+
+    impl Notifier for MockNotifier {
+        fn notify(&self, title: &str, body: &str) {
+            self.sent
+                .borrow_mut()
+                .push((title.to_string(), body.to_string()));
+        }
+    }
+
+    fn monitored(muted: bool) -> MonitoredPipeline {
+        MonitoredPipeline {
+            workspace: "ws".to_string(),
+            repo_slug: "api".to_string(),
+            repo_name: "API".to_string(),
+            muted,
+            ..MonitoredPipeline::default()
+        }
+    }
+
+    fn status(state: PipelineState, build_number: u32, muted: bool) -> OverallStatus {
+        let pipeline = PipelineStatusInfo {
+            build_number: Some(build_number),
+            pipeline_url: Some(format!(
+                "https://bitbucket.org/ws/api/pipelines/results/{}",
+                build_number
+            )),
+            ..PipelineStatusInfo::new(&monitored(muted), state, Local::now())
+        };
+        OverallStatus::new(vec![pipeline], "2024-01-01 00:00:00".to_string(), false)
+    }
+
+    /// `status` as stored after the first check since launch
+    fn first_check(mut status: OverallStatus) -> OverallStatus {
+        notify_transitions(
+            &MockNotifier::default(),
+            None,
+            &mut status,
+            &mut IncidentTracker::default(),
+            Utc::now(),
+        );
+        status
+    }
+
+    fn sent(notifier: &MockNotifier) -> Vec<(String, String)> {
+        notifier.sent.borrow().clone()
+    }
+
+    #[test]
+    fn first_check_with_failure_does_not_notify() {
+        let notifier = MockNotifier::default();
+
+        notify_transitions(
+            &notifier,
+            None,
+            &mut status(PipelineState::Failed, 1, false),
+            &mut IncidentTracker::default(),
+            Utc::now(),
+        );
+
+        assert!(sent(&notifier).is_empty());
+    }
+
+    #[test]
+    fn healthy_to_failed_notifies() {
+        let notifier = MockNotifier::default();
+        let old = status(PipelineState::Healthy, 1, false);
+
+        notify_transitions(
+            &notifier,
+            Some(&old),
+            &mut status(PipelineState::Failed, 2, false),
+            &mut IncidentTracker::default(),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            sent(&notifier),
+            vec![(
+                "Pipeline Failed".to_string(),
+                "API has failed\nhttps://bitbucket.org/ws/api/pipelines/results/2".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn failed_to_failed_with_same_build_does_not_renotify() {
+        let notifier = MockNotifier::default();
+        let old = first_check(status(PipelineState::Failed, 2, false));
+
+        notify_transitions(
+            &notifier,
+            Some(&old),
+            &mut status(PipelineState::Failed, 2, false),
+            &mut IncidentTracker::default(),
+            Utc::now(),
+        );
+
+        assert!(sent(&notifier).is_empty());
+    }
+
+    #[test]
+    fn failed_to_healthy_notifies_fixed() {
+        let notifier = MockNotifier::default();
+        let old = first_check(status(PipelineState::Failed, 2, false));
+
+        notify_transitions(
+            &notifier,
+            Some(&old),
+            &mut status(PipelineState::Healthy, 3, false),
+            &mut IncidentTracker::default(),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            sent(&notifier),
+            vec![(
+                "Pipeline Fixed".to_string(),
+                "API is now healthy\nhttps://bitbucket.org/ws/api/pipelines/results/3".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn failure_fixed_by_a_run_seen_in_progress_is_resolved() {
+        let notifier = MockNotifier::default();
+        let mut incidents = IncidentTracker::default();
+        let failed_at = Utc::now();
+        let fixed_at = failed_at + chrono::Duration::minutes(20);
+
+        let healthy = status(PipelineState::Healthy, 1, false);
+        let mut failed = status(PipelineState::Failed, 2, false);
+        let mut running = status(PipelineState::InProgress, 3, false);
+        let mut fixed = status(PipelineState::Healthy, 3, false);
+        notify_transitions(&notifier, Some(&healthy), &mut failed, &mut incidents, failed_at);
+        let fixing = notify_transitions(&notifier, Some(&failed), &mut running, &mut incidents, failed_at);
+        let recovered = notify_transitions(&notifier, Some(&running), &mut fixed, &mut incidents, fixed_at);
+
+        assert_eq!(fixing.recoveries, 0);
+        assert_eq!(recovered.recoveries, 1);
+        assert!(!incidents.is_open("ws", "api"));
+        assert_eq!(incidents.recent()[0].duration_secs, 20 * 60);
+        assert_eq!(sent(&notifier)[1].0, "Pipeline Fixed");
+
+        // The next outage starts afresh rather than at the first one's start
+        let later = fixed_at + chrono::Duration::hours(1);
+        let mut failed_again = status(PipelineState::Failed, 4, false);
+        let mut fixed_again = status(PipelineState::Healthy, 5, false);
+        notify_transitions(&notifier, Some(&fixed), &mut failed_again, &mut incidents, later);
+        notify_transitions(&notifier, Some(&failed_again), &mut fixed_again, &mut incidents, later);
+        assert_eq!(incidents.recent()[0].duration_secs, 0);
+    }
+
+    #[test]
+    fn muted_pipeline_does_not_notify() {
+        let notifier = MockNotifier::default();
+        let old = status(PipelineState::Healthy, 1, true);
+
+        notify_transitions(
+            &notifier,
+            Some(&old),
+            &mut status(PipelineState::Failed, 2, true),
+            &mut IncidentTracker::default(),
+            Utc::now(),
+        );
+
+        assert!(sent(&notifier).is_empty());
+    }
+
+    #[test]
+    fn fixed_notification_includes_outage_duration() {
+        let notifier = MockNotifier::default();
+        let mut incidents = IncidentTracker::default();
+        let failed_at = Utc::now();
+        let fixed_at = failed_at + chrono::Duration::minutes(134);
+
+        let healthy = status(PipelineState::Healthy, 1, false);
+        let mut failed = status(PipelineState::Failed, 2, false);
+        let mut fixed = status(PipelineState::Healthy, 3, false);
+        notify_transitions(&notifier, Some(&healthy), &mut failed, &mut incidents, failed_at);
+        notify_transitions(&notifier, Some(&failed), &mut fixed, &mut incidents, fixed_at);
+
+        assert_eq!(
+            sent(&notifier)[1],
+            (
+                "Pipeline Fixed".to_string(),
+                "API is healthy again after 2h 14m\nhttps://bitbucket.org/ws/api/pipelines/results/3"
+                    .to_string()
+            )
+        );
+
+        let log = incidents.recent();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].duration_secs, 134 * 60);
+        assert_eq!(
+            log[0].failed_url.as_deref(),
+            Some("https://bitbucket.org/ws/api/pipelines/results/2")
+        );
+    }
+
+    #[test]
+    fn failures_and_recoveries_are_counted_once_per_transition() {
+        let notifier = MockNotifier::default();
+        let mut incidents = IncidentTracker::default();
+        let healthy = status(PipelineState::Healthy, 1, false);
+        let mut failed = status(PipelineState::Failed, 2, false);
+        let mut failed_again = status(PipelineState::Failed, 3, false);
+        let mut fixed = status(PipelineState::Healthy, 4, false);
+
+        let broke = notify_transitions(&notifier, Some(&healthy), &mut failed, &mut incidents, Utc::now());
+        let still_broken =
+            notify_transitions(&notifier, Some(&failed), &mut failed_again, &mut incidents, Utc::now());
+        let recovered =
+            notify_transitions(&notifier, Some(&failed_again), &mut fixed, &mut incidents, Utc::now());
+
+        assert_eq!((broke.failures, broke.recoveries), (1, 0));
+        assert_eq!((still_broken.failures, still_broken.recoveries), (0, 0));
+        assert_eq!((recovered.failures, recovered.recoveries), (0, 1));
+        assert!(broke.incidents_changed && recovered.incidents_changed);
+        assert!(broke.announced_workspaces.contains("ws"));
+    }
+
+    #[test]
+    fn muted_failure_is_counted_but_not_announced() {
+        let transitions = notify_transitions(
+            &MockNotifier::default(),
+            Some(&status(PipelineState::Healthy, 1, true)),
+            &mut status(PipelineState::Failed, 2, true),
+            &mut IncidentTracker::default(),
+            Utc::now(),
+        );
+
+        assert_eq!(transitions.failures, 1);
+        assert!(transitions.announced_workspaces.is_empty());
+    }
+
+    fn off_hours(mut status: OverallStatus) -> OverallStatus {
+        status.pipeline_statuses_mut()[0].off_hours = true;
+        status
+    }
+
+    #[test]
+    fn off_hours_failure_notifies_when_active_hours_begin() {
+        let notifier = MockNotifier::default();
+        let mut incidents = IncidentTracker::default();
+        let healthy = off_hours(status(PipelineState::Healthy, 1, false));
+        let mut failed_off_hours = off_hours(status(PipelineState::Failed, 2, false));
+        let mut failed_active = status(PipelineState::Failed, 2, false);
+
+        notify_transitions(&notifier, Some(&healthy), &mut failed_off_hours, &mut incidents, Utc::now());
+        assert!(sent(&notifier).is_empty());
+
+        notify_transitions(&notifier, Some(&failed_off_hours), &mut failed_active, &mut incidents, Utc::now());
+        assert_eq!(sent(&notifier).len(), 1);
+        assert_eq!(sent(&notifier)[0].0, "Pipeline Failed");
+
+        // Still failing on the next check: no repeat
+        let mut still_failed = status(PipelineState::Failed, 2, false);
+        notify_transitions(&notifier, Some(&failed_active), &mut still_failed, &mut incidents, Utc::now());
+        assert_eq!(sent(&notifier).len(), 1);
+    }
+
+    #[test]
+    fn failure_announced_before_off_hours_is_not_announced_again() {
+        let notifier = MockNotifier::default();
+        let mut incidents = IncidentTracker::default();
+        let healthy = status(PipelineState::Healthy, 1, false);
+        let mut failed_active = status(PipelineState::Failed, 2, false);
+        let mut failed_off_hours = off_hours(status(PipelineState::Failed, 2, false));
+        let mut failed_next_morning = status(PipelineState::Failed, 2, false);
+
+        notify_transitions(&notifier, Some(&healthy), &mut failed_active, &mut incidents, Utc::now());
+        notify_transitions(&notifier, Some(&failed_active), &mut failed_off_hours, &mut incidents, Utc::now());
+        notify_transitions(
+            &notifier,
+            Some(&failed_off_hours),
+            &mut failed_next_morning,
+            &mut incidents,
+            Utc::now(),
+        );
+
+        assert_eq!(sent(&notifier).len(), 1);
+    }
+
+    #[test]
+    fn off_hours_failure_fixed_before_active_hours_stays_quiet() {
+        let notifier = MockNotifier::default();
+        let mut incidents = IncidentTracker::default();
+        let failed = first_check(off_hours(status(PipelineState::Failed, 2, false)));
+        let mut fixed = status(PipelineState::Healthy, 3, false);
+
+        notify_transitions(&notifier, Some(&failed), &mut fixed, &mut incidents, Utc::now());
+
+        assert!(sent(&notifier).is_empty());
+    }
+
+    #[test]
+    fn off_hours_failure_does_not_make_overall_status_unhealthy() {
+        let failed = off_hours(status(PipelineState::Failed, 2, false));
+        let overall = OverallStatus::new(failed.into_pipeline_statuses(), "now".to_string(), false);
+
+        assert!(overall.is_healthy);
+    }
+
+    #[test]
+    fn state_changed_events_only_for_pipelines_that_changed_state() {
+        let failed = status(PipelineState::Failed, 2, false);
+        let mut web = monitored(false);
+        web.repo_slug = "web".to_string();
+        let with_web = |status: OverallStatus| {
+            let mut statuses = status.into_pipeline_statuses();
+            statuses.push(PipelineStatusInfo::new(&web, PipelineState::Healthy, Local::now()));
+            OverallStatus::new(statuses, "now".to_string(), false)
+        };
+        let previous = with_web(status(PipelineState::Healthy, 1, false));
+        let current = with_web(failed.clone());
+
+        let changes = pipeline_state_changes(Some(&previous), &current);
+
+        assert_eq!(
+            changes,
+            [PipelineStateChangedEvent {
+                pipeline: failed.pipeline_statuses()[0].clone(),
+                previous_state: PipelineState::Healthy,
+            }]
+        );
+        assert!(pipeline_state_changes(None, &current).is_empty());
+        // A new run in the same state is not a state change
+        assert!(pipeline_state_changes(Some(&failed), &status(PipelineState::Failed, 3, false)).is_empty());
+    }
+
+    #[test]
+    fn paused_run_is_unhealthy_only_when_configured() {
+        let paused = status(PipelineState::Paused, 2, false).into_pipeline_statuses();
+
+        let lenient = OverallStatus::new(paused.clone(), "now".to_string(), false);
+        let strict = OverallStatus::new(paused, "now".to_string(), true);
+
+        assert!(lenient.is_healthy);
+        assert!(!strict.is_healthy);
+        assert!(strict.failed_pipelines.is_empty());
+        assert_eq!(strict.paused_count, 1);
+    }
+
+    #[test]
+    fn off_hours_paused_run_is_not_awaiting_approval() {
+        let paused = off_hours(status(PipelineState::Paused, 2, false));
+        let overall = OverallStatus::new(paused.into_pipeline_statuses(), "now".to_string(), true);
+
+        assert_eq!(overall.paused_count, 0);
+        assert!(overall.is_healthy);
+    }
+
+    fn step(state: &str, result: Option<&str>) -> PipelineStep {
+        serde_json::from_value(serde_json::json!({
+            "uuid": "{step}",
+            "name": "Deploy",
+            "state": {
+                "name": state,
+                "result": result.map(|name| serde_json::json!({ "name": name })),
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn gate_step_decides_the_pipeline_state() {
+        assert_eq!(
+            gate_state(&step("COMPLETED", Some("SUCCESSFUL")), false),
+            (PipelineState::Healthy, None)
+        );
+        assert_eq!(
+            gate_state(&step("COMPLETED", Some("FAILED")), false),
+            (PipelineState::Failed, Some("step 'Deploy' failed".to_string()))
+        );
+        assert_eq!(
+            gate_state(&step("COMPLETED", Some("NOT_RUN")), false),
+            (PipelineState::Failed, Some("step 'Deploy' not run".to_string()))
+        );
+        assert_eq!(
+            gate_state(&step("COMPLETED", Some("STOPPED")), false),
+            (PipelineState::Failed, Some("step 'Deploy' stopped".to_string()))
+        );
+        assert_eq!(gate_state(&step("PENDING", None), true), (PipelineState::Paused, None));
+        assert_eq!(gate_state(&step("PENDING", None), false), (PipelineState::InProgress, None));
+        assert_eq!(gate_state(&step("IN_PROGRESS", None), false), (PipelineState::InProgress, None));
+    }
+
+    #[tokio::test]
+    async fn refresh_requests_in_a_burst_are_coalesced() {
+        let (tx, mut rx) = mpsc::channel(8);
+        for announce in [false, true, false] {
+            tx.try_send(RefreshRequest { announce }).unwrap();
+        }
+
+        assert_eq!(
+            next_refresh(&mut rx, Duration::from_millis(20)).await,
+            Some(RefreshRequest { announce: true })
+        );
+        assert!(rx.try_recv().is_err());
+
+        drop(tx);
+        assert_eq!(next_refresh(&mut rx, Duration::from_millis(20)).await, None);
+    }
+
+    #[test]
+    fn only_a_401_counts_as_rejected_credentials() {
+        assert!(is_rejection::<()>(&Err(BitbucketError::AuthenticationFailed)));
+        assert!(!is_rejection::<()>(&Err(BitbucketError::Forbidden("/user".to_string()))));
+        assert!(!is_rejection::<()>(&Err(BitbucketError::RateLimited(None))));
+        assert!(!is_rejection(&Ok(())));
+    }
 }
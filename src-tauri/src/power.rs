@@ -0,0 +1,300 @@
+use crate::bitbucket::budget::{self, BudgetState};
+use crate::bitbucket::conditional::{self, ConditionalCacheStats};
+use crate::config::AppState;
+use crate::i18n::t;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// How long a probe of the power source and network is reused
+const ENVIRONMENT_CACHE_TTL: Duration = Duration::from_secs(30);
+
+// Latest probe and when it was taken
+static ENVIRONMENT: std::sync::Mutex<Option<(Instant, Environment)>> = std::sync::Mutex::new(None);
+
+/// Power source and network metering as reported by the OS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Environment {
+    /// None when the OS doesn't report a power source
+    pub on_battery: Option<bool>,
+    /// None when the OS doesn't report network metering
+    pub metered: Option<bool>,
+}
+
+/// Probe the power source and network, reusing a probe younger than `ENVIRONMENT_CACHE_TTL`
+///
+/// Probing runs commands such as `pmset` and `nmcli`, so it happens on a blocking thread;
+/// call it before taking the state lock rather than while holding it.
+pub async fn probe_environment() -> Environment {
+    let cached = ENVIRONMENT.lock().ok().and_then(|cached| *cached);
+    if let Some((at, environment)) = cached {
+        if at.elapsed() < ENVIRONMENT_CACHE_TTL {
+            return environment;
+        }
+    }
+
+    let environment = tauri::async_runtime::spawn_blocking(|| Environment {
+        on_battery: is_on_battery(),
+        metered: is_metered_network(),
+    })
+    .await
+    .unwrap_or_default();
+    if let Ok(mut cached) = ENVIRONMENT.lock() {
+        *cached = Some((Instant::now(), environment));
+    }
+    environment
+}
+
+/// Why polling is running at its current rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PollingMode {
+    #[default]
+    Normal,
+    /// Interval multiplied while running on battery
+    ReducedOnBattery,
+    /// No checks while on a metered connection
+    PausedOnMetered,
+    /// Interval stretched to keep API requests within the hourly budget
+    ReducedForApiBudget,
+}
+
+impl PollingMode {
+    /// Tooltip header describing the mode
+    pub fn tooltip_title(&self) -> &'static str {
+        match self {
+            PollingMode::Normal => t("tooltip.title"),
+            PollingMode::ReducedOnBattery => t("tooltip.reduced_on_battery"),
+            PollingMode::PausedOnMetered => t("tooltip.paused_on_metered"),
+            PollingMode::ReducedForApiBudget => t("tooltip.reduced_for_api_budget"),
+        }
+    }
+}
+
+/// Effective polling policy after applying power and network conditions
+#[derive(Debug, Clone, Serialize)]
+pub struct PollingPolicy {
+    pub mode: PollingMode,
+    pub reduce_polling_on_battery: bool,
+    pub pause_on_metered: bool,
+    pub base_interval_seconds: u64,
+    pub effective_interval_seconds: u64,
+    /// None when the OS doesn't report a power source
+    pub on_battery: Option<bool>,
+    /// None when the OS doesn't report network metering
+    pub metered: Option<bool>,
+    pub api_budget: BudgetState,
+}
+
+/// Work out the polling policy for the current state and environment
+pub fn current_policy(state: &AppState, environment: Environment) -> PollingPolicy {
+    let base = state.polling_interval_seconds;
+    let on_battery = if state.reduce_polling_on_battery {
+        environment.on_battery
+    } else {
+        None
+    };
+    let metered = if state.pause_on_metered {
+        environment.metered
+    } else {
+        None
+    };
+
+    let (mode, effective) = if metered == Some(true) {
+        (PollingMode::PausedOnMetered, base)
+    } else if on_battery == Some(true) {
+        (
+            PollingMode::ReducedOnBattery,
+            base.saturating_mul(state.battery_interval_multiplier.max(1)),
+        )
+    } else {
+        (PollingMode::Normal, base)
+    };
+
+    // Slow down further when the busiest workspace would run over its API budget
+    let api_budget = budget::api_budget_state(base);
+    let budgeted = base.saturating_mul(api_budget.stretch);
+    let (mode, effective) = if mode != PollingMode::PausedOnMetered && budgeted > effective {
+        (PollingMode::ReducedForApiBudget, budgeted)
+    } else {
+        (mode, effective)
+    };
+
+    // Bitbucket reporting its quota nearly used up outweighs the budget estimate
+    let (mode, effective) = match state.rate_limit {
+        Some(rate_limit) if mode != PollingMode::PausedOnMetered && rate_limit.is_low() => {
+            let until_reset = rate_limit.exhausted_for(budget::now_unix()).unwrap_or(0);
+            (
+                PollingMode::ReducedForApiBudget,
+                effective.max(base.saturating_mul(2)).max(until_reset),
+            )
+        }
+        _ => (mode, effective),
+    };
+
+    PollingPolicy {
+        mode,
+        reduce_polling_on_battery: state.reduce_polling_on_battery,
+        pause_on_metered: state.pause_on_metered,
+        base_interval_seconds: base,
+        effective_interval_seconds: effective,
+        on_battery,
+        metered,
+        api_budget,
+    }
+}
+
+/// Configured interval and how long until the polling loop checks again
+#[derive(Debug, Clone, Serialize)]
+pub struct PollingStatus {
+    pub interval_seconds: u64,
+    /// Shortest interval settings accept
+    pub min_interval_seconds: u64,
+    /// Counted down from the effective interval; 0 once a check is due
+    pub next_check_in_seconds: u64,
+    /// Paused on a metered connection or until rejected credentials are replaced
+    pub is_paused: bool,
+    pub last_poll_duration_ms: u64,
+}
+
+/// Where the polling loop is in its cycle
+pub fn polling_status(state: &AppState, environment: Environment) -> PollingStatus {
+    let policy = current_policy(state, environment);
+    PollingStatus {
+        interval_seconds: state.polling_interval_seconds,
+        min_interval_seconds: state.min_polling_interval(),
+        next_check_in_seconds: seconds_until_next_check(
+            policy.effective_interval_seconds,
+            state.last_check_at.map(|at| at.elapsed()),
+        ),
+        is_paused: policy.mode == PollingMode::PausedOnMetered || state.auth_broken,
+        last_poll_duration_ms: state.last_poll_duration.map_or(0, |d| d.as_millis() as u64),
+    }
+}
+
+/// How much polling has done since launch
+#[derive(Debug, Clone, Serialize)]
+pub struct PollMetrics {
+    pub polls_completed: u64,
+    pub last_poll_duration_ms: u64,
+    /// Requests answered from the conditional request cache
+    pub conditional_cache: ConditionalCacheStats,
+}
+
+pub fn poll_metrics(state: &AppState) -> PollMetrics {
+    PollMetrics {
+        polls_completed: state.polls_completed,
+        last_poll_duration_ms: state.last_poll_duration.map_or(0, |d| d.as_millis() as u64),
+        conditional_cache: conditional::conditional_cache_stats(),
+    }
+}
+
+/// Seconds left of the interval; 0 before the first check or when one is overdue
+fn seconds_until_next_check(interval_seconds: u64, elapsed: Option<Duration>) -> u64 {
+    match elapsed {
+        Some(elapsed) => interval_seconds.saturating_sub(elapsed.as_secs()),
+        None => 0,
+    }
+}
+
+/// Whether the machine is running on battery power
+#[cfg(target_os = "macos")]
+fn is_on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    if text.contains("'Battery Power'") {
+        Some(true)
+    } else if text.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Whether the machine is running on battery power
+#[cfg(target_os = "linux")]
+fn is_on_battery() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut has_battery = false;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" | "USB" => {
+                let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return Some(false);
+                }
+            }
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+
+    has_battery.then_some(true)
+}
+
+/// Whether the machine is running on battery power
+#[cfg(target_os = "windows")]
+fn is_on_battery() -> Option<bool> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // SAFETY: SYSTEM_POWER_STATUS is plain data and GetSystemPowerStatus only writes to it
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    match status.ACLineStatus {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the machine is running on battery power
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn is_on_battery() -> Option<bool> {
+    None
+}
+
+/// Whether the active connection is metered (NetworkManager only)
+#[cfg(target_os = "linux")]
+fn is_metered_network() -> Option<bool> {
+    let output = std::process::Command::new("nmcli")
+        .args(["-t", "-g", "GENERAL.METERED", "device", "show"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // One line per device, e.g. "yes", "no (guessed)", "unknown"
+    let text = String::from_utf8_lossy(&output.stdout).to_lowercase();
+    if text.lines().any(|line| line.starts_with("yes")) {
+        Some(true)
+    } else if text.lines().any(|line| line.starts_with("no")) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Whether the active connection is metered (not exposed on this platform)
+#[cfg(not(target_os = "linux"))]
+fn is_metered_network() -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn countdown_saturates_once_a_check_is_overdue() {
+        assert_eq!(seconds_until_next_check(60, Some(Duration::from_secs(13))), 47);
+        assert_eq!(seconds_until_next_check(60, Some(Duration::from_secs(90))), 0);
+        assert_eq!(seconds_until_next_check(60, None), 0);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::bitbucket::{BitbucketClient, Pipeline, PullRequest};
+use crate::config::{MonitoredPipeline, PipelineState};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+#[cfg(test)]
+use ts_rs::TS;
+
+/// How long a repository's open pull request list is reused; their pipelines are
+/// still checked on every poll
+pub const PULL_REQUEST_REFRESH: Duration = Duration::from_secs(600);
+
+/// Latest pipeline of one of the user's open pull requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(TS))]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestStatus {
+    pub workspace: String,
+    pub repo_slug: String,
+    pub id: u32,
+    pub title: String,
+    /// Source branch the pipeline ran on
+    pub branch: String,
+    /// Unknown when the branch has no runs yet
+    pub state: PipelineState,
+    pub url: Option<String>,
+}
+
+/// Open pull requests per (workspace, repo_slug) and when they were fetched
+#[derive(Debug, Clone, Default)]
+pub struct PullRequestCache {
+    entries: HashMap<(String, String), (Instant, Vec<PullRequest>)>,
+}
+
+impl PullRequestCache {
+    fn is_fresh(&self, key: &(String, String), now: Instant) -> bool {
+        self.entries
+            .get(key)
+            .is_some_and(|(fetched_at, _)| now.duration_since(*fetched_at) < PULL_REQUEST_REFRESH)
+    }
+}
+
+/// Check the source branch of each open pull request in repositories with `track_my_prs`
+pub async fn check_pull_requests(
+    client: &BitbucketClient,
+    monitored: &[MonitoredPipeline],
+    cache: &mut PullRequestCache,
+    now: Instant,
+) -> Vec<PullRequestStatus> {
+    let mut statuses = Vec::new();
+    let mut tracked = HashSet::new();
+
+    for pipeline in monitored.iter().filter(|p| p.track_my_prs) {
+        let key = (pipeline.workspace.clone(), pipeline.repo_slug.clone());
+        // A repository can be monitored on several branches
+        if !tracked.insert(key.clone()) {
+            continue;
+        }
+
+        if !cache.is_fresh(&key, now) {
+            match client
+                .get_my_open_pull_requests(&pipeline.workspace, &pipeline.repo_slug)
+                .await
+            {
+                Ok(pull_requests) => {
+                    cache.entries.insert(key.clone(), (now, pull_requests));
+                }
+                // Keep showing the last list until the next refresh succeeds
+                Err(e) => tracing::warn!(
+                    "Failed to list pull requests for {}/{}: {}",
+                    pipeline.workspace,
+                    pipeline.repo_slug,
+                    e
+                ),
+            }
+        }
+        let Some((_, pull_requests)) = cache.entries.get(&key) else {
+            continue;
+        };
+
+        for pull_request in pull_requests {
+            let branch = &pull_request.source.branch.name;
+            let state = match client
+                .get_latest_pipeline(&pipeline.workspace, &pipeline.repo_slug, Some(branch))
+                .await
+            {
+                Ok(Some(run)) => run_state(&run),
+                Ok(None) => PipelineState::Unknown,
+                Err(e) => {
+                    tracing::warn!("Failed to check pull request #{}: {}", pull_request.id, e);
+                    PipelineState::Unknown
+                }
+            };
+            statuses.push(PullRequestStatus {
+                workspace: pipeline.workspace.clone(),
+                repo_slug: pipeline.repo_slug.clone(),
+                id: pull_request.id,
+                title: pull_request.title.clone(),
+                branch: branch.clone(),
+                state,
+                url: pull_request.html_url().map(str::to_string),
+            });
+        }
+    }
+
+    // Forget repositories that stopped tracking pull requests
+    cache.entries.retain(|key, _| tracked.contains(key));
+    statuses
+}
+
+/// State shown for a pull request's latest run
+fn run_state(run: &Pipeline) -> PipelineState {
+    if run.is_failed() {
+        PipelineState::Failed
+    } else if run.is_paused() {
+        PipelineState::Paused
+    } else if run.is_in_progress() {
+        PipelineState::InProgress
+    } else {
+        PipelineState::Healthy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_request_lists_are_refetched_after_the_refresh_interval() {
+        let fetched_at = Instant::now();
+        let key = ("ws".to_string(), "api".to_string());
+        let mut cache = PullRequestCache::default();
+        cache.entries.insert(key.clone(), (fetched_at, Vec::new()));
+
+        assert!(cache.is_fresh(&key, fetched_at + Duration::from_secs(60)));
+        assert!(!cache.is_fresh(&key, fetched_at + PULL_REQUEST_REFRESH));
+        assert!(!cache.is_fresh(&("ws".to_string(), "web".to_string()), fetched_at));
+    }
+}
@@ -0,0 +1,266 @@
+use crate::config::{OverallStatus, PipelineState, PipelineStatusInfo};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+pub const QUICK_OPEN_LABEL: &str = "quick-open";
+
+/// Most matches returned for one query
+pub const MAX_MATCHES: usize = 20;
+
+/// A monitored pipeline matching a quick-open query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusMatch {
+    /// Identifies the entry for `open_status_entry`
+    pub key: String,
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+    pub project_name: Option<String>,
+    pub branch: Option<String>,
+    pub state: PipelineState,
+    /// Latest run, or the repository when there is none
+    pub url: Option<String>,
+    pub score: i64,
+}
+
+/// Key of a pipeline: `workspace/repo_slug`, plus `@branch` when monitored on one
+pub fn pipeline_key(workspace: &str, repo_slug: &str, branch: Option<&str>) -> String {
+    match branch {
+        Some(branch) => format!("{}/{}@{}", workspace, repo_slug, branch),
+        None => format!("{}/{}", workspace, repo_slug),
+    }
+}
+
+/// Key of a pipeline status, see `pipeline_key`
+pub fn status_key(status: &PipelineStatusInfo) -> String {
+    pipeline_key(&status.workspace, &status.repo_slug, status.monitored_branch.as_deref())
+}
+
+/// Find the status with the given key
+pub fn find_entry<'a>(status: &'a OverallStatus, key: &str) -> Option<&'a PipelineStatusInfo> {
+    status
+        .pipeline_statuses()
+        .iter()
+        .find(|s| status_key(s) == key)
+}
+
+/// Score `candidate` against `query` as a case-insensitive subsequence
+///
+/// Consecutive characters and characters starting a word score higher; gaps cost a little.
+/// The best-scoring alignment wins, so "api" matches the end of "payments-api" rather than
+/// the first "a". Returns None when not every query character appears in order.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let bonus = |j: usize| {
+        if j == 0 || !candidate[j - 1].is_alphanumeric() {
+            9
+        } else {
+            1
+        }
+    };
+
+    // best[j]: best score with the current query character matched at candidate[j]
+    let mut best: Vec<Option<i64>> = candidate
+        .iter()
+        .enumerate()
+        .map(|(j, c)| (*c == query[0]).then(|| bonus(j) - j.min(5) as i64))
+        .collect();
+
+    for wanted in &query[1..] {
+        best = (0..candidate.len())
+            .map(|j| {
+                if candidate[j] != *wanted {
+                    return None;
+                }
+                (0..j)
+                    .filter_map(|k| {
+                        best[k].map(|score| {
+                            if k + 1 == j {
+                                score + 5
+                            } else {
+                                score - (j - k - 1).min(5) as i64
+                            }
+                        })
+                    })
+                    .max()
+                    .map(|score| score + bonus(j))
+            })
+            .collect();
+    }
+
+    best.into_iter().flatten().max()
+}
+
+/// Best score of a status over its repository name, slug, project and branch
+fn status_score(query: &str, status: &PipelineStatusInfo) -> Option<i64> {
+    [
+        Some(status.repo_name.as_str()),
+        Some(status.repo_slug.as_str()),
+        status.project_name.as_deref(),
+        status.project_key.as_deref(),
+        status.monitored_branch.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|field| fuzzy_score(query, field))
+    .max()
+}
+
+/// Monitored pipelines matching `query`, best first
+///
+/// An empty query lists every pipeline by name.
+pub fn search(status: &OverallStatus, query: &str) -> Vec<StatusMatch> {
+    let mut matches: Vec<StatusMatch> = status
+        .pipeline_statuses()
+        .iter()
+        .filter_map(|s| {
+            status_score(query, s).map(|score| StatusMatch {
+                key: status_key(s),
+                workspace: s.workspace.clone(),
+                repo_slug: s.repo_slug.clone(),
+                repo_name: s.repo_name.clone(),
+                project_name: s.project_name.clone(),
+                branch: s.monitored_branch.clone().or_else(|| s.branch.clone()),
+                state: s.state.clone(),
+                url: s.pipeline_url.clone().or_else(|| s.repo_url.clone()),
+                score,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.repo_name.to_lowercase().cmp(&b.repo_name.to_lowercase()))
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    matches.truncate(MAX_MATCHES);
+    matches
+}
+
+/// Show the quick-open window, or hide it if it is already showing
+pub fn toggle_quick_open<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(QUICK_OPEN_LABEL) {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+        } else {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        return Ok(());
+    }
+
+    let url = WebviewUrl::App("quick-open.html".into());
+    let window = WebviewWindowBuilder::new(app_handle, QUICK_OPEN_LABEL, url)
+        .title("cdMenu Quick Open")
+        .inner_size(560.0, 360.0)
+        .center()
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .build()
+        .map_err(|e| format!("Failed to create quick open window: {}", e))?;
+    let _ = window.set_focus();
+    tracing::info!("Quick open window created");
+    Ok(())
+}
+
+/// Hide the quick-open window if it is open
+pub fn hide_quick_open<R: Runtime>(app_handle: &AppHandle<R>) {
+    if let Some(window) = app_handle.get_webview_window(QUICK_OPEN_LABEL) {
+        let _ = window.hide();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MonitoredPipeline;
+    use chrono::Local;
+
+    fn status(repo_slug: &str, repo_name: &str, project: &str, branch: Option<&str>) -> PipelineStatusInfo {
+        let pipeline = MonitoredPipeline {
+            workspace: "ws".to_string(),
+            project_name: Some(project.to_string()),
+            repo_slug: repo_slug.to_string(),
+            repo_name: repo_name.to_string(),
+            branch: branch.map(str::to_string),
+            ..MonitoredPipeline::default()
+        };
+        PipelineStatusInfo::new(&pipeline, PipelineState::Healthy, Local::now())
+    }
+
+    fn overall(statuses: Vec<PipelineStatusInfo>) -> OverallStatus {
+        OverallStatus::new(statuses, "2024-01-01 00:00:00".to_string(), false)
+    }
+
+    #[test]
+    fn matches_are_case_insensitive_subsequences() {
+        assert!(fuzzy_score("pay", "Payments API").is_some());
+        assert!(fuzzy_score("pmapi", "payments-api").is_some());
+        assert!(fuzzy_score("pm api", "payments-api").is_some());
+        assert_eq!(fuzzy_score("apix", "payments-api"), None);
+        assert_eq!(fuzzy_score("ipa", "api"), None);
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn consecutive_and_word_start_matches_rank_higher() {
+        let prefix = fuzzy_score("api", "api-gateway").unwrap();
+        let word = fuzzy_score("api", "payments-api").unwrap();
+        let scattered = fuzzy_score("api", "mapping-tool").unwrap();
+        assert!(prefix > word);
+        assert!(word > scattered);
+    }
+
+    #[test]
+    fn search_ranks_best_match_first_over_every_field() {
+        let status = overall(vec![
+            status("web", "Web", "Frontend", Some("main")),
+            status("payments-api", "Payments API", "Billing", None),
+            status("api-gateway", "API Gateway", "Platform", Some("release")),
+        ]);
+
+        let keys: Vec<String> = search(&status, "api").into_iter().map(|m| m.key).collect();
+        assert_eq!(keys, vec!["ws/api-gateway@release", "ws/payments-api"]);
+
+        let by_project = search(&status, "billing");
+        assert_eq!(by_project.len(), 1);
+        assert_eq!(by_project[0].repo_slug, "payments-api");
+
+        let by_branch = search(&status, "release");
+        assert_eq!(by_branch[0].key, "ws/api-gateway@release");
+    }
+
+    #[test]
+    fn empty_query_lists_everything_by_name() {
+        let status = overall(vec![
+            status("web", "Web", "Frontend", None),
+            status("api", "api", "Platform", None),
+        ]);
+
+        let names: Vec<String> = search(&status, "  ").into_iter().map(|m| m.repo_name).collect();
+        assert_eq!(names, vec!["api", "Web"]);
+    }
+
+    #[test]
+    fn entries_are_found_by_key() {
+        let status = overall(vec![
+            status("api", "API", "Platform", Some("main")),
+            status("api", "API", "Platform", Some("develop")),
+        ]);
+
+        let entry = find_entry(&status, "ws/api@develop").unwrap();
+        assert_eq!(entry.monitored_branch.as_deref(), Some("develop"));
+        assert!(find_entry(&status, "ws/api").is_none());
+    }
+}
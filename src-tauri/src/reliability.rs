@@ -0,0 +1,287 @@
+use crate::config::MonitoredPipeline;
+use crate::incidents::{IncidentEvent, IncidentEventKind, INCIDENT_EVENT_RETENTION_DAYS};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Window the MTTR line in each pipeline's submenu covers
+pub const MTTR_MENU_WINDOW_DAYS: u32 = 30;
+
+/// How reliable one pipeline was over a report window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineReliability {
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+    /// Failures that started in the window
+    pub failures: u32,
+    pub failures_per_week: f64,
+    /// Outages counted towards MTTR and the longest outage
+    pub outages: u32,
+    /// Mean time to recovery; None without any outage in the window
+    pub mttr_secs: Option<u64>,
+    pub longest_outage_secs: Option<u64>,
+    /// Start of an outage still going on at the end of the window
+    pub failing_since: Option<DateTime<Utc>>,
+}
+
+/// Mean time to recovery, failure frequency and longest outage per pipeline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReliabilityReport {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub window_days: u32,
+    /// Outages still going on count up to `until`
+    pub include_open: bool,
+    /// Every monitored pipeline, plus any with events that is no longer monitored
+    pub pipelines: Vec<PipelineReliability>,
+}
+
+impl PipelineReliability {
+    fn new(workspace: &str, repo_slug: &str, repo_name: &str) -> Self {
+        Self {
+            workspace: workspace.to_string(),
+            repo_slug: repo_slug.to_string(),
+            repo_name: repo_name.to_string(),
+            failures: 0,
+            failures_per_week: 0.0,
+            outages: 0,
+            mttr_secs: None,
+            longest_outage_secs: None,
+            failing_since: None,
+        }
+    }
+}
+
+/// A pipeline's counts and the lengths of its outages, while the report is built
+struct Tally {
+    reliability: PipelineReliability,
+    outage_secs: Vec<u64>,
+}
+
+// Helper: The pipeline's tally, added on first use
+fn tally<'a>(
+    tallies: &'a mut Vec<Tally>,
+    index: &mut HashMap<(String, String), usize>,
+    workspace: &str,
+    repo_slug: &str,
+    repo_name: &str,
+) -> &'a mut Tally {
+    let i = *index
+        .entry((workspace.to_string(), repo_slug.to_string()))
+        .or_insert_with(|| {
+            tallies.push(Tally {
+                reliability: PipelineReliability::new(workspace, repo_slug, repo_name),
+                outage_secs: Vec::new(),
+            });
+            tallies.len() - 1
+        });
+    &mut tallies[i]
+}
+
+/// Check a report window fits the kept events
+pub fn validate_window(window_days: u32) -> Result<(), String> {
+    if window_days == 0 || i64::from(window_days) > INCIDENT_EVENT_RETENTION_DAYS {
+        return Err(format!(
+            "Report window must be between 1 and {} days",
+            INCIDENT_EVENT_RETENTION_DAYS
+        ));
+    }
+    Ok(())
+}
+
+/// Report on `events` (in recorded order) over the `window_days` before `until`
+///
+/// An outage counts when it ended in the window, or with `include_open` when it is still
+/// going on. Events are paired in the order they were recorded rather than by timestamp,
+/// so a clock set back between a failure and its recovery gives a zero-length outage
+/// instead of a negative one.
+pub fn reliability_report(
+    events: &[IncidentEvent],
+    monitored: &[MonitoredPipeline],
+    until: DateTime<Utc>,
+    window_days: u32,
+    include_open: bool,
+) -> ReliabilityReport {
+    let since = until - Duration::days(window_days.into());
+    let mut tallies: Vec<Tally> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    let mut open: BTreeMap<(String, String), &IncidentEvent> = BTreeMap::new();
+
+    // Monitored on several branches, a repository still gets one entry
+    for pipeline in monitored {
+        tally(&mut tallies, &mut index, &pipeline.workspace, &pipeline.repo_slug, &pipeline.repo_name);
+    }
+
+    for event in events.iter().filter(|e| e.at < until) {
+        let key = (event.workspace.clone(), event.repo_slug.clone());
+        let in_window = event.at >= since;
+        match event.kind {
+            IncidentEventKind::Failed => {
+                open.entry(key).or_insert(event);
+                if in_window {
+                    tally(&mut tallies, &mut index, &event.workspace, &event.repo_slug, &event.repo_name)
+                        .reliability
+                        .failures += 1;
+                }
+            }
+            IncidentEventKind::Recovered => {
+                let started = open.remove(&key);
+                if in_window {
+                    let secs = match started {
+                        Some(failed) => (event.at - failed.at).num_seconds().max(0) as u64,
+                        None => event.duration_secs.unwrap_or(0),
+                    };
+                    tally(&mut tallies, &mut index, &event.workspace, &event.repo_slug, &event.repo_name)
+                        .outage_secs
+                        .push(secs);
+                }
+            }
+        }
+    }
+
+    for failed in open.into_values() {
+        let entry = tally(&mut tallies, &mut index, &failed.workspace, &failed.repo_slug, &failed.repo_name);
+        entry.reliability.failing_since = Some(failed.at);
+        if include_open {
+            entry.outage_secs.push((until - failed.at).num_seconds().max(0) as u64);
+        }
+    }
+
+    let pipelines = tallies
+        .into_iter()
+        .map(|Tally { mut reliability, outage_secs }| {
+            reliability.failures_per_week =
+                f64::from(reliability.failures) * 7.0 / f64::from(window_days.max(1));
+            reliability.outages = outage_secs.len() as u32;
+            reliability.mttr_secs = (!outage_secs.is_empty())
+                .then(|| outage_secs.iter().sum::<u64>() / outage_secs.len() as u64);
+            reliability.longest_outage_secs = outage_secs.iter().max().copied();
+            reliability
+        })
+        .collect();
+
+    ReliabilityReport {
+        since,
+        until,
+        window_days,
+        include_open,
+        pipelines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, day, hour, 0, 0).unwrap()
+    }
+
+    fn event(slug: &str, kind: IncidentEventKind, at: DateTime<Utc>) -> IncidentEvent {
+        IncidentEvent {
+            workspace: "ws".to_string(),
+            repo_slug: slug.to_string(),
+            repo_name: slug.to_string(),
+            kind,
+            at,
+            duration_secs: None,
+            url: None,
+        }
+    }
+
+    fn failed(slug: &str, at: DateTime<Utc>) -> IncidentEvent {
+        event(slug, IncidentEventKind::Failed, at)
+    }
+
+    fn recovered(slug: &str, at: DateTime<Utc>) -> IncidentEvent {
+        event(slug, IncidentEventKind::Recovered, at)
+    }
+
+    fn monitored(slug: &str) -> MonitoredPipeline {
+        MonitoredPipeline {
+            workspace: "ws".to_string(),
+            repo_slug: slug.to_string(),
+            repo_name: slug.to_string(),
+            ..MonitoredPipeline::default()
+        }
+    }
+
+    #[test]
+    fn mttr_frequency_and_longest_outage_per_pipeline() {
+        let events = vec![
+            // Ended before the window: ignored
+            failed("api", at(1, 9)),
+            recovered("api", at(1, 10)),
+            failed("api", at(10, 9)),
+            recovered("api", at(10, 10)),
+            failed("api", at(12, 9)),
+            recovered("api", at(12, 12)),
+        ];
+
+        let report = reliability_report(&events, &[monitored("api")], at(15, 9), 7, false);
+
+        let api = &report.pipelines[0];
+        assert_eq!(api.failures, 2);
+        assert_eq!(api.failures_per_week, 2.0);
+        assert_eq!(api.outages, 2);
+        assert_eq!(api.mttr_secs, Some(2 * 3600));
+        assert_eq!(api.longest_outage_secs, Some(3 * 3600));
+        assert_eq!(api.failing_since, None);
+    }
+
+    #[test]
+    fn open_outages_count_to_the_end_of_the_window_only_when_asked() {
+        let events = vec![
+            failed("api", at(10, 9)),
+            recovered("api", at(10, 10)),
+            failed("api", at(14, 9)),
+        ];
+
+        let excluded = reliability_report(&events, &[], at(15, 9), 30, false);
+        let included = reliability_report(&events, &[], at(15, 9), 30, true);
+
+        assert_eq!(excluded.pipelines[0].mttr_secs, Some(3600));
+        assert_eq!(excluded.pipelines[0].failing_since, Some(at(14, 9)));
+        assert_eq!(included.pipelines[0].outages, 2);
+        assert_eq!(included.pipelines[0].mttr_secs, Some((3600 + 24 * 3600) / 2));
+        assert_eq!(included.pipelines[0].longest_outage_secs, Some(24 * 3600));
+    }
+
+    #[test]
+    fn pipelines_without_incidents_are_listed_once() {
+        let report = reliability_report(
+            &[],
+            &[monitored("api"), monitored("api"), monitored("web")],
+            at(15, 9),
+            30,
+            true,
+        );
+
+        let slugs: Vec<&str> = report.pipelines.iter().map(|p| p.repo_slug.as_str()).collect();
+        assert_eq!(slugs, ["api", "web"]);
+        assert_eq!(report.pipelines[0].failures, 0);
+        assert_eq!(report.pipelines[0].mttr_secs, None);
+        assert_eq!(report.pipelines[0].longest_outage_secs, None);
+    }
+
+    #[test]
+    fn clock_set_back_between_failure_and_recovery_gives_zero_length_outage() {
+        let events = vec![failed("api", at(12, 10)), recovered("api", at(12, 9))];
+
+        let report = reliability_report(&events, &[], at(15, 9), 7, false);
+
+        assert_eq!(report.pipelines[0].mttr_secs, Some(0));
+        assert_eq!(report.pipelines[0].failing_since, None);
+    }
+
+    #[test]
+    fn window_must_fit_the_kept_events() {
+        assert!(validate_window(30).is_ok());
+        assert!(validate_window(0).is_err());
+        assert!(validate_window(INCIDENT_EVENT_RETENTION_DAYS as u32 + 1).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::commands::save_config_helper;
+use crate::config::AppState;
+use std::future::Future;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// How long background tasks get to stop and the config to be written before exiting anyway
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Cancellation shared by the background tasks, and the tasks to wait for on quit
+#[derive(Default)]
+pub struct Shutdown {
+    pub token: CancellationToken,
+    /// Polling loop and refresh task, stopped by `token`
+    tasks: std::sync::Mutex<Vec<tauri::async_runtime::JoinHandle<()>>>,
+    /// Config writer, which stops once its queue is closed and drained
+    config_writer: std::sync::Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    /// Activity log writer, which likewise stops once its queue is closed
+    activity_writer: std::sync::Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+impl Shutdown {
+    /// Wait for `task` to stop when the app quits
+    pub fn track(&self, task: tauri::async_runtime::JoinHandle<()>) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.push(task);
+        }
+    }
+
+    pub fn track_config_writer(&self, task: tauri::async_runtime::JoinHandle<()>) {
+        if let Ok(mut writer) = self.config_writer.lock() {
+            *writer = Some(task);
+        }
+    }
+
+    pub fn track_activity_writer(&self, task: tauri::async_runtime::JoinHandle<()>) {
+        if let Ok(mut writer) = self.activity_writer.lock() {
+            *writer = Some(task);
+        }
+    }
+}
+
+/// Stop background tasks, flush the config and exit
+///
+/// Checks in flight are cancelled, which drops their HTTP requests. A second quit
+/// while this runs is ignored.
+pub async fn shutdown(app_handle: AppHandle) {
+    let shutdown = app_handle.state::<Shutdown>();
+    if shutdown.token.is_cancelled() {
+        return;
+    }
+    tracing::info!("Shutting down");
+    shutdown.token.cancel();
+    let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+
+    let tasks = shutdown.tasks.lock().map(|mut t| std::mem::take(&mut *t)).unwrap_or_default();
+    if !wait_until(deadline, tasks).await {
+        tracing::warn!("Background tasks did not stop in time");
+    }
+
+    // Queue the final state, then close the queue so the writer drains it and stops
+    let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+    if let Err(e) = save_config_helper(&app_handle, &state).await {
+        tracing::error!("Failed to save config on shutdown: {}", e);
+    }
+    state.lock().await.config_write_tx = None;
+    let writer = shutdown.config_writer.lock().ok().and_then(|mut w| w.take());
+    if !wait_until(deadline, writer).await {
+        tracing::warn!("Config writer did not finish in time");
+    }
+
+    // Same for the activity log, whose writer may still be waiting out its debounce
+    let final_log = {
+        let mut state_guard = state.lock().await;
+        let entries = state_guard.activity.entries();
+        state_guard.activity_write_tx.take().map(|tx| (tx, entries))
+    };
+    if let Some((tx, entries)) = final_log {
+        if !matches!(tokio::time::timeout_at(deadline, tx.send(entries)).await, Ok(Ok(()))) {
+            tracing::error!("Failed to queue the activity log on shutdown");
+        }
+    }
+    let writer = shutdown.activity_writer.lock().ok().and_then(|mut w| w.take());
+    if !wait_until(deadline, writer).await {
+        tracing::warn!("Activity log writer did not finish in time");
+    }
+
+    app_handle.exit(0);
+}
+
+/// Await every task until `deadline`; returns false if any was still running then
+async fn wait_until<F: Future>(deadline: Instant, tasks: impl IntoIterator<Item = F>) -> bool {
+    for task in tasks {
+        if tokio::time::timeout_at(deadline, task).await.is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Shut down cleanly on SIGTERM, e.g. from launchctl or systemd
+#[cfg(unix)]
+pub async fn shutdown_on_sigterm(app_handle: AppHandle) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(sigterm) => sigterm,
+        Err(e) => {
+            tracing::warn!("Failed to listen for SIGTERM: {}", e);
+            return;
+        }
+    };
+    if sigterm.recv().await.is_some() {
+        tracing::info!("SIGTERM received");
+        shutdown(app_handle).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancelled_tasks_stop_before_the_deadline() {
+        let token = CancellationToken::new();
+        let tasks: Vec<_> = (0..3)
+            .map(|_| {
+                let token = token.clone();
+                tokio::spawn(async move { token.cancelled().await })
+            })
+            .collect();
+
+        token.cancel();
+
+        assert!(wait_until(Instant::now() + Duration::from_secs(1), tasks).await);
+    }
+
+    #[tokio::test]
+    async fn a_task_ignoring_cancellation_is_given_up_on() {
+        let token = CancellationToken::new();
+        let stuck = tokio::spawn(std::future::pending::<()>());
+
+        token.cancel();
+
+        assert!(!wait_until(Instant::now() + Duration::from_millis(20), [stuck]).await);
+    }
+}
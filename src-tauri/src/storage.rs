@@ -0,0 +1,87 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_notification::NotificationExt;
+
+/// Environment variable that overrides where config and credentials are stored
+pub const CONFIG_DIR_ENV: &str = "CDMENU_CONFIG_DIR";
+
+/// Tooltip title while settings can't be saved
+pub const STORAGE_ERROR_TITLE: &str = "cdMenu - storage error";
+
+/// Result of the startup storage self-check
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StorageStatus {
+    /// Resolved config directory, if one could be determined
+    pub config_dir: Option<PathBuf>,
+    /// True when `CDMENU_CONFIG_DIR` is set
+    pub overridden: bool,
+    /// Why the directory isn't writable, if it isn't
+    pub error: Option<String>,
+}
+
+/// Directory holding config.json and credentials, honouring `CDMENU_CONFIG_DIR`
+pub fn config_dir<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    match config_dir_override() {
+        Some(dir) => Ok(dir),
+        None => app_handle
+            .path()
+            .app_config_dir()
+            .map_err(|e| format!("Failed to get config dir: {}", e)),
+    }
+}
+
+fn config_dir_override() -> Option<PathBuf> {
+    std::env::var_os(CONFIG_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Check that the config directory can be created and written to
+pub fn check_storage<R: Runtime>(app_handle: &AppHandle<R>) -> StorageStatus {
+    let overridden = config_dir_override().is_some();
+    match config_dir(app_handle) {
+        Ok(dir) => StorageStatus {
+            error: write_probe(&dir).err(),
+            config_dir: Some(dir),
+            overridden,
+        },
+        Err(e) => StorageStatus {
+            config_dir: None,
+            overridden,
+            error: Some(e),
+        },
+    }
+}
+
+// Helper: Create, write and remove a probe file in `dir`
+fn write_probe(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+
+    let probe = dir.join(".write-probe");
+    std::fs::write(&probe, b"ok").map_err(|e| format!("Failed to write to config dir: {}", e))?;
+    std::fs::remove_file(&probe)
+        .map_err(|e| format!("Failed to clean up config dir probe: {}", e))
+}
+
+/// Tell the user once, at startup, that settings won't be saved
+pub fn notify_storage_error<R: Runtime>(app_handle: &AppHandle<R>, status: &StorageStatus) {
+    let Some(error) = &status.error else {
+        return;
+    };
+    let path = status
+        .config_dir
+        .as_ref()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| "unknown location".to_string());
+
+    let _ = app_handle
+        .notification()
+        .builder()
+        .title("cdMenu can't save settings")
+        .body(format!(
+            "{}\n{}\nSet {} to a writable folder.",
+            path, error, CONFIG_DIR_ENV
+        ))
+        .show();
+}
@@ -1,22 +1,198 @@
-use crate::config::{OverallStatus, PipelineState};
+use crate::bitbucket::get_repository_url;
+use crate::commands;
+use crate::config::{
+    AppState, ConnectionStatus, IconTheme, OverallStatus, PipelineState, PipelineStatusInfo, QuickAction, RunRecord,
+    UnknownReason,
+};
+use crate::drift::EnvironmentDrift;
+use crate::i18n::{t, tf, tp};
+use crate::incidents::format_duration;
+use crate::pipeline_detail::{self, PipelineKey};
+use crate::polling::{self, Notifier};
+use crate::reliability::{ReliabilityReport, MTTR_MENU_WINDOW_DAYS};
+use crate::shutdown;
+use crate::wallboard;
+use chrono::{DateTime, Local, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tauri::{
     image::Image,
-    menu::{IconMenuItem, Menu, MenuItem, PredefinedMenuItem},
-    tray::TrayIconBuilder,
-    AppHandle, Emitter, Manager, Runtime,
+    menu::{CheckMenuItem, IconMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{TrayIcon, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Emitter, Manager, Theme,
 };
-use std::sync::RwLock;
-use std::collections::HashMap;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+// Pipeline behind each per-pipeline menu item of each tray, for click handling
+static PIPELINE_STATUS_MAP: RwLock<BTreeMap<String, HashMap<String, PipelineStatusInfo>>> =
+    RwLock::new(BTreeMap::new());
+
+// Web page behind each pull request item of each tray, for click handling. Kept apart from
+// MENU_CONTROLLERS, which is held across menu calls that wait for the main thread the
+// click handler runs on
+static PULL_REQUEST_URLS: RwLock<BTreeMap<String, HashMap<String, String>>> =
+    RwLock::new(BTreeMap::new());
+
+// Menu currently shown in each tray, so repeated polls edit it instead of rebuilding (and closing) it
+static MENU_CONTROLLERS: RwLock<BTreeMap<String, TrayMenuController>> = RwLock::new(BTreeMap::new());
+
+// Polling interval shown as checked, and the check items of each tray's current menu
+static CHECKED_INTERVAL: AtomicU64 = AtomicU64::new(0);
+static INTERVAL_ITEMS: RwLock<BTreeMap<String, Vec<(u64, CheckMenuItem<tauri::Wry>)>>> =
+    RwLock::new(BTreeMap::new());
+
+// Whether pipeline items end with their recent results
+static SHOW_HISTORY_GLYPHS: AtomicBool = AtomicBool::new(true);
+
+// Whether pipelines with no run data collapse into a single menu line
+static HIDE_UNKNOWN_IN_MENU: AtomicBool = AtomicBool::new(false);
+
+// Whether running pipelines are listed in their own section instead of under their project
+static GROUP_RUNNING_SEPARATELY: AtomicBool = AtomicBool::new(false);
 
-// Store pipeline URLs for click handling
-static PIPELINE_URLS: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+// Connection line added to every tooltip while Bitbucket can't be reached reliably
+static CONNECTION_STATUS: RwLock<ConnectionStatus> = RwLock::new(ConnectionStatus::Connected);
 
+// Failures and recoveries since launch, summarized in the tooltip once something failed
+static SESSION_FAILURES: AtomicU64 = AtomicU64::new(0);
+static SESSION_RECOVERIES: AtomicU64 = AtomicU64::new(0);
+
+// Mean time to recovery over `MTTR_MENU_WINDOW_DAYS`, by (workspace, repo_slug)
+static MTTR_SECS: RwLock<BTreeMap<(String, String), u64>> = RwLock::new(BTreeMap::new());
+
+// Icon variant picked in settings, and the system appearance `IconTheme::Auto` follows
+static ICON_THEME: RwLock<IconTheme> = RwLock::new(IconTheme::Auto);
+static CURRENT_THEME: RwLock<Theme> = RwLock::new(Theme::Light);
+
+// Why the latest status menu couldn't be built, cleared once one builds
+static LAST_MENU_BUILD_ERROR: RwLock<Option<String>> = RwLock::new(None);
+
+// Running failure flashes by tray id, so an icon update or click can cancel its tray's flash
+static FLASH: std::sync::Mutex<BTreeMap<String, CancellationToken>> =
+    std::sync::Mutex::new(BTreeMap::new());
+
+/// Id of the tray icon for the single configured account
 pub const TRAY_ID: &str = "main";
 
+/// Ids of the tray icons shown, so a second one (e.g. for another account) gets a unique id
+#[derive(Debug, Clone, Default)]
+pub struct TrayManager {
+    ids: Arc<std::sync::Mutex<Vec<String>>>,
+    /// Number of the next generated id; ids aren't reused, so they're unique without the lock
+    next: Arc<AtomicU64>,
+    /// (workspace, tray id) of each per-workspace tray
+    workspaces: Arc<std::sync::Mutex<Vec<(String, String)>>>,
+}
+
+impl TrayManager {
+    /// Record a tray built under a known id, such as `TRAY_ID`
+    pub fn register(&self, id: &str) {
+        if let Ok(mut ids) = self.ids.lock() {
+            if !ids.iter().any(|existing| existing == id) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+
+    /// Reserve a new id of the form "tray-0", "tray-1", ...
+    pub fn next_id(&self) -> String {
+        let id = format!("tray-{}", self.next.fetch_add(1, Ordering::Relaxed));
+        if let Ok(mut ids) = self.ids.lock() {
+            ids.push(id.clone());
+        }
+        id
+    }
+
+    /// (workspace, tray id) of each per-workspace tray, in the order they were added
+    pub fn workspace_trays(&self) -> Vec<(String, String)> {
+        self.workspaces.lock().map(|w| w.clone()).unwrap_or_default()
+    }
+
+    /// Reserve a tray id for a workspace
+    fn add_workspace_tray(&self, workspace: &str) -> String {
+        let id = self.next_id();
+        if let Ok(mut workspaces) = self.workspaces.lock() {
+            workspaces.push((workspace.to_string(), id.clone()));
+        }
+        id
+    }
+
+    /// Release a workspace's tray id
+    fn remove_workspace_tray(&self, workspace: &str) {
+        let removed = self.workspaces.lock().ok().and_then(|mut workspaces| {
+            let pos = workspaces.iter().position(|(w, _)| w == workspace)?;
+            Some(workspaces.remove(pos).1)
+        });
+        if let (Some(id), Ok(mut ids)) = (removed, self.ids.lock()) {
+            ids.retain(|existing| *existing != id);
+        }
+    }
+}
+
+/// Show a tray per workspace in `workspaces`, or only the main tray when it's empty
+pub fn sync_workspace_trays(app_handle: &AppHandle, trays: &TrayManager, workspaces: &[String]) {
+    for (workspace, id) in trays.workspace_trays() {
+        if !workspaces.contains(&workspace) {
+            trays.remove_workspace_tray(&workspace);
+            let _ = app_handle.remove_tray_by_id(&id);
+            forget_tray_menu(&id);
+        }
+    }
+    let shown = trays.workspace_trays();
+    for workspace in workspaces {
+        if !shown.iter().any(|(w, _)| w == workspace) {
+            let id = trays.add_workspace_tray(workspace);
+            if let Err(e) = build_workspace_tray(app_handle, &id) {
+                tracing::error!("Failed to create tray for workspace {}: {}", workspace, e);
+            }
+        }
+    }
+    if let Some(main) = app_handle.tray_by_id(TRAY_ID) {
+        let _ = main.set_visible(workspaces.is_empty());
+    }
+}
+
+/// Build a workspace's tray; its menu clicks reach the main tray's handler, which sees every menu event
+fn build_workspace_tray(app_handle: &AppHandle, tray_id: &str) -> Result<(), tauri::Error> {
+    let menu = build_initial_menu(app_handle, tray_id)?;
+    TrayIconBuilder::with_id(tray_id)
+        .icon(Image::from_bytes(tray_icon(TrayStatus::Gray, dark_icons()))?)
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .tooltip(t("tooltip.loading"))
+        .build(app_handle)?;
+    Ok(())
+}
+
+/// Polling intervals offered in the tray submenu (seconds, label)
+const INTERVAL_CHOICES: &[(u64, &str)] = &[
+    (30, "30s"),
+    (60, "1m"),
+    (120, "2m"),
+    (300, "5m"),
+    (900, "15m"),
+    (1800, "30m"),
+];
+
 // Embed the tray icons at compile time
 const ICON_GREEN: &[u8] = include_bytes!("../icons/tray-green.png");
 const ICON_RED: &[u8] = include_bytes!("../icons/tray-red.png");
 const ICON_GRAY: &[u8] = include_bytes!("../icons/tray-gray.png");
+const ICON_RED_DIM: &[u8] = include_bytes!("../icons/tray-red-dim.png");
+
+// Darker tray icons for a light menu bar
+const ICON_GREEN_LIGHT: &[u8] = include_bytes!("../icons/tray-green-light.png");
+const ICON_RED_LIGHT: &[u8] = include_bytes!("../icons/tray-red-light.png");
+const ICON_GRAY_LIGHT: &[u8] = include_bytes!("../icons/tray-gray-light.png");
+const ICON_RED_DIM_LIGHT: &[u8] = include_bytes!("../icons/tray-red-dim-light.png");
+
+/// How long each frame of the failure flash is shown
+const FLASH_FRAME: Duration = Duration::from_millis(500);
+/// Frames in a failure flash; even, so the last frame is solid red
+const FLASH_FRAMES: u32 = 10;
 
 // Menu icons (smaller versions)
 const MENU_ICON_GREEN: &[u8] = include_bytes!("../icons/menu-green.png");
@@ -24,6 +200,12 @@ const MENU_ICON_RED: &[u8] = include_bytes!("../icons/menu-red.png");
 const MENU_ICON_GRAY: &[u8] = include_bytes!("../icons/menu-gray.png");
 const MENU_ICON_BLUE: &[u8] = include_bytes!("../icons/menu-blue.png");
 
+// Lighter menu icons for a dark menu
+const MENU_ICON_GREEN_DARK: &[u8] = include_bytes!("../icons/menu-green-dark.png");
+const MENU_ICON_RED_DARK: &[u8] = include_bytes!("../icons/menu-red-dark.png");
+const MENU_ICON_GRAY_DARK: &[u8] = include_bytes!("../icons/menu-gray-dark.png");
+const MENU_ICON_BLUE_DARK: &[u8] = include_bytes!("../icons/menu-blue-dark.png");
+
 /// Tray status indicator
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum TrayStatus {
@@ -35,54 +217,101 @@ pub enum TrayStatus {
     Gray,
 }
 
+/// What the tray menu should show
+#[derive(Clone, Copy, Debug)]
+pub enum MenuContext<'a> {
+    /// No credentials saved yet
+    NoCredentials,
+    /// Credentials saved but nothing selected to monitor
+    NoPipelines,
+    /// Pipeline status (None while a check is in progress)
+    Status(Option<&'a OverallStatus>),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum MenuContextKind {
+    NoCredentials,
+    NoPipelines,
+}
+
 /// Build the system tray with menu
-pub fn build_tray<R: Runtime>(app: &tauri::App<R>) -> Result<(), tauri::Error> {
+pub fn build_tray(app: &tauri::App, tray_id: &str) -> Result<(), tauri::Error> {
     // Create initial menu (will be updated dynamically)
-    let menu = build_initial_menu(app)?;
+    let menu = build_initial_menu(app, tray_id)?;
 
     // Load initial gray icon
-    let icon = Image::from_bytes(ICON_GRAY)?;
+    let icon = Image::from_bytes(tray_icon(TrayStatus::Gray, dark_icons()))?;
 
-    let _tray = TrayIconBuilder::with_id(TRAY_ID)
+    let _tray = TrayIconBuilder::with_id(tray_id)
         .icon(icon)
         .menu(&menu)
         .show_menu_on_left_click(true)
-        .tooltip("cdMenu - Loading...")
+        .tooltip(t("tooltip.loading"))
         .on_menu_event(|app, event| {
             let id = event.id.as_ref();
             match id {
                 "refresh" => {
-                    log::info!("Refresh requested from tray menu");
+                    tracing::info!("Refresh requested from tray menu");
                     let _ = app.emit("trigger-refresh", ());
                 }
-                "settings" => {
-                    log::info!("Opening settings window");
+                "settings" | "setup_credentials" | "configure_pipelines" | "no_pipeline_data" => {
+                    tracing::info!("Opening settings window");
                     if let Some(window) = app.get_webview_window("settings") {
                         let _ = window.show();
                         let _ = window.set_focus();
                     }
                 }
+                "wallboard" => {
+                    tracing::info!("Opening wallboard window");
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = wallboard::open_wallboard(&app).await {
+                            tracing::error!("{}", e);
+                        }
+                    });
+                }
+                _ if id.starts_with("interval_") => {
+                    let Ok(seconds) = id["interval_".len()..].parse::<u64>() else {
+                        return;
+                    };
+                    tracing::info!("Polling interval set to {}s from tray menu", seconds);
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let state: tauri::State<Arc<Mutex<AppState>>> = app.state();
+                        if let Err(e) = commands::apply_polling_interval(&app, &state, seconds).await {
+                            tracing::error!("Failed to set polling interval: {}", e);
+                        }
+                    });
+                }
                 "quit" => {
-                    log::info!("Quit requested from tray menu");
-                    app.exit(0);
+                    tracing::info!("Quit requested from tray menu");
+                    tauri::async_runtime::spawn(shutdown::shutdown(app.clone()));
+                }
+                _ if id.starts_with("pull_request_") => {
+                    if let Some(url) = pull_request_url(id) {
+                        let _ = open::that(url);
+                    }
                 }
                 _ => {
                     // Check if it's a pipeline click
                     if id.starts_with("pipeline_") {
-                        if let Ok(urls) = PIPELINE_URLS.read() {
-                            if let Some(url_map) = urls.as_ref() {
-                                if let Some(url) = url_map.get(id) {
-                                    log::info!("Opening pipeline URL: {}", url);
-                                    let _ = open::that(url);
-                                }
-                            }
+                        // Items share ids across trays, so any tray's entry will do
+                        let status = PIPELINE_STATUS_MAP
+                            .read()
+                            .ok()
+                            .and_then(|maps| maps.values().find_map(|map| map.get(id).cloned()));
+                        if let Some(status) = status {
+                            handle_pipeline_action(app, id, &status);
                         }
                     }
                 }
             }
         })
-        .on_tray_icon_event(|_tray, _event| {
-            // Menu shows on click, no additional handling needed
+        .on_tray_icon_event(|tray, event| {
+            // Menu shows on click; opening it acknowledges a flashing failure
+            if matches!(event, TrayIconEvent::Click { .. }) {
+                stop_flashing_on_click(tray);
+            }
         })
         .build(app)?;
 
@@ -90,136 +319,1058 @@ pub fn build_tray<R: Runtime>(app: &tauri::App<R>) -> Result<(), tauri::Error> {
 }
 
 /// Build the initial menu before any status is available
-fn build_initial_menu<R: Runtime>(app: &tauri::App<R>) -> Result<Menu<R>, tauri::Error> {
-    let status_item = MenuItem::with_id(app, "status", "Loading...", false, None::<&str>)?;
+fn build_initial_menu<M: Manager<tauri::Wry>>(
+    app: &M,
+    tray_id: &str,
+) -> Result<Menu<tauri::Wry>, tauri::Error> {
+    let status_item = MenuItem::with_id(app, "status", t("menu.loading"), false, None::<&str>)?;
     let separator = PredefinedMenuItem::separator(app)?;
-    let refresh = MenuItem::with_id(app, "refresh", "Refresh Now", true, None::<&str>)?;
-    let settings = MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
-    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let refresh = MenuItem::with_id(app, "refresh", t("menu.refresh"), true, None::<&str>)?;
+    let wallboard = MenuItem::with_id(app, "wallboard", t("menu.wallboard"), true, None::<&str>)?;
+    let settings = MenuItem::with_id(app, "settings", t("menu.settings"), true, None::<&str>)?;
+    let interval = build_interval_submenu(app, tray_id)?;
+    let quit = MenuItem::with_id(app, "quit", t("menu.quit"), true, None::<&str>)?;
+
+    Menu::with_items(
+        app,
+        &[&status_item, &separator, &refresh, &wallboard, &settings, &interval, &quit],
+    )
+}
+
+/// Build a minimal menu for when the status menu can't be built
+///
+/// Some Wayland compositors reject individual menu items; with only these
+/// the tray can still refresh, reach settings and quit.
+fn build_initial_menu_fallback<M: Manager<tauri::Wry>>(
+    app: &M,
+) -> Result<Menu<tauri::Wry>, tauri::Error> {
+    let refresh = MenuItem::with_id(app, "refresh", t("menu.refresh"), true, None::<&str>)?;
+    let settings = MenuItem::with_id(app, "settings", t("menu.settings"), true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", t("menu.quit"), true, None::<&str>)?;
+
+    Menu::with_items(app, &[&refresh, &settings, &quit])
+}
+
+/// Build a tray's "Polling interval" submenu with the current interval checked
+fn build_interval_submenu<M: Manager<tauri::Wry>>(
+    manager: &M,
+    tray_id: &str,
+) -> Result<Submenu<tauri::Wry>, tauri::Error> {
+    let current = CHECKED_INTERVAL.load(Ordering::Relaxed);
+    let mut choices = Vec::new();
+    for (seconds, label) in INTERVAL_CHOICES {
+        let item = CheckMenuItem::with_id(
+            manager,
+            format!("interval_{}", seconds),
+            *label,
+            true,
+            *seconds == current,
+            None::<&str>,
+        )?;
+        choices.push((*seconds, item));
+    }
+
+    let item_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> =
+        choices.iter().map(|(_, item)| item as _).collect();
+    let submenu = Submenu::with_items(manager, t("menu.polling_interval"), true, &item_refs)?;
+
+    if let Ok(mut items) = INTERVAL_ITEMS.write() {
+        items.insert(tray_id.to_string(), choices);
+    }
+    Ok(submenu)
+}
+
+/// Check every tray's submenu item for the given interval (and uncheck the rest)
+pub fn set_checked_interval(seconds: u64) {
+    CHECKED_INTERVAL.store(seconds, Ordering::Relaxed);
+    // Copied out so the lock isn't held while the menu calls wait for the main thread
+    let items: Vec<(u64, CheckMenuItem<tauri::Wry>)> = INTERVAL_ITEMS
+        .read()
+        .map(|items| items.values().flatten().cloned().collect())
+        .unwrap_or_default();
+    for (choice, item) in items {
+        let _ = item.set_checked(choice == seconds);
+    }
+}
+
+/// Show or hide the recent-results glyphs after pipeline names
+pub fn set_show_history_glyphs(show: bool) {
+    SHOW_HISTORY_GLYPHS.store(show, Ordering::Relaxed);
+}
+
+/// Collapse pipelines with no run data into one "N with no pipeline data" line
+pub fn set_hide_unknown_in_menu(hide: bool) {
+    HIDE_UNKNOWN_IN_MENU.store(hide, Ordering::Relaxed);
+}
+
+/// List running and paused pipelines in a section of their own, longest running first
+pub fn set_group_running_separately(enabled: bool) {
+    GROUP_RUNNING_SEPARATELY.store(enabled, Ordering::Relaxed);
+}
+
+/// Draw light or dark icons, or follow the system appearance
+pub fn set_icon_theme(theme: IconTheme) {
+    if let Ok(mut current) = ICON_THEME.write() {
+        *current = theme;
+    }
+}
+
+/// Record the system appearance, which `IconTheme::Auto` follows
+pub fn set_current_theme(theme: Theme) {
+    if let Ok(mut current) = CURRENT_THEME.write() {
+        *current = theme;
+    }
+}
+
+/// Whether icons are drawn for a dark menu bar and menus
+fn dark_icons() -> bool {
+    let theme = ICON_THEME.read().map(|t| *t).unwrap_or_default();
+    match theme {
+        IconTheme::Light => false,
+        IconTheme::Dark => true,
+        IconTheme::Auto => CURRENT_THEME.read().is_ok_and(|t| *t == Theme::Dark),
+    }
+}
+
+/// Tray icon for a status, in the variant for the menu bar's appearance
+fn tray_icon(status: TrayStatus, dark: bool) -> &'static [u8] {
+    match (status, dark) {
+        (TrayStatus::Green, true) => ICON_GREEN,
+        (TrayStatus::Red, true) => ICON_RED,
+        (TrayStatus::Gray, true) => ICON_GRAY,
+        (TrayStatus::Green, false) => ICON_GREEN_LIGHT,
+        (TrayStatus::Red, false) => ICON_RED_LIGHT,
+        (TrayStatus::Gray, false) => ICON_GRAY_LIGHT,
+    }
+}
+
+/// Swap a menu icon for its dark-menu variant when `dark` is set
+fn themed_menu_icon(icon: &'static [u8], dark: bool) -> &'static [u8] {
+    const DARK_VARIANTS: [(&[u8], &[u8]); 4] = [
+        (MENU_ICON_GREEN, MENU_ICON_GREEN_DARK),
+        (MENU_ICON_RED, MENU_ICON_RED_DARK),
+        (MENU_ICON_GRAY, MENU_ICON_GRAY_DARK),
+        (MENU_ICON_BLUE, MENU_ICON_BLUE_DARK),
+    ];
+    if !dark {
+        return icon;
+    }
+    DARK_VARIANTS
+        .iter()
+        .find(|(light, _)| *light == icon)
+        .map_or(icon, |(_, dark)| *dark)
+}
+
+/// Record the connection status shown at the end of the tooltip
+pub fn set_connection_status(status: ConnectionStatus) {
+    if let Ok(mut current) = CONNECTION_STATUS.write() {
+        *current = status;
+    }
+}
+
+/// Record the MTTR shown in each pipeline's submenu; pipelines without outages get no line
+pub fn set_mttr(report: &ReliabilityReport) {
+    let mttr = report
+        .pipelines
+        .iter()
+        .filter_map(|p| Some(((p.workspace.clone(), p.repo_slug.clone()), p.mttr_secs?)))
+        .collect();
+    if let Ok(mut current) = MTTR_SECS.write() {
+        *current = mttr;
+    }
+}
+
+/// Why the latest status menu couldn't be built, if it couldn't
+pub fn last_menu_build_error() -> Option<String> {
+    LAST_MENU_BUILD_ERROR.read().ok().and_then(|e| e.clone())
+}
+
+// Helper: Record the outcome of building a status menu
+fn set_menu_build_error(error: Option<String>) {
+    if let Ok(mut current) = LAST_MENU_BUILD_ERROR.write() {
+        *current = error;
+    }
+}
+
+/// Record the session counts shown at the end of the tooltip
+pub fn set_session_counts(failures: u64, recoveries: u64) {
+    SESSION_FAILURES.store(failures, Ordering::Relaxed);
+    SESSION_RECOVERIES.store(recoveries, Ordering::Relaxed);
+}
+
+/// Tooltip line such as "Session: 3 failures, 2 recoveries", or None before the first failure
+fn session_line(failures: u64, recoveries: u64) -> Option<String> {
+    if failures == 0 {
+        return None;
+    }
+    Some(tf(
+        "tooltip.session",
+        &[
+            ("failures", &tp("tooltip.session_failures", failures as usize, &[])),
+            ("recoveries", &tp("tooltip.session_recoveries", recoveries as usize, &[])),
+        ],
+    ))
+}
+
+/// Tooltip line for a connection problem, or None when connected
+fn connection_status_line(status: ConnectionStatus) -> Option<String> {
+    match status {
+        ConnectionStatus::Connected => None,
+        ConnectionStatus::Degraded(errors) => {
+            Some(tp("tooltip.connection_degraded", errors as usize, &[]))
+        }
+        ConnectionStatus::Disconnected => Some(t("tooltip.disconnected").to_string()),
+    }
+}
+
+/// Pipelines left out of the menu (and the healthy counts) for having no run data
+pub fn hidden_unknown_count(status: &OverallStatus) -> usize {
+    if HIDE_UNKNOWN_IN_MENU.load(Ordering::Relaxed) {
+        status.unknown_count
+    } else {
+        0
+    }
+}
+
+/// Compact run history, e.g. " ✓✓✗", most recent last
+fn history_glyphs(history: &[RunRecord]) -> String {
+    if history.is_empty() || !SHOW_HISTORY_GLYPHS.load(Ordering::Relaxed) {
+        return String::new();
+    }
+    let glyphs: String = history.iter().map(|r| r.result.glyph()).collect();
+    format!(" {}", glyphs)
+}
+
+/// Update a tray's menu for the given context
+pub fn update_tray_menu(app_handle: &AppHandle, tray_id: &str, context: MenuContext) {
+    update_menu(app_handle, tray_id, None, context);
+}
+
+/// Show a status in the main tray's menu and in each workspace tray's menu
+pub fn update_status_menus(app_handle: &AppHandle, trays: &TrayManager, status: Option<&OverallStatus>) {
+    update_menu(app_handle, TRAY_ID, None, MenuContext::Status(status));
+    for (workspace, id) in trays.workspace_trays() {
+        update_menu(app_handle, &id, Some(&workspace), MenuContext::Status(status));
+    }
+}
+
+// Helper: Update a tray's menu, listing only `workspace`'s pipelines when given
+fn update_menu(app_handle: &AppHandle, tray_id: &str, workspace: Option<&str>, context: MenuContext) {
+    if let Ok(mut controllers) = MENU_CONTROLLERS.write() {
+        controllers
+            .entry(tray_id.to_string())
+            .or_insert_with(|| TrayMenuController::new(tray_id, workspace))
+            .update(app_handle, context);
+    }
+}
+
+// Helper: Drop what a removed tray's menu showed
+fn forget_tray_menu(tray_id: &str) {
+    if let Ok(mut controllers) = MENU_CONTROLLERS.write() {
+        controllers.remove(tray_id);
+    }
+    if let Ok(mut maps) = PIPELINE_STATUS_MAP.write() {
+        maps.remove(tray_id);
+    }
+    if let Ok(mut urls) = PULL_REQUEST_URLS.write() {
+        urls.remove(tray_id);
+    }
+    if let Ok(mut items) = INTERVAL_ITEMS.write() {
+        items.remove(tray_id);
+    }
+}
+
+/// Forget the shown menus so the next update rebuilds every item, e.g. in a new language
+pub fn reset_tray_menu() {
+    if let Ok(mut controllers) = MENU_CONTROLLERS.write() {
+        for controller in controllers.values_mut() {
+            controller.shown = None;
+        }
+    }
+}
+
+/// Build menu for an unconfigured state with a shortcut into settings
+fn build_setup_menu(
+    app_handle: &AppHandle,
+    tray_id: &str,
+    message: &str,
+    action_id: &str,
+    action_label: &str,
+) -> Result<Menu<tauri::Wry>, tauri::Error> {
+    let status_item = MenuItem::with_id(app_handle, "status", message, false, None::<&str>)?;
+    let action = MenuItem::with_id(app_handle, action_id, action_label, true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app_handle)?;
+    let refresh = MenuItem::with_id(app_handle, "refresh", t("menu.refresh"), true, None::<&str>)?;
+    let wallboard = MenuItem::with_id(app_handle, "wallboard", t("menu.wallboard"), true, None::<&str>)?;
+    let settings = MenuItem::with_id(app_handle, "settings", t("menu.settings"), true, None::<&str>)?;
+    let interval = build_interval_submenu(app_handle, tray_id)?;
+    let quit = MenuItem::with_id(app_handle, "quit", t("menu.quit"), true, None::<&str>)?;
+
+    Menu::with_items(
+        app_handle,
+        &[
+            &status_item,
+            &action,
+            &separator,
+            &refresh,
+            &wallboard,
+            &settings,
+            &interval,
+            &quit,
+        ],
+    )
+}
+
+/// Status to list pipelines for, or None when the placeholder should be shown
+/// (no status yet, or every monitored pipeline was removed)
+fn pipelines_to_show(status: Option<&OverallStatus>) -> Option<&OverallStatus> {
+    status.filter(|s| !s.pipeline_statuses().is_empty())
+}
+
+/// A clickable item inside a pipeline's submenu
+#[derive(Debug, Clone, PartialEq)]
+struct MenuAction {
+    id: String,
+    label: String,
+    enabled: bool,
+}
+
+/// One pipeline's submenu
+#[derive(Debug, Clone, PartialEq)]
+struct PipelineRow {
+    /// (workspace, repo_slug)
+    key: (String, String),
+    text: String,
+    icon: &'static [u8],
+    checked: String,
+    /// Variables of a custom or manual run, e.g. "region=eu, version=2.3.1"
+    variables: Option<String>,
+    /// Deployment drift, e.g. "staging +4 vs prod"
+    drift: Option<String>,
+    /// Mean time to recovery, e.g. "MTTR: 42m over last 30d"
+    mttr: Option<String>,
+    links: Vec<MenuAction>,
+    /// Quick actions, then "Remove from monitoring" once Pipelines is disabled
+    quick_actions: Vec<MenuAction>,
+}
+
+impl PipelineRow {
+    fn actions(&self) -> impl Iterator<Item = &MenuAction> {
+        self.links.iter().chain(&self.quick_actions)
+    }
 
-    Menu::with_items(app, &[&status_item, &separator, &refresh, &settings, &quit])
+    /// Same pipeline with the same items, whatever their text and state
+    fn same_layout(&self, other: &PipelineRow) -> bool {
+        let ids = |row: &PipelineRow| -> Vec<(String, String)> {
+            row.actions().map(|a| (a.id.clone(), a.label.clone())).collect()
+        };
+        self.key == other.key
+            && self.variables.is_some() == other.variables.is_some()
+            && self.drift.is_some() == other.drift.is_some()
+            && self.mttr.is_some() == other.mttr.is_some()
+            && self.links.len() == other.links.len()
+            && ids(self) == ids(other)
+    }
+}
+
+/// One of the user's open pull requests
+#[derive(Debug, Clone, PartialEq)]
+struct PullRequestRow {
+    id: String,
+    text: String,
+    icon: &'static [u8],
+    url: Option<String>,
+}
+
+/// What the status menu shows, independent of the Tauri items that display it
+#[derive(Debug, Clone, PartialEq)]
+struct StatusMenuModel {
+    summary: String,
+    /// Project header and its pipelines
+    groups: Vec<(String, Vec<PipelineRow>)>,
+    /// Collapsed line for hidden pipelines with no run data
+    no_data: Option<String>,
+    /// "My pull requests" section
+    pull_requests: Vec<PullRequestRow>,
+    last_checked: String,
+}
+
+impl StatusMenuModel {
+    fn rows(&self) -> impl Iterator<Item = &PipelineRow> {
+        self.groups.iter().flat_map(|(_, rows)| rows)
+    }
+
+    /// Whether both models need the same items, so one can be shown by editing the other
+    fn same_layout(&self, other: &StatusMenuModel) -> bool {
+        let pr_ids = |model: &StatusMenuModel| -> Vec<(String, bool)> {
+            model
+                .pull_requests
+                .iter()
+                .map(|pr| (pr.id.clone(), pr.url.is_some()))
+                .collect()
+        };
+        self.no_data == other.no_data
+            && pr_ids(self) == pr_ids(other)
+            && self.groups.len() == other.groups.len()
+            && self
+                .groups
+                .iter()
+                .zip(&other.groups)
+                .all(|((a, a_rows), (b, b_rows))| {
+                    a == b
+                        && a_rows.len() == b_rows.len()
+                        && a_rows.iter().zip(b_rows).all(|(a, b)| a.same_layout(b))
+                })
+    }
 }
 
-/// Update the tray menu with current pipeline status
-pub fn update_tray_menu(app_handle: &AppHandle, status: Option<&OverallStatus>) {
-    if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
-        if let Ok(menu) = build_status_menu(app_handle, status) {
+/// Edit to an item of the shown status menu; rows are numbered across all groups
+#[derive(Debug, Clone, PartialEq)]
+enum MenuChange {
+    Summary(String),
+    PipelineText(usize, String),
+    PipelineIcon(usize, &'static [u8]),
+    Checked(usize, String),
+    Variables(usize, String),
+    Drift(usize, String),
+    Mttr(usize, String),
+    /// Row, then link or quick action (links first)
+    ActionEnabled(usize, usize, bool),
+    PullRequestText(usize, String),
+    PullRequestIcon(usize, &'static [u8]),
+    LastChecked(String),
+}
+
+/// Item edits that turn `old` into `new`, or None when pipelines were added,
+/// removed or reordered and the menu has to be rebuilt
+fn diff_menus(old: &StatusMenuModel, new: &StatusMenuModel) -> Option<Vec<MenuChange>> {
+    if !old.same_layout(new) {
+        return None;
+    }
+
+    let mut changes = Vec::new();
+    if old.summary != new.summary {
+        changes.push(MenuChange::Summary(new.summary.clone()));
+    }
+    for (row, (a, b)) in old.rows().zip(new.rows()).enumerate() {
+        if a.text != b.text {
+            changes.push(MenuChange::PipelineText(row, b.text.clone()));
+        }
+        if a.icon != b.icon {
+            changes.push(MenuChange::PipelineIcon(row, b.icon));
+        }
+        if a.checked != b.checked {
+            changes.push(MenuChange::Checked(row, b.checked.clone()));
+        }
+        if let (Some(a_vars), Some(b_vars)) = (&a.variables, &b.variables) {
+            if a_vars != b_vars {
+                changes.push(MenuChange::Variables(row, b_vars.clone()));
+            }
+        }
+        if let (Some(a_drift), Some(b_drift)) = (&a.drift, &b.drift) {
+            if a_drift != b_drift {
+                changes.push(MenuChange::Drift(row, b_drift.clone()));
+            }
+        }
+        if let (Some(a_mttr), Some(b_mttr)) = (&a.mttr, &b.mttr) {
+            if a_mttr != b_mttr {
+                changes.push(MenuChange::Mttr(row, b_mttr.clone()));
+            }
+        }
+        for (action, (a, b)) in a.actions().zip(b.actions()).enumerate() {
+            if a.enabled != b.enabled {
+                changes.push(MenuChange::ActionEnabled(row, action, b.enabled));
+            }
+        }
+    }
+    for (i, (a, b)) in old.pull_requests.iter().zip(&new.pull_requests).enumerate() {
+        if a.text != b.text {
+            changes.push(MenuChange::PullRequestText(i, b.text.clone()));
+        }
+        if a.icon != b.icon {
+            changes.push(MenuChange::PullRequestIcon(i, b.icon));
+        }
+    }
+    if old.last_checked != new.last_checked {
+        changes.push(MenuChange::LastChecked(new.last_checked.clone()));
+    }
+    Some(changes)
+}
+
+/// Menu icon for a run state without any detail
+fn state_icon(state: &PipelineState) -> &'static [u8] {
+    match state {
+        PipelineState::Healthy | PipelineState::Paused => MENU_ICON_GREEN,
+        PipelineState::Failed => MENU_ICON_RED,
+        PipelineState::InProgress => MENU_ICON_BLUE,
+        PipelineState::Unknown => MENU_ICON_GRAY,
+    }
+}
+
+/// Web page of the pull request behind a shown "pull_request_{i}" item
+fn pull_request_url(id: &str) -> Option<String> {
+    let urls = PULL_REQUEST_URLS.read().ok()?;
+    urls.values().find_map(|tray_urls| tray_urls.get(id).cloned())
+}
+
+/// Longest branch name shown in full in the menu
+const BRANCH_LABEL_MAX_CHARS: usize = 20;
+
+/// Branch name cut to `BRANCH_LABEL_MAX_CHARS`, ending in "…" when shortened
+fn short_branch(branch: &str) -> String {
+    if branch.chars().count() <= BRANCH_LABEL_MAX_CHARS {
+        return branch.to_string();
+    }
+    let kept: String = branch.chars().take(BRANCH_LABEL_MAX_CHARS - 1).collect();
+    format!("{}…", kept)
+}
+
+/// Icon and status suffix for a pipeline's submenu title
+fn pipeline_badge(pipeline: &PipelineStatusInfo) -> (&'static [u8], String) {
+    match pipeline.state {
+        PipelineState::Healthy => (MENU_ICON_GREEN, String::new()),
+        PipelineState::Failed => match &pipeline.failed_step_name {
+            Some(step) => (MENU_ICON_RED, tf("pipeline.failed_at", &[("step", step)])),
+            None => (MENU_ICON_RED, t("pipeline.failed").to_string()),
+        },
+        PipelineState::InProgress => (MENU_ICON_BLUE, t("pipeline.running").to_string()),
+        PipelineState::Paused => {
+            let stage = pipeline.stage_name.as_deref().unwrap_or(t("pipeline.paused"));
+            (MENU_ICON_GREEN, format!(" - ({})", stage))
+        }
+        PipelineState::Unknown if pipeline.pipelines_disabled => {
+            (MENU_ICON_GRAY, t("pipeline.disabled").to_string())
+        }
+        PipelineState::Unknown => match (&pipeline.unknown_reason, pipeline.error_kind.as_deref()) {
+            // Red, like a padlock, so auth problems stand out from repositories that never ran
+            (Some(UnknownReason::AuthFailed), _) => {
+                (MENU_ICON_RED, format!(" - {}", t("unknown.auth_failed")))
+            }
+            (Some(UnknownReason::RateLimited), _) => {
+                (MENU_ICON_GRAY, format!(" - {}", t("unknown.rate_limited")))
+            }
+            (Some(UnknownReason::NoRuns), _) => {
+                (MENU_ICON_GRAY, format!(" - {}", t("unknown.no_runs")))
+            }
+            (Some(UnknownReason::GateStepNotFound), _) => {
+                (MENU_ICON_GRAY, format!(" - {}", t("unknown.gate_step_not_found")))
+            }
+            (_, Some(kind)) if kind.starts_with("Forbidden") => {
+                (MENU_ICON_RED, t("pipeline.missing_scope").to_string())
+            }
+            (_, Some(_)) => (MENU_ICON_GRAY, t("pipeline.unreachable").to_string()),
+            (_, None) => (MENU_ICON_GRAY, String::new()),
+        },
+    }
+}
+
+/// Describe the status menu, along with the pipeline behind each clickable item id
+fn status_menu_model(
+    status: &OverallStatus,
+    options: MenuOptions,
+    now: DateTime<Utc>,
+) -> (StatusMenuModel, HashMap<String, PipelineStatusInfo>) {
+    let mut status_map = HashMap::new();
+    let mut pipeline_row = |i: usize, show_elapsed: bool| {
+        let pipeline = &status.pipeline_statuses()[i];
+        let name = if pipeline.repo_name.is_empty() {
+            &pipeline.repo_slug
+        } else {
+            &pipeline.repo_name
+        };
+        let (icon, status_text) = pipeline_badge(pipeline);
+        // Tells apart a repository monitored on several branches
+        let branch = match &pipeline.monitored_branch {
+            Some(branch) => format!(" ({})", short_branch(branch)),
+            None => String::new(),
+        };
+        let gate = match &pipeline.gate_step {
+            Some(step) => tf("pipeline.gate", &[("step", step)]),
+            None => String::new(),
+        };
+        let off_hours = if pipeline.off_hours { t("pipeline.off_hours") } else { "" };
+        let elapsed = match (show_elapsed, started_at(pipeline)) {
+            (true, Some(started)) => {
+                let secs = (now - started).num_seconds().max(0) as u64;
+                tf("menu.elapsed", &[("elapsed", &format_duration(secs))])
+            }
+            _ => String::new(),
+        };
+
+        // Each pipeline gets a submenu of links; ids share the "pipeline_" prefix
+        let links = [
+            (format!("pipeline_{}", i), t("menu.open_pipeline")),
+            (format!("pipeline_repo_{}", i), t("menu.open_repository")),
+            (format!("pipeline_branch_{}", i), t("menu.open_branch")),
+            (format!("pipeline_copy_{}", i), t("menu.copy_link")),
+        ]
+        .into_iter()
+        .map(|(id, label)| MenuAction {
+            enabled: pipeline_action_url(&id, pipeline).is_some(),
+            id,
+            label: label.to_string(),
+        })
+        .chain(std::iter::once(MenuAction {
+            id: format!("pipeline_details_{}", i),
+            label: t("menu.details").to_string(),
+            enabled: true,
+        }));
+        let quick_actions = pipeline.quick_actions.iter().enumerate().map(|(j, action)| {
+            let id = format!("pipeline_run_{}_{}", i, j);
+            MenuAction {
+                enabled: quick_action_for(&id, pipeline).is_some(),
+                id,
+                label: tf("menu.run_action", &[("label", &action.label)]),
+            }
+        });
+        // Nothing left to watch once Pipelines is switched off
+        let remove = pipeline.pipelines_disabled.then(|| MenuAction {
+            id: format!("pipeline_remove_{}", i),
+            label: t("menu.remove_from_monitoring").to_string(),
+            enabled: true,
+        });
+        let row = PipelineRow {
+            key: (pipeline.workspace.clone(), pipeline.repo_slug.clone()),
+            // Indented with spaces under the section header
+            text: format!(
+                "  {}{}{}{}{}{}{}",
+                name,
+                branch,
+                gate,
+                status_text,
+                elapsed,
+                off_hours,
+                history_glyphs(&pipeline.history)
+            ),
+            icon: themed_menu_icon(icon, options.dark),
+            checked: checked_text(now, pipeline.checked_at),
+            variables: pipeline.variables.as_deref().map(variables_text),
+            drift: pipeline.environment_drift.as_ref().map(drift_text),
+            mttr: options
+                .mttr_secs
+                .get(&(pipeline.workspace.clone(), pipeline.repo_slug.clone()))
+                .map(|secs| mttr_text(*secs)),
+            links: links.collect(),
+            quick_actions: quick_actions.chain(remove).collect(),
+        };
+
+        // Store the pipeline for click handling
+        for action in row.actions().filter(|a| a.enabled) {
+            status_map.insert(action.id.clone(), pipeline.clone());
+        }
+        row
+    };
+
+    let plan = plan_menu(status, &options);
+    let mut groups = Vec::new();
+    if !plan.running.is_empty() {
+        let rows = plan.running.iter().map(|&i| pipeline_row(i, true)).collect();
+        groups.push((t("menu.running_section").to_string(), rows));
+    }
+    for project in plan.groups {
+        let rows = project.pipelines.iter().map(|&i| pipeline_row(i, false)).collect();
+        groups.push((project.header.to_uppercase(), rows));
+    }
+
+    // A workspace's tray summarizes only that workspace; item ids keep the full status's
+    // numbering so they stay unique across trays
+    let scoped = options.workspace.as_deref().map(|ws| status.for_workspace(ws));
+    let summarized = scoped.as_ref().unwrap_or(status);
+    let hidden = hidden_unknown_count(summarized);
+    let model = StatusMenuModel {
+        // Summary header so failures are visible without scanning groups
+        summary: summary_header_text(summarized),
+        groups,
+        no_data: (hidden > 0).then(|| tp("menu.no_pipeline_data", hidden, &[])),
+        pull_requests: status
+            .pull_requests
+            .iter()
+            .enumerate()
+            .filter(|(_, pr)| options.workspace.as_ref().is_none_or(|ws| pr.workspace == *ws))
+            .map(|(i, pr)| PullRequestRow {
+                id: format!("pull_request_{}", i),
+                text: format!("  {} (#{})", pr.title, pr.id),
+                icon: themed_menu_icon(state_icon(&pr.state), options.dark),
+                url: pr.url.clone(),
+            })
+            .collect(),
+        // The stalest pipeline's check
+        last_checked: last_checked_text(summarized),
+    };
+    (model, status_map)
+}
+
+/// A pipeline's submenu and the items in it
+struct RowHandles {
+    submenu: Submenu<tauri::Wry>,
+    checked: MenuItem<tauri::Wry>,
+    variables: Option<MenuItem<tauri::Wry>>,
+    drift: Option<MenuItem<tauri::Wry>>,
+    mttr: Option<MenuItem<tauri::Wry>>,
+    /// Link and quick action items, links first
+    actions: Vec<MenuItem<tauri::Wry>>,
+}
+
+/// Items of the shown status menu that change between checks
+struct StatusMenuHandles {
+    summary: MenuItem<tauri::Wry>,
+    rows: Vec<RowHandles>,
+    pull_requests: Vec<IconMenuItem<tauri::Wry>>,
+    last_checked: MenuItem<tauri::Wry>,
+}
+
+impl StatusMenuHandles {
+    fn apply(&self, change: &MenuChange) -> Result<(), tauri::Error> {
+        match change {
+            MenuChange::Summary(text) => self.summary.set_text(text),
+            MenuChange::PipelineText(row, text) => self.rows[*row].submenu.set_text(text),
+            MenuChange::PipelineIcon(row, icon) => {
+                self.rows[*row].submenu.set_icon(Image::from_bytes(icon).ok())
+            }
+            MenuChange::Checked(row, text) => self.rows[*row].checked.set_text(text),
+            MenuChange::Variables(row, text) => match &self.rows[*row].variables {
+                Some(item) => item.set_text(text),
+                None => Ok(()),
+            },
+            MenuChange::Drift(row, text) => match &self.rows[*row].drift {
+                Some(item) => item.set_text(text),
+                None => Ok(()),
+            },
+            MenuChange::Mttr(row, text) => match &self.rows[*row].mttr {
+                Some(item) => item.set_text(text),
+                None => Ok(()),
+            },
+            MenuChange::ActionEnabled(row, action, enabled) => {
+                self.rows[*row].actions[*action].set_enabled(*enabled)
+            }
+            MenuChange::PullRequestText(i, text) => self.pull_requests[*i].set_text(text),
+            MenuChange::PullRequestIcon(i, icon) => {
+                self.pull_requests[*i].set_icon(Image::from_bytes(icon).ok())
+            }
+            MenuChange::LastChecked(text) => self.last_checked.set_text(text),
+        }
+    }
+}
+
+/// What the tray menu currently shows
+enum ShownMenu {
+    Setup(MenuContextKind),
+    /// None is the "No pipelines configured" placeholder
+    Status(Option<(StatusMenuModel, StatusMenuHandles)>),
+}
+
+/// Keeps the shown menu's items so updates can edit them in place
+///
+/// Swapping in a new menu closes it if it's open, so that only happens when
+/// pipelines are added, removed or reordered, or the kind of menu changes.
+pub struct TrayMenuController {
+    tray_id: String,
+    /// Only this workspace's pipelines are listed
+    workspace: Option<String>,
+    shown: Option<ShownMenu>,
+}
+
+impl TrayMenuController {
+    fn new(tray_id: &str, workspace: Option<&str>) -> Self {
+        Self {
+            tray_id: tray_id.to_string(),
+            workspace: workspace.map(str::to_string),
+            shown: None,
+        }
+    }
+
+    fn update(&mut self, app_handle: &AppHandle, context: MenuContext) {
+        let status = match context {
+            MenuContext::NoCredentials => {
+                return self.show_setup(
+                    app_handle,
+                    MenuContextKind::NoCredentials,
+                    t("menu.not_configured"),
+                    "setup_credentials",
+                    t("menu.set_up_credentials"),
+                )
+            }
+            MenuContext::NoPipelines => {
+                return self.show_setup(
+                    app_handle,
+                    MenuContextKind::NoPipelines,
+                    t("menu.no_pipelines_selected"),
+                    "configure_pipelines",
+                    t("menu.configure_pipelines"),
+                )
+            }
+            MenuContext::Status(status) => status,
+        };
+
+        let (model, status_map) = match pipelines_to_show(status) {
+            Some(s) => {
+                let options = MenuOptions {
+                    workspace: self.workspace.clone(),
+                    ..MenuOptions::current()
+                };
+                let (model, status_map) = status_menu_model(s, options, Utc::now());
+                (Some(model), status_map)
+            }
+            None => (None, HashMap::new()),
+        };
+        // Store pipelines and pull request links globally for click handler
+        if let Ok(mut maps) = PIPELINE_STATUS_MAP.write() {
+            maps.insert(self.tray_id.clone(), status_map);
+        }
+        let pr_urls = model
+            .iter()
+            .flat_map(|model| &model.pull_requests)
+            .filter_map(|pr| Some((pr.id.clone(), pr.url.clone()?)))
+            .collect();
+        if let Ok(mut urls) = PULL_REQUEST_URLS.write() {
+            urls.insert(self.tray_id.clone(), pr_urls);
+        }
+
+        match (&mut self.shown, model) {
+            (Some(ShownMenu::Status(None)), None) => {}
+            (Some(ShownMenu::Status(Some((shown, handles)))), Some(model)) => {
+                match diff_menus(shown, &model) {
+                    Some(changes) => {
+                        for change in &changes {
+                            if let Err(e) = handles.apply(change) {
+                                tracing::warn!("Failed to update tray menu item: {}", e);
+                            }
+                        }
+                        *shown = model;
+                    }
+                    None => self.show_status(app_handle, Some(model)),
+                }
+            }
+            (_, model) => self.show_status(app_handle, model),
+        }
+    }
+
+    fn show_setup(
+        &mut self,
+        app_handle: &AppHandle,
+        kind: MenuContextKind,
+        message: &str,
+        action_id: &str,
+        action_label: &str,
+    ) {
+        if matches!(self.shown, Some(ShownMenu::Setup(shown)) if shown == kind) {
+            return;
+        }
+        let menu = build_setup_menu(app_handle, &self.tray_id, message, action_id, action_label);
+        if let (Some(tray), Ok(menu)) = (app_handle.tray_by_id(&self.tray_id), menu) {
             let _ = tray.set_menu(Some(menu));
+            self.shown = Some(ShownMenu::Setup(kind));
+        }
+    }
+
+    fn show_status(&mut self, app_handle: &AppHandle, model: Option<StatusMenuModel>) {
+        let Some(tray) = app_handle.tray_by_id(&self.tray_id) else {
+            return;
+        };
+        match build_status_menu(app_handle, &self.tray_id, model.as_ref()) {
+            Ok((menu, handles)) => {
+                set_menu_build_error(None);
+                let _ = tray.set_menu(Some(menu));
+                self.shown = Some(ShownMenu::Status(model.zip(handles)));
+            }
+            Err(e) => {
+                tracing::error!("Failed to build tray menu: {}", e);
+                set_menu_build_error(Some(e.to_string()));
+                match build_initial_menu_fallback(app_handle) {
+                    Ok(menu) => {
+                        let _ = tray.set_menu(Some(menu));
+                    }
+                    Err(e) => tracing::error!("Failed to build fallback tray menu: {}", e),
+                }
+                // Nothing is shown to edit in place, so the next update tries a full build again
+                self.shown = None;
+            }
         }
     }
 }
 
 /// Build menu with pipeline status grouped by project
-fn build_status_menu(app_handle: &AppHandle, status: Option<&OverallStatus>) -> Result<Menu<tauri::Wry>, tauri::Error> {
+fn build_status_menu(
+    app_handle: &AppHandle,
+    tray_id: &str,
+    model: Option<&StatusMenuModel>,
+) -> Result<(Menu<tauri::Wry>, Option<StatusMenuHandles>), tauri::Error> {
     let mut items: Vec<Box<dyn tauri::menu::IsMenuItem<tauri::Wry>>> = Vec::new();
-    let mut url_map: HashMap<String, String> = HashMap::new();
+    let mut handles = None;
 
-    match status {
-        Some(s) => {
-            // Group pipelines by project (use project_name, fallback to workspace)
-            let mut projects: Vec<String> = Vec::new();
-            for pipeline in &s.pipeline_statuses {
-                let project = pipeline.project_name.clone()
-                    .unwrap_or_else(|| pipeline.workspace.clone());
-                if !projects.contains(&project) {
-                    projects.push(project);
-                }
-            }
+    match model {
+        Some(model) => {
+            let summary = MenuItem::with_id(
+                app_handle,
+                "summary_header",
+                &model.summary,
+                false,
+                None::<&str>,
+            )?;
+            items.push(Box::new(summary.clone()));
+            items.push(Box::new(PredefinedMenuItem::separator(app_handle)?));
 
-            for (proj_idx, project) in projects.iter().enumerate() {
+            let mut row_handles = Vec::new();
+            for (proj_idx, (header, rows)) in model.groups.iter().enumerate() {
                 // Add project header
                 let proj_header = MenuItem::with_id(
                     app_handle,
                     format!("proj_header_{}", proj_idx),
-                    project.to_uppercase(),
+                    header,
                     false,
                     None::<&str>,
                 )?;
                 items.push(Box::new(proj_header));
 
                 // Add pipelines for this project
-                for (i, pipeline) in s.pipeline_statuses.iter().enumerate() {
-                    let pipeline_project = pipeline.project_name.clone()
-                        .unwrap_or_else(|| pipeline.workspace.clone());
-                    if &pipeline_project != project {
-                        continue;
-                    }
-
-                    let name = if pipeline.repo_name.is_empty() {
-                        &pipeline.repo_slug
-                    } else {
-                        &pipeline.repo_name
+                for (row_idx, row) in rows.iter().enumerate() {
+                    let submenu = Submenu::new_with_icon(
+                        app_handle,
+                        &row.text,
+                        true,
+                        Image::from_bytes(row.icon).ok(),
+                    )?;
+                    let checked = MenuItem::with_id(
+                        app_handle,
+                        format!("checked_{}_{}", proj_idx, row_idx),
+                        &row.checked,
+                        false,
+                        None::<&str>,
+                    )?;
+                    submenu.append(&checked)?;
+                    let variables = match &row.variables {
+                        Some(text) => {
+                            let item = MenuItem::with_id(
+                                app_handle,
+                                format!("variables_{}_{}", proj_idx, row_idx),
+                                text,
+                                false,
+                                None::<&str>,
+                            )?;
+                            submenu.append(&item)?;
+                            Some(item)
+                        }
+                        None => None,
                     };
-
-                    let (icon_bytes, status_text) = match pipeline.state {
-                        PipelineState::Healthy => (MENU_ICON_GREEN, String::new()),
-                        PipelineState::Failed => (MENU_ICON_RED, " - FAILED".to_string()),
-                        PipelineState::InProgress => (MENU_ICON_BLUE, " - running".to_string()),
-                        PipelineState::Paused => {
-                            let stage = pipeline.stage_name.as_deref().unwrap_or("paused");
-                            (MENU_ICON_GREEN, format!(" - ({})", stage))
+                    let drift = match &row.drift {
+                        Some(text) => {
+                            let item = MenuItem::with_id(
+                                app_handle,
+                                format!("drift_{}_{}", proj_idx, row_idx),
+                                text,
+                                false,
+                                None::<&str>,
+                            )?;
+                            submenu.append(&item)?;
+                            Some(item)
                         }
-                        PipelineState::Unknown => (MENU_ICON_GRAY, String::new()),
+                        None => None,
+                    };
+                    let mttr = match &row.mttr {
+                        Some(text) => {
+                            let item = MenuItem::with_id(
+                                app_handle,
+                                format!("mttr_{}_{}", proj_idx, row_idx),
+                                text,
+                                false,
+                                None::<&str>,
+                            )?;
+                            submenu.append(&item)?;
+                            Some(item)
+                        }
+                        None => None,
                     };
 
-                    let menu_id = format!("pipeline_{}", i);
-                    let has_url = pipeline.pipeline_url.is_some();
-
-                    // Store URL for click handling
-                    if let Some(ref url) = pipeline.pipeline_url {
-                        url_map.insert(menu_id.clone(), url.clone());
-                    }
-
-                    // Create menu item with icon (indented with spaces)
-                    let display_text = format!("  {}{}", name, status_text);
-                    if let Ok(icon) = Image::from_bytes(icon_bytes) {
-                        let item = IconMenuItem::with_id(
-                            app_handle,
-                            &menu_id,
-                            &display_text,
-                            has_url,
-                            Some(icon),
-                            None::<&str>,
-                        )?;
-                        items.push(Box::new(item));
-                    } else {
+                    let mut action_items = Vec::new();
+                    for (n, action) in row.actions().enumerate() {
+                        if n == row.links.len() {
+                            submenu.append(&PredefinedMenuItem::separator(app_handle)?)?;
+                        }
                         let item = MenuItem::with_id(
                             app_handle,
-                            &menu_id,
-                            &display_text,
-                            has_url,
+                            &action.id,
+                            &action.label,
+                            action.enabled,
                             None::<&str>,
                         )?;
-                        items.push(Box::new(item));
+                        submenu.append(&item)?;
+                        action_items.push(item);
                     }
+                    items.push(Box::new(submenu.clone()));
+                    row_handles.push(RowHandles {
+                        submenu,
+                        checked,
+                        variables,
+                        drift,
+                        mttr,
+                        actions: action_items,
+                    });
                 }
 
                 // Add separator between projects (but not after the last one)
-                if proj_idx < projects.len() - 1 {
-                    let sep = PredefinedMenuItem::separator(app_handle)?;
-                    items.push(Box::new(sep));
+                if proj_idx < model.groups.len() - 1 {
+                    items.push(Box::new(PredefinedMenuItem::separator(app_handle)?));
                 }
             }
 
             // Separator before last checked
-            let sep1 = PredefinedMenuItem::separator(app_handle)?;
-            items.push(Box::new(sep1));
+            items.push(Box::new(PredefinedMenuItem::separator(app_handle)?));
+
+            // Kept apart from monitored pipelines; clicking one opens the pull request
+            let mut pr_handles = Vec::new();
+            if !model.pull_requests.is_empty() {
+                let header = MenuItem::with_id(
+                    app_handle,
+                    "pull_requests_header",
+                    t("menu.my_pull_requests"),
+                    false,
+                    None::<&str>,
+                )?;
+                items.push(Box::new(header));
+                for pr in &model.pull_requests {
+                    let item = IconMenuItem::with_id(
+                        app_handle,
+                        &pr.id,
+                        &pr.text,
+                        pr.url.is_some(),
+                        Image::from_bytes(pr.icon).ok(),
+                        None::<&str>,
+                    )?;
+                    items.push(Box::new(item.clone()));
+                    pr_handles.push(item);
+                }
+                items.push(Box::new(PredefinedMenuItem::separator(app_handle)?));
+            }
+
+            // Opens settings, where these repositories can be removed
+            if let Some(no_data) = &model.no_data {
+                let item =
+                    MenuItem::with_id(app_handle, "no_pipeline_data", no_data, true, None::<&str>)?;
+                items.push(Box::new(item));
+            }
 
-            // Add last checked time
             let last_checked = MenuItem::with_id(
                 app_handle,
                 "last_checked",
-                format!("Last checked: {}", s.last_checked),
+                &model.last_checked,
                 false,
                 None::<&str>,
             )?;
-            items.push(Box::new(last_checked));
+            items.push(Box::new(last_checked.clone()));
+
+            handles = Some(StatusMenuHandles {
+                summary,
+                rows: row_handles,
+                pull_requests: pr_handles,
+                last_checked,
+            });
         }
+        // No status yet, or nothing left to monitor: show a placeholder row instead
         None => {
             let no_status = MenuItem::with_id(
                 app_handle,
                 "no_status",
-                "No pipelines configured",
+                t("menu.no_pipelines"),
                 false,
                 None::<&str>,
             )?;
@@ -227,47 +1378,864 @@ fn build_status_menu(app_handle: &AppHandle, status: Option<&OverallStatus>) ->
         }
     }
 
-    // Store URLs globally for click handler
-    if let Ok(mut urls) = PIPELINE_URLS.write() {
-        *urls = Some(url_map);
-    }
-
     // Separator
     let separator = PredefinedMenuItem::separator(app_handle)?;
     items.push(Box::new(separator));
 
     // Action items
-    let refresh = MenuItem::with_id(app_handle, "refresh", "Refresh Now", true, None::<&str>)?;
-    let settings = MenuItem::with_id(app_handle, "settings", "Settings...", true, None::<&str>)?;
-    let quit = MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>)?;
+    let refresh = MenuItem::with_id(app_handle, "refresh", t("menu.refresh"), true, None::<&str>)?;
+    let wallboard = MenuItem::with_id(app_handle, "wallboard", t("menu.wallboard"), true, None::<&str>)?;
+    let settings = MenuItem::with_id(app_handle, "settings", t("menu.settings"), true, None::<&str>)?;
+    let interval = build_interval_submenu(app_handle, tray_id)?;
+    let quit = MenuItem::with_id(app_handle, "quit", t("menu.quit"), true, None::<&str>)?;
 
     items.push(Box::new(refresh));
+    items.push(Box::new(wallboard));
     items.push(Box::new(settings));
+    items.push(Box::new(interval));
     items.push(Box::new(quit));
 
     // Build menu from items
     let item_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = items.iter().map(|b| b.as_ref()).collect();
-    Menu::with_items(app_handle, &item_refs)
+    Ok((Menu::with_items(app_handle, &item_refs)?, handles))
 }
 
-/// Update the tray icon based on status
-pub fn update_tray_icon(app_handle: &AppHandle, status: TrayStatus) {
-    if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
-        let icon_bytes = match status {
-            TrayStatus::Green => ICON_GREEN,
-            TrayStatus::Red => ICON_RED,
-            TrayStatus::Gray => ICON_GRAY,
-        };
+/// How the status menu sections pipelines
+#[derive(Debug, Clone, Default)]
+struct MenuOptions {
+    /// List only this workspace's pipelines, for its own tray
+    workspace: Option<String>,
+    /// Leave out pipelines with no run data
+    hide_no_data: bool,
+    /// Move running and paused pipelines out of their projects into a section of their own
+    group_running_separately: bool,
+    /// Mean time to recovery of pipelines that had outages, by (workspace, repo_slug)
+    mttr_secs: BTreeMap<(String, String), u64>,
+    /// Use the menu icons drawn for a dark menu
+    dark: bool,
+}
+
+impl MenuOptions {
+    /// Options as currently set in settings
+    fn current() -> Self {
+        Self {
+            workspace: None,
+            hide_no_data: HIDE_UNKNOWN_IN_MENU.load(Ordering::Relaxed),
+            group_running_separately: GROUP_RUNNING_SEPARATELY.load(Ordering::Relaxed),
+            mttr_secs: MTTR_SECS.read().map(|mttr| mttr.clone()).unwrap_or_default(),
+            dark: dark_icons(),
+        }
+    }
+}
+
+/// Which pipelines each section of the status menu lists, as indices into the status list
+#[derive(Debug, PartialEq)]
+struct MenuPlan {
+    /// Running and paused pipelines, longest running first
+    running: Vec<usize>,
+    /// The remaining pipelines by project, leaving out projects with none left
+    groups: Vec<ProjectGroup>,
+}
+
+/// Decide which section each pipeline is listed in, and in what order
+fn plan_menu(status: &OverallStatus, options: &MenuOptions) -> MenuPlan {
+    let statuses = status.pipeline_statuses();
+    let shown = |i: &usize| {
+        options.workspace.as_ref().is_none_or(|ws| statuses[*i].workspace == *ws)
+            && !(options.hide_no_data && statuses[*i].has_no_data())
+    };
+    let is_running = |i: &usize| {
+        options.group_running_separately
+            && matches!(statuses[*i].state, PipelineState::InProgress | PipelineState::Paused)
+    };
+
+    let mut running: Vec<usize> = (0..statuses.len()).filter(shown).filter(is_running).collect();
+    // Runs without a start time go last; the sort is stable, so ties keep status order
+    running.sort_by_key(|&i| {
+        let started = started_at(&statuses[i]);
+        (started.is_none(), started)
+    });
+
+    let mut groups = group_by_project(statuses);
+    for group in &mut groups {
+        group.pipelines.retain(|i| shown(i) && !is_running(i));
+    }
+    groups.retain(|group| !group.pipelines.is_empty());
+
+    MenuPlan { running, groups }
+}
+
+/// When the pipeline's latest run started, if known
+fn started_at(pipeline: &PipelineStatusInfo) -> Option<DateTime<Utc>> {
+    let created = pipeline.created_on.as_deref()?;
+    DateTime::parse_from_rfc3339(created)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Pipelines sharing a project, in first-seen order
+#[derive(Debug, PartialEq)]
+struct ProjectGroup {
+    /// Project name, falling back to the key, then the workspace
+    header: String,
+    /// Indices into the status list
+    pipelines: Vec<usize>,
+}
+
+/// Group pipelines by project key (falling back to workspace)
+///
+/// Keyed on the project key rather than the name, which may not have been fetched.
+fn group_by_project(statuses: &[PipelineStatusInfo]) -> Vec<ProjectGroup> {
+    let mut keys: Vec<(String, String)> = Vec::new();
+    let mut groups: Vec<ProjectGroup> = Vec::new();
+
+    for (i, pipeline) in statuses.iter().enumerate() {
+        let key = (
+            pipeline.workspace.clone(),
+            pipeline
+                .project_key
+                .clone()
+                .unwrap_or_else(|| pipeline.workspace.clone()),
+        );
+        match keys.iter().position(|k| *k == key) {
+            Some(pos) => groups[pos].pipelines.push(i),
+            None => {
+                let header = pipeline
+                    .project_name
+                    .clone()
+                    .unwrap_or_else(|| key.1.clone());
+                keys.push(key);
+                groups.push(ProjectGroup {
+                    header,
+                    pipelines: vec![i],
+                });
+            }
+        }
+    }
+
+    groups
+}
+
+/// e.g. "Checked 4m ago"
+fn checked_text(now: DateTime<Utc>, checked_at: DateTime<Utc>) -> String {
+    tf("menu.checked", &[("age", &format_age(now, checked_at))])
+}
+
+/// Footer text using the oldest per-pipeline check, so it shows how stale the menu can be
+fn last_checked_text(status: &OverallStatus) -> String {
+    match status.pipeline_statuses().iter().map(|p| p.checked_at).min() {
+        Some(oldest) => tf(
+            "menu.last_checked",
+            &[("time", &oldest.with_timezone(&Local).format("%H:%M:%S"))],
+        ),
+        None => tf("menu.last_checked", &[("time", &status.last_checked)]),
+    }
+}
+
+/// One line for a run's variables, e.g. "region=eu, version=2.3.1"
+fn variables_text(variables: &[(String, String)]) -> String {
+    variables
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// MTTR line, e.g. "MTTR: 42m over last 30d"
+fn mttr_text(secs: u64) -> String {
+    tf(
+        "menu.mttr",
+        &[
+            ("mttr", &format_duration(secs)),
+            ("days", &MTTR_MENU_WINDOW_DAYS),
+        ],
+    )
+}
+
+/// Drift line, e.g. "staging +4 vs prod"
+fn drift_text(drift: &EnvironmentDrift) -> String {
+    tf(
+        if drift.truncated { "menu.drift_at_least" } else { "menu.drift" },
+        &[
+            ("from", &drift.from),
+            ("ahead", &drift.ahead),
+            ("to", &drift.to),
+        ],
+    )
+}
+
+/// Relative age such as "just now", "4m ago" or "2h ago"
+fn format_age(now: DateTime<Utc>, then: DateTime<Utc>) -> String {
+    let secs = (now - then).num_seconds().max(0);
+    match secs {
+        0..=59 => t("age.just_now").to_string(),
+        60..=3_599 => tf("age.minutes", &[("n", &(secs / 60))]),
+        3_600..=86_399 => tf("age.hours", &[("n", &(secs / 3_600))]),
+        _ => tf("age.days", &[("n", &(secs / 86_400))]),
+    }
+}
+
+/// URL a per-pipeline menu item opens, if the pipeline has one
+fn pipeline_action_url(id: &str, status: &PipelineStatusInfo) -> Option<String> {
+    if id.starts_with("pipeline_repo_") {
+        Some(get_repository_url(
+            status.repo_url.as_deref(),
+            &status.workspace,
+            &status.repo_slug,
+        ))
+    } else if id.starts_with("pipeline_branch_") {
+        status.branch_url.clone()
+    } else {
+        // "pipeline_{i}" opens the run, "pipeline_copy_{i}" copies its link
+        status.pipeline_url.clone()
+    }
+}
+
+/// Quick action behind a "pipeline_run_{i}_{j}" item, with the branch it runs on
+fn quick_action_for<'a>(id: &str, status: &'a PipelineStatusInfo) -> Option<(&'a QuickAction, String)> {
+    let index: usize = id.strip_prefix("pipeline_run_")?.rsplit('_').next()?.parse().ok()?;
+    let action = status.quick_actions.get(index)?;
+    let branch = action.branch.clone().or_else(|| status.branch.clone())?;
+    Some((action, branch))
+}
+
+/// Run the action behind a per-pipeline menu item
+fn handle_pipeline_action(app: &AppHandle, id: &str, status: &PipelineStatusInfo) {
+    if let Some((action, branch)) = quick_action_for(id, status) {
+        let app = app.clone();
+        let (workspace, repo_slug) = (status.workspace.clone(), status.repo_slug.clone());
+        let selector = action.selector.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = polling::run_custom_pipeline(
+                &app, &workspace, &repo_slug, &branch, &selector, &[],
+            )
+            .await
+            {
+                tracing::error!(
+                    "Failed to run '{}' on {}/{}: {}",
+                    selector,
+                    workspace,
+                    repo_slug,
+                    e
+                );
+                app.notify(t("notify.not_started.title"), &format!("{}: {}", repo_slug, e));
+            }
+        });
+        return;
+    }
+    if id.starts_with("pipeline_remove_") {
+        let app = app.clone();
+        let (workspace, repo_slug) = (status.workspace.clone(), status.repo_slug.clone());
+        let branch = status.monitored_branch.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) =
+                commands::remove_monitored_pipeline(&app, &workspace, &repo_slug, branch.as_deref())
+                    .await
+            {
+                tracing::error!("Failed to remove {}/{}: {}", workspace, repo_slug, e);
+            }
+        });
+        return;
+    }
+    if id.starts_with("pipeline_details_") {
+        pipeline_detail::open_pipeline_detail(app, PipelineKey::of(status));
+        return;
+    }
+    if id.starts_with("pipeline_copy_") {
+        if let Some(url) = &status.pipeline_url {
+            if let Err(e) = commands::copy_link(app, url) {
+                tracing::error!("{}", e);
+            }
+        }
+        return;
+    }
+    if let Some(url) = pipeline_action_url(id, status) {
+        tracing::info!(
+            "Opening {} for {}/{}",
+            url,
+            status.workspace,
+            status.repo_slug
+        );
+        let _ = open::that(url);
+    }
+}
+
+/// Summary line shown at the top of the menu
+fn summary_header_text(status: &OverallStatus) -> String {
+    if status.is_healthy {
+        let shown = status.total_monitored - hidden_unknown_count(status);
+        return tp("summary.all_healthy", shown, &[]);
+    }
+
+    let mut parts = Vec::new();
+    if !status.failed_pipelines.is_empty() {
+        parts.push(tp("summary.failing", status.failed_pipelines.len(), &[]));
+    }
+    let failing_prs = status.unhealthy_pull_request_count();
+    if failing_prs > 0 {
+        parts.push(tp("summary.pull_requests_failing", failing_prs, &[]));
+    }
+    if status.paused_count > 0 {
+        parts.push(tp("summary.awaiting_approval", status.paused_count, &[]));
+    }
+    if status.in_progress_count > 0 {
+        parts.push(tp("summary.running", status.in_progress_count, &[]));
+    }
+    format!("⚠ {}", parts.join(" · "))
+}
+
+/// Update a tray's icon based on status, ending a failure flash on that tray
+pub fn update_tray_icon(app_handle: &AppHandle, tray_id: &str, status: TrayStatus) {
+    stop_flashing(tray_id);
+    if let Some(tray) = app_handle.tray_by_id(tray_id) {
+        if let Ok(icon) = Image::from_bytes(tray_icon(status, dark_icons())) {
+            let _ = tray.set_icon(Some(icon));
+        }
+    }
+}
+
+/// Blink a tray's icon between red and dimmed red for a few seconds, then settle on red
+///
+/// Replaces a flash already running on that tray. Any icon update or opening the menu ends it early.
+pub fn flash_tray_icon(app_handle: &AppHandle, tray_id: &str) {
+    let token = CancellationToken::new();
+    if let Ok(mut flash) = FLASH.lock() {
+        if let Some(previous) = flash.insert(tray_id.to_string(), token.clone()) {
+            previous.cancel();
+        }
+    }
+
+    let (red, dim) = if dark_icons() {
+        (ICON_RED, ICON_RED_DIM)
+    } else {
+        (ICON_RED_LIGHT, ICON_RED_DIM_LIGHT)
+    };
+    let app_handle = app_handle.clone();
+    let tray_id = tray_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        for frame in 1..=FLASH_FRAMES {
+            tokio::select! {
+                _ = token.cancelled() => return,
+                _ = tokio::time::sleep(FLASH_FRAME) => {}
+            }
+            let icon = if frame % 2 == 1 { dim } else { red };
+            // The frame is drawn on the main thread, where icon updates also land, and checks
+            // the token there so it can't land after the update that cancelled it. FLASH isn't
+            // held meanwhile: set_icon waits for the main thread, whose click handler locks it.
+            let (handle, tray_id, token) = (app_handle.clone(), tray_id.clone(), token.clone());
+            let _ = app_handle.run_on_main_thread(move || {
+                if token.is_cancelled() {
+                    return;
+                }
+                let tray = handle.tray_by_id(&tray_id);
+                if let (Some(tray), Ok(icon)) = (tray, Image::from_bytes(icon)) {
+                    let _ = tray.set_icon(Some(icon));
+                }
+            });
+        }
+        // A replaced flash is cancelled under the lock, so an uncancelled token is still this tray's
+        if let Ok(mut flash) = FLASH.lock() {
+            if !token.is_cancelled() {
+                flash.remove(&tray_id);
+            }
+        }
+    });
+}
+
+/// Cancel the failure flash if it is running on this tray; returns whether it was
+fn stop_flashing(tray_id: &str) -> bool {
+    let Ok(mut flash) = FLASH.lock() else {
+        return false;
+    };
+    // Cancelled under the lock, which a finishing flash relies on
+    flash.remove(tray_id).map(|token| token.cancel()).is_some()
+}
 
-        if let Ok(icon) = Image::from_bytes(icon_bytes) {
+/// End a flash when its tray's menu is opened, leaving the icon solid red
+fn stop_flashing_on_click(tray: &TrayIcon) {
+    if stop_flashing(tray.id().as_ref()) {
+        if let Ok(icon) = Image::from_bytes(tray_icon(TrayStatus::Red, dark_icons())) {
             let _ = tray.set_icon(Some(icon));
         }
     }
 }
 
-/// Update the tray tooltip
-pub fn update_tray_tooltip(app_handle: &AppHandle, tooltip: &str) {
-    if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
+/// Update a tray's tooltip, adding lines for session activity and a degraded connection
+pub fn update_tray_tooltip(app_handle: &AppHandle, tray_id: &str, tooltip: &str) {
+    let session = session_line(
+        SESSION_FAILURES.load(Ordering::Relaxed),
+        SESSION_RECOVERIES.load(Ordering::Relaxed),
+    );
+    let connection = CONNECTION_STATUS
+        .read()
+        .ok()
+        .and_then(|status| connection_status_line(*status));
+    let mut tooltip = tooltip.to_string();
+    for line in [session, connection].into_iter().flatten() {
+        tooltip.push('\n');
+        tooltip.push_str(&line);
+    }
+    if let Some(tray) = app_handle.tray_by_id(tray_id) {
         let _ = tray.set_tooltip(Some(tooltip));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MonitoredPipeline;
+
+    fn status(project_key: Option<&str>, project_name: Option<&str>, repo: &str) -> PipelineStatusInfo {
+        let monitored = MonitoredPipeline {
+            workspace: "ws".to_string(),
+            project_key: project_key.map(str::to_string),
+            project_name: project_name.map(str::to_string),
+            repo_slug: repo.to_string(),
+            repo_name: repo.to_string(),
+            ..MonitoredPipeline::default()
+        };
+        PipelineStatusInfo::new(&monitored, PipelineState::Healthy, Local::now())
+    }
+
+    #[test]
+    fn groups_by_project_key_when_name_is_missing() {
+        let statuses = vec![
+            status(Some("API"), None, "api"),
+            status(Some("WEB"), None, "web"),
+            status(Some("API"), None, "api-client"),
+            status(None, None, "scratch"),
+        ];
+
+        let groups = group_by_project(&statuses);
+
+        assert_eq!(
+            groups,
+            vec![
+                ProjectGroup { header: "API".to_string(), pipelines: vec![0, 2] },
+                ProjectGroup { header: "WEB".to_string(), pipelines: vec![1] },
+                ProjectGroup { header: "ws".to_string(), pipelines: vec![3] },
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_project_name_as_header_when_known() {
+        let statuses = vec![
+            status(Some("API"), Some("Backend API"), "api"),
+            status(Some("API"), None, "api-client"),
+        ];
+
+        let groups = group_by_project(&statuses);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].header, "Backend API");
+        assert_eq!(groups[0].pipelines, vec![0, 1]);
+    }
+
+    #[test]
+    fn empty_status_shows_the_no_pipelines_placeholder() {
+        let empty = OverallStatus::new(Vec::new(), "12:00:00".to_string(), false);
+        let one = OverallStatus::new(vec![status(None, None, "api")], "12:00:00".to_string(), false);
+
+        assert!(pipelines_to_show(Some(&empty)).is_none());
+        assert!(pipelines_to_show(None).is_none());
+        assert!(pipelines_to_show(Some(&one)).is_some());
+    }
+
+    #[test]
+    fn quick_action_runs_on_its_branch_or_the_latest_runs() {
+        let action = |branch: Option<&str>| QuickAction {
+            label: "Deploy".to_string(),
+            selector: "deploy-staging".to_string(),
+            branch: branch.map(str::to_string),
+        };
+        let mut pipeline = status(None, None, "api");
+        pipeline.quick_actions = vec![action(Some("release")), action(None)];
+
+        assert!(quick_action_for("pipeline_run_3_1", &pipeline).is_none());
+
+        pipeline.branch = Some("main".to_string());
+        let (first, branch) = quick_action_for("pipeline_run_3_0", &pipeline).unwrap();
+        assert_eq!(first.selector, "deploy-staging");
+        assert_eq!(branch, "release");
+        assert_eq!(quick_action_for("pipeline_run_3_1", &pipeline).unwrap().1, "main");
+        assert!(quick_action_for("pipeline_run_3_2", &pipeline).is_none());
+        assert!(quick_action_for("pipeline_3", &pipeline).is_none());
+    }
+
+    fn model(statuses: Vec<PipelineStatusInfo>, now: DateTime<Utc>) -> StatusMenuModel {
+        let overall = OverallStatus::new(statuses, "12:00:00".to_string(), false);
+        status_menu_model(&overall, MenuOptions::default(), now).0
+    }
+
+    #[test]
+    fn unchanged_status_needs_no_edits() {
+        let now = Utc::now();
+        let statuses = vec![status(Some("API"), None, "api"), status(Some("WEB"), None, "web")];
+
+        let changes = diff_menus(&model(statuses.clone(), now), &model(statuses, now));
+
+        assert_eq!(changes, Some(Vec::new()));
+    }
+
+    #[test]
+    fn state_and_time_changes_are_edited_in_place() {
+        let now = Utc::now();
+        let checked_now = |mut s: PipelineStatusInfo| {
+            s.checked_at = now;
+            s
+        };
+        let healthy = checked_now(status(Some("API"), None, "api"));
+        let web = checked_now(status(Some("API"), None, "web"));
+        let failed = PipelineStatusInfo {
+            state: PipelineState::Failed,
+            pipeline_url: Some("https://bitbucket.org/ws/api/pipelines/results/7".to_string()),
+            ..healthy.clone()
+        };
+        let old = model(vec![healthy, web.clone()], now);
+        let new = model(vec![failed, web], now + chrono::Duration::minutes(4));
+
+        let changes = diff_menus(&old, &new).expect("same pipelines keep the layout");
+
+        assert!(changes.contains(&MenuChange::Summary(new.summary.clone())));
+        assert!(changes.contains(&MenuChange::PipelineText(0, "  api - FAILED".to_string())));
+        assert!(changes.contains(&MenuChange::PipelineIcon(0, MENU_ICON_RED)));
+        assert!(changes.contains(&MenuChange::ActionEnabled(0, 0, true)));
+        // "Copy link" follows "Open pipeline" once there is a run to link to
+        assert!(changes.contains(&MenuChange::ActionEnabled(0, 3, true)));
+        assert!(changes.contains(&MenuChange::Checked(1, "Checked 4m ago".to_string())));
+        assert!(!changes.contains(&MenuChange::PipelineText(1, "  web".to_string())));
+    }
+
+    #[test]
+    fn appearance_change_swaps_menu_icons_in_place() {
+        let now = Utc::now();
+        let overall = OverallStatus::new(vec![status(Some("API"), None, "api")], "12:00:00".to_string(), false);
+        let dark = MenuOptions { dark: true, ..MenuOptions::default() };
+
+        let light_model = status_menu_model(&overall, MenuOptions::default(), now).0;
+        let dark_model = status_menu_model(&overall, dark, now).0;
+
+        let changes = diff_menus(&light_model, &dark_model).expect("same pipelines keep the layout");
+        assert_eq!(changes, [MenuChange::PipelineIcon(0, MENU_ICON_GREEN_DARK)]);
+    }
+
+    #[test]
+    fn tray_icons_follow_the_menu_bar_appearance() {
+        assert_eq!(tray_icon(TrayStatus::Red, true), ICON_RED);
+        assert_eq!(tray_icon(TrayStatus::Red, false), ICON_RED_LIGHT);
+        assert_eq!(themed_menu_icon(MENU_ICON_BLUE, false), MENU_ICON_BLUE);
+        assert_eq!(themed_menu_icon(MENU_ICON_BLUE, true), MENU_ICON_BLUE_DARK);
+    }
+
+    #[test]
+    fn run_variables_get_their_own_row() {
+        let now = Utc::now();
+        let vars = |version: &str| {
+            Some(vec![
+                ("region".to_string(), "eu".to_string()),
+                ("version".to_string(), version.to_string()),
+                ("token".to_string(), "********".to_string()),
+            ])
+        };
+        let plain = status(None, None, "api");
+        let custom = PipelineStatusInfo { variables: vars("2.3.1"), ..plain.clone() };
+        let shown = model(vec![custom.clone()], now);
+
+        assert_eq!(
+            shown.groups[0].1[0].variables.as_deref(),
+            Some("region=eu, version=2.3.1, token=********")
+        );
+        assert!(diff_menus(&shown, &model(vec![plain], now)).is_none());
+        let bumped = PipelineStatusInfo { variables: vars("2.4.0"), ..custom };
+        assert_eq!(
+            diff_menus(&shown, &model(vec![bumped], now)),
+            Some(vec![MenuChange::Variables(
+                0,
+                "region=eu, version=2.4.0, token=********".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn session_line_appears_after_the_first_failure() {
+        assert_eq!(session_line(0, 0), None);
+        assert_eq!(session_line(1, 0).as_deref(), Some("Session: 1 failure, 0 recoveries"));
+        assert_eq!(session_line(3, 2).as_deref(), Some("Session: 3 failures, 2 recoveries"));
+    }
+
+    #[test]
+    fn deployment_drift_gets_its_own_row() {
+        let now = Utc::now();
+        let drift = |ahead: u32| {
+            Some(EnvironmentDrift {
+                ahead,
+                truncated: false,
+                from: "staging".to_string(),
+                to: "prod".to_string(),
+            })
+        };
+        let plain = status(None, None, "api");
+        let drifting = PipelineStatusInfo { environment_drift: drift(4), ..plain.clone() };
+        let shown = model(vec![drifting.clone()], now);
+
+        assert_eq!(shown.groups[0].1[0].drift.as_deref(), Some("staging +4 vs prod"));
+        assert!(diff_menus(&shown, &model(vec![plain], now)).is_none());
+        let promoted = PipelineStatusInfo { environment_drift: drift(0), ..drifting };
+        assert_eq!(
+            diff_menus(&shown, &model(vec![promoted], now)),
+            Some(vec![MenuChange::Drift(0, "staging +0 vs prod".to_string())])
+        );
+        let far_ahead = EnvironmentDrift { ahead: 2000, truncated: true, ..drift(0).unwrap() };
+        assert_eq!(drift_text(&far_ahead), "staging +2000 or more vs prod");
+    }
+
+    #[test]
+    fn mttr_line_is_shown_for_pipelines_with_outages() {
+        let now = Utc::now();
+        let overall = OverallStatus::new(
+            vec![status(None, None, "api"), status(None, None, "web")],
+            "12:00:00".to_string(),
+            false,
+        );
+        let options = |secs: u64| MenuOptions {
+            mttr_secs: BTreeMap::from([(("ws".to_string(), "api".to_string()), secs)]),
+            ..MenuOptions::default()
+        };
+
+        let shown = status_menu_model(&overall, options(42 * 60), now).0;
+        let mttr: Vec<Option<&str>> = shown.rows().map(|row| row.mttr.as_deref()).collect();
+        assert_eq!(mttr, [Some("MTTR: 42m over last 30d"), None]);
+
+        let slower = status_menu_model(&overall, options(2 * 3600), now).0;
+        assert_eq!(
+            diff_menus(&shown, &slower),
+            Some(vec![MenuChange::Mttr(0, "MTTR: 2h 0m over last 30d".to_string())])
+        );
+    }
+
+    fn running(repo: &str, state: PipelineState, created_on: Option<&str>) -> PipelineStatusInfo {
+        PipelineStatusInfo {
+            state,
+            created_on: created_on.map(str::to_string),
+            ..status(Some("API"), None, repo)
+        }
+    }
+
+    #[test]
+    fn running_pipelines_are_planned_in_project_groups_by_default() {
+        let overall = OverallStatus::new(
+            vec![
+                running("api", PipelineState::InProgress, Some("2024-05-01T10:05:00Z")),
+                status(Some("API"), None, "web"),
+            ],
+            "12:00:00".to_string(),
+            false,
+        );
+
+        let plan = plan_menu(&overall, &MenuOptions::default());
+
+        assert!(plan.running.is_empty());
+        assert_eq!(plan.groups, vec![ProjectGroup { header: "API".to_string(), pipelines: vec![0, 1] }]);
+    }
+
+    #[test]
+    fn running_section_lists_longest_running_first_without_duplicates() {
+        let overall = OverallStatus::new(
+            vec![
+                running("api", PipelineState::InProgress, Some("2024-05-01T10:05:00Z")),
+                status(Some("API"), None, "web"),
+                running("deploy", PipelineState::Paused, Some("2024-05-01T09:30:00+00:00")),
+                running("cli", PipelineState::InProgress, None),
+                running("docs", PipelineState::InProgress, Some("2024-05-01T10:01:00Z")),
+            ],
+            "12:00:00".to_string(),
+            false,
+        );
+        let options = MenuOptions { group_running_separately: true, ..MenuOptions::default() };
+
+        let plan = plan_menu(&overall, &options);
+
+        assert_eq!(plan.running, vec![2, 4, 0, 3]);
+        assert_eq!(plan.groups, vec![ProjectGroup { header: "API".to_string(), pipelines: vec![1] }]);
+    }
+
+    #[test]
+    fn running_section_shows_elapsed_time_under_its_own_header() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T10:12:00Z").unwrap().with_timezone(&Utc);
+        let overall = OverallStatus::new(
+            vec![running("api", PipelineState::InProgress, Some("2024-05-01T10:05:00Z"))],
+            "12:00:00".to_string(),
+            false,
+        );
+        let options = MenuOptions { group_running_separately: true, ..MenuOptions::default() };
+
+        let model = status_menu_model(&overall, options, now).0;
+
+        assert_eq!(model.groups.len(), 1);
+        assert_eq!(model.groups[0].0, "RUNNING");
+        assert_eq!(model.groups[0].1[0].text, "  api - running (7m)");
+    }
+
+    #[test]
+    fn added_or_reordered_pipelines_need_a_rebuild() {
+        let now = Utc::now();
+        let api = status(Some("API"), None, "api");
+        let web = status(Some("API"), None, "web");
+        let shown = model(vec![api.clone(), web.clone()], now);
+
+        assert!(diff_menus(&shown, &model(vec![web.clone(), api.clone()], now)).is_none());
+        assert!(diff_menus(&shown, &model(vec![api.clone()], now)).is_none());
+        assert!(diff_menus(&shown, &model(vec![api, web, status(None, None, "cli")], now)).is_none());
+    }
+
+    #[test]
+    fn tray_manager_hands_out_unused_ids() {
+        let trays = TrayManager::default();
+        trays.register(TRAY_ID);
+
+        assert_eq!(trays.next_id(), "tray-0");
+        assert_eq!(trays.next_id(), "tray-1");
+        assert_eq!(trays.clone().next_id(), "tray-2");
+    }
+
+    #[test]
+    fn workspace_trays_release_their_ids() {
+        let trays = TrayManager::default();
+        trays.register(TRAY_ID);
+
+        assert_eq!(trays.add_workspace_tray("acme"), "tray-0");
+        assert_eq!(trays.add_workspace_tray("labs"), "tray-1");
+        trays.remove_workspace_tray("acme");
+
+        assert_eq!(trays.workspace_trays(), vec![("labs".to_string(), "tray-1".to_string())]);
+        // A released id isn't handed out again, so a tray still being removed can't clash
+        assert_eq!(trays.next_id(), "tray-2");
+    }
+
+    #[test]
+    fn workspace_menu_lists_only_its_pipelines_under_their_usual_ids() {
+        let now = Utc::now();
+        let in_workspace = |ws: &str, repo: &str, state: PipelineState| PipelineStatusInfo {
+            workspace: ws.to_string(),
+            state,
+            pipeline_url: Some(format!("https://bitbucket.org/{}/{}/pipelines/results/1", ws, repo)),
+            ..status(Some("API"), None, repo)
+        };
+        let overall = OverallStatus::new(
+            vec![
+                in_workspace("acme", "api", PipelineState::Failed),
+                in_workspace("labs", "web", PipelineState::Healthy),
+            ],
+            "12:00:00".to_string(),
+            false,
+        );
+        let options = MenuOptions { workspace: Some("labs".to_string()), ..MenuOptions::default() };
+
+        let (model, status_map) = status_menu_model(&overall, options, now);
+
+        assert_eq!(model.rows().count(), 1);
+        assert_eq!(model.rows().next().unwrap().key.1, "web");
+        assert_eq!(model.summary, "✓ 1 pipeline healthy");
+        assert!(status_map.contains_key("pipeline_1"));
+        assert!(!status_map.contains_key("pipeline_0"));
+    }
+
+    #[test]
+    fn rows_show_the_monitored_branch() {
+        let on_branch = |branch: &str| PipelineStatusInfo {
+            monitored_branch: Some(branch.to_string()),
+            ..status(None, None, "api")
+        };
+        let statuses = vec![on_branch("main"), on_branch("release/2024-hotfix-login"), status(None, None, "web")];
+
+        let model = model(statuses, Utc::now());
+
+        let texts: Vec<&str> = model.rows().map(|row| row.text.as_str()).collect();
+        assert_eq!(texts, ["  api (main)", "  api (release/2024-hotfix…)", "  web"]);
+    }
+
+    #[test]
+    fn format_age_uses_the_largest_whole_unit() {
+        let now = Utc::now();
+
+        assert_eq!(format_age(now, now - chrono::Duration::seconds(30)), "just now");
+        assert_eq!(format_age(now, now - chrono::Duration::minutes(4)), "4m ago");
+        assert_eq!(format_age(now, now - chrono::Duration::minutes(130)), "2h ago");
+        assert_eq!(format_age(now, now - chrono::Duration::days(3)), "3d ago");
+        // Clock skew never shows a negative age
+        assert_eq!(format_age(now, now + chrono::Duration::minutes(5)), "just now");
+    }
+
+    #[test]
+    fn last_checked_uses_the_oldest_pipeline_check() {
+        let now = Utc::now();
+        let mut fresh = status(Some("API"), None, "api");
+        fresh.checked_at = now;
+        let mut stale = status(Some("API"), None, "web");
+        stale.checked_at = now - chrono::Duration::minutes(10);
+        let overall = OverallStatus::new(vec![fresh, stale.clone()], "now".to_string(), false);
+
+        let expected = stale.checked_at.with_timezone(&Local).format("%H:%M:%S");
+        assert_eq!(last_checked_text(&overall), format!("Last checked: {}", expected));
+    }
+
+    #[test]
+    fn unknown_badge_depends_on_the_reason() {
+        let unknown = |reason| PipelineStatusInfo {
+            state: PipelineState::Unknown,
+            unknown_reason: Some(reason),
+            ..status(None, None, "api")
+        };
+
+        assert_eq!(
+            pipeline_badge(&unknown(UnknownReason::NoRuns)),
+            (MENU_ICON_GRAY, " - No pipeline runs found".to_string())
+        );
+        assert_eq!(
+            pipeline_badge(&unknown(UnknownReason::AuthFailed)),
+            (MENU_ICON_RED, " - ⚠ Auth error".to_string())
+        );
+        assert_eq!(
+            pipeline_badge(&unknown(UnknownReason::RateLimited)),
+            (MENU_ICON_GRAY, " - ⚠ Rate limited".to_string())
+        );
+        assert_eq!(
+            pipeline_badge(&unknown(UnknownReason::GateStepNotFound)),
+            (MENU_ICON_GRAY, " - gate step not found".to_string())
+        );
+    }
+
+    #[test]
+    fn disabled_pipelines_offer_removal() {
+        let disabled = PipelineStatusInfo {
+            state: PipelineState::Unknown,
+            unknown_reason: Some(UnknownReason::NoRuns),
+            pipelines_disabled: true,
+            ..status(None, None, "api")
+        };
+        let row = &model(vec![disabled], Utc::now()).groups[0].1[0];
+
+        assert_eq!(row.text, "  api - pipelines disabled");
+        let remove = row.quick_actions.last().unwrap();
+        assert_eq!(remove.id, "pipeline_remove_0");
+        assert_eq!(remove.label, "Remove from monitoring");
+    }
+
+    #[test]
+    fn summary_names_failing_pull_requests_that_count_against_health() {
+        let failing_pr = crate::pull_requests::PullRequestStatus {
+            workspace: "ws".to_string(),
+            repo_slug: "api".to_string(),
+            id: 7,
+            title: "Add login".to_string(),
+            branch: "feature/login".to_string(),
+            state: PipelineState::Failed,
+            url: None,
+        };
+        let overall = OverallStatus::new(vec![status(None, None, "api")], "12:00:00".to_string(), false)
+            .with_pull_requests(vec![failing_pr], true);
+
+        assert_eq!(summary_header_text(&overall), "⚠ 1 pull request failing");
+    }
+}
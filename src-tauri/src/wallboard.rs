@@ -0,0 +1,109 @@
+use crate::commands::save_config_helper;
+use crate::config::{AppState, WindowGeometry};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder, Window};
+use tokio::sync::Mutex;
+
+pub const WALLBOARD_LABEL: &str = "wallboard";
+
+/// Show the wallboard window, creating it on first use
+pub async fn open_wallboard<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(WALLBOARD_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    let (always_on_top, geometry) = {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        let state_guard = state.lock().await;
+        (
+            state_guard.wallboard_always_on_top,
+            state_guard.wallboard_geometry.clone(),
+        )
+    };
+
+    let url = WebviewUrl::App("wallboard.html".into());
+    let mut builder = WebviewWindowBuilder::new(app_handle, WALLBOARD_LABEL, url)
+        .title("cdMenu Wallboard")
+        .always_on_top(always_on_top);
+
+    match geometry {
+        Some(geometry) => {
+            builder = builder.inner_size(geometry.width, geometry.height);
+            // Only restore the position if the monitor it was on is still connected
+            if monitor_available(app_handle, geometry.monitor.as_deref()) {
+                builder = builder.position(geometry.x, geometry.y);
+            } else {
+                builder = builder.center();
+            }
+        }
+        None => {
+            builder = builder.inner_size(1280.0, 720.0).center();
+        }
+    }
+
+    let window = builder
+        .build()
+        .map_err(|e| format!("Failed to create wallboard window: {}", e))?;
+    let _ = window.set_focus();
+    tracing::info!("Wallboard window created");
+    Ok(())
+}
+
+/// Apply always-on-top to the wallboard if it is open
+pub fn apply_always_on_top<R: Runtime>(app_handle: &AppHandle<R>, always_on_top: bool) {
+    if let Some(window) = app_handle.get_webview_window(WALLBOARD_LABEL) {
+        let _ = window.set_always_on_top(always_on_top);
+    }
+}
+
+/// Remember the wallboard's size, position and monitor, then persist
+pub fn save_geometry(window: &Window) {
+    let Some(geometry) = current_geometry(window) else {
+        return;
+    };
+
+    let app_handle = window.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let state: tauri::State<Arc<Mutex<AppState>>> = app_handle.state();
+        {
+            let mut state_guard = state.lock().await;
+            state_guard.wallboard_geometry = Some(geometry);
+        }
+        if let Err(e) = save_config_helper(&app_handle, &state).await {
+            tracing::error!("Failed to save wallboard geometry: {}", e);
+        }
+    });
+}
+
+/// Read the window's logical geometry
+fn current_geometry(window: &Window) -> Option<WindowGeometry> {
+    let scale = window.scale_factor().ok()?;
+    let position = window.outer_position().ok()?.to_logical::<f64>(scale);
+    let size = window.inner_size().ok()?.to_logical::<f64>(scale);
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+
+    Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        monitor,
+    })
+}
+
+/// Whether a monitor with this name is connected (unnamed monitors always match)
+fn monitor_available<R: Runtime>(app_handle: &AppHandle<R>, name: Option<&str>) -> bool {
+    let Some(name) = name else {
+        return true;
+    };
+    app_handle
+        .available_monitors()
+        .map(|monitors| monitors.iter().any(|m| m.name().map(|n| n.as_str()) == Some(name)))
+        .unwrap_or(false)
+}
@@ -0,0 +1,401 @@
+use crate::bitbucket::TlsConfig;
+use crate::config::{MonitoredPipeline, PipelineStatusInfo};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// How long to wait for a webhook to accept an event
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Card colours for failures and recoveries
+const FAILED_COLOR: u32 = 0xD7_00_00;
+const FIXED_COLOR: u32 = 0x2E_B8_86;
+
+/// Payload shape a webhook target expects
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// Slack incoming webhook (`{"text": ...}`)
+    #[default]
+    Slack,
+    /// Microsoft Teams incoming webhook MessageCard
+    Teams,
+    /// Discord webhook embed
+    Discord,
+    /// The event's own fields, for custom receivers
+    GenericJson,
+}
+
+/// A named incoming-webhook URL (e.g. a Slack channel)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WebhookTarget {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+}
+
+/// Which way a pipeline's state changed
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Failed,
+    Fixed,
+}
+
+/// A pipeline failure or recovery, as forwarded to webhooks
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct WebhookEvent {
+    #[serde(rename = "event")]
+    pub transition: Transition,
+    pub title: String,
+    pub body: String,
+    pub workspace: String,
+    pub repo_slug: String,
+    pub repo_name: String,
+    pub branch: Option<String>,
+    pub build_number: Option<u32>,
+    pub url: Option<String>,
+}
+
+impl WebhookEvent {
+    pub fn new(pipeline: &PipelineStatusInfo, transition: Transition, title: &str, body: &str) -> Self {
+        Self {
+            transition,
+            title: title.to_string(),
+            body: body.to_string(),
+            workspace: pipeline.workspace.clone(),
+            repo_slug: pipeline.repo_slug.clone(),
+            repo_name: pipeline.repo_name.clone(),
+            branch: pipeline.branch.clone(),
+            build_number: pipeline.build_number,
+            url: pipeline.pipeline_url.clone(),
+        }
+    }
+
+    /// Made-up failure used to try out a target
+    pub fn sample() -> Self {
+        Self {
+            transition: Transition::Failed,
+            title: "Pipeline Failed".to_string(),
+            body: "cdMenu test notification - example-repo has failed".to_string(),
+            workspace: "example".to_string(),
+            repo_slug: "example-repo".to_string(),
+            repo_name: "example-repo".to_string(),
+            branch: Some("main".to_string()),
+            build_number: Some(1),
+            url: None,
+        }
+    }
+}
+
+impl WebhookFormat {
+    /// Request body for `event` in this format
+    pub fn payload(self, event: &WebhookEvent) -> Value {
+        let color = match event.transition {
+            Transition::Failed => FAILED_COLOR,
+            Transition::Fixed => FIXED_COLOR,
+        };
+
+        match self {
+            WebhookFormat::Slack => json!({ "text": format!("*{}*\n{}", event.title, event.body) }),
+            WebhookFormat::Teams => {
+                let mut card = json!({
+                    "@type": "MessageCard",
+                    "@context": "https://schema.org/extensions",
+                    "themeColor": format!("{:06X}", color),
+                    "summary": event.title,
+                    "title": event.title,
+                    "text": event.body,
+                });
+                if let Some(url) = &event.url {
+                    card["potentialAction"] = json!([{
+                        "@type": "OpenUri",
+                        "name": "Open pipeline",
+                        "targets": [{ "os": "default", "uri": url }],
+                    }]);
+                }
+                card
+            }
+            WebhookFormat::Discord => {
+                let mut embed = json!({
+                    "title": event.title,
+                    "description": event.body,
+                    "color": color,
+                });
+                if let Some(url) = &event.url {
+                    embed["url"] = json!(url);
+                }
+                json!({ "embeds": [embed] })
+            }
+            WebhookFormat::GenericJson => json!(event),
+        }
+    }
+}
+
+/// What a webhook answered, for troubleshooting
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Webhook targets and which one unrouted pipelines use
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct NotificationRoutes {
+    #[serde(default)]
+    pub targets: Vec<WebhookTarget>,
+    /// Target for pipelines without a `notification_route`
+    #[serde(default)]
+    pub default_target: Option<String>,
+}
+
+impl NotificationRoutes {
+    /// Check target names and URLs, and that every referenced route exists
+    pub fn validate(&self, pipelines: &[MonitoredPipeline]) -> Result<(), String> {
+        for (i, target) in self.targets.iter().enumerate() {
+            if target.name.trim().is_empty() {
+                return Err("Webhook target names can't be empty".to_string());
+            }
+            if self.targets[..i].iter().any(|t| t.name == target.name) {
+                return Err(format!("Webhook target '{}' is defined twice", target.name));
+            }
+            let url = reqwest::Url::parse(&target.url)
+                .map_err(|e| format!("Webhook target '{}' has an invalid URL: {}", target.name, e))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(format!(
+                    "Webhook target '{}' must use an http or https URL",
+                    target.name
+                ));
+            }
+        }
+
+        if let Some(default) = &self.default_target {
+            if self.find(default).is_none() {
+                return Err(format!(
+                    "Default notification route '{}' doesn't match any webhook target",
+                    default
+                ));
+            }
+        }
+
+        validate_pipeline_routes(self, pipelines)
+    }
+
+    /// Target for a pipeline's route, falling back to the default target
+    pub fn target_for(&self, route: Option<&str>) -> Option<&WebhookTarget> {
+        match route {
+            Some(name) => self.find(name),
+            None => self.default_target.as_deref().and_then(|name| self.find(name)),
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<&WebhookTarget> {
+        self.targets.iter().find(|t| t.name == name)
+    }
+}
+
+/// Check that every pipeline's `notification_route` names an existing target
+pub fn validate_pipeline_routes(
+    routes: &NotificationRoutes,
+    pipelines: &[MonitoredPipeline],
+) -> Result<(), String> {
+    for pipeline in pipelines {
+        if let Some(route) = &pipeline.notification_route {
+            if routes.find(route).is_none() {
+                return Err(format!(
+                    "{}/{} uses notification route '{}', which doesn't match any webhook target",
+                    pipeline.workspace, pipeline.repo_slug, route
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Post an event to a webhook in the target's format, returning whatever it answered
+///
+/// Uses the same TLS options as the Bitbucket client, so webhooks behind the same
+/// corporate proxy or private CA work too.
+pub async fn deliver(
+    target: &WebhookTarget,
+    event: &WebhookEvent,
+    tls: &TlsConfig,
+) -> Result<WebhookResponse, String> {
+    let client = tls.build_client(WEBHOOK_TIMEOUT).map_err(|e| format!("{}", e))?;
+
+    let response = client
+        .post(&target.url)
+        .json(&target.format.payload(event))
+        .send()
+        .await
+        .map_err(|e| format!("{}", e))?;
+
+    Ok(WebhookResponse {
+        status: response.status().as_u16(),
+        body: response.text().await.map_err(|e| format!("{}", e))?,
+    })
+}
+
+/// Post an event to a webhook, failing unless it was accepted
+pub async fn send(target: &WebhookTarget, event: &WebhookEvent, tls: &TlsConfig) -> Result<(), String> {
+    let response = deliver(target, event, tls).await?;
+    if !(200..300).contains(&response.status) {
+        return Err(format!("Webhook returned {}: {}", response.status, response.body));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes() -> NotificationRoutes {
+        NotificationRoutes {
+            targets: vec![
+                WebhookTarget {
+                    name: "platform".to_string(),
+                    url: "https://hooks.slack.com/services/T/B/platform".to_string(),
+                    format: WebhookFormat::Slack,
+                },
+                WebhookTarget {
+                    name: "mobile".to_string(),
+                    url: "https://hooks.slack.com/services/T/B/mobile".to_string(),
+                    format: WebhookFormat::Slack,
+                },
+            ],
+            default_target: Some("platform".to_string()),
+        }
+    }
+
+    fn pipeline(route: Option<&str>) -> MonitoredPipeline {
+        MonitoredPipeline {
+            workspace: "ws".to_string(),
+            repo_slug: "app".to_string(),
+            repo_name: "App".to_string(),
+            notification_route: route.map(str::to_string),
+            ..MonitoredPipeline::default()
+        }
+    }
+
+    #[test]
+    fn routed_pipeline_uses_its_target_and_others_use_default() {
+        let routes = routes();
+
+        assert_eq!(routes.target_for(Some("mobile")).unwrap().name, "mobile");
+        assert_eq!(routes.target_for(None).unwrap().name, "platform");
+    }
+
+    #[test]
+    fn unknown_route_is_rejected() {
+        let err = routes().validate(&[pipeline(Some("desktop"))]).unwrap_err();
+
+        assert!(err.contains("'desktop'"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_default_target_is_rejected() {
+        let routes = NotificationRoutes {
+            default_target: Some("ops".to_string()),
+            ..routes()
+        };
+
+        assert!(routes.validate(&[]).is_err());
+    }
+
+    #[test]
+    fn valid_routes_pass() {
+        assert!(routes().validate(&[pipeline(Some("mobile")), pipeline(None)]).is_ok());
+    }
+
+    fn event(url: Option<&str>) -> WebhookEvent {
+        WebhookEvent {
+            transition: Transition::Failed,
+            title: "Pipeline Failed".to_string(),
+            body: "API has failed".to_string(),
+            workspace: "ws".to_string(),
+            repo_slug: "api".to_string(),
+            repo_name: "API".to_string(),
+            branch: Some("main".to_string()),
+            build_number: Some(42),
+            url: url.map(str::to_string),
+        }
+    }
+
+    const URL: &str = "https://bitbucket.org/ws/api/pipelines/results/42";
+
+    #[test]
+    fn slack_payload() {
+        assert_eq!(
+            WebhookFormat::Slack.payload(&event(Some(URL))),
+            json!({ "text": "*Pipeline Failed*\nAPI has failed" })
+        );
+    }
+
+    #[test]
+    fn teams_payload() {
+        assert_eq!(
+            WebhookFormat::Teams.payload(&event(Some(URL))),
+            json!({
+                "@type": "MessageCard",
+                "@context": "https://schema.org/extensions",
+                "themeColor": "D70000",
+                "summary": "Pipeline Failed",
+                "title": "Pipeline Failed",
+                "text": "API has failed",
+                "potentialAction": [{
+                    "@type": "OpenUri",
+                    "name": "Open pipeline",
+                    "targets": [{ "os": "default", "uri": URL }],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn discord_payload() {
+        let fixed = WebhookEvent {
+            transition: Transition::Fixed,
+            title: "Pipeline Fixed".to_string(),
+            body: "API is now healthy".to_string(),
+            ..event(None)
+        };
+
+        assert_eq!(
+            WebhookFormat::Discord.payload(&fixed),
+            json!({
+                "embeds": [{
+                    "title": "Pipeline Fixed",
+                    "description": "API is now healthy",
+                    "color": 0x2EB886,
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn generic_json_payload() {
+        assert_eq!(
+            WebhookFormat::GenericJson.payload(&event(Some(URL))),
+            json!({
+                "event": "failed",
+                "title": "Pipeline Failed",
+                "body": "API has failed",
+                "workspace": "ws",
+                "repo_slug": "api",
+                "repo_name": "API",
+                "branch": "main",
+                "build_number": 42,
+                "url": URL,
+            })
+        );
+    }
+
+    #[test]
+    fn format_defaults_to_slack() {
+        let target: WebhookTarget =
+            serde_json::from_str(r#"{ "name": "ops", "url": "https://example.com/hook" }"#).unwrap();
+
+        assert_eq!(target.format, WebhookFormat::Slack);
+    }
+}
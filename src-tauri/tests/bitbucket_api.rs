@@ -0,0 +1,638 @@
+//! Exercises `BitbucketClient` against a mock Bitbucket API serving fixture JSON
+
+use cdmenu_lib::bitbucket::conditional::conditional_cache_stats;
+use cdmenu_lib::bitbucket::{
+    BitbucketClient, BitbucketError, Pipeline, PipelineVariable, TlsConfig, ACCESS_TOKEN_USERNAME,
+};
+use mockito::{Matcher, Server, ServerGuard};
+
+/// Load a fixture, pointing any `{{base}}` links at the mock server
+fn fixture(name: &str, server: &ServerGuard) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing fixture {}: {}", path, e))
+        .replace("{{base}}", &server.url())
+}
+
+fn client(server: &ServerGuard) -> BitbucketClient {
+    BitbucketClient::new("user", "secret", &TlsConfig::default())
+        .expect("client should build without TLS overrides")
+        .with_api_base(&server.url())
+}
+
+/// Serve the pipelines fixture whatever filter is requested, like an API that ignores it
+async fn serve_pipelines(server: &mut ServerGuard) -> mockito::Mock {
+    let body = fixture("pipelines.json", server);
+    server
+        .mock("GET", "/repositories/acme/api/pipelines/")
+        .match_query(Matcher::UrlEncoded("sort".into(), "-created_on".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(body)
+        .create_async()
+        .await
+}
+
+#[tokio::test]
+async fn lists_workspaces() {
+    let mut server = Server::new_async().await;
+    let body = fixture("workspaces.json", &server);
+    let mock = server
+        .mock("GET", "/workspaces")
+        .match_query(Matcher::Any)
+        .match_header("authorization", "Basic dXNlcjpzZWNyZXQ=")
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let workspaces = client(&server).get_workspaces().await.unwrap();
+
+    assert_eq!(workspaces.len(), 1);
+    assert_eq!(workspaces[0].slug, "acme");
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn follows_next_links_across_repository_pages() {
+    let mut server = Server::new_async().await;
+    let page1 = fixture("repositories_page1.json", &server);
+    let page2 = fixture("repositories_page2.json", &server);
+    let first = server
+        .mock("GET", "/repositories/acme")
+        .match_query(Matcher::UrlEncoded("sort".into(), "-updated_on".into()))
+        .with_status(200)
+        .with_body(page1)
+        .create_async()
+        .await;
+    let second = server
+        .mock("GET", "/repositories/acme")
+        .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+        .with_status(200)
+        .with_body(page2)
+        .create_async()
+        .await;
+
+    let repos = client(&server).get_repositories("acme").await.unwrap();
+
+    let slugs: Vec<&str> = repos.iter().map(|r| r.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["api", "web", "mobile"]);
+    assert_eq!(repos[2].html_url(), Some("https://bitbucket.org/acme/mobile"));
+    first.assert_async().await;
+    second.assert_async().await;
+}
+
+#[tokio::test]
+async fn looks_up_a_single_repository_directly() {
+    let mut server = Server::new_async().await;
+    let body = fixture("repository.json", &server);
+    let mock = server
+        .mock("GET", "/repositories/acme/api")
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let repo = client(&server).get_repository("acme", "api").await.unwrap();
+
+    assert_eq!(repo.full_name, "acme/api");
+    assert_eq!(repo.main_branch_name(), Some("master"));
+    assert_eq!(repo.project.map(|p| p.key), Some("CORE".to_string()));
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn latest_pipeline_requests_the_branch_filter() {
+    let mut server = Server::new_async().await;
+    let body = fixture("pipelines.json", &server);
+    let mock = server
+        .mock("GET", "/repositories/acme/api/pipelines/")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("q".into(), "target.ref_name=\"main\"".into()),
+            Matcher::UrlEncoded("sort".into(), "-created_on".into()),
+            Matcher::UrlEncoded("pagelen".into(), "5".into()),
+        ]))
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let pipeline = client(&server)
+        .get_latest_pipeline("acme", "api", Some("main"))
+        .await
+        .unwrap()
+        .expect("main has runs");
+
+    assert_eq!(pipeline.build_number, 14);
+    assert!(pipeline.is_in_progress());
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn latest_pipeline_rechecks_the_branch_when_the_api_ignores_the_filter() {
+    let mut server = Server::new_async().await;
+    let _mock = serve_pipelines(&mut server).await;
+    let client = client(&server);
+
+    let failed = client
+        .get_latest_pipeline("acme", "api", Some("feature/login"))
+        .await
+        .unwrap()
+        .expect("feature/login has a run");
+    let missing = client
+        .get_latest_pipeline("acme", "api", Some("hotfix"))
+        .await
+        .unwrap();
+
+    assert_eq!(failed.build_number, 13);
+    assert!(failed.is_failed());
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn latest_pipeline_falls_back_to_the_branch_parameter_when_the_filter_finds_nothing() {
+    let mut server = Server::new_async().await;
+    let filtered = server
+        .mock("GET", "/repositories/acme/api/pipelines/")
+        .match_query(Matcher::UrlEncoded("q".into(), "target.ref_name=\"feature/login\"".into()))
+        .with_status(200)
+        .with_body(r#"{"values": []}"#)
+        .create_async()
+        .await;
+    let body = fixture("pipelines.json", &server);
+    let fallback = server
+        .mock("GET", "/repositories/acme/api/pipelines/")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("target.branch".into(), "feature/login".into()),
+            Matcher::UrlEncoded("pagelen".into(), "20".into()),
+        ]))
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let pipeline = client(&server)
+        .get_latest_pipeline("acme", "api", Some("feature/login"))
+        .await
+        .unwrap()
+        .expect("feature/login has a run");
+
+    assert_eq!(pipeline.build_number, 13);
+    filtered.assert_async().await;
+    fallback.assert_async().await;
+}
+
+#[tokio::test]
+async fn latest_pipeline_without_branch_is_the_newest_run() {
+    let mut server = Server::new_async().await;
+    let _mock = serve_pipelines(&mut server).await;
+
+    let pipelines = client(&server)
+        .get_pipelines("acme", "api", None, 20)
+        .await
+        .unwrap();
+    let latest = client(&server)
+        .get_latest_pipeline("acme", "api", None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(pipelines.len(), 4);
+    assert!(pipelines[3].is_successful());
+    assert_eq!(latest.build_number, 14);
+}
+
+#[tokio::test]
+async fn finds_the_step_a_paused_pipeline_waits_on() {
+    let mut server = Server::new_async().await;
+    let _pipelines = serve_pipelines(&mut server).await;
+    let steps = fixture("steps_paused.json", &server);
+    let steps_mock = server
+        .mock("GET", "/repositories/acme/api/pipelines/%7Bpipe-12%7D/steps/")
+        .with_status(200)
+        .with_body(steps)
+        .create_async()
+        .await;
+    let client = client(&server);
+
+    let paused = client
+        .get_latest_pipeline("acme", "api", Some("release"))
+        .await
+        .unwrap()
+        .expect("release has a run");
+    assert!(paused.is_paused());
+
+    let step = client
+        .get_pending_step("acme", "api", &paused.uuid)
+        .await
+        .unwrap()
+        .expect("a step is pending");
+
+    assert_eq!(step.uuid, "{step-deploy}");
+    assert_eq!(step.name.as_deref(), Some("Deploy to production"));
+    steps_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn runs_a_custom_pipeline_with_variables() {
+    let mut server = Server::new_async().await;
+    let body = fixture("pipeline_created.json", &server);
+    let mock = server
+        .mock("POST", "/repositories/acme/api/pipelines/")
+        .match_body(Matcher::Json(serde_json::json!({
+            "target": {
+                "type": "pipeline_ref_target",
+                "ref_type": "branch",
+                "ref_name": "main",
+                "selector": { "type": "custom", "pattern": "deploy-staging" },
+            },
+            "variables": [{ "key": "DRY_RUN", "value": "true", "secured": false }],
+        })))
+        .with_status(201)
+        .with_body(body)
+        .create_async()
+        .await;
+    let variables = [PipelineVariable {
+        key: "DRY_RUN".to_string(),
+        value: "true".to_string(),
+        secured: false,
+    }];
+
+    let pipeline = client(&server)
+        .run_custom_pipeline("acme", "api", "main", "deploy-staging", &variables)
+        .await
+        .unwrap();
+
+    assert_eq!(pipeline.build_number, 15);
+    assert_eq!(
+        pipeline.html_url(),
+        Some("https://bitbucket.org/acme/api/pipelines/results/15")
+    );
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn lists_my_open_pull_requests() {
+    let mut server = Server::new_async().await;
+    let user = server
+        .mock("GET", "/user")
+        .with_status(200)
+        .with_body(r#"{ "display_name": "Me", "uuid": "{user-1}" }"#)
+        .create_async()
+        .await;
+    let body = fixture("pullrequests.json", &server);
+    let pull_requests = server
+        .mock("GET", "/repositories/acme/api/pullrequests")
+        .match_query(Matcher::UrlEncoded(
+            "q".into(),
+            r#"state="OPEN" AND author.uuid="{user-1}""#.into(),
+        ))
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let mine = client(&server)
+        .get_my_open_pull_requests("acme", "api")
+        .await
+        .unwrap();
+
+    assert_eq!(mine.len(), 1);
+    assert_eq!(mine[0].id, 42);
+    assert_eq!(mine[0].source.branch.name, "feature/login");
+    assert_eq!(
+        mine[0].html_url(),
+        Some("https://bitbucket.org/acme/api/pull-requests/42")
+    );
+    user.assert_async().await;
+    pull_requests.assert_async().await;
+}
+
+#[tokio::test]
+async fn latest_deployed_commit_skips_unfinished_and_failed_deployments() {
+    let mut server = Server::new_async().await;
+    let body = fixture("deployments.json", &server);
+    let mock = server
+        .mock("GET", "/repositories/acme/api/deployments/")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("q".into(), r#"environment.uuid="{env-staging}""#.into()),
+            Matcher::UrlEncoded("sort".into(), "-state.completed_on".into()),
+        ]))
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let commit = client(&server)
+        .get_latest_deployed_commit("acme", "api", "{env-staging}")
+        .await
+        .unwrap();
+
+    assert_eq!(commit.as_deref(), Some("aaa111"));
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn counts_commits_ahead_across_pages() {
+    let mut server = Server::new_async().await;
+    let page1 = fixture("commits_page1.json", &server);
+    let page2 = fixture("commits_page2.json", &server);
+    let first = server
+        .mock("GET", "/repositories/acme/api/commits/aaa111")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("exclude".into(), "fff000".into()),
+            Matcher::UrlEncoded("fields".into(), "next,values.hash".into()),
+        ]))
+        .with_status(200)
+        .with_body(page1)
+        .create_async()
+        .await;
+    let second = server
+        .mock("GET", "/repositories/acme/api/commits/aaa111")
+        .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+        .with_status(200)
+        .with_body(page2)
+        .create_async()
+        .await;
+
+    let ahead = client(&server)
+        .count_commits_ahead("acme", "api", "aaa111", "fff000")
+        .await
+        .unwrap();
+
+    assert_eq!(ahead, (4, false));
+    first.assert_async().await;
+    second.assert_async().await;
+}
+
+#[tokio::test]
+async fn commit_count_at_the_page_limit_is_a_lower_bound() {
+    let mut server = Server::new_async().await;
+    let endless = format!(
+        r#"{{"next": "{}/repositories/acme/api/commits/aaa111?page=2", "values": [{{"hash": "aaa111"}}]}}"#,
+        server.url()
+    );
+    let pages = server
+        .mock("GET", "/repositories/acme/api/commits/aaa111")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body(endless)
+        .expect(20)
+        .create_async()
+        .await;
+
+    let ahead = client(&server)
+        .count_commits_ahead("acme", "api", "aaa111", "fff000")
+        .await
+        .unwrap();
+
+    assert_eq!(ahead, (20, true));
+    pages.assert_async().await;
+}
+
+#[tokio::test]
+async fn reads_whether_pipelines_are_enabled() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("GET", "/repositories/acme/api/pipelines_config")
+        .with_status(200)
+        .with_body(r#"{ "enabled": false, "repository": { "type": "repository" } }"#)
+        .create_async()
+        .await;
+
+    let config = client(&server).get_pipelines_config("acme", "api").await.unwrap();
+
+    assert!(!config.enabled);
+    mock.assert_async().await;
+}
+
+fn token_client(server: &ServerGuard) -> BitbucketClient {
+    BitbucketClient::new(ACCESS_TOKEN_USERNAME, "token", &TlsConfig::default())
+        .expect("client should build without TLS overrides")
+        .with_api_base(&server.url())
+}
+
+#[tokio::test]
+async fn repository_token_is_valid_without_workspace_scope() {
+    let mut server = Server::new_async().await;
+    let body = fixture("repository.json", &server);
+    let repo = server
+        .mock("GET", "/repositories/acme/api")
+        .match_header("authorization", "Bearer token")
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+    let _pipelines = serve_pipelines(&mut server).await;
+    let _workspaces = server
+        .mock("GET", "/workspaces")
+        .match_query(Matcher::Any)
+        .with_status(403)
+        .create_async()
+        .await;
+
+    let check = token_client(&server)
+        .validate_access_token(Some(("acme", "api")))
+        .await
+        .unwrap();
+
+    assert!(check.can_read_repos);
+    assert!(check.can_read_pipelines);
+    assert!(!check.can_list_workspaces);
+    repo.assert_async().await;
+}
+
+#[tokio::test]
+async fn rejected_token_is_told_apart_from_missing_workspace_scope() {
+    let mut server = Server::new_async().await;
+    let _repo = server
+        .mock("GET", "/repositories/acme/api")
+        .with_status(401)
+        .create_async()
+        .await;
+    let _workspaces = server
+        .mock("GET", "/workspaces")
+        .match_query(Matcher::Any)
+        .with_status(403)
+        .create_async()
+        .await;
+    let client = token_client(&server);
+
+    let invalid = client.validate_access_token(Some(("acme", "api"))).await;
+    let unscoped = client.validate_access_token(None).await;
+
+    assert!(matches!(invalid, Err(BitbucketError::AuthenticationFailed)));
+    assert!(matches!(unscoped, Err(BitbucketError::Forbidden(_))));
+}
+
+/// Serve a pipelines listing tagged with `etag`, but only when `sent` is the `If-None-Match` sent
+async fn serve_tagged_pipelines(
+    server: &mut ServerGuard,
+    repo_slug: &str,
+    sent: Matcher,
+    etag: &str,
+    body: String,
+) -> mockito::Mock {
+    server
+        .mock("GET", format!("/repositories/acme/{}/pipelines/", repo_slug).as_str())
+        .match_query(Matcher::Any)
+        .match_header("if-none-match", sent)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", etag)
+        .with_body(body)
+        .create_async()
+        .await
+}
+
+// Credentials of their own, so no other test's cached responses are revalidated
+fn conditional_client(server: &ServerGuard) -> BitbucketClient {
+    BitbucketClient::new("etag-user", "secret", &TlsConfig::default())
+        .unwrap()
+        .with_api_base(&server.url())
+}
+
+#[tokio::test]
+async fn not_modified_returns_the_cached_pipelines() {
+    let mut server = Server::new_async().await;
+    let body = fixture("pipelines.json", &server);
+    let first = serve_tagged_pipelines(&mut server, "cached", Matcher::Missing, "\"v1\"", body).await;
+    let not_modified = server
+        .mock("GET", "/repositories/acme/cached/pipelines/")
+        .match_query(Matcher::Any)
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(304)
+        .create_async()
+        .await;
+    let client = conditional_client(&server);
+    let hits_before = conditional_cache_stats().hits;
+
+    let fetched = client.get_pipelines("acme", "cached", None, 20).await.unwrap();
+    let revalidated = client.get_pipelines("acme", "cached", None, 20).await.unwrap();
+
+    first.assert_async().await;
+    not_modified.assert_async().await;
+    let builds = |pipelines: &[Pipeline]| pipelines.iter().map(|p| p.build_number).collect::<Vec<_>>();
+    assert_eq!(revalidated.len(), 4);
+    assert_eq!(builds(&revalidated), builds(&fetched));
+    // Other tests run alongside, so only check the count went up
+    assert!(conditional_cache_stats().hits > hits_before);
+}
+
+#[tokio::test]
+async fn changed_etag_refreshes_the_cached_pipelines() {
+    let mut server = Server::new_async().await;
+    let body = fixture("pipelines.json", &server);
+    let mut listing: serde_json::Value = serde_json::from_str(&body).unwrap();
+    listing["values"].as_array_mut().unwrap().truncate(1);
+    let first = serve_tagged_pipelines(&mut server, "changed", Matcher::Missing, "\"v1\"", body).await;
+    let changed = serve_tagged_pipelines(
+        &mut server,
+        "changed",
+        Matcher::Exact("\"v1\"".into()),
+        "\"v2\"",
+        listing.to_string(),
+    )
+    .await;
+    let client = conditional_client(&server);
+
+    let fetched = client.get_pipelines("acme", "changed", None, 20).await.unwrap();
+    let refreshed = client.get_pipelines("acme", "changed", None, 20).await.unwrap();
+
+    first.assert_async().await;
+    changed.assert_async().await;
+    assert_eq!(fetched.len(), 4);
+    assert_eq!(refreshed.len(), 1);
+    assert_eq!(refreshed[0].build_number, fetched[0].build_number);
+}
+
+#[tokio::test]
+async fn lists_pipeline_schedules_with_their_branch() {
+    let mut server = Server::new_async().await;
+    let body = fixture("schedules.json", &server);
+    let mock = server
+        .mock("GET", "/repositories/acme/api/pipelines_config/schedules/")
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let schedules = client(&server).get_pipeline_schedules("acme", "api").await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(schedules.len(), 2);
+    assert!(schedules[0].enabled);
+    assert_eq!(schedules[0].cron_pattern, "0 0 6 * * ? *");
+    assert_eq!(schedules[0].branch.as_deref(), Some("main"));
+    assert_eq!(schedules[0].next_scheduled, None);
+    assert!(!schedules[1].enabled);
+    assert_eq!(schedules[1].next_scheduled.as_deref(), Some("2024-05-06T12:30:00.000Z"));
+}
+
+#[tokio::test]
+async fn maps_401_to_authentication_failed() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/workspaces")
+        .match_query(Matcher::Any)
+        .with_status(401)
+        .create_async()
+        .await;
+
+    let result = client(&server).get_workspaces().await;
+
+    assert!(matches!(result, Err(BitbucketError::AuthenticationFailed)));
+}
+
+#[tokio::test]
+async fn maps_403_to_forbidden() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/repositories/acme/api/pipelines/")
+        .match_query(Matcher::Any)
+        .with_status(403)
+        .create_async()
+        .await;
+
+    let result = client(&server).get_latest_pipeline("acme", "api", None).await;
+
+    assert!(matches!(result, Err(BitbucketError::Forbidden(_))));
+}
+
+#[tokio::test]
+async fn missing_build_number_is_none_but_other_404s_are_errors() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("GET", Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let client = client(&server);
+
+    let by_number = client.get_pipeline_by_build_number("acme", "api", 99).await;
+    let repo = client.get_repository("acme", "gone").await;
+
+    assert!(matches!(by_number, Ok(None)));
+    assert!(matches!(repo, Err(BitbucketError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn maps_429_with_retry_after() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/workspaces")
+        .match_query(Matcher::Any)
+        .with_status(429)
+        .with_header("retry-after", "30")
+        .create_async()
+        .await;
+
+    let result = client(&server).get_workspaces().await;
+
+    match result {
+        Err(e @ BitbucketError::RateLimited(Some(30))) => {
+            assert!(e.to_string().contains("retry after 30s"), "{}", e)
+        }
+        other => panic!("expected RateLimited(Some(30)), got {:?}", other),
+    }
+}
@@ -0,0 +1,77 @@
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+const filterInput = document.getElementById('activity-filter');
+const clearBtn = document.getElementById('clear-activity-btn');
+const list = document.getElementById('activity-list');
+const emptyState = document.getElementById('activity-empty');
+
+document.addEventListener('DOMContentLoaded', () => {
+    filterInput.addEventListener('input', load);
+    clearBtn.addEventListener('click', clearLog);
+
+    // New events are prepended while they match the filter
+    listen('activity-event', (event) => {
+        if (matchesFilter(event.payload)) {
+            list.prepend(renderEvent(event.payload));
+            emptyState.style.display = 'none';
+        }
+    });
+    listen('activity-cleared', load);
+
+    load();
+});
+
+async function load() {
+    try {
+        const events = await invoke('get_activity_log', { filter: filterInput.value || null });
+        list.innerHTML = '';
+        events.forEach(event => list.appendChild(renderEvent(event)));
+        emptyState.style.display = events.length ? 'none' : '';
+    } catch (e) {
+        console.error('Failed to load activity log:', e);
+    }
+}
+
+// Mirrors the backend's case-insensitive filter for live events
+function matchesFilter(event) {
+    const filter = filterInput.value.trim().toLowerCase();
+    if (!filter) return true;
+    return [event.pipelineKey, event.detail, event.kind]
+        .some(value => value && value.toLowerCase().includes(filter));
+}
+
+function renderEvent(event) {
+    const item = document.createElement('li');
+    item.className = `activity-${event.kind.replace('_', '-')}`;
+
+    const time = document.createElement('span');
+    time.className = 'activity-time';
+    const at = new Date(event.at);
+    time.textContent = at.toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' });
+    time.title = at.toLocaleString();
+    item.appendChild(time);
+
+    if (event.pipelineKey) {
+        const pipeline = document.createElement('span');
+        pipeline.className = 'activity-pipeline';
+        // "workspace/repo@branch" reads as "repo@branch"
+        pipeline.textContent = event.pipelineKey.split('/').slice(1).join('/');
+        pipeline.title = event.pipelineKey;
+        item.appendChild(pipeline);
+    }
+
+    const detail = document.createElement('span');
+    detail.textContent = event.detail;
+    item.appendChild(detail);
+    return item;
+}
+
+async function clearLog() {
+    if (!confirm('Remove every entry from the activity log?')) return;
+    try {
+        await invoke('clear_activity_log');
+    } catch (e) {
+        console.error('Failed to clear activity log:', e);
+    }
+}
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * When a pipeline matters, in local time, e.g. weekdays 09:00-18:00
+ *
+ * A window whose `end` is before its `start` runs past midnight and belongs
+ * to the day it starts on.
+ */
+export type ActiveHours = { 
+/**
+ * Days the window starts on, e.g. ["Mon", "Tue"]
+ */
+days: Array<string>, 
+/**
+ * "HH:MM"
+ */
+start: string, 
+/**
+ * "HH:MM"
+ */
+end: string, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * When the digest notification is posted, in local time, and the days it covers
+ */
+export type DigestSettings = { 
+/**
+ * Post the digest as a notification (opt-in)
+ */
+enabled: boolean, weekday: string, 
+/**
+ * "HH:MM"
+ */
+time: string, windowDays: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Two deployment environments to compare, by name or slug, e.g. staging against production
+ */
+export type DriftEnvironments = { from: string, to: string, };
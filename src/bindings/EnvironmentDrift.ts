@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Commits deployed to `from` that `to` doesn't have yet
+ */
+export type EnvironmentDrift = { ahead: number, 
+/**
+ * Counting stopped at the page limit, so `ahead` is a lower bound
+ */
+truncated: boolean, from: string, to: string, };
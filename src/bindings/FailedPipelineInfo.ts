@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Information about a failed pipeline
+ */
+export type FailedPipelineInfo = { workspace: string, repoSlug: string, repoName: string, branch: string | null, buildNumber: number, failureReason: string, };
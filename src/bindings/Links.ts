@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Link } from "./Link";
+
+/**
+ * Hypermedia links attached to API objects
+ */
+export type Links = { 
+/**
+ * Web page for the object
+ */
+html: Link | null, };
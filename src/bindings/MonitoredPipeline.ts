@@ -0,0 +1,61 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActiveHours } from "./ActiveHours";
+import type { DriftEnvironments } from "./DriftEnvironments";
+import type { QuickAction } from "./QuickAction";
+
+/**
+ * A pipeline configuration to monitor
+ */
+export type MonitoredPipeline = { workspace: string, projectKey: string | null, projectName: string | null, repoSlug: string, repoName: string, 
+/**
+ * Optional: monitor a specific branch only
+ */
+branch: string | null, 
+/**
+ * Repository web URL from the API, used to build pipeline links
+ */
+repoUrl: string | null, 
+/**
+ * Still shown in the tray, but never notifies
+ */
+muted: boolean, 
+/**
+ * When the pipeline was first monitored (RFC 3339, UTC)
+ */
+addedAt: string | null, 
+/**
+ * Webhook target for failure/recovery events; None uses the default target
+ */
+notificationRoute: string | null, 
+/**
+ * Follow one pipeline definition (selector pattern) across all branches instead of `branch`
+ */
+pipelineDefinitionUuid: string | null, 
+/**
+ * Outside these hours failures don't count toward overall health or notify
+ */
+activeHours: ActiveHours | null, 
+/**
+ * Custom pipelines that can be started from the tray
+ */
+quickActions: Array<QuickAction>, 
+/**
+ * Also list the pipelines of the user's open pull requests in this repository
+ */
+trackMyPrs: boolean, 
+/**
+ * Without a `branch`, follow the repository's main branch instead of any branch
+ */
+autoDefaultBranch: boolean, 
+/**
+ * Main branch looked up for `auto_default_branch`
+ */
+resolvedBranch: string | null, 
+/**
+ * Judge health by this step alone (e.g. "deploy-prod"), ignoring the rest of the run
+ */
+gateStep: string | null, 
+/**
+ * Show how many commits one environment's deployment is ahead of another's
+ */
+driftEnvironments: DriftEnvironments | null, };
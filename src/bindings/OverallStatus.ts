@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FailedPipelineInfo } from "./FailedPipelineInfo";
+import type { PipelineStatusInfo } from "./PipelineStatusInfo";
+import type { PullRequestStatus } from "./PullRequestStatus";
+
+/**
+ * Overall status of all monitored pipelines
+ */
+export type OverallStatus = { isHealthy: boolean, failedPipelines: Array<FailedPipelineInfo>, pipelineStatuses: Array<PipelineStatusInfo>, inProgressCount: number, 
+/**
+ * Pipelines paused waiting for manual approval
+ */
+pausedCount: number, 
+/**
+ * Pipelines with no run data (Unknown without an error)
+ */
+unknownCount: number, totalMonitored: number, lastChecked: string, 
+/**
+ * Pipelines of the user's open pull requests, listed separately from monitored pipelines
+ */
+pullRequests: Array<PullRequestStatus>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Failures and outages of one pipeline within a digest window
+ */
+export type PipelineDigest = { workspace: string, repoSlug: string, repoName: string, failures: number, recoveries: number, 
+/**
+ * Time spent failing inside the window, including an outage still going on
+ */
+redSecs: number, };
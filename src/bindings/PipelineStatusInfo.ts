@@ -0,0 +1,103 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EnvironmentDrift } from "./EnvironmentDrift";
+import type { PipelineState } from "./PipelineState";
+import type { QuickAction } from "./QuickAction";
+import type { RunRecord } from "./RunRecord";
+import type { UnknownReason } from "./UnknownReason";
+
+/**
+ * Individual pipeline status info
+ */
+export type PipelineStatusInfo = { workspace: string, projectKey: string | null, projectName: string | null, repoSlug: string, repoName: string, state: PipelineState, failureReason: string | null, 
+/**
+ * Name of the step that failed, for failed pipelines
+ */
+failedStepName: string | null, 
+/**
+ * Step that alone decides this pipeline's state, when configured
+ */
+gateStep: string | null, 
+/**
+ * Debug representation of the `BitbucketError` when the check failed
+ */
+errorKind: string | null, 
+/**
+ * The check couldn't reach Bitbucket, e.g. the connection failed or timed out
+ */
+networkError: boolean, 
+/**
+ * Set when the state is Unknown
+ */
+unknownReason: UnknownReason | null, pipelineUrl: string | null, 
+/**
+ * Repository web page
+ */
+repoUrl: string | null, 
+/**
+ * Branch the latest run was for
+ */
+branch: string | null, 
+/**
+ * Branch view for the latest run's branch
+ */
+branchUrl: string | null, 
+/**
+ * Branch the pipeline is monitored on, when one is configured
+ */
+monitoredBranch: string | null, 
+/**
+ * Stage name when pipeline is paused (e.g., deployment environment)
+ */
+stageName: string | null, 
+/**
+ * Pending step a paused pipeline is waiting on
+ */
+pausedStepUuid: string | null, 
+/**
+ * How the latest run was started, e.g. "push" or "schedule"
+ */
+triggerType: string | null, 
+/**
+ * Who started the latest run, i.e. who pushed the commit for push-triggered runs
+ */
+commitAuthor: string | null, 
+/**
+ * Variables of a custom or manual run, secured values masked as the API returns them
+ */
+variables: Array<[string, string]> | null, 
+/**
+ * Commits deployed to one environment but not yet to the other
+ */
+environmentDrift: EnvironmentDrift | null, 
+/**
+ * Pipelines is switched off in the repository's settings
+ */
+pipelinesDisabled: boolean, 
+/**
+ * Notifications are suppressed for this pipeline
+ */
+muted: boolean, 
+/**
+ * Webhook target for failure/recovery events
+ */
+notificationRoute: string | null, 
+/**
+ * Custom pipelines that can be started from the tray
+ */
+quickActions: Array<QuickAction>, 
+/**
+ * Outside the pipeline's active hours: shown, but not counted or notified
+ */
+offHours: boolean, pipelineUuid: string | null, buildNumber: number | null, createdOn: string | null, completedOn: string | null, 
+/**
+ * Run time so far, or total run time once completed
+ */
+durationSecs: number | null, 
+/**
+ * Recent finished runs, oldest first
+ */
+history: Array<RunRecord>, 
+/**
+ * When this entry's data was fetched
+ */
+checkedAt: string, };
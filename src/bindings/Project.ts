@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Bitbucket project (within a workspace)
+ */
+export type Project = { uuid: string, key: string, name: string, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PipelineState } from "./PipelineState";
+
+/**
+ * Latest pipeline of one of the user's open pull requests
+ */
+export type PullRequestStatus = { workspace: string, repoSlug: string, id: number, title: string, 
+/**
+ * Source branch the pipeline ran on
+ */
+branch: string, 
+/**
+ * Unknown when the branch has no runs yet
+ */
+state: PipelineState, url: string | null, };
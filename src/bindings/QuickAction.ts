@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A custom pipeline run offered in a pipeline's tray submenu, e.g. `custom: deploy-staging`
+ */
+export type QuickAction = { label: string, 
+/**
+ * Custom pipeline name from bitbucket-pipelines.yml
+ */
+selector: string, 
+/**
+ * Branch to run on; None uses the branch of the latest run
+ */
+branch: string | null, };
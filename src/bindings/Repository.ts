@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BranchRef } from "./BranchRef";
+import type { Links } from "./Links";
+import type { Project } from "./Project";
+
+/**
+ * Bitbucket repository
+ */
+export type Repository = { uuid: string, slug: string, name: string, full_name: string, project: Project | null, links: Links | null, 
+/**
+ * Branch the repository builds by default
+ */
+mainbranch: BranchRef | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RunResult } from "./RunResult";
+
+/**
+ * A finished run remembered for the history glyphs
+ */
+export type RunRecord = { buildNumber: number, result: RunResult, };
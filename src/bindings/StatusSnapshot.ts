@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OverallStatus } from "./OverallStatus";
+
+/**
+ * Last known status plus whether a check is currently running
+ */
+export type StatusSnapshot = { checking: boolean, status: OverallStatus | null, };
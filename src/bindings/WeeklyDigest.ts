@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PipelineDigest } from "./PipelineDigest";
+
+/**
+ * Summary of failures and recoveries over the last `window_days`
+ */
+export type WeeklyDigest = { since: string, until: string, windowDays: number, failures: number, recoveries: number, 
+/**
+ * Mean length of the outages that ended in the window
+ */
+meanTimeToRecoverySecs: number | null, 
+/**
+ * Pipelines that failed or were red in the window, worst offender first
+ */
+pipelines: Array<PipelineDigest>, 
+/**
+ * One-line summary, as posted in the notification
+ */
+headline: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Bitbucket workspace
+ */
+export type Workspace = { uuid: string, slug: string, name: string, };
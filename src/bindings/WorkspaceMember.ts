@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Member of a workspace, as listed by `/workspaces/{workspace}/members`
+ */
+export type WorkspaceMember = { account_id: string, display_name: string, nickname: string, };
@@ -5,18 +5,30 @@ const { listen } = window.__TAURI__.event;
 const opener = window.__TAURI_PLUGIN_OPENER__ || window.__TAURI__?.opener;
 const open = opener?.open || (() => window.open(arguments[0], '_blank'));
 
+// Shape version of the status-updated event payload
+const STATUS_PAYLOAD_VERSION = 1;
+
 // State
 let currentUsername = '';
 let currentAppPassword = '';
 let workspaces = [];
 let projects = [];
 let repositories = [];
+/** @type {import('./bindings/MonitoredPipeline').MonitoredPipeline[]} */
 let monitoredPipelines = [];
+let pipelineStats = [];
+let monitoredGroups = [];
 let currentWorkspace = '';
+let workspaceMembers = [];
+let lastStatus = null;
 
 // DOM Elements
 const authForm = document.getElementById('auth-form');
 const usernameInput = document.getElementById('username');
+const credentialKindSelect = document.getElementById('credential-kind');
+const usernameGroup = document.getElementById('username-group');
+const tokenRepositoryGroup = document.getElementById('token-repository-group');
+const tokenRepositoryInput = document.getElementById('token-repository');
 const appPasswordInput = document.getElementById('app-password');
 const saveAuthBtn = document.getElementById('save-auth-btn');
 const authStatus = document.getElementById('auth-status');
@@ -24,27 +36,111 @@ const workspaceSelect = document.getElementById('workspace-select');
 const projectSelect = document.getElementById('project-select');
 const repoSelect = document.getElementById('repo-select');
 const addPipelineBtn = document.getElementById('add-pipeline-btn');
+const pipelinePreview = document.getElementById('pipeline-preview');
+const repoSlugInput = document.getElementById('repo-slug-input');
+const repoDetails = document.getElementById('repo-details');
 const pipelineList = document.getElementById('pipeline-list');
+const addGroupBtn = document.getElementById('add-group-btn');
+const groupList = document.getElementById('group-list');
 const pollingIntervalInput = document.getElementById('polling-interval');
+const reduceOnBatteryInput = document.getElementById('reduce-on-battery');
+const apiBudgetInput = document.getElementById('api-budget');
+const pauseOnMeteredInput = document.getElementById('pause-on-metered');
+const pollingModeText = document.getElementById('polling-mode');
+const nextCheckText = document.getElementById('next-check');
+const notifyOnPausedInput = document.getElementById('notify-on-paused');
+const notificationCategoryInputs = document.querySelectorAll('.notification-category');
+const treatPausedAsUnhealthyInput = document.getElementById('treat-paused-as-unhealthy');
+const includePrsInHealthInput = document.getElementById('include-prs-in-health');
+const showHistoryGlyphsInput = document.getElementById('show-history-glyphs');
+const hideUnknownInMenuInput = document.getElementById('hide-unknown-in-menu');
+const groupRunningSeparatelyInput = document.getElementById('group-running-separately');
+const multiTrayInput = document.getElementById('multi-tray');
+const flashOnFailureInput = document.getElementById('flash-on-failure');
+const iconThemeSelect = document.getElementById('icon-theme-select');
+const localeSelect = document.getElementById('locale-select');
+const wallboardOnTopInput = document.getElementById('wallboard-always-on-top');
+const wallboardOnLaunchInput = document.getElementById('wallboard-start-on-launch');
+const openWallboardBtn = document.getElementById('open-wallboard-btn');
+const openActivityBtn = document.getElementById('open-activity-btn');
 const statusDetails = document.getElementById('status-details');
+const pipelineDetailSection = document.getElementById('pipeline-detail-section');
+const pipelineDetailTitle = document.getElementById('pipeline-detail-title');
+const pipelineDetailStatus = document.getElementById('pipeline-detail-status');
+const pipelineDetailActivity = document.getElementById('pipeline-detail-activity');
+const pipelineDetailSchedules = document.getElementById('pipeline-detail-schedules');
+const pipelineDetailMuteBtn = document.getElementById('pipeline-detail-mute-btn');
+const incidentList = document.getElementById('incident-list');
+const digestHeadline = document.getElementById('digest-headline');
+const digestMttr = document.getElementById('digest-mttr');
+const digestList = document.getElementById('digest-list');
+const digestEnabledInput = document.getElementById('digest-enabled');
+const digestWeekdaySelect = document.getElementById('digest-weekday');
+const digestTimeInput = document.getElementById('digest-time');
+const digestWindowInput = document.getElementById('digest-window');
+const memberList = document.getElementById('member-list');
 const statusIndicator = document.getElementById('status-indicator');
 const createPasswordLink = document.getElementById('create-password-link');
+const hotkeyRefreshInput = document.getElementById('hotkey-refresh');
+const hotkeyOpenSettingsInput = document.getElementById('hotkey-open-settings');
+const hotkeyQuickOpenInput = document.getElementById('hotkey-quick-open');
+const hotkeyStatus = document.getElementById('hotkey-status');
+const customCaPathInput = document.getElementById('custom-ca-path');
+const acceptInvalidCertsInput = document.getElementById('accept-invalid-certs');
+const tlsStatus = document.getElementById('tls-status');
+const followStatusPathInput = document.getElementById('follow-status-path');
+const publishStatusPathInput = document.getElementById('publish-status-path');
+const followerMode = document.getElementById('follower-mode');
+const followerStatus = document.getElementById('follower-status');
 
 // Initialize
 document.addEventListener('DOMContentLoaded', async () => {
     await loadSavedCredentials();
     await loadMonitoredPipelines();
+    await loadMonitoredGroups();
     await loadPollingInterval();
+    await loadHotkeys();
+    await loadTlsSettings();
+    await loadFollowerMode();
+    await loadWallboardOptions();
     await loadCurrentStatus();
+    await loadIncidentLog();
+    await loadDigestSettings();
+    await loadWeeklyDigest();
     setupEventListeners();
     listenForStatusUpdates();
+    listenForFirstRun();
+    listenForStalePipelines();
+    listenForIntervalChanges();
+    listenForNavigation();
+    setInterval(() => {
+        if (nextCheckIn > 0) {
+            nextCheckIn -= 1;
+            renderNextCheck();
+        }
+    }, 1000);
 });
 
+const ACCESS_TOKEN_USERNAME = 'x-token-auth';
+
+function updateCredentialKindFields() {
+    const isToken = credentialKindSelect.value === 'access_token';
+    usernameGroup.style.display = isToken ? 'none' : '';
+    usernameInput.required = !isToken;
+    tokenRepositoryGroup.style.display = isToken ? '' : 'none';
+}
+
 async function loadSavedCredentials() {
     try {
         const username = await invoke('get_credentials');
         if (username) {
-            usernameInput.value = username;
+            // Access tokens are saved under a placeholder username
+            if (username === ACCESS_TOKEN_USERNAME) {
+                credentialKindSelect.value = 'access_token';
+                updateCredentialKindFields();
+            } else {
+                usernameInput.value = username;
+            }
             currentUsername = username;
             // Try to get password to check if we have valid credentials
             const password = await invoke('get_app_password');
@@ -63,24 +159,144 @@ async function loadSavedCredentials() {
 async function loadMonitoredPipelines() {
     try {
         monitoredPipelines = await invoke('get_monitored_pipelines');
+        pipelineStats = await invoke('get_pipeline_stats');
         renderPipelineList();
     } catch (e) {
         console.error('Failed to load monitored pipelines:', e);
     }
 }
 
+async function loadMonitoredGroups() {
+    try {
+        monitoredGroups = await invoke('get_monitored_groups');
+        renderGroupList();
+    } catch (e) {
+        console.error('Failed to load monitored projects:', e);
+    }
+}
+
 async function loadPollingInterval() {
     try {
-        const interval = await invoke('get_polling_interval');
-        pollingIntervalInput.value = interval;
+        const polling = await invoke('get_polling_interval');
+        pollingIntervalInput.value = polling.interval_seconds;
+        pollingIntervalInput.min = polling.min_interval_seconds;
+        showNextCheck(polling);
+        notifyOnPausedInput.checked = await invoke('get_notify_on_paused');
+        const categories = await invoke('get_notification_categories');
+        notificationCategoryInputs.forEach(input => {
+            input.checked = categories.some(c => c.category === input.dataset.category && c.enabled);
+        });
+        treatPausedAsUnhealthyInput.checked = await invoke('get_treat_paused_as_unhealthy');
+        includePrsInHealthInput.checked = await invoke('get_include_prs_in_health');
+        showHistoryGlyphsInput.checked = await invoke('get_show_history_glyphs');
+        hideUnknownInMenuInput.checked = await invoke('get_hide_unknown_in_menu');
+        groupRunningSeparatelyInput.checked = await invoke('get_group_running_separately');
+        multiTrayInput.checked = await invoke('get_multi_tray');
+        flashOnFailureInput.checked = (await invoke('get_attention_animation')) === 'flash';
+        iconThemeSelect.value = await invoke('get_icon_theme');
+        localeSelect.value = (await invoke('get_locale')) ?? '';
+        await loadPollingStatus();
     } catch (e) {
         console.error('Failed to load polling interval:', e);
     }
 }
 
+// Seconds until the next check, counted down locally between refreshes; null while paused
+let nextCheckIn = null;
+
+function showNextCheck(polling) {
+    nextCheckIn = polling.is_paused ? null : polling.next_check_in_seconds;
+    renderNextCheck();
+}
+
+function renderNextCheck() {
+    if (nextCheckIn === null) {
+        nextCheckText.textContent = '';
+    } else if (nextCheckIn > 0) {
+        nextCheckText.textContent = `Next check in ${nextCheckIn}s`;
+    } else {
+        nextCheckText.textContent = 'Checking now';
+    }
+}
+
+async function refreshNextCheck() {
+    try {
+        showNextCheck(await invoke('get_polling_interval'));
+    } catch (e) {
+        console.error('Failed to load next check:', e);
+    }
+}
+
+async function loadPollingStatus() {
+    try {
+        const policy = await invoke('get_polling_status');
+        reduceOnBatteryInput.checked = policy.reduce_polling_on_battery;
+        pauseOnMeteredInput.checked = policy.pause_on_metered;
+        apiBudgetInput.value = policy.api_budget.limit_per_hour;
+        const budget = policy.api_budget;
+        const rateLimit = await invoke('get_rate_limit_info');
+        if (policy.mode === 'reduced_for_api_budget' && rateLimit && rateLimit.is_low) {
+            const resetAt = new Date(rateLimit.reset_at * 1000).toLocaleTimeString();
+            pollingModeText.textContent = `Rate limit: ${rateLimit.remaining} of ${rateLimit.limit} requests left until ${resetAt}, checking every ${policy.effective_interval_seconds}s`;
+        } else if (policy.mode === 'reduced_for_api_budget') {
+            pollingModeText.textContent = `API budget: ${budget.busiest_workspace} would use ${budget.projected_per_hour} requests/hour, checking every ${policy.effective_interval_seconds}s`;
+        } else if (policy.mode === 'reduced_on_battery') {
+            pollingModeText.textContent = `On battery: checking every ${policy.effective_interval_seconds}s`;
+        } else if (policy.mode === 'paused_on_metered') {
+            pollingModeText.textContent = 'Paused: metered network';
+        } else {
+            pollingModeText.textContent = '';
+        }
+    } catch (e) {
+        console.error('Failed to load polling status:', e);
+    }
+}
+
+async function loadHotkeys() {
+    try {
+        const hotkeys = await invoke('get_hotkeys');
+        hotkeyRefreshInput.value = hotkeys.refresh || '';
+        hotkeyOpenSettingsInput.value = hotkeys.open_settings || '';
+        hotkeyQuickOpenInput.value = hotkeys.quick_open || '';
+    } catch (e) {
+        console.error('Failed to load hotkeys:', e);
+    }
+}
+
+async function loadTlsSettings() {
+    try {
+        const tls = await invoke('get_tls_settings');
+        customCaPathInput.value = tls.custom_ca_path || '';
+        acceptInvalidCertsInput.checked = tls.danger_accept_invalid_certs;
+    } catch (e) {
+        console.error('Failed to load TLS settings:', e);
+    }
+}
+
+async function loadFollowerMode() {
+    try {
+        const follower = await invoke('get_follower_mode');
+        followStatusPathInput.value = follower.follow_status_path || '';
+        publishStatusPathInput.value = follower.publish_status_path || '';
+        followerMode.textContent = follower.following ? 'Following the published status' : '';
+    } catch (e) {
+        console.error('Failed to load follower mode:', e);
+    }
+}
+
+async function loadWallboardOptions() {
+    try {
+        const options = await invoke('get_wallboard_options');
+        wallboardOnTopInput.checked = options.always_on_top;
+        wallboardOnLaunchInput.checked = options.start_on_launch;
+    } catch (e) {
+        console.error('Failed to load wallboard options:', e);
+    }
+}
+
 async function loadCurrentStatus() {
     try {
-        const status = await invoke('get_pipeline_statuses');
+        const { status } = await invoke('get_pipeline_statuses');
         if (status) {
             updateStatusDisplay(status);
         }
@@ -89,22 +305,159 @@ async function loadCurrentStatus() {
     }
 }
 
+async function loadIncidentLog() {
+    try {
+        const incidents = await invoke('get_incident_log');
+        renderIncidentList(incidents);
+    } catch (e) {
+        console.error('Failed to load incident log:', e);
+    }
+}
+
+function formatDuration(secs) {
+    const days = Math.floor(secs / 86400);
+    const hours = Math.floor((secs % 86400) / 3600);
+    const minutes = Math.floor((secs % 3600) / 60);
+    if (days > 0) return `${days}d ${hours}h`;
+    if (hours > 0) return `${hours}h ${minutes}m`;
+    if (minutes > 0) return `${minutes}m`;
+    return `${secs}s`;
+}
+
+function renderIncidentList(incidents) {
+    if (incidents.length === 0) {
+        incidentList.innerHTML = '<li class="empty">No incidents since launch</li>';
+        return;
+    }
+
+    incidentList.innerHTML = incidents.map(i => `
+        <li>
+            <span class="pipeline-name">${i.repo_name || i.repo_slug}</span>
+            <span class="incident-detail">down ${formatDuration(i.duration_secs)}, fixed ${new Date(i.ended_at).toLocaleString()}</span>
+        </li>
+    `).join('');
+}
+
+async function loadDigestSettings() {
+    try {
+        /** @type {import('./bindings/DigestSettings').DigestSettings} */
+        const settings = await invoke('get_digest_settings');
+        digestEnabledInput.checked = settings.enabled;
+        digestWeekdaySelect.value = settings.weekday;
+        digestTimeInput.value = settings.time;
+        digestWindowInput.value = settings.windowDays;
+    } catch (e) {
+        console.error('Failed to load digest settings:', e);
+    }
+}
+
+async function loadWeeklyDigest() {
+    try {
+        renderWeeklyDigest(await invoke('get_weekly_digest'));
+    } catch (e) {
+        console.error('Failed to load weekly digest:', e);
+    }
+}
+
+/** @param {import('./bindings/WeeklyDigest').WeeklyDigest} digest */
+function renderWeeklyDigest(digest) {
+    digestHeadline.textContent = digest.headline;
+    digestMttr.textContent = digest.meanTimeToRecoverySecs === null
+        ? ''
+        : `Mean time to recovery: ${formatDuration(digest.meanTimeToRecoverySecs)}`;
+
+    if (digest.pipelines.length === 0) {
+        digestList.innerHTML = '<li class="empty">No failures in this window</li>';
+        return;
+    }
+
+    digestList.innerHTML = digest.pipelines.map(p => `
+        <li>
+            <span class="pipeline-name">${p.repoName || p.repoSlug}</span>
+            <span class="incident-detail">${p.failures} failed, red for ${formatDuration(p.redSecs)}</span>
+        </li>
+    `).join('');
+}
+
+async function saveDigestSettings() {
+    try {
+        await invoke('set_digest_settings', {
+            settings: {
+                enabled: digestEnabledInput.checked,
+                weekday: digestWeekdaySelect.value,
+                time: digestTimeInput.value,
+                windowDays: parseInt(digestWindowInput.value, 10) || 7
+            }
+        });
+        await loadWeeklyDigest();
+        showNotification('Digest settings saved!', 'success');
+    } catch (e) {
+        showNotification(`Failed to save digest settings: ${e}`, 'error');
+    }
+}
+
+async function loadWorkspaceMembers(workspace) {
+    if (!currentUsername || !currentAppPassword) return;
+
+    memberList.innerHTML = '<li class="empty">Loading...</li>';
+    try {
+        workspaceMembers = await invoke('get_workspace_members', {
+            username: currentUsername,
+            appPassword: currentAppPassword,
+            workspace
+        });
+        renderMemberList();
+    } catch (e) {
+        console.error('Failed to load workspace members:', e);
+        workspaceMembers = [];
+        memberList.innerHTML = `<li class="empty">${e}</li>`;
+    }
+}
+
+// List members, with the failing repositories whose latest run they started
+function renderMemberList() {
+    if (workspaceMembers.length === 0) {
+        memberList.innerHTML = '<li class="empty">No members found</li>';
+        return;
+    }
+
+    const failing = (lastStatus?.pipelineStatuses || [])
+        .filter(p => p.state === 'Failed' && p.workspace === currentWorkspace);
+    memberList.replaceChildren(...workspaceMembers.map(m => {
+        const repos = failing
+            .filter(p => p.commitAuthor && (p.commitAuthor === m.display_name || p.commitAuthor === m.nickname))
+            .map(p => p.repoName || p.repoSlug);
+        const detail = repos.length > 0 ? `last pushed to failing ${repos.join(', ')}` : m.nickname;
+        const li = document.createElement('li');
+        li.append(
+            textElement('span', 'pipeline-name', m.display_name || m.nickname),
+            textElement('span', 'incident-detail', detail)
+        );
+        return li;
+    }));
+}
+
 function setupEventListeners() {
     // Auth form submission
     authForm.addEventListener('submit', async (e) => {
         e.preventDefault();
         await saveCredentials();
     });
+    credentialKindSelect.addEventListener('change', updateCredentialKindFields);
 
     // Workspace selection - loads projects
     workspaceSelect.addEventListener('change', async () => {
         const workspace = workspaceSelect.value;
         currentWorkspace = workspace;
+        repoSlugInput.disabled = !workspace;
+        repoDetails.textContent = '';
         if (workspace) {
+            loadWorkspaceMembers(workspace);
             await loadProjects(workspace);
         } else {
             projectSelect.innerHTML = '<option value="">Select Project</option>';
             projectSelect.disabled = true;
+            addGroupBtn.disabled = true;
             repoSelect.innerHTML = '<option value="">Select Repository</option>';
             repoSelect.disabled = true;
             addPipelineBtn.disabled = true;
@@ -114,6 +467,7 @@ function setupEventListeners() {
     // Project selection - loads repositories
     projectSelect.addEventListener('change', async () => {
         const projectKey = projectSelect.value;
+        addGroupBtn.disabled = !projectKey;
         if (projectKey && currentWorkspace) {
             await loadRepositoriesByProject(currentWorkspace, projectKey);
         } else {
@@ -124,16 +478,162 @@ function setupEventListeners() {
     });
 
     // Repository selection
-    repoSelect.addEventListener('change', () => {
+    repoSelect.addEventListener('change', async () => {
         addPipelineBtn.disabled = !repoSelect.value;
+        await previewSelectedPipeline();
+    });
+
+    // Typing a slug looks the repository up directly, once typing pauses
+    let slugLookupTimer = null;
+    repoSlugInput.addEventListener('input', () => {
+        clearTimeout(slugLookupTimer);
+        slugLookupTimer = setTimeout(lookUpRepositoryBySlug, 400);
     });
 
     // Add pipeline button
     addPipelineBtn.addEventListener('click', addMonitoredPipeline);
 
+    // Monitor whole project button
+    addGroupBtn.addEventListener('click', addMonitoredGroup);
+
     // Save settings
     document.getElementById('save-settings-btn').addEventListener('click', saveSettings);
 
+    // Failure and recovery notifications
+    notificationCategoryInputs.forEach(input => {
+        input.addEventListener('change', async () => {
+            try {
+                await invoke('set_notification_category_enabled', {
+                    category: input.dataset.category,
+                    enabled: input.checked
+                });
+            } catch (e) {
+                showNotification(`Failed to save setting: ${e}`, 'error');
+            }
+        });
+    });
+
+    // Approval notifications
+    notifyOnPausedInput.addEventListener('change', async () => {
+        try {
+            await invoke('set_notify_on_paused', { enabled: notifyOnPausedInput.checked });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    treatPausedAsUnhealthyInput.addEventListener('change', async () => {
+        try {
+            await invoke('set_treat_paused_as_unhealthy', { enabled: treatPausedAsUnhealthyInput.checked });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    includePrsInHealthInput.addEventListener('change', async () => {
+        try {
+            await invoke('set_include_prs_in_health', { enabled: includePrsInHealthInput.checked });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    // Run history glyphs in the tray menu
+    showHistoryGlyphsInput.addEventListener('change', async () => {
+        try {
+            await invoke('set_show_history_glyphs', { enabled: showHistoryGlyphsInput.checked });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    hideUnknownInMenuInput.addEventListener('change', async () => {
+        try {
+            await invoke('set_hide_unknown_in_menu', { enabled: hideUnknownInMenuInput.checked });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    groupRunningSeparatelyInput.addEventListener('change', async () => {
+        try {
+            await invoke('set_group_running_separately', { enabled: groupRunningSeparatelyInput.checked });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    multiTrayInput.addEventListener('change', async () => {
+        try {
+            await invoke('set_multi_tray', { enabled: multiTrayInput.checked });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    flashOnFailureInput.addEventListener('change', async () => {
+        try {
+            await invoke('set_attention_animation', {
+                animation: flashOnFailureInput.checked ? 'flash' : 'none',
+            });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    iconThemeSelect.addEventListener('change', async () => {
+        try {
+            await invoke('set_icon_theme', { theme: iconThemeSelect.value });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    // Language of the tray menu, tooltip and notifications
+    localeSelect.addEventListener('change', async () => {
+        try {
+            await invoke('set_locale', { locale: localeSelect.value || null });
+        } catch (e) {
+            showNotification(`Failed to save setting: ${e}`, 'error');
+        }
+    });
+
+    // Wallboard
+    wallboardOnTopInput.addEventListener('change', saveWallboardOptions);
+    wallboardOnLaunchInput.addEventListener('change', saveWallboardOptions);
+    openWallboardBtn.addEventListener('click', async () => {
+        try {
+            await invoke('open_wallboard');
+        } catch (e) {
+            showNotification(`Failed to open wallboard: ${e}`, 'error');
+        }
+    });
+
+    // Activity log
+    openActivityBtn.addEventListener('click', async () => {
+        try {
+            await invoke('open_activity_log');
+        } catch (e) {
+            showNotification(`Failed to open activity log: ${e}`, 'error');
+        }
+    });
+
+    // Weekly digest
+    document.getElementById('save-digest-btn').addEventListener('click', saveDigestSettings);
+
+    // Save hotkeys
+    document.getElementById('save-hotkeys-btn').addEventListener('click', saveHotkeys);
+
+    // TLS settings
+    document.getElementById('save-tls-btn').addEventListener('click', saveTlsSettings);
+
+    // Follower mode
+    document.getElementById('save-follower-btn').addEventListener('click', saveFollowerMode);
+
+    // Factory reset
+    document.getElementById('reset-defaults-btn').addEventListener('click', resetToDefaults);
+    document.getElementById('test-connection-btn').addEventListener('click', testConnection);
+
     // Refresh button
     document.getElementById('refresh-btn').addEventListener('click', async () => {
         statusDetails.innerHTML = '<p class="loading">Refreshing...</p>';
@@ -148,11 +648,14 @@ function setupEventListeners() {
 }
 
 async function saveCredentials() {
-    const username = usernameInput.value.trim();
+    const credentialKind = credentialKindSelect.value;
+    const isToken = credentialKind === 'access_token';
+    const username = isToken ? ACCESS_TOKEN_USERNAME : usernameInput.value.trim();
     const appPassword = appPasswordInput.value.trim();
+    const repository = isToken ? tokenRepositoryInput.value.trim() || null : null;
 
     if (!username || !appPassword) {
-        showAuthStatus('Please enter both username and app password', 'error');
+        showAuthStatus(isToken ? 'Please enter the access token' : 'Please enter both username and app password', 'error');
         return;
     }
 
@@ -162,14 +665,22 @@ async function saveCredentials() {
     saveAuthBtn.disabled = true;
 
     try {
-        await invoke('save_credentials', { username, appPassword });
+        const check = await invoke('save_credentials', { username, appPassword, credentialKind, repository });
 
         currentUsername = username;
         currentAppPassword = appPassword;
         appPasswordInput.value = '';
         appPasswordInput.placeholder = '••••••••••••••••';
 
-        showAuthStatus('Credentials saved successfully!', 'success');
+        if (!check.can_read_pipelines) {
+            showAuthStatus('Credentials saved, but they cannot read pipelines. Add the pipelines:read scope to your app password.', 'warning');
+        } else if (!check.can_read_repos) {
+            showAuthStatus('Credentials saved, but they cannot read repositories. Add the repository:read scope to your app password.', 'warning');
+        } else if (isToken && !check.can_list_workspaces) {
+            showAuthStatus('Token saved. It is a repository token, so it cannot list workspaces for the pickers below.', 'warning');
+        } else {
+            showAuthStatus('Credentials saved successfully!', 'success');
+        }
         await loadWorkspaces();
     } catch (e) {
         showAuthStatus(`Error: ${e}`, 'error');
@@ -288,12 +799,79 @@ function populateRepoSelect() {
     repoSelect.disabled = false;
 }
 
+async function previewSelectedPipeline() {
+    const repoSlug = repoSelect.value;
+    pipelinePreview.textContent = '';
+    if (!currentWorkspace || !repoSlug) return;
+
+    pipelinePreview.textContent = 'Checking latest pipeline...';
+    try {
+        const preview = await invoke('preview_pipeline', {
+            workspace: currentWorkspace,
+            repoSlug,
+            branch: null
+        });
+        // Ignore stale results if the selection changed meanwhile
+        if (repoSelect.value !== repoSlug) return;
+        const build = preview.buildNumber ? `#${preview.buildNumber} ` : '';
+        const detail = preview.failureReason ? ` (${preview.failureReason})` : '';
+        pipelinePreview.textContent = `Latest: ${build}${preview.state}${detail}`;
+    } catch (e) {
+        pipelinePreview.textContent = `Preview unavailable: ${e}`;
+    }
+}
+
+async function lookUpRepositoryBySlug() {
+    const repoSlug = repoSlugInput.value.trim();
+    repoDetails.textContent = '';
+    if (!currentWorkspace || !repoSlug || !currentUsername || !currentAppPassword) return;
+
+    repoDetails.textContent = 'Looking up repository...';
+    try {
+        const repo = await invoke('get_repository_details', {
+            username: currentUsername,
+            appPassword: currentAppPassword,
+            workspace: currentWorkspace,
+            repoSlug
+        });
+        // Ignore stale results if the slug changed meanwhile
+        if (repoSlugInput.value.trim() !== repoSlug) return;
+        const details = [repo.full_name];
+        if (repo.project) details.push(`project ${repo.project.name || repo.project.key}`);
+        if (repo.mainbranch) details.push(`main branch ${repo.mainbranch.name}`);
+        repoDetails.textContent = details.join(' · ');
+        selectLookedUpRepository(repo);
+    } catch (e) {
+        if (repoSlugInput.value.trim() !== repoSlug) return;
+        repoDetails.textContent = `Not found: ${e}`;
+    }
+}
+
+// Select a looked-up repository so it can be previewed and added like a listed one
+async function selectLookedUpRepository(repo) {
+    if (repo.project && projects.some(p => p.key === repo.project.key)
+        && projectSelect.value !== repo.project.key) {
+        projectSelect.value = repo.project.key;
+        addGroupBtn.disabled = false;
+        await loadRepositoriesByProject(currentWorkspace, repo.project.key);
+    }
+    if (!repositories.some(r => r.slug === repo.slug)) {
+        repositories.push(repo);
+        populateRepoSelect();
+    }
+    repoSelect.value = repo.slug;
+    addPipelineBtn.disabled = false;
+    await previewSelectedPipeline();
+}
+
 async function addMonitoredPipeline() {
     const workspace = workspaceSelect.value;
     const projectKey = projectSelect.value;
     const projectName = projectSelect.options[projectSelect.selectedIndex]?.text || null;
     const repoSlug = repoSelect.value;
     const repoName = repoSelect.options[repoSelect.selectedIndex].text;
+    const repo = repositories.find(r => r.slug === repoSlug);
+    const repoUrl = repo?.links?.html?.href || null;
 
     if (!workspace || !projectKey || !repoSlug) {
         showNotification('Please select workspace, project, and repository', 'error');
@@ -302,7 +880,7 @@ async function addMonitoredPipeline() {
 
     // Check for duplicates
     const exists = monitoredPipelines.some(
-        p => p.workspace === workspace && p.repo_slug === repoSlug
+        p => p.workspace === workspace && p.repoSlug === repoSlug
     );
 
     if (exists) {
@@ -312,16 +890,26 @@ async function addMonitoredPipeline() {
 
     monitoredPipelines.push({
         workspace,
-        project_key: projectKey,
-        project_name: projectName,
-        repo_slug: repoSlug,
-        repo_name: repoName,
-        branch: null
+        projectKey: projectKey,
+        projectName: projectName,
+        repoSlug: repoSlug,
+        repoName: repoName,
+        branch: null,
+        repoUrl: repoUrl,
+        muted: false,
+        notificationRoute: null,
+        pipelineDefinitionUuid: null,
+        activeHours: null,
+        quickActions: [],
+        trackMyPrs: false,
+        autoDefaultBranch: false,
+        resolvedBranch: null
     });
 
     try {
         await invoke('save_monitored_pipelines', { pipelines: monitoredPipelines });
-        renderPipelineList();
+        // Reload to pick up the addedAt timestamp set by the backend
+        await loadMonitoredPipelines();
         showNotification('Pipeline added!', 'success');
 
         // Reset selects
@@ -344,7 +932,7 @@ function renderPipelineList() {
     // Group pipelines by project (fall back to workspace if no project)
     const grouped = {};
     monitoredPipelines.forEach((pipeline, index) => {
-        const groupKey = pipeline.project_name || pipeline.workspace;
+        const groupKey = pipeline.projectName || pipeline.workspace;
         if (!grouped[groupKey]) {
             grouped[groupKey] = [];
         }
@@ -364,9 +952,72 @@ function renderPipelineList() {
             const li = document.createElement('li');
             li.className = 'pipeline-item';
             li.innerHTML = `
-                <span class="pipeline-name">${pipeline.repo_name || pipeline.repo_slug}</span>
+                <span class="pipeline-name" title="${formatAddedAt(pipeline.addedAt)}">${pipeline.repoName || pipeline.repoSlug}</span>
+                <span class="pipeline-history">${historyGlyphs(pipeline)}</span>
+                <button type="button" class="open-repo-btn" data-index="${index}">Open</button>
+                <button type="button" class="copy-link-btn" data-index="${index}" title="Copy a link to the latest run">Copy Link</button>
+                <button type="button" class="mute-btn" data-index="${index}">${pipeline.muted ? 'Unmute' : 'Mute'}</button>
+                <button type="button" class="track-prs-btn" data-index="${index}" title="List the pipelines of my open pull requests">${pipeline.trackMyPrs ? 'Untrack PRs' : 'Track PRs'}</button>
+                ${pipeline.branch ? '' : `<button type="button" class="default-branch-btn" data-index="${index}" title="Only show runs of the repository's main branch">${defaultBranchLabel(pipeline)}</button>`}
+                <button type="button" class="drift-btn" data-index="${index}" title="Show how many commits one deployment environment is ahead of another"></button>
                 <button type="button" class="remove-btn" data-index="${index}">Remove</button>
+                <div class="drift-form" hidden>
+                    <input type="text" class="drift-from" placeholder="From, e.g. staging">
+                    <input type="text" class="drift-to" placeholder="To, e.g. production">
+                    <button type="button" class="drift-save-btn">Save</button>
+                    <button type="button" class="drift-clear-btn">Clear</button>
+                </div>
             `;
+            // Environment names are typed by the user, so they are never parsed as HTML
+            li.querySelector('.drift-btn').textContent = driftLabel(pipeline);
+            li.querySelector('.drift-from').value = pipeline.driftEnvironments?.from ?? '';
+            li.querySelector('.drift-to').value = pipeline.driftEnvironments?.to ?? '';
+            li.querySelector('.open-repo-btn').addEventListener('click', async () => {
+                try {
+                    await invoke('open_repository', {
+                        workspace: pipeline.workspace,
+                        repoSlug: pipeline.repoSlug
+                    });
+                } catch (e) {
+                    showNotification(`${e}`, 'error');
+                }
+            });
+            li.querySelector('.copy-link-btn').addEventListener('click', async () => {
+                try {
+                    await invoke('copy_pipeline_url', {
+                        workspace: pipeline.workspace,
+                        repoSlug: pipeline.repoSlug,
+                        branch: pipeline.branch
+                    });
+                } catch (e) {
+                    showNotification(`${e}`, 'error');
+                }
+            });
+            li.querySelector('.mute-btn').addEventListener('click', () => {
+                toggleMute(index);
+            });
+            li.querySelector('.track-prs-btn').addEventListener('click', () => {
+                toggleTrackPrs(index);
+            });
+            li.querySelector('.default-branch-btn')?.addEventListener('click', () => {
+                toggleDefaultBranch(index);
+            });
+            li.querySelector('.drift-btn').addEventListener('click', () => {
+                const form = li.querySelector('.drift-form');
+                form.hidden = !form.hidden;
+            });
+            li.querySelector('.drift-save-btn').addEventListener('click', () => {
+                const from = li.querySelector('.drift-from').value.trim();
+                const to = li.querySelector('.drift-to').value.trim();
+                if (!from || !to) {
+                    showNotification('Enter both environments to compare', 'error');
+                    return;
+                }
+                setDriftEnvironments(index, { from, to });
+            });
+            li.querySelector('.drift-clear-btn').addEventListener('click', () => {
+                setDriftEnvironments(index, null);
+            });
             li.querySelector('.remove-btn').addEventListener('click', () => {
                 removePipeline(index);
             });
@@ -375,6 +1026,100 @@ function renderPipelineList() {
     });
 }
 
+function historyGlyphs(pipeline) {
+    const stats = pipelineStats.find(
+        s => s.workspace === pipeline.workspace && s.repoSlug === pipeline.repoSlug
+    );
+    if (!stats) return '';
+    return stats.history.map(r => (r.result === 'passed' ? '✓' : '✗')).join('');
+}
+
+function formatAddedAt(addedAt) {
+    if (!addedAt) return '';
+    const days = Math.floor((Date.now() - new Date(addedAt).getTime()) / 86400000);
+    if (days <= 0) return 'Added today';
+    if (days === 1) return 'Added yesterday';
+    return `Added ${days} days ago`;
+}
+
+async function toggleMute(index) {
+    const pipeline = monitoredPipelines[index];
+    pipeline.muted = !pipeline.muted;
+    try {
+        await invoke('save_monitored_pipelines', { pipelines: monitoredPipelines });
+        renderPipelineList();
+    } catch (e) {
+        pipeline.muted = !pipeline.muted;
+        showNotification(`Failed to update: ${e}`, 'error');
+    }
+}
+
+async function toggleTrackPrs(index) {
+    const pipeline = monitoredPipelines[index];
+    pipeline.trackMyPrs = !pipeline.trackMyPrs;
+    try {
+        await invoke('save_monitored_pipelines', { pipelines: monitoredPipelines });
+        renderPipelineList();
+    } catch (e) {
+        pipeline.trackMyPrs = !pipeline.trackMyPrs;
+        showNotification(`Failed to update: ${e}`, 'error');
+    }
+}
+
+function driftLabel(pipeline) {
+    const drift = pipeline.driftEnvironments;
+    return drift ? `Drift: ${drift.from} vs ${drift.to}` : 'Drift';
+}
+
+async function setDriftEnvironments(index, driftEnvironments) {
+    const pipeline = monitoredPipelines[index];
+    const previous = pipeline.driftEnvironments;
+    pipeline.driftEnvironments = driftEnvironments;
+    try {
+        await invoke('save_monitored_pipelines', { pipelines: monitoredPipelines });
+        renderPipelineList();
+    } catch (e) {
+        pipeline.driftEnvironments = previous;
+        showNotification(`Failed to update: ${e}`, 'error');
+    }
+}
+
+function defaultBranchLabel(pipeline) {
+    if (!pipeline.autoDefaultBranch) return 'Any Branch';
+    return pipeline.resolvedBranch ? `Main (${pipeline.resolvedBranch})` : 'Main Branch';
+}
+
+async function toggleDefaultBranch(index) {
+    const pipeline = monitoredPipelines[index];
+    pipeline.autoDefaultBranch = !pipeline.autoDefaultBranch;
+    try {
+        await invoke('save_monitored_pipelines', { pipelines: monitoredPipelines });
+        if (pipeline.autoDefaultBranch) {
+            await detectDefaultBranches();
+        } else {
+            renderPipelineList();
+        }
+    } catch (e) {
+        pipeline.autoDefaultBranch = !pipeline.autoDefaultBranch;
+        showNotification(`Failed to update: ${e}`, 'error');
+    }
+}
+
+async function detectDefaultBranches() {
+    try {
+        const resolved = await invoke('resolve_default_branches');
+        await loadMonitoredPipelines();
+        if (resolved.length === 0) {
+            showNotification('No main branches found', 'error');
+            return;
+        }
+        const summary = resolved.map(r => `${r.repo_name}: ${r.branch}`).join(', ');
+        showNotification(`Following main branches - ${summary}`, 'success');
+    } catch (e) {
+        showNotification(`Failed to look up main branches: ${e}`, 'error');
+    }
+}
+
 async function removePipeline(index) {
     monitoredPipelines.splice(index, 1);
     try {
@@ -386,26 +1131,417 @@ async function removePipeline(index) {
     }
 }
 
+async function addMonitoredGroup() {
+    const workspace = workspaceSelect.value;
+    const projectKey = projectSelect.value;
+
+    if (!workspace || !projectKey) {
+        showNotification('Please select workspace and project', 'error');
+        return;
+    }
+
+    const exists = monitoredGroups.some(
+        g => g.workspace === workspace && g.project_key === projectKey
+    );
+
+    if (exists) {
+        showNotification('This project is already auto-monitored', 'error');
+        return;
+    }
+
+    monitoredGroups.push({
+        workspace,
+        project_key: projectKey,
+        branch: null,
+        exclude: []
+    });
+
+    try {
+        await invoke('save_monitored_groups', { groups: monitoredGroups });
+        renderGroupList();
+        showNotification('Project added!', 'success');
+    } catch (e) {
+        showNotification(`Failed to save: ${e}`, 'error');
+        monitoredGroups.pop();
+    }
+}
+
+function renderGroupList() {
+    groupList.innerHTML = '';
+
+    if (monitoredGroups.length === 0) {
+        groupList.innerHTML = '<li class="empty">No projects auto-monitored</li>';
+        return;
+    }
+
+    monitoredGroups.forEach((group, index) => {
+        const li = document.createElement('li');
+        li.className = 'pipeline-item';
+        li.innerHTML = `
+            <span class="pipeline-name">${group.workspace} / ${group.project_key}</span>
+            <button type="button" class="remove-btn" data-index="${index}">Remove</button>
+        `;
+        li.querySelector('.remove-btn').addEventListener('click', () => {
+            removeGroup(index);
+        });
+        groupList.appendChild(li);
+    });
+}
+
+async function removeGroup(index) {
+    monitoredGroups.splice(index, 1);
+    try {
+        await invoke('save_monitored_groups', { groups: monitoredGroups });
+        renderGroupList();
+        showNotification('Project removed', 'success');
+    } catch (e) {
+        showNotification(`Failed to remove: ${e}`, 'error');
+    }
+}
+
 async function saveSettings() {
     const interval = parseInt(pollingIntervalInput.value, 10);
 
-    if (interval < 30) {
-        showNotification('Interval must be at least 30 seconds', 'error');
+    const minInterval = parseInt(pollingIntervalInput.min, 10);
+    if (interval < minInterval) {
+        showNotification(`Interval must be at least ${minInterval} seconds`, 'error');
         return;
     }
 
     try {
         await invoke('set_polling_interval', { seconds: interval });
+        await invoke('set_power_settings', {
+            reducePollingOnBattery: reduceOnBatteryInput.checked,
+            pauseOnMetered: pauseOnMeteredInput.checked,
+            apiBudgetPerHour: parseInt(apiBudgetInput.value, 10) || null
+        });
+        await loadPollingStatus();
         showNotification('Settings saved!', 'success');
     } catch (e) {
         showNotification(`Failed to save settings: ${e}`, 'error');
     }
 }
 
+async function saveWallboardOptions() {
+    try {
+        await invoke('set_wallboard_options', {
+            alwaysOnTop: wallboardOnTopInput.checked,
+            startOnLaunch: wallboardOnLaunchInput.checked
+        });
+    } catch (e) {
+        showNotification(`Failed to save wallboard options: ${e}`, 'error');
+    }
+}
+
+async function saveHotkeys() {
+    try {
+        await invoke('set_hotkeys', {
+            refresh: hotkeyRefreshInput.value.trim(),
+            openSettings: hotkeyOpenSettingsInput.value.trim(),
+            quickOpen: hotkeyQuickOpenInput.value.trim()
+        });
+        hotkeyStatus.style.display = 'none';
+        showNotification('Hotkeys saved!', 'success');
+    } catch (e) {
+        // Structured error: { kind, action, hotkey, message }
+        hotkeyStatus.textContent = e.message || e;
+        hotkeyStatus.className = 'status-message error';
+        hotkeyStatus.style.display = 'block';
+        const inputs = {
+            open_settings: hotkeyOpenSettingsInput,
+            quick_open: hotkeyQuickOpenInput
+        };
+        const input = inputs[e.action] || hotkeyRefreshInput;
+        input.focus();
+    }
+}
+
+async function saveTlsSettings() {
+    try {
+        await invoke('set_tls_settings', {
+            customCaPath: customCaPathInput.value.trim(),
+            dangerAcceptInvalidCerts: acceptInvalidCertsInput.checked
+        });
+        showTlsStatus('TLS settings saved', 'success');
+    } catch (e) {
+        showTlsStatus(`Error: ${e}`, 'error');
+    }
+}
+
+async function testConnection() {
+    const username = credentialKindSelect.value === 'access_token'
+        ? ACCESS_TOKEN_USERNAME
+        : usernameInput.value.trim() || currentUsername;
+    const appPassword = appPasswordInput.value.trim() || currentAppPassword;
+
+    if (!username || !appPassword) {
+        showTlsStatus('Enter credentials to test the connection', 'error');
+        return;
+    }
+
+    try {
+        const result = await invoke('test_connection', { username, appPassword });
+        if (result.ok) {
+            showTlsStatus(result.message, 'success');
+        } else if (result.kind === 'tls' || result.kind === 'invalid_ca') {
+            showTlsStatus(`Certificate problem: ${result.message}`, 'error');
+        } else {
+            showTlsStatus(result.message, 'error');
+        }
+    } catch (e) {
+        showTlsStatus(`Error: ${e}`, 'error');
+    }
+}
+
+async function resetToDefaults() {
+    if (!confirm('Remove all settings, monitored pipelines and saved credentials?')) return;
+    try {
+        await invoke('reset_to_defaults', { confirm: true });
+    } catch (e) {
+        showNotification(`Failed to reset: ${e}`, 'error');
+    }
+}
+
+async function saveFollowerMode() {
+    try {
+        await invoke('set_follower_mode', {
+            followStatusPath: followStatusPathInput.value.trim(),
+            publishStatusPath: publishStatusPathInput.value.trim()
+        });
+        showFollowerStatus('Follower settings saved', 'success');
+    } catch (e) {
+        showFollowerStatus(`Error: ${e}`, 'error');
+    }
+}
+
+function showFollowerStatus(message, type) {
+    followerStatus.textContent = message;
+    followerStatus.className = `status-message ${type}`;
+    followerStatus.style.display = 'block';
+}
+
+function showTlsStatus(message, type) {
+    tlsStatus.textContent = message;
+    tlsStatus.className = `status-message ${type}`;
+    tlsStatus.style.display = 'block';
+}
+
 function listenForStatusUpdates() {
     listen('status-updated', (event) => {
-        const status = event.payload;
+        const { version, status } = event.payload;
+        if (version !== STATUS_PAYLOAD_VERSION) {
+            console.warn(`Unexpected status payload version ${version}`);
+        }
         updateStatusDisplay(status);
+        refreshNextCheck();
+        loadIncidentLog();
+        loadWeeklyDigest();
+        if (detailKey) {
+            invoke('get_pipeline_detail', { key: detailKey })
+                .then(renderPipelineDetail)
+                .catch(e => console.error('Failed to refresh pipeline detail:', e));
+        }
+    });
+}
+
+function listenForStalePipelines() {
+    const banner = document.getElementById('stale-banner');
+    const message = document.getElementById('stale-message');
+    const stale = [];
+
+    listen('pipeline-stale', (event) => {
+        const { repo_name, repo_slug } = event.payload;
+        stale.push(repo_name || repo_slug);
+        message.textContent = stale.length === 1
+            ? `Repository '${stale[0]}' no longer exists. Remove from monitoring?`
+            : `${stale.length} repositories no longer exist (${stale.join(', ')}). Remove from monitoring?`;
+        banner.style.display = 'block';
+    });
+
+    document.getElementById('remove-stale-btn').addEventListener('click', async () => {
+        try {
+            const removed = await invoke('confirm_remove_stale_pipelines');
+            stale.length = 0;
+            banner.style.display = 'none';
+            await loadMonitoredPipelines();
+            showNotification(`Removed ${removed} pipeline(s)`, 'success');
+        } catch (e) {
+            showNotification(`Failed to remove: ${e}`, 'error');
+        }
+    });
+
+    document.getElementById('keep-stale-btn').addEventListener('click', () => {
+        stale.length = 0;
+        banner.style.display = 'none';
+    });
+}
+
+// Key of the pipeline the detail view shows, if it is open
+let detailKey = null;
+
+function listenForNavigation() {
+    listen('navigate', (event) => {
+        const { view, key } = event.payload;
+        if (view === 'pipeline') {
+            showPipelineDetail(key);
+        }
+    });
+
+    pipelineDetailMuteBtn.addEventListener('click', async () => {
+        const index = monitoredPipelines.findIndex(p =>
+            p.workspace === detailKey.workspace
+            && p.repoSlug === detailKey.repoSlug
+            && (p.branch || null) === (detailKey.branch || null)
+        );
+        if (index === -1) return;
+        await toggleMute(index);
+        await showPipelineDetail(detailKey);
+    });
+
+    document.getElementById('pipeline-detail-close-btn').addEventListener('click', () => {
+        detailKey = null;
+        pipelineDetailSection.style.display = 'none';
+    });
+}
+
+async function showPipelineDetail(key) {
+    try {
+        const detail = await invoke('get_pipeline_detail', { key });
+        detailKey = key;
+        renderPipelineDetail(detail);
+        pipelineDetailSection.style.display = 'block';
+        pipelineDetailSection.scrollIntoView();
+    } catch (e) {
+        showNotification(`Failed to load pipeline: ${e}`, 'error');
+        return;
+    }
+    // Schedules aren't part of the polled status, so fetch them once per opening
+    await loadPipelineSchedules(key);
+}
+
+async function loadPipelineSchedules(key) {
+    pipelineDetailSchedules.innerHTML = '<li class="empty">Loading schedules...</li>';
+    if (!currentUsername || !currentAppPassword) {
+        pipelineDetailSchedules.innerHTML = '';
+        return;
+    }
+    try {
+        const schedules = await invoke('get_pipeline_schedules', {
+            username: currentUsername,
+            appPassword: currentAppPassword,
+            workspace: key.workspace,
+            repoSlug: key.repoSlug
+        });
+        if (detailKey !== key) return;
+        renderPipelineSchedules(schedules);
+    } catch (e) {
+        if (detailKey !== key) return;
+        pipelineDetailSchedules.innerHTML = `<li class="empty">Failed to load schedules: ${e}</li>`;
+    }
+}
+
+function renderPipelineSchedules(schedules) {
+    if (schedules.length === 0) {
+        pipelineDetailSchedules.innerHTML = '<li class="empty">No schedules</li>';
+        return;
+    }
+    pipelineDetailSchedules.innerHTML = schedules.map(s => {
+        const next = !s.enabled
+            ? 'disabled'
+            : s.next_scheduled ? `next ${formatRelative(s.next_scheduled)}` : 'not scheduled';
+        return `
+            <li>
+                <span>${s.branch || 'any branch'} <code>${s.cron_pattern}</code></span>
+                <span class="incident-detail">${next}</span>
+            </li>
+        `;
+    }).join('');
+}
+
+// "in 4 hours" or "5 minutes ago"
+function formatRelative(at) {
+    const secs = Math.round((new Date(at).getTime() - Date.now()) / 1000);
+    const units = [['day', 86400], ['hour', 3600], ['minute', 60]];
+    const [unit, size] = units.find(([, size]) => Math.abs(secs) >= size) || ['second', 1];
+    const count = Math.round(Math.abs(secs) / size);
+    const amount = `${count} ${unit}${count === 1 ? '' : 's'}`;
+    return secs >= 0 ? `in ${amount}` : `${amount} ago`;
+}
+
+function renderPipelineDetail(detail) {
+    const { key, status, stats, reliability, activity, flags } = detail;
+    const branch = key.branch ? ` (${key.branch})` : '';
+    pipelineDetailTitle.textContent = `${stats.repoName || key.repoSlug}${branch}`;
+
+    const lines = [];
+    if (status) {
+        const build = status.buildNumber ? ` #${status.buildNumber}` : '';
+        const reason = status.failureReason ? ` - ${status.failureReason}` : '';
+        lines.push(textElement('p', '', `${status.state}${build}${reason}`));
+    } else {
+        lines.push(textElement('p', 'loading', 'Not checked yet'));
+    }
+    const glyphs = stats.history.map(r => (r.result === 'passed' ? '✓' : '✗')).join('');
+    lines.push(textElement('p', '', `${stats.passed} passed, ${stats.failed} failed ${glyphs}`));
+    if (reliability && reliability.mttrSecs !== null) {
+        lines.push(textElement('p', 'incident-detail', `MTTR ${formatDuration(reliability.mttrSecs)} over ${reliability.outages} outage(s)`));
+    }
+    const notes = [flags.muted && 'muted', flags.offHours && 'outside active hours', flags.stale && 'repository not found']
+        .filter(Boolean);
+    if (notes.length > 0) {
+        lines.push(textElement('p', 'incident-detail', notes.join(', ')));
+    }
+    pipelineDetailStatus.replaceChildren(...lines);
+    pipelineDetailMuteBtn.textContent = flags.muted ? 'Unmute' : 'Mute';
+
+    if (activity.length === 0) {
+        pipelineDetailActivity.innerHTML = '<li class="empty">No activity since the log was cleared</li>';
+        return;
+    }
+    pipelineDetailActivity.replaceChildren(...activity.map(e => {
+        const li = document.createElement('li');
+        li.append(
+            textElement('span', 'incident-detail', new Date(e.at).toLocaleString()),
+            textElement('span', '', e.detail)
+        );
+        return li;
+    }));
+}
+
+// Element showing `text` as is, for values from Bitbucket that could contain markup
+function textElement(tag, className, text) {
+    const element = document.createElement(tag);
+    if (className) element.className = className;
+    element.textContent = text;
+    return element;
+}
+
+function listenForFirstRun() {
+    const overlay = document.getElementById('welcome-overlay');
+    listen('first-run', () => {
+        overlay.style.display = 'flex';
+    });
+    document.getElementById('welcome-dismiss-btn').addEventListener('click', () => {
+        overlay.style.display = 'none';
+        usernameInput.focus();
+    });
+}
+
+function listenForIntervalChanges() {
+    // Interval can also be changed from the tray submenu
+    listen('polling-interval-changed', async (event) => {
+        pollingIntervalInput.value = event.payload;
+        await loadPollingStatus();
+        await refreshNextCheck();
+    });
+    // Battery, metered network or API budget changed how often checks run
+    listen('polling-mode-changed', loadPollingStatus);
+    // Every setting changed at once; start over from the backend's defaults
+    listen('config-reset', () => window.location.reload());
+    // A pipeline was removed from its tray menu; one from a project also joins its exclusions
+    listen('monitored-pipelines-changed', async () => {
+        await loadMonitoredPipelines();
+        await loadMonitoredGroups();
     });
 }
 
@@ -416,23 +1552,27 @@ function updateStatusDisplay(status) {
         return;
     }
 
-    statusIndicator.className = status.is_healthy ? 'status-green' : 'status-red';
+    statusIndicator.className = status.isHealthy ? 'status-green' : 'status-red';
+    lastStatus = status;
+    if (workspaceMembers.length > 0) {
+        renderMemberList();
+    }
 
-    if (status.is_healthy) {
-        let html = `<p class="healthy">All ${status.total_monitored} pipeline(s) healthy</p>`;
-        if (status.in_progress_count > 0) {
-            html += `<p class="in-progress">${status.in_progress_count} in progress</p>`;
+    if (status.isHealthy) {
+        let html = `<p class="healthy">All ${status.totalMonitored} pipeline(s) healthy</p>`;
+        if (status.inProgressCount > 0) {
+            html += `<p class="in-progress">${status.inProgressCount} in progress</p>`;
         }
-        html += `<p class="last-checked">Last checked: ${status.last_checked}</p>`;
+        html += `<p class="last-checked">Last checked: ${status.lastChecked}</p>`;
         statusDetails.innerHTML = html;
     } else {
-        const failedList = status.failed_pipelines
-            .map(p => `<li>${p.repo_name || p.repo_slug} - ${p.failure_reason}</li>`)
+        const failedList = status.failedPipelines
+            .map(p => `<li>${p.repoName || p.repoSlug} - ${p.failureReason}</li>`)
             .join('');
         statusDetails.innerHTML = `
-            <p class="failed">${status.failed_pipelines.length} pipeline(s) failed</p>
+            <p class="failed">${status.failedPipelines.length} pipeline(s) failed</p>
             <ul class="failed-list">${failedList}</ul>
-            <p class="last-checked">Last checked: ${status.last_checked}</p>
+            <p class="last-checked">Last checked: ${status.lastChecked}</p>
         `;
     }
 }
@@ -0,0 +1,92 @@
+const { invoke } = window.__TAURI__.core;
+const { getCurrentWindow } = window.__TAURI__.window;
+
+const queryInput = document.getElementById('quick-open-query');
+const results = document.getElementById('quick-open-results');
+
+let matches = [];
+let selected = 0;
+
+document.addEventListener('DOMContentLoaded', () => {
+    queryInput.addEventListener('input', search);
+    queryInput.addEventListener('keydown', handleKey);
+
+    // Start from a fresh search every time the window is shown
+    window.addEventListener('focus', () => {
+        queryInput.select();
+        search();
+    });
+
+    queryInput.focus();
+    search();
+});
+
+async function search() {
+    try {
+        matches = await invoke('search_status', { query: queryInput.value });
+    } catch (e) {
+        console.error('Failed to search pipelines:', e);
+        matches = [];
+    }
+    selected = 0;
+    render();
+}
+
+function render() {
+    results.innerHTML = '';
+    matches.forEach((match, i) => {
+        const item = document.createElement('li');
+        item.className = `state-${match.state.toLowerCase()}`;
+        if (i === selected) {
+            item.classList.add('selected');
+        }
+
+        const name = document.createElement('div');
+        name.textContent = match.repo_name || match.repo_slug;
+        item.appendChild(name);
+
+        const detail = document.createElement('div');
+        detail.className = 'result-detail';
+        detail.textContent = [match.state, match.project_name, match.branch]
+            .filter(Boolean)
+            .join(' - ');
+        item.appendChild(detail);
+
+        item.addEventListener('click', () => open(i));
+        results.appendChild(item);
+    });
+}
+
+function handleKey(event) {
+    switch (event.key) {
+        case 'ArrowDown':
+            event.preventDefault();
+            selected = Math.min(selected + 1, matches.length - 1);
+            render();
+            break;
+        case 'ArrowUp':
+            event.preventDefault();
+            selected = Math.max(selected - 1, 0);
+            render();
+            break;
+        case 'Enter':
+            event.preventDefault();
+            open(selected);
+            break;
+        case 'Escape':
+            getCurrentWindow().hide();
+            break;
+    }
+}
+
+async function open(index) {
+    const match = matches[index];
+    if (!match) {
+        return;
+    }
+    try {
+        await invoke('open_status_entry', { key: match.key });
+    } catch (e) {
+        console.error('Failed to open pipeline:', e);
+    }
+}
@@ -0,0 +1,67 @@
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+// Shape version of the status-updated event payload
+const STATUS_PAYLOAD_VERSION = 1;
+
+const indicator = document.getElementById('wallboard-indicator');
+const summary = document.getElementById('wallboard-summary');
+const checking = document.getElementById('wallboard-checking');
+const grid = document.getElementById('wallboard-grid');
+const lastChecked = document.getElementById('wallboard-last-checked');
+
+document.addEventListener('DOMContentLoaded', async () => {
+    try {
+        const snapshot = await invoke('get_pipeline_statuses');
+        checking.style.display = snapshot.checking ? 'inline' : 'none';
+        renderStatus(snapshot.status);
+    } catch (e) {
+        console.error('Failed to load status:', e);
+    }
+
+    listen('status-updated', (event) => {
+        const { version, status } = event.payload;
+        if (version !== STATUS_PAYLOAD_VERSION) {
+            console.warn(`Unexpected status payload version ${version}`);
+        }
+        checking.style.display = 'none';
+        renderStatus(status);
+    });
+});
+
+function renderStatus(status) {
+    if (!status) {
+        indicator.className = 'status-gray';
+        summary.textContent = 'No status available';
+        grid.innerHTML = '';
+        lastChecked.textContent = '';
+        return;
+    }
+
+    indicator.className = status.isHealthy ? 'status-green' : 'status-red';
+    summary.textContent = status.isHealthy
+        ? `All ${status.totalMonitored} pipeline(s) healthy`
+        : `${status.failedPipelines.length} pipeline(s) failed`;
+
+    grid.innerHTML = '';
+    status.pipelineStatuses.forEach(p => {
+        const tile = document.createElement('div');
+        tile.className = `wallboard-tile state-${p.state.toLowerCase()}`;
+
+        const name = document.createElement('div');
+        name.className = 'tile-name';
+        name.textContent = p.repoName || p.repoSlug;
+        tile.appendChild(name);
+
+        const detail = document.createElement('div');
+        detail.className = 'tile-detail';
+        detail.textContent = p.failedStepName
+            ? `${p.state} - ${p.failedStepName}`
+            : p.failureReason || p.state;
+        tile.appendChild(detail);
+
+        grid.appendChild(tile);
+    });
+
+    lastChecked.textContent = `Last checked: ${status.lastChecked}`;
+}